@@ -13,12 +13,14 @@
 // limitations under the License.
 
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::OnceCell;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
+use std::error::Error as _;
 use std::ffi::OsString;
 use std::fmt;
 use std::fmt::Debug;
@@ -133,6 +135,8 @@ use jj_lib::str_util::StringExpression;
 use jj_lib::str_util::StringMatcher;
 use jj_lib::transaction::Transaction;
 use jj_lib::transaction::TransactionCommitError;
+use jj_lib::tree_merge::AutoResolution;
+use jj_lib::tree_merge::AutoResolutionKind;
 use jj_lib::working_copy;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::LockedWorkingCopy;
@@ -157,6 +161,7 @@ use tracing_subscriber::prelude::*;
 
 use crate::command_error::CommandError;
 use crate::command_error::cli_error;
+use crate::command_error::config_error;
 use crate::command_error::config_error_with_message;
 use crate::command_error::handle_command_result;
 use crate::command_error::internal_error;
@@ -187,6 +192,7 @@ use crate::merge_tools::MergeEditor;
 use crate::merge_tools::MergeToolConfigError;
 use crate::operation_templater::OperationTemplateLanguage;
 use crate::operation_templater::OperationTemplateLanguageExtension;
+use crate::output_format::OutputFormat;
 use crate::revset_util;
 use crate::revset_util::RevsetExpressionEvaluator;
 use crate::revset_util::parse_union_name_patterns;
@@ -320,6 +326,8 @@ struct CommandHelperData {
     store_factories: StoreFactories,
     working_copy_factories: WorkingCopyFactories,
     workspace_loader_factory: Box<dyn WorkspaceLoaderFactory>,
+    // Whether the command looked up `--output-format`.
+    output_format_used: Cell<bool>,
 }
 
 impl CommandHelper {
@@ -351,6 +359,15 @@ impl CommandHelper {
         &self.data.config_env
     }
 
+    /// Machine-readable output format requested by `--output-format`.
+    ///
+    /// Commands which support `--output-format` must look it up through this
+    /// method. Otherwise, a warning is emitted after the command completes.
+    pub fn output_format(&self) -> Option<OutputFormat> {
+        self.data.output_format_used.set(true);
+        self.data.global_args.output_format
+    }
+
     /// Unprocessed (or unresolved) configuration data.
     ///
     /// Use this only if the unmodified config data is needed. For example, `jj
@@ -1589,10 +1606,31 @@ to the current parents may contain changes from multiple commits.
     }
 
     /// Parses the given strings as file patterns.
+    ///
+    /// File predicates such as `binary()` are rejected. Use
+    /// [`Self::parse_file_patterns_with_filters()`] if the command supports
+    /// them.
     pub fn parse_file_patterns(
         &self,
         ui: &Ui,
         values: &[String],
+    ) -> Result<FilesetExpression, CommandError> {
+        let expression = self.parse_file_patterns_with_filters(ui, values)?;
+        if expression.has_filters() {
+            return Err(user_error(
+                "File predicates such as `binary()` are not supported by this command",
+            ));
+        }
+        Ok(expression)
+    }
+
+    /// Parses the given strings as file patterns which may contain file
+    /// predicates. The predicates should be resolved by
+    /// [`Self::resolve_file_filters()`] before building matcher.
+    pub fn parse_file_patterns_with_filters(
+        &self,
+        ui: &Ui,
+        values: &[String],
     ) -> Result<FilesetExpression, CommandError> {
         // TODO: This function might be superseded by parse_union_filesets(),
         // but it would be weird if parse_union_*() had a special case for the
@@ -1620,12 +1658,31 @@ to the current parents may contain changes from multiple commits.
         Ok(FilesetExpression::union_all(expressions))
     }
 
+    /// Evaluates file predicates in the `expression` against the files in
+    /// `trees`.
+    pub async fn resolve_file_filters(
+        &self,
+        expression: &FilesetExpression,
+        trees: &[&MergedTree],
+    ) -> Result<FilesetExpression, CommandError> {
+        if !expression.has_filters() {
+            return Ok(expression.clone());
+        }
+        let base_ignores = self.base_ignores()?;
+        Ok(expression.resolve_filters(trees, &base_ignores).await?)
+    }
+
     pub fn auto_tracking_matcher(&self, ui: &Ui) -> Result<Box<dyn Matcher>, CommandError> {
         let mut diagnostics = FilesetDiagnostics::new();
         let pattern = self.settings().get_string("snapshot.auto-track")?;
         let context = self.env.fileset_parse_context_for_config();
         let expression = fileset::parse(&mut diagnostics, &pattern, &context)?;
         print_parse_diagnostics(ui, "In `snapshot.auto-track`", &diagnostics)?;
+        if expression.has_filters() {
+            return Err(config_error(
+                "File predicates are not supported in `snapshot.auto-track`",
+            ));
+        }
         Ok(expression.to_matcher())
     }
 
@@ -2810,6 +2867,21 @@ impl WorkspaceCommandTransaction<'_> {
             .commit_template_language(self.tx.repo(), id_prefix_context)
     }
 
+    /// Parses template of the given language into evaluation tree.
+    pub fn parse_template<'a, C, L>(
+        &self,
+        ui: &Ui,
+        language: &L,
+        template_text: &str,
+    ) -> Result<TemplateRenderer<'a, C>, CommandError>
+    where
+        C: Clone + 'a,
+        L: TemplateLanguage<'a> + ?Sized,
+        L::Property: WrapTemplateProperty<'a, C>,
+    {
+        self.helper.env.parse_template(ui, language, template_text)
+    }
+
     /// Parses commit template with the current transaction state.
     pub fn parse_commit_template(
         &self,
@@ -2830,6 +2902,8 @@ impl WorkspaceCommandTransaction<'_> {
         if num_rebased > 0 {
             writeln!(ui.status(), "Rebased {num_rebased} descendant commits.")?;
         }
+        let auto_resolutions = tx.repo_mut().take_auto_resolutions();
+        print_auto_resolutions(ui, helper.path_converter(), &auto_resolutions)?;
         // Acquire git import/export lock before finishing the transaction to ensure
         // Git HEAD export happens atomically with the transaction commit.
         let git_import_export_lock = helper.lock_git_import_export()?;
@@ -3351,6 +3425,51 @@ Discard the conflicting changes with `jj restore --from {}`.",
     Ok(())
 }
 
+/// Prints file conflicts which were resolved, or failed to be resolved, by
+/// merge drivers while rewriting commits.
+fn print_auto_resolutions(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    resolutions: &[AutoResolution],
+) -> io::Result<()> {
+    for (kind, heading) in [
+        (
+            AutoResolutionKind::Recorded,
+            "Resolved conflicts by recorded resolutions in these paths:",
+        ),
+        (
+            AutoResolutionKind::MergeDriver,
+            "Resolved conflicts by merge drivers in these paths:",
+        ),
+    ] {
+        let resolved_paths = resolutions
+            .iter()
+            .filter(|resolution| resolution.result.as_ref().is_ok_and(|k| *k == kind))
+            .map(|resolution| &resolution.path)
+            .unique()
+            .collect_vec();
+        if !resolved_paths.is_empty()
+            && let Some(mut formatter) = ui.status_formatter()
+        {
+            writeln!(formatter, "{heading}")?;
+            for path in resolved_paths {
+                writeln!(formatter, "  {}", path_converter.format_file_path(path))?;
+            }
+        }
+    }
+    for resolution in resolutions {
+        if let Err(err) = &resolution.result {
+            writeln!(
+                ui.warning_default(),
+                "Failed to resolve conflicts in {}: {err}",
+                path_converter.format_file_path(&resolution.path)
+            )?;
+            print_error_sources(ui, err.source())?;
+        }
+    }
+    Ok(())
+}
+
 /// Prints warning about explicit paths that don't match any of the tree
 /// entries.
 pub fn print_unmatched_explicit_paths<'a>(
@@ -3500,7 +3619,7 @@ impl LogContentFormat {
         content_fn: impl AsyncFnOnce(&mut dyn Formatter) -> Result<(), E>,
     ) -> Result<(), E> {
         if self.word_wrap {
-            let mut recorder = FormatRecorder::new(formatter.maybe_color());
+            let mut recorder = FormatRecorder::for_formatter(formatter);
             content_fn(&mut recorder).await?;
             text_util::write_wrapped(formatter, &recorder, self.width)?;
         } else {
@@ -3785,6 +3904,22 @@ pub struct GlobalArgs {
     #[arg(add = ArgValueCandidates::new(complete::operations))]
     pub at_operation: Option<String>,
 
+    /// Print machine-readable output
+    ///
+    /// Supported by `jj status`, `jj bookmark list`, `jj op log`, `jj
+    /// workspace list`, `jj git remote list`, `jj config list`, and `jj file
+    /// annotate`. Instead of rendering a template, these commands serialize
+    /// their items in JSON. With `json`, the items are printed as a single
+    /// array. With `ndjson`, each item is printed on its own line. Other
+    /// commands ignore this option with a warning.
+    ///
+    /// See [`jj help -k json-output`] for the schema of each command.
+    ///
+    /// [`jj help -k json-output`]:
+    ///     https://docs.jj-vcs.dev/latest/json-output/
+    #[arg(long, value_name = "FORMAT", global = true)]
+    pub output_format: Option<OutputFormat>,
+
     /// Enable debug logging
     #[arg(long, global = true)]
     pub debug: bool,
@@ -4268,6 +4403,18 @@ fn parse_args(app: &Command, string_args: &[String]) -> Result<(ArgMatches, Args
     Ok((matches, args))
 }
 
+fn warn_if_output_format_ignored(ui: &Ui, command: &CommandHelper) -> io::Result<()> {
+    let data = &command.data;
+    if data.global_args.output_format.is_some() && !data.output_format_used.get() {
+        writeln!(
+            ui.warning_default(),
+            "`--output-format` is not supported by `jj {}` and was ignored",
+            command_name(&data.matches)
+        )?;
+    }
+    Ok(())
+}
+
 fn command_name(mut matches: &ArgMatches) -> String {
     let mut command = String::new();
     while let Some((subcommand, new_matches)) = matches.subcommand() {
@@ -4675,6 +4822,7 @@ impl<'a> CliRunner<'a> {
             store_factories: self.store_factories,
             working_copy_factories: self.working_copy_factories,
             workspace_loader_factory: self.workspace_loader_factory,
+            output_format_used: Cell::new(false),
         };
         let command_helper = CommandHelper {
             data: Rc::new(command_helper_data),
@@ -4688,7 +4836,9 @@ impl<'a> CliRunner<'a> {
                     };
                     Box::new(AsyncCliDispatchFn(f))
                 });
-        dispatch.call(ui, &command_helper).await
+        dispatch.call(ui, &command_helper).await?;
+        warn_if_output_format_ignored(ui, &command_helper)?;
+        Ok(())
     }
 
     #[must_use]
@@ -744,6 +744,7 @@ impl From<AbsorbError> for CommandError {
         match err {
             AbsorbError::Backend(err) => err.into(),
             AbsorbError::RevsetEvaluation(err) => err.into(),
+            AbsorbError::InvalidDestination { .. } => internal_error(err),
         }
     }
 }
@@ -842,6 +843,10 @@ fn file_pattern_parse_error_hint(err: &FilePatternParseError) -> Option<String>
         }
         FilePatternParseError::RelativePath(_) => None,
         FilePatternParseError::GlobPattern(_) => None,
+        FilePatternParseError::RegexPattern(_) => None,
+        FilePatternParseError::NameWithSeparator(_) => Some(String::from(
+            "Use substring: or regex: pattern to match path components.",
+        )),
     }
 }
 
@@ -1111,12 +1116,20 @@ fn handle_clap_error(ui: &mut Ui, err: &clap::Error, hints: &[ErrorHint]) -> io:
     // https://github.com/clap-rs/clap/blob/master/src/error/mod.rs
     match err.kind() {
         clap::error::ErrorKind::DisplayHelp | clap::error::ErrorKind::DisplayVersion => {
-            write!(ui.stdout(), "{clap_str}")?;
+            if ui.color() {
+                write!(ui.stdout(), "{clap_str}")?;
+            } else {
+                write!(ui.stdout_formatter(), "{clap_str}")?;
+            }
             return Ok(0);
         }
         _ => {}
     }
-    write!(ui.stderr(), "{clap_str}")?;
+    if ui.color() {
+        write!(ui.stderr(), "{clap_str}")?;
+    } else {
+        write!(ui.stderr_formatter(), "{clap_str}")?;
+    }
     // Skip the first source error, which should be printed inline.
     print_error_sources(ui, err.source().and_then(|err| err.source()))?;
     print_error_hints(ui, hints)?;
@@ -12,24 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
+use std::io::Write as _;
+use std::ops::Range;
+
+use bstr::ByteSlice as _;
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use indoc::formatdoc;
+use jj_lib::absorb::AbsorbHunk;
+use jj_lib::absorb::AbsorbHunkDestination;
 use jj_lib::absorb::AbsorbSource;
 use jj_lib::absorb::absorb_hunks;
 use jj_lib::absorb::split_hunks_to_trees;
+use jj_lib::backend::CommitId;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merge::Diff;
+use jj_lib::repo::Repo as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::cli_util::print_updated_commits;
 use crate::command_error::CommandError;
 use crate::command_error::user_error;
 use crate::complete;
 use crate::diff_util::DiffFormat;
+use crate::formatter::Formatter;
+use crate::formatter::FormatterExt as _;
 use crate::ui::Ui;
 
 /// Move changes from a revision into the stack of mutable revisions
@@ -47,6 +59,12 @@ use crate::ui::Ui;
 /// The source revision will be abandoned if all changes are absorbed into the
 /// destination revisions, and if the source revision has no description.
 ///
+/// With the `--dry-run` option, each hunk is shown with the revision it would
+/// be absorbed into, or the reason why it would be left in the source revision.
+/// With the `--pick-ambiguous` option, you will be asked to choose the
+/// destination of each hunk which matches multiple candidate revisions. Only
+/// the candidates to which the hunk applies without conflicts are offered.
+///
 /// The modification made by `jj absorb` can be reviewed by `jj op show -p`.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct AbsorbArgs {
@@ -81,6 +99,14 @@ pub(crate) struct AbsorbArgs {
     #[arg(long, value_name = "NAME")]
     #[arg(add = ArgValueCandidates::new(complete::diff_editors))]
     tool: Option<String>,
+
+    /// Show where each hunk would be absorbed without modifying the repo
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Choose the destination of hunks with multiple candidate revisions
+    #[arg(long)]
+    pick_ambiguous: bool,
 }
 
 #[instrument(skip_all)]
@@ -149,14 +175,74 @@ pub(crate) async fn cmd_absorb(
         AbsorbSource::from_commit(repo, source_commit.clone()).await?
     };
 
-    let selected_trees = split_hunks_to_trees(repo, &source, &destinations, &matcher).await?;
+    let mut selected_trees = split_hunks_to_trees(repo, &source, &destinations, &matcher).await?;
 
     let path_converter = workspace_command.path_converter();
-    for (path, reason) in selected_trees.skipped_paths {
-        let ui_path = path_converter.format_file_path(&path);
+    for (path, reason) in &selected_trees.skipped_paths {
+        let ui_path = path_converter.format_file_path(path);
         writeln!(ui.warning_default(), "Skipping {ui_path}: {reason}")?;
     }
 
+    if args.pick_ambiguous {
+        for index in 0..selected_trees.hunks.len() {
+            let hunk = &selected_trees.hunks[index];
+            if !matches!(hunk.destination, AbsorbHunkDestination::Ambiguous(_)) {
+                continue;
+            }
+            let clean_candidates = selected_trees.clean_candidates(repo, index).await?;
+            if clean_candidates.is_empty() {
+                writeln!(
+                    ui.warning_default(),
+                    "{} {} was not absorbed because it conflicts with all candidate destinations",
+                    workspace_command.format_file_path(&hunk.path),
+                    format_hunk_range(hunk)
+                )?;
+                continue;
+            }
+            if let Some(commit_id) =
+                pick_destination(ui, &workspace_command, hunk, &clean_candidates)?
+            {
+                selected_trees
+                    .absorb_hunk(repo, &source, index, &commit_id)
+                    .await?;
+            }
+        }
+    }
+
+    if args.dry_run {
+        ui.request_pager();
+        let mut formatter = ui.stdout_formatter();
+        for hunk in &selected_trees.hunks {
+            write_hunk(formatter.as_mut(), &workspace_command, hunk)?;
+        }
+        return Ok(());
+    }
+
+    let num_ambiguous = selected_trees
+        .hunks
+        .iter()
+        .filter(|hunk| matches!(hunk.destination, AbsorbHunkDestination::Ambiguous(_)))
+        .count();
+    if num_ambiguous > 0 && !args.pick_ambiguous {
+        if num_ambiguous == 1 {
+            writeln!(
+                ui.warning_default(),
+                "1 hunk was not absorbed because it has multiple candidate destinations"
+            )?;
+        } else {
+            writeln!(
+                ui.warning_default(),
+                "{num_ambiguous} hunks were not absorbed because they have multiple candidate \
+                 destinations"
+            )?;
+        }
+        writeln!(
+            ui.hint_default(),
+            "Use `jj absorb --dry-run` to show them, or `jj absorb --pick-ambiguous` to choose \
+             their destinations."
+        )?;
+    }
+
     workspace_command
         .check_rewritable(selected_trees.target_commits.keys())
         .await?;
@@ -211,3 +297,133 @@ pub(crate) async fn cmd_absorb(
     }
     Ok(())
 }
+
+fn format_hunk_range(hunk: &AbsorbHunk) -> String {
+    fn to_line_number(range: &Range<usize>) -> usize {
+        if range.is_empty() {
+            range.start
+        } else {
+            range.start + 1
+        }
+    }
+
+    format!(
+        "@@ -{},{} +{},{} @@",
+        to_line_number(&hunk.left_lines),
+        hunk.left_lines.len(),
+        to_line_number(&hunk.right_lines),
+        hunk.right_lines.len()
+    )
+}
+
+fn write_hunk_header(
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+    hunk: &AbsorbHunk,
+) -> io::Result<()> {
+    write!(
+        formatter,
+        "{} ",
+        workspace_command.format_file_path(&hunk.path)
+    )?;
+    write!(
+        formatter.labeled("diff").labeled("hunk_header"),
+        "{}",
+        format_hunk_range(hunk)
+    )
+}
+
+fn write_hunk_lines(formatter: &mut dyn Formatter, hunk: &AbsorbHunk) -> io::Result<()> {
+    let mut formatter = formatter.labeled("diff");
+    for (label, sigil, text) in [
+        ("removed", "-", &hunk.left_text),
+        ("added", "+", &hunk.right_text),
+    ] {
+        for line in text.lines_with_terminator() {
+            write!(formatter.labeled(label), "{sigil}")?;
+            formatter.labeled(label).write_all(line)?;
+            if !line.ends_with(b"\n") {
+                write!(formatter, "\n\\ No newline at end of file\n")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the hunk with its destination.
+fn write_hunk(
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+    hunk: &AbsorbHunk,
+) -> Result<(), CommandError> {
+    if hunk.destination == AbsorbHunkDestination::NewFile {
+        writeln!(
+            formatter,
+            "{} not absorbed: the file is new",
+            workspace_command.format_file_path(&hunk.path)
+        )?;
+        return Ok(());
+    }
+    write_hunk_header(formatter, workspace_command, hunk)?;
+    let candidates = match &hunk.destination {
+        AbsorbHunkDestination::Commits(commit_ids) => {
+            writeln!(formatter, " absorbed into:")?;
+            commit_ids
+        }
+        AbsorbHunkDestination::NewFile => unreachable!(),
+        AbsorbHunkDestination::NotInDestinations => {
+            writeln!(
+                formatter,
+                " not absorbed: the lines were not modified by any destination revision"
+            )?;
+            &vec![]
+        }
+        AbsorbHunkDestination::Ambiguous(commit_ids) => {
+            writeln!(
+                formatter,
+                " not absorbed: multiple candidate destination revisions:"
+            )?;
+            commit_ids
+        }
+    };
+    let store = workspace_command.repo().store();
+    for commit_id in candidates {
+        let commit = store.get_commit(commit_id)?;
+        write!(formatter, "  ")?;
+        workspace_command.write_commit_summary(formatter, &commit)?;
+        writeln!(formatter)?;
+    }
+    write_hunk_lines(formatter, hunk)?;
+    Ok(())
+}
+
+/// Prompts the user to choose the destination of the ambiguous hunk.
+fn pick_destination(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    hunk: &AbsorbHunk,
+    candidates: &[CommitId],
+) -> Result<Option<CommitId>, CommandError> {
+    let mut formatter = ui.stderr_formatter();
+    write_hunk_header(formatter.as_mut(), workspace_command, hunk)?;
+    writeln!(formatter, " can be absorbed into these revisions:")?;
+    write_hunk_lines(formatter.as_mut(), hunk)?;
+    let store = workspace_command.repo().store();
+    let mut choices = vec![];
+    for (index, commit_id) in candidates.iter().enumerate() {
+        let commit = store.get_commit(commit_id)?;
+        write!(formatter, "{}: ", index + 1)?;
+        workspace_command.write_commit_summary(formatter.as_mut(), &commit)?;
+        writeln!(formatter)?;
+        choices.push(format!("{}", index + 1));
+    }
+    writeln!(formatter, "s: leave it in the source revision")?;
+    choices.push("s".to_owned());
+    drop(formatter);
+    let index = ui.prompt_choice(
+        "Enter the index of the destination revision",
+        &choices,
+        Some(candidates.len()),
+    )?;
+    Ok(candidates.get(index).cloned())
+}
@@ -31,6 +31,7 @@ use crate::commit_ref_list::RefFilterPredicates;
 use crate::commit_ref_list::SortKey;
 use crate::commit_templater::CommitRef;
 use crate::complete;
+use crate::output_format::JsonListWriter;
 use crate::revset_util::parse_union_name_patterns;
 use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
@@ -195,12 +196,21 @@ pub async fn cmd_bookmark_list(
     commit_ref_list::sort(repo.store(), &mut bookmark_list_items, &sort_keys)?;
 
     ui.request_pager();
-    let mut formatter = ui.stdout_formatter();
-    bookmark_list_items
+    let commit_refs = bookmark_list_items
         .iter()
-        .flat_map(|item| itertools::chain([&item.primary], &item.tracked))
-        .try_for_each(|commit_ref| template.format(commit_ref, formatter.as_mut()))?;
-    drop(formatter);
+        .flat_map(|item| itertools::chain([&item.primary], &item.tracked));
+    if let Some(output_format) = command.output_format() {
+        let mut writer = JsonListWriter::new(ui.stdout(), output_format);
+        for commit_ref in commit_refs {
+            writer.write_item(commit_ref.as_ref())?;
+        }
+        writer.finish()?;
+    } else {
+        let mut formatter = ui.stdout_formatter();
+        for commit_ref in commit_refs {
+            template.format(commit_ref, formatter.as_mut())?;
+        }
+    }
 
     warn_unmatched_local_or_remote_bookmarks(ui, view, &name_expr)?;
 
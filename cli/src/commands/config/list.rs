@@ -28,6 +28,7 @@ use crate::config::AnnotatedValue;
 use crate::config::resolved_config_values;
 use crate::generic_templater;
 use crate::generic_templater::GenericTemplateLanguage;
+use crate::output_format::JsonListWriter;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
@@ -107,7 +108,14 @@ pub async fn cmd_config_list(
         annotated_values.retain(|annotated| !annotated.is_overridden);
     }
 
-    if !annotated_values.is_empty() {
+    if let Some(output_format) = command.output_format() {
+        ui.request_pager();
+        let mut writer = JsonListWriter::new(ui.stdout(), output_format);
+        for annotated in &annotated_values {
+            writer.write_item(annotated)?;
+        }
+        writer.finish()?;
+    } else if !annotated_values.is_empty() {
         ui.request_pager();
         let mut formatter = ui.stdout_formatter();
         for annotated in &annotated_values {
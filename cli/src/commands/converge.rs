@@ -38,6 +38,7 @@ use jj_lib::converge::find_divergent_changes;
 use jj_lib::files::FileMergeHunkLevel;
 use jj_lib::merge::MergeBuilder;
 use jj_lib::merge::SameChange;
+use jj_lib::merge_driver::MergeDrivers;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
 use jj_lib::tree_merge::MergeOptions;
@@ -596,6 +597,9 @@ fn materialize_conflicted_description(
         merge: MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: base_commit.store().merge_options().diff_algorithm,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         },
     };
     materialize_merge_result_to_bytes(&description_merge, &conflict_labels, &options).to_string()
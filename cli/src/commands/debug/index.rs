@@ -79,6 +79,42 @@ pub async fn cmd_debug_index(
             writeln!(ui.stdout(), "    Number of paths: {}", level.num_paths)?;
             writeln!(ui.stdout(), "    Name: {}", level.name)?;
         }
+
+        writeln!(ui.stdout(), "=== Commit metadata ===")?;
+        if let Some(range) = &stats.commit_metadata_commits_range {
+            writeln!(ui.stdout(), "Indexed commits: {range:?}")?;
+        } else {
+            writeln!(ui.stdout(), "Indexed commits: none")?;
+        }
+        writeln!(ui.stdout(), "Stats per level:")?;
+        for (i, level) in stats.commit_metadata_levels.iter().enumerate() {
+            writeln!(ui.stdout(), "  Level {i}:")?;
+            writeln!(ui.stdout(), "    Number of commits: {}", level.num_commits)?;
+            writeln!(
+                ui.stdout(),
+                "    Number of text bytes: {}",
+                level.num_text_bytes
+            )?;
+            writeln!(ui.stdout(), "    Name: {}", level.name)?;
+        }
+
+        writeln!(ui.stdout(), "=== Trigrams ===")?;
+        if let Some(range) = &stats.trigram_commits_range {
+            writeln!(ui.stdout(), "Indexed commits: {range:?}")?;
+        } else {
+            writeln!(ui.stdout(), "Indexed commits: none")?;
+        }
+        writeln!(ui.stdout(), "Stats per level:")?;
+        for (i, level) in stats.trigram_levels.iter().enumerate() {
+            writeln!(ui.stdout(), "  Level {i}:")?;
+            writeln!(ui.stdout(), "    Number of commits: {}", level.num_commits)?;
+            writeln!(
+                ui.stdout(),
+                "    Number of trigrams: {}",
+                level.num_trigrams
+            )?;
+            writeln!(ui.stdout(), "    Name: {}", level.name)?;
+        }
     } else {
         return Err(user_error(format!(
             "Cannot get stats for indexes of type '{}'",
@@ -16,6 +16,8 @@ mod copy_detection;
 mod fileset;
 mod index;
 mod index_changed_paths;
+mod index_commit_metadata;
+mod index_trigrams;
 mod init_simple;
 mod local_working_copy;
 mod object;
@@ -40,6 +42,10 @@ use self::index::DebugIndexArgs;
 use self::index::cmd_debug_index;
 use self::index_changed_paths::DebugIndexChangedPathsArgs;
 use self::index_changed_paths::cmd_debug_index_changed_paths;
+use self::index_commit_metadata::DebugIndexCommitMetadataArgs;
+use self::index_commit_metadata::cmd_debug_index_commit_metadata;
+use self::index_trigrams::DebugIndexTrigramsArgs;
+use self::index_trigrams::cmd_debug_index_trigrams;
 use self::init_simple::DebugInitSimpleArgs;
 use self::init_simple::cmd_debug_init_simple;
 use self::local_working_copy::DebugLocalWorkingCopyArgs;
@@ -75,6 +81,8 @@ pub enum DebugCommand {
     Fileset(DebugFilesetArgs),
     Index(DebugIndexArgs),
     IndexChangedPaths(DebugIndexChangedPathsArgs),
+    IndexCommitMetadata(DebugIndexCommitMetadataArgs),
+    IndexTrigrams(DebugIndexTrigramsArgs),
     InitSimple(DebugInitSimpleArgs),
     LocalWorkingCopy(DebugLocalWorkingCopyArgs),
     #[command(subcommand)]
@@ -102,6 +110,10 @@ pub async fn cmd_debug(
         DebugCommand::IndexChangedPaths(args) => {
             cmd_debug_index_changed_paths(ui, command, args).await
         }
+        DebugCommand::IndexCommitMetadata(args) => {
+            cmd_debug_index_commit_metadata(ui, command, args).await
+        }
+        DebugCommand::IndexTrigrams(args) => cmd_debug_index_trigrams(ui, command, args).await,
         DebugCommand::InitSimple(args) => cmd_debug_init_simple(ui, command, args).await,
         DebugCommand::LocalWorkingCopy(args) => {
             cmd_debug_local_working_copy(ui, command, args).await
@@ -121,12 +121,12 @@ pub(crate) async fn cmd_diff(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui).await?;
     let repo = workspace_command.repo();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let matcher = fileset_expression.to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns_with_filters(ui, &args.paths)?;
 
     let from_tree;
     let to_tree;
-    let mut copy_records = CopyRecords::default();
+    // Pairs of commits to detect copies between
+    let mut copy_commit_pairs = Vec::new();
     if args.from.is_some() || args.to.is_some() {
         let resolve_revision = async |r: &Option<RevisionArg>| {
             workspace_command
@@ -137,9 +137,7 @@ pub(crate) async fn cmd_diff(
         let to = resolve_revision(&args.to).await?;
         from_tree = from.tree();
         to_tree = to.tree();
-
-        let records = get_copy_records(repo.store(), from.id(), to.id(), &matcher).await?;
-        copy_records.add_records(records);
+        copy_commit_pairs.push((from.id().clone(), to.id().clone()));
     } else {
         let revision_args = args
             .revisions
@@ -186,12 +184,21 @@ pub(crate) async fn cmd_diff(
 
         for p in &parents {
             for to in &heads {
-                let records = get_copy_records(repo.store(), p.id(), to.id(), &matcher).await?;
-                copy_records.add_records(records);
+                copy_commit_pairs.push((p.id().clone(), to.id().clone()));
             }
         }
     }
 
+    let matcher = workspace_command
+        .resolve_file_filters(&fileset_expression, &[&from_tree, &to_tree])
+        .await?
+        .to_matcher();
+    let mut copy_records = CopyRecords::default();
+    for (from_id, to_id) in &copy_commit_pairs {
+        let records = get_copy_records(repo.store(), from_id, to_id, &matcher).await?;
+        copy_records.add_records(records);
+    }
+
     // -T disables both short/long rendering formats, but it might be okay to
     // enable long format if explicitly specified (assuming -T is for short or
     // summary output.)
@@ -212,7 +219,8 @@ pub(crate) async fn cmd_diff(
     ui.request_pager();
     if let Some(template) = &maybe_template {
         let tree_diff = from_tree.diff_stream_with_copies(&to_tree, &matcher, &copy_records);
-        show_templated(ui.stdout_formatter().as_mut(), tree_diff, template).await?;
+        let trees = Diff::new(&from_tree, &to_tree);
+        show_templated(ui.stdout_formatter().as_mut(), tree_diff, trees, template).await?;
     }
     diff_renderer
         .show_diff(
@@ -27,6 +27,8 @@ use crate::command_error::CommandError;
 use crate::command_error::user_error;
 use crate::commit_templater::AnnotationLine;
 use crate::complete;
+use crate::output_format::JsonListWriter;
+use crate::output_format::OutputFormat;
 use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
 
@@ -108,18 +110,28 @@ pub(crate) async fn cmd_file_annotate(
         .await?;
     let annotation = annotator.to_annotation();
 
-    render_file_annotation(repo.as_ref(), ui, &template, &annotation).await?;
+    render_file_annotation(
+        repo.as_ref(),
+        ui,
+        command.output_format(),
+        &template,
+        &annotation,
+    )
+    .await?;
     Ok(())
 }
 
 async fn render_file_annotation(
     repo: &dyn Repo,
     ui: &mut Ui,
+    output_format: Option<OutputFormat>,
     template_render: &TemplateRenderer<'_, AnnotationLine>,
     annotation: &FileAnnotation,
 ) -> Result<(), CommandError> {
     ui.request_pager();
-    let mut formatter = ui.stdout_formatter();
+    let mut json_writer =
+        output_format.map(|output_format| JsonListWriter::new(ui.stdout(), output_format));
+    let mut formatter = json_writer.is_none().then(|| ui.stdout_formatter());
     let mut last_id = None;
     // At least in cases where the repository was jj-initialized shallowly,
     // then unshallow'd with git, some changes will not have a commit id
@@ -143,9 +155,16 @@ async fn render_file_annotation(
             original_line_number: line_origin.line_number + 1,
             first_line_in_hunk,
         };
-        template_render.format(&annotation_line, formatter.as_mut())?;
+        if let Some(writer) = &mut json_writer {
+            writer.write_item(&annotation_line)?;
+        } else if let Some(formatter) = &mut formatter {
+            template_render.format(&annotation_line, formatter.as_mut())?;
+        }
         last_id = Some(&line_origin.commit_id);
     }
+    if let Some(writer) = json_writer {
+        writer.finish()?;
+    }
 
     Ok(())
 }
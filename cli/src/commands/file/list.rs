@@ -68,8 +68,11 @@ pub(crate) async fn cmd_file_list(
         .resolve_single_rev(ui, &args.revision)
         .await?;
     let tree = commit.tree();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let matcher = fileset_expression.to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns_with_filters(ui, &args.paths)?;
+    let matcher = workspace_command
+        .resolve_file_filters(&fileset_expression, &[&tree])
+        .await?
+        .to_matcher();
     let template: TemplateRenderer<TreeEntry> = {
         let language = workspace_command.commit_template_language();
         let text = match &args.template {
@@ -87,6 +90,7 @@ pub(crate) async fn cmd_file_list(
         let entry = TreeEntry {
             path,
             value: value?,
+            tree: tree.clone(),
         };
         template.format(&entry, formatter.as_mut())?;
     }
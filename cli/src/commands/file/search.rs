@@ -15,21 +15,44 @@
 use std::io;
 use std::io::Write as _;
 
+use bstr::BString;
+use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::BackendResult;
+use jj_lib::commit::Commit;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::conflicts::materialize_tree_value;
-use jj_lib::repo::Repo as _;
+use jj_lib::diff::DiffHunkKind;
+use jj_lib::diff_presentation::diff_by_line;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::matchers::FilesMatcher;
+use jj_lib::matchers::IntersectionMatcher;
+use jj_lib::matchers::Matcher;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::revset::DiffMatchSide;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::str_util::StringExpression;
 use jj_lib::str_util::StringMatcher;
 use jj_lib::str_util::StringPattern;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
 use crate::command_error::cli_error;
+use crate::commit_templater::FileSearchMatch;
 use crate::complete;
+use crate::diff_util::LineDiffOptions;
 use crate::formatter::Formatter;
+use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
 
 /// Search for content in files
@@ -37,8 +60,9 @@ use crate::ui::Ui;
 /// Prints each line that matches the specified pattern, prefixed by the file
 /// path. Use `--name-only` to print only the file paths.
 ///
-/// This is an early version of the command. It does not search files
-/// concurrently.
+/// With `--revisions`, searches the history instead. Each matched line is
+/// reported once, attributed to the commit where the line was added or
+/// changed, similar to `git log -S`.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FileSearchArgs {
     /// The revision to search files in
@@ -46,6 +70,15 @@ pub(crate) struct FileSearchArgs {
     #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
     revision: RevisionArg,
 
+    /// Search lines added or changed in these revisions
+    ///
+    /// Commits are searched concurrently, and the matches are printed in
+    /// reverse topological order.
+    #[arg(long, value_name = "REVSETS")]
+    #[arg(conflicts_with_all = ["revision", "name_only", "line_number"])]
+    #[arg(add = ArgValueCompleter::new(complete::revset_expression_all))]
+    revisions: Vec<RevisionArg>,
+
     /// The pattern to search for in a single line
     ///
     /// It is a [string pattern syntax] like `kind:pattern`.  The kind
@@ -68,6 +101,24 @@ pub(crate) struct FileSearchArgs {
     #[arg(long, short = 'n')]
     line_number: bool,
 
+    /// Render each matched line using the given template
+    ///
+    /// All 0-argument methods of the [`FileSearchMatch` type] are available as
+    /// keywords in the template expression. See [`jj help -k templates`] for
+    /// more information.
+    ///
+    /// If not specified, matches found by `--revisions` are rendered by the
+    /// `templates.file_search` setting.
+    ///
+    /// [`FileSearchMatch` type]:
+    ///     https://docs.jj-vcs.dev/latest/templates/#filesearchmatch-type
+    ///
+    /// [`jj help -k templates`]:
+    ///     https://docs.jj-vcs.dev/latest/templates/
+    #[arg(long, short = 'T', conflicts_with_all = ["name_only", "line_number"])]
+    #[arg(add = ArgValueCandidates::new(complete::template_aliases))]
+    template: Option<String>,
+
     /// Only search files matching these prefixes (instead of all files)
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     #[arg(add = ArgValueCompleter::new(complete::all_revision_files))]
@@ -81,71 +132,140 @@ pub(crate) async fn cmd_file_search(
     args: &FileSearchArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui).await?;
+    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let pattern = if let Some((kind, pattern)) = args.pattern.split_once(':') {
+        StringPattern::from_str_kind(pattern, kind)
+    } else {
+        StringPattern::from_str_kind(args.pattern.as_str(), "regex")
+    }
+    .map_err(cli_error)?;
+    let template_text = match &args.template {
+        Some(value) => Some(value.clone()),
+        None if !args.revisions.is_empty() => Some(
+            workspace_command
+                .settings()
+                .get_string("templates.file_search")?,
+        ),
+        None => None,
+    };
+    let language = workspace_command.commit_template_language();
+    let template = template_text
+        .map(|text| workspace_command.parse_template(ui, &language, &text))
+        .transpose()?;
+
+    if args.revisions.is_empty() {
+        search_revision(
+            ui,
+            &workspace_command,
+            args,
+            &fileset_expression,
+            &pattern,
+            template.as_ref(),
+        )
+        .await
+    } else {
+        search_revisions(
+            ui,
+            &workspace_command,
+            args,
+            &fileset_expression,
+            pattern,
+            &template.unwrap(),
+        )
+        .await
+    }
+}
+
+async fn search_revision(
+    ui: &mut Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &FileSearchArgs,
+    fileset_expression: &FilesetExpression,
+    pattern: &StringPattern,
+    template: Option<&TemplateRenderer<'_, FileSearchMatch>>,
+) -> Result<(), CommandError> {
     let commit = workspace_command
         .resolve_single_rev(ui, &args.revision)
         .await?;
     let tree = commit.tree();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let file_matcher = fileset_expression.to_matcher();
+    let mut file_matcher = fileset_expression.to_matcher();
+    // Files which can't contain matching lines can be skipped without reading
+    // if the index tracks changed lines.
+    if let Some(paths) = workspace_command
+        .repo()
+        .index()
+        .paths_maybe_containing_lines(commit.id(), &StringExpression::pattern(pattern.clone()))
+        .await?
+    {
+        file_matcher = Box::new(IntersectionMatcher::new(
+            file_matcher,
+            FilesMatcher::new(paths),
+        ));
+    }
 
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let store = workspace_command.repo().store().clone();
 
-    let pattern = if let Some((kind, pattern)) = args.pattern.split_once(':') {
-        StringPattern::from_str_kind(pattern, kind)
-    } else {
-        StringPattern::from_str_kind(args.pattern.as_str(), "regex")
-    }
-    .map_err(cli_error)?;
     let pattern_matcher = pattern.to_matcher();
-    // TODO: Read files concurrently (depending on backend)
-    for (path, value) in tree.entries_matching(file_matcher.as_ref()) {
-        let value = value?;
-        let materialized =
-            materialize_tree_value(store.as_ref(), &path, value, tree.labels()).await?;
-        match materialized {
-            MaterializedTreeValue::Absent => panic!("Entry for absent path in file listing"),
-            MaterializedTreeValue::AccessDenied(error) => {
-                let ui_path = workspace_command.format_file_path(&path);
+    let mut files = futures::stream::iter(tree.entries_matching(file_matcher.as_ref()))
+        .map(async |(path, value)| {
+            let materialized =
+                materialize_tree_value(store.as_ref(), &path, value?, tree.labels()).await?;
+            let contents = match materialized {
+                MaterializedTreeValue::Absent => panic!("Entry for absent path in file listing"),
+                MaterializedTreeValue::AccessDenied(error) => SearchedContents::AccessDenied(error),
+                MaterializedTreeValue::File(mut materialized_file_value) => {
+                    SearchedContents::Sides(vec![materialized_file_value.read_all(&path).await?])
+                }
+                // TODO: Optionally also print the conflict side
+                MaterializedTreeValue::FileConflict(materialized_file_value) => {
+                    let contents = materialized_file_value.contents;
+                    SearchedContents::Sides(contents.adds().map(|c| c.to_vec()).collect())
+                }
+                MaterializedTreeValue::Symlink { .. }
+                | MaterializedTreeValue::OtherConflict { .. }
+                | MaterializedTreeValue::GitSubmodule(_) => SearchedContents::Sides(vec![]),
+                MaterializedTreeValue::Tree(_) => panic!("Entry for tree in file listing"),
+            };
+            Ok::<_, BackendError>((path, contents))
+        })
+        .buffered(store.concurrency());
+    while let Some((path, contents)) = files.try_next().await? {
+        let ui_path = workspace_command.format_file_path(&path);
+        match contents {
+            SearchedContents::AccessDenied(error) => {
                 writeln!(
                     ui.warning_default(),
                     "Skipping '{ui_path}' due to permission error: {error}"
                 )?;
             }
-            MaterializedTreeValue::File(mut materialized_file_value) => {
-                let content = materialized_file_value.read_all(&path).await?;
-                // TODO: Make output templated
-                let ui_path = workspace_command.format_file_path(&path);
-                if args.name_only {
-                    if pattern_matcher.match_lines(&content).next().is_some() {
-                        writeln!(formatter, "{ui_path}")?;
+            SearchedContents::Sides(sides) => {
+                if let Some(template) = template {
+                    for content in &sides {
+                        for (line_number, line) in matching_lines(content, &pattern_matcher) {
+                            let search_match = FileSearchMatch {
+                                commit: commit.clone(),
+                                path: path.clone(),
+                                content: line_with_eol(line),
+                                line_number,
+                            };
+                            template.format(&search_match, formatter.as_mut())?;
+                        }
                     }
-                } else {
-                    write_matches(
-                        formatter.as_mut(),
-                        &ui_path,
-                        &content,
-                        &pattern_matcher,
-                        args.line_number,
-                    )?;
-                }
-            }
-            MaterializedTreeValue::Symlink { .. } => {}
-            MaterializedTreeValue::FileConflict(materialized_file_value) => {
-                let ui_path = workspace_command.format_file_path(&path);
-                // TODO: Optionally also print the conflict side
-                let mut adds = materialized_file_value.contents.adds();
-                if args.name_only {
-                    // Multiple blobs per file; print the path if any blob
-                    // matches.
-                    if adds.any(|c| pattern_matcher.match_lines(c).next().is_some()) {
+                } else if args.name_only {
+                    // Multiple blobs per conflicted file; print the path if
+                    // any blob matches.
+                    if sides
+                        .iter()
+                        .any(|c| pattern_matcher.match_lines(c).next().is_some())
+                    {
                         writeln!(formatter, "{ui_path}")?;
                     }
                 } else {
                     // -n numbers lines within each side independently; there
                     // is no meaningful unified line numbering across sides.
-                    for content in adds {
+                    for content in &sides {
                         write_matches(
                             formatter.as_mut(),
                             &ui_path,
@@ -156,15 +276,163 @@ pub(crate) async fn cmd_file_search(
                     }
                 }
             }
-            MaterializedTreeValue::OtherConflict { .. } => {}
-            MaterializedTreeValue::GitSubmodule(_) => {}
-            MaterializedTreeValue::Tree(_) => panic!("Entry for tree in file listing"),
         }
     }
-    print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
+    print_unmatched_explicit_paths(ui, workspace_command, fileset_expression, [&tree])?;
     Ok(())
 }
 
+async fn search_revisions(
+    ui: &mut Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &FileSearchArgs,
+    fileset_expression: &FilesetExpression,
+    pattern: StringPattern,
+    template: &TemplateRenderer<'_, FileSearchMatch>,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    let pattern_matcher = pattern.to_matcher();
+    let file_matcher = fileset_expression.to_matcher();
+    let line_diff_options = LineDiffOptions::from_settings(workspace_command.settings())?;
+    // Narrow down the commits by revset filter, which may be accelerated by
+    // the index.
+    let mut revset = workspace_command.parse_union_revsets(ui, &args.revisions)?;
+    revset.intersect_with(&RevsetExpression::filter(
+        RevsetFilterPredicate::DiffLines {
+            text: StringExpression::pattern(pattern),
+            files: fileset_expression.clone(),
+            side: DiffMatchSide::Right,
+        },
+    ));
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let mut commit_matches = revset
+        .evaluate_to_commits()?
+        .map_err(CommandError::from)
+        .map_ok(async |commit| {
+            let matches = find_added_lines(
+                repo.as_ref(),
+                &commit,
+                &*file_matcher,
+                &pattern_matcher,
+                &line_diff_options,
+            )
+            .await?;
+            Ok::<_, CommandError>((commit, matches))
+        })
+        .try_buffered(repo.store().concurrency());
+    while let Some((commit, matches)) = commit_matches.try_next().await? {
+        for (path, line_number, content) in matches {
+            let search_match = FileSearchMatch {
+                commit: commit.clone(),
+                path,
+                content,
+                line_number,
+            };
+            template.format(&search_match, formatter.as_mut())?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds lines added or changed by the `commit` which match the pattern.
+/// Returns list of `(path, line_number, content)`.
+async fn find_added_lines(
+    repo: &dyn Repo,
+    commit: &Commit,
+    file_matcher: &dyn Matcher,
+    pattern_matcher: &StringMatcher,
+    line_diff_options: &LineDiffOptions,
+) -> BackendResult<Vec<(RepoPathBuf, usize, BString)>> {
+    let store = repo.store();
+    let from_tree = commit.parent_tree(repo).await?;
+    let to_tree = commit.tree();
+    let mut tree_diff = from_tree.diff_stream(&to_tree, file_matcher);
+    let mut matches = vec![];
+    while let Some(entry) = tree_diff.next().await {
+        let values = entry.values?;
+        let left_value =
+            materialize_tree_value(store, &entry.path, values.before, from_tree.labels()).await?;
+        let right_value =
+            materialize_tree_value(store, &entry.path, values.after, to_tree.labels()).await?;
+        let left = file_content_for_search(&entry.path, left_value).await?;
+        // Lines of conflicted file are numbered within each side, and the
+        // lines added to multiple sides are reported once.
+        let right_sides = match right_value {
+            MaterializedTreeValue::File(mut file) => vec![file.read_all(&entry.path).await?],
+            MaterializedTreeValue::FileConflict(file) => {
+                file.contents.adds().map(|c| c.to_vec()).collect()
+            }
+            _ => continue,
+        };
+        let mut file_matches = vec![];
+        for right in &right_sides {
+            let diff = diff_by_line(
+                [&left, right],
+                &line_diff_options.compare_mode,
+                line_diff_options.algorithm,
+            );
+            let mut line_number = 1;
+            for hunk in diff.hunks() {
+                let right_lines = hunk.contents[1].split_inclusive(|b| *b == b'\n');
+                if hunk.kind == DiffHunkKind::Matching {
+                    line_number += right_lines.count();
+                    continue;
+                }
+                for line in right_lines {
+                    let stripped = line.strip_suffix(b"\n").unwrap_or(line);
+                    if pattern_matcher.is_match_bytes(stripped) {
+                        file_matches.push((line_number, line_with_eol(line)));
+                    }
+                    line_number += 1;
+                }
+            }
+        }
+        file_matches.sort_unstable();
+        file_matches.dedup();
+        matches.extend(
+            file_matches
+                .into_iter()
+                .map(|(line_number, content)| (entry.path.clone(), line_number, content)),
+        );
+    }
+    Ok(matches)
+}
+
+/// Returns the contents of the old file to compare with. Lines in any side
+/// of a conflicted file aren't considered new.
+async fn file_content_for_search(
+    path: &RepoPath,
+    value: MaterializedTreeValue,
+) -> BackendResult<Vec<u8>> {
+    match value {
+        MaterializedTreeValue::File(mut file) => file.read_all(path).await,
+        MaterializedTreeValue::FileConflict(file) => Ok(file
+            .contents
+            .adds()
+            .flat_map(|c| c.iter().copied())
+            .collect()),
+        _ => Ok(vec![]),
+    }
+}
+
+fn line_with_eol(line: &[u8]) -> BString {
+    let mut line = BString::from(line);
+    if !line.ends_with(b"\n") {
+        line.push(b'\n');
+    }
+    line
+}
+
+/// File contents to be searched.
+enum SearchedContents {
+    /// The file couldn't be read.
+    AccessDenied(Box<dyn std::error::Error + Send + Sync>),
+    /// Contents of the file, or of each side if the file is conflicted.
+    Sides(Vec<Vec<u8>>),
+}
+
 fn write_matches(
     formatter: &mut dyn Formatter,
     ui_path: &str,
@@ -172,14 +440,7 @@ fn write_matches(
     matcher: &StringMatcher,
     line_number: bool,
 ) -> io::Result<()> {
-    let matches = content
-        .split_inclusive(|b| *b == b'\n')
-        .enumerate()
-        .filter_map(|(i, line)| {
-            let stripped = line.strip_suffix(b"\n").unwrap_or(line);
-            matcher.is_match_bytes(stripped).then_some((i + 1, line))
-        });
-    for (line_no, line) in matches {
+    for (line_no, line) in matching_lines(content, matcher) {
         if line_number {
             write!(formatter, "{ui_path}:{line_no}:")?;
         } else {
@@ -192,3 +453,17 @@ fn write_matches(
     }
     Ok(())
 }
+
+/// Iterates matching lines with 1-based line numbers.
+fn matching_lines<'a>(
+    content: &'a [u8],
+    matcher: &StringMatcher,
+) -> impl Iterator<Item = (usize, &'a [u8])> {
+    content
+        .split_inclusive(|b| *b == b'\n')
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let stripped = line.strip_suffix(b"\n").unwrap_or(line);
+            matcher.is_match_bytes(stripped).then_some((i + 1, line))
+        })
+}
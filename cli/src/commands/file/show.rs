@@ -112,6 +112,7 @@ pub(crate) async fn cmd_file_show(
             let entry = TreeEntry {
                 path: path.to_owned(),
                 value,
+                tree: tree.clone(),
             };
             write_tree_entries(ui, &workspace_command, &template, &tree, [Ok(entry)]).await?;
             return Ok(());
@@ -127,7 +128,11 @@ pub(crate) async fn cmd_file_show(
         &tree,
         tree.entries_matching(matcher.as_ref())
             .map(|(path, value)| Ok((path, value?)))
-            .map_ok(|(path, value)| TreeEntry { path, value }),
+            .map_ok(|(path, value)| TreeEntry {
+                path,
+                value,
+                tree: tree.clone(),
+            }),
     )
     .await?;
     print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
@@ -140,7 +145,10 @@ fn get_single_path(expression: &FilesetExpression) -> Option<&RepoPath> {
             // Not using pattern.as_path() because files-in:<path> shouldn't
             // select the literal <path> itself.
             FilePattern::FilePath(path) | FilePattern::PrefixPath(path) => Some(path),
-            FilePattern::FileGlob { .. } | FilePattern::PrefixGlob { .. } => None,
+            FilePattern::FilesInPath(_)
+            | FilePattern::FileGlob { .. }
+            | FilePattern::PrefixGlob { .. }
+            | FilePattern::PathRegex { .. } => None,
         },
         _ => None,
     }
@@ -226,8 +226,11 @@ pub(crate) async fn cmd_fix(
 
     let trees: Vec<_> = commits.iter().map(|commit| commit.tree()).collect();
 
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let matcher = fileset_expression.to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns_with_filters(ui, &args.paths)?;
+    let matcher = workspace_command
+        .resolve_file_filters(&fileset_expression, &trees.iter().collect_vec())
+        .await?
+        .to_matcher();
 
     let mut tx = workspace_command.start_transaction();
     let mut parallel_fixer = ParallelFileFixer::new(|store, file_to_fix| {
@@ -567,6 +570,11 @@ fn get_tools_config(
                 ));
             }
             print_parse_diagnostics(ui, &format!("In `fix.tools.{name}`"), &diagnostics)?;
+            if expression.has_filters() {
+                return Err(config_error(format!(
+                    "File predicates are not supported in `fix.tools.{name}.patterns`"
+                )));
+            }
             Ok(ToolConfig {
                 command: tool.command,
                 matcher: expression.to_matcher(),
@@ -22,6 +22,7 @@ use jj_lib::repo::Repo as _;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
+use crate::output_format::JsonListWriter;
 use crate::ui::Ui;
 
 /// List Git remotes
@@ -35,6 +36,9 @@ pub async fn cmd_git_remote_list(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui).await?;
     let git_repo = git::get_git_repo(workspace_command.repo().store())?;
+    let mut json_writer = command
+        .output_format()
+        .map(|output_format| JsonListWriter::new(ui.stdout(), output_format));
     for remote_name in git_repo.remote_names() {
         let Ok(remote_name) = str::from_utf8(&remote_name).map(RemoteName::new) else {
             continue; // ignore non-UTF-8 remote names which we don't support
@@ -42,6 +46,14 @@ pub async fn cmd_git_remote_list(
         let Some(remote) = git::try_find_active_remote(&git_repo, remote_name)? else {
             continue; // ignore empty [remote "<name>"] section
         };
+        if let Some(writer) = &mut json_writer {
+            writer.write_item(&RemoteOutput {
+                name: remote_name.as_str(),
+                fetch_url: get_json_url(&remote, gix::remote::Direction::Fetch),
+                push_url: get_json_url(&remote, gix::remote::Direction::Push),
+            })?;
+            continue;
+        }
         let fetch_url = get_url(&remote, gix::remote::Direction::Fetch);
         let push_url = get_url(&remote, gix::remote::Direction::Push);
         if fetch_url == push_url {
@@ -58,12 +70,29 @@ pub async fn cmd_git_remote_list(
             )?;
         }
     }
+    if let Some(writer) = json_writer {
+        writer.finish()?;
+    }
     Ok(())
 }
 
+/// Remote entry printed by `--output-format`.
+#[derive(serde::Serialize)]
+struct RemoteOutput<'a> {
+    name: &'a str,
+    fetch_url: Option<String>,
+    push_url: Option<String>,
+}
+
 fn get_url(remote: &Remote, direction: gix::remote::Direction) -> BString {
     remote
         .url(direction)
         .map(|url| url.to_bstring())
         .unwrap_or_else(|| "<no URL>".into())
 }
+
+fn get_json_url(remote: &Remote, direction: gix::remote::Direction) -> Option<String> {
+    remote
+        .url(direction)
+        .map(|url| url.to_bstring().to_string())
+}
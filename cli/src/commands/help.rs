@@ -88,7 +88,7 @@ pub(crate) async fn cmd_help(
     if ui.color() {
         write!(ui.stdout(), "{}", help_text.ansi())?;
     } else {
-        write!(ui.stdout(), "{help_text}")?;
+        write!(ui.stdout_formatter(), "{help_text}")?;
     }
     Ok(())
 }
@@ -134,6 +134,11 @@ const KEYWORDS: &[Keyword] = &[
         description: "Definitions of various terms",
         content: include_str!(concat!("../../", env!("JJ_DOCS_DIR"), "glossary.md")),
     },
+    Keyword {
+        name: "json-output",
+        description: "Machine-readable output of listing commands",
+        content: include_str!(concat!("../../", env!("JJ_DOCS_DIR"), "json-output.md")),
+    },
     Keyword {
         name: "revsets",
         description: "A functional language for selecting a set of revision",
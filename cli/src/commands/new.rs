@@ -207,6 +207,7 @@ pub(crate) async fn cmd_new(
 
     let mut tx = workspace_command.start_transaction();
     let merged_tree = merge_commit_trees(tx.repo(), &parent_commits).await?;
+    let merged_tree = tx.repo_mut().auto_resolve_conflicts(merged_tree).await?;
     let mut commit_builder = tx
         .repo_mut()
         .new_commit(parent_commit_ids, merged_tree)
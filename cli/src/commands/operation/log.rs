@@ -41,6 +41,8 @@ use crate::formatter::Formatter;
 use crate::graphlog::GraphStyle;
 use crate::graphlog::get_graphlog;
 use crate::operation_templater::OperationTemplateLanguage;
+use crate::output_format::JsonListWriter;
+use crate::output_format::OutputFormat;
 use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
 
@@ -113,7 +115,15 @@ pub async fn cmd_op_log(
         let workspace_command = command.workspace_helper(ui).await?;
         let current_op = workspace_command.repo().operation();
         let repo_loader = workspace_command.workspace().repo_loader();
-        do_op_log(ui, workspace_command.env(), repo_loader, current_op, args).await
+        do_op_log(
+            ui,
+            command.output_format(),
+            workspace_command.env(),
+            repo_loader,
+            current_op,
+            args,
+        )
+        .await
     } else {
         // Don't load the repo so that the operation history can be inspected
         // even with a corrupted repo state. For example, you can find the first
@@ -123,12 +133,21 @@ pub async fn cmd_op_log(
         let repo_loader = workspace.repo_loader();
         let current_op =
             command.resolve_operation(ui, workspace.repo_loader(), workspace.workspace_name())?;
-        do_op_log(ui, &workspace_env, repo_loader, &current_op, args).await
+        do_op_log(
+            ui,
+            command.output_format(),
+            &workspace_env,
+            repo_loader,
+            &current_op,
+            args,
+        )
+        .await
     }
 }
 
 async fn do_op_log(
     ui: &mut Ui,
+    output_format: Option<OutputFormat>,
     workspace_env: &WorkspaceCommandEnvironment,
     repo_loader: &RepoLoader,
     current_op: &Operation,
@@ -233,11 +252,26 @@ async fn do_op_log(
     };
 
     ui.request_pager();
-    let mut formatter = ui.stdout_formatter();
-    let formatter = formatter.as_mut();
     let stream =
         op_walk::walk_ancestors(slice::from_ref(current_op)).take(args.limit.unwrap_or(usize::MAX));
 
+    if let Some(output_format) = output_format {
+        // Graph and diff aren't included in machine-readable output.
+        let mut ops: Vec<_> = stream.try_collect().await?;
+        if args.reversed {
+            ops.reverse();
+        }
+        let mut writer = JsonListWriter::new(ui.stdout(), output_format);
+        for op in &ops {
+            writer.write_item(op)?;
+        }
+        writer.finish()?;
+        return Ok(());
+    }
+
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+
     if !args.no_graph {
         let mut raw_output = formatter.raw()?;
         let mut graph = get_graphlog(graph_style, raw_output.as_mut());
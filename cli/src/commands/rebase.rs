@@ -12,24 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
 use std::io::Write as _;
+use std::iter;
+use std::iter::zip;
+use std::process::Command;
 use std::sync::Arc;
 
 use clap::ArgGroup;
 use clap_complete::ArgValueCompleter;
 use futures::TryStreamExt as _;
+use indoc::indoc;
+use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
 use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetStreamExt as _;
 use jj_lib::rewrite::EmptyBehavior;
 use jj_lib::rewrite::MoveCommitsLocation;
 use jj_lib::rewrite::MoveCommitsStats;
 use jj_lib::rewrite::MoveCommitsTarget;
+use jj_lib::rewrite::PlannedCommit;
 use jj_lib::rewrite::RebaseOptions;
 use jj_lib::rewrite::RewriteRefsOptions;
+use jj_lib::rewrite::apply_rebase_plan;
 use jj_lib::rewrite::compute_move_commits;
 use jj_lib::rewrite::find_duplicate_divergent_commits;
 use tracing::instrument;
@@ -41,8 +52,13 @@ use crate::cli_util::compute_commit_location;
 use crate::cli_util::print_updated_commits;
 use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
+use crate::command_error::cli_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::complete;
+use crate::description_util::TextEditor;
+use crate::description_util::edit_description;
+use crate::description_util::try_combine_messages;
 use crate::ui::Ui;
 
 /// Move revisions to different parent(s)
@@ -262,6 +278,30 @@ use crate::ui::Ui;
 /// |/          |/
 /// J           J
 /// ```
+///
+/// ### Rebasing interactively
+///
+/// With `--interactive/-i`, a plan listing the revisions to rebase is opened in
+/// an editor, similar to `git rebase --interactive`. For example, `jj rebase -i
+/// -o main` opens a plan for the revisions between `main` and `@`:
+///
+/// ```text
+/// pick 8f0a5b3c1d2e Add feature
+/// pick 2c4d6e8f0a1b Fix typo in feature
+/// pick 9e7d5c3b1a0f Add tests
+/// ```
+///
+/// Each line can be reordered or given a different action, such as `squash`
+/// or `drop`. The actions are described in the plan. Since conflicts don't
+/// stop the rebase, the whole plan is applied in a single operation. The
+/// revisions must form a linear chain.
+///
+/// Unlike `git rebase`, `exec` lines don't run while the plan is being
+/// applied. The commands run in plan order after the rebase has finished,
+/// from the workspace root, and the working copy isn't updated to the
+/// revisions. Each command is run by the shell (`sh -c`, or `cmd /C` on
+/// Windows), and can refer to the revision listed above it by `$JJ_COMMIT_ID`,
+/// e.g. `exec jj log -r $JJ_COMMIT_ID`.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 #[command(group(ArgGroup::new("to_rebase").args(&["branch", "source", "revisions"])))]
@@ -323,6 +363,17 @@ pub(crate) struct RebaseArgs {
     /// removed.
     #[arg(long)]
     simplify_parents: bool,
+
+    /// Edit the plan of the rebase in a text editor
+    ///
+    /// The plan is edited with the `ui.sequence-editor` setting, which
+    /// defaults to `ui.editor`.
+    #[arg(
+        long,
+        short,
+        conflicts_with_all = ["skip_emptied", "keep_divergent", "simplify_parents"],
+    )]
+    interactive: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -381,6 +432,11 @@ pub(crate) async fn cmd_rebase(
         },
         simplify_ancestor_merge: args.simplify_parents,
     };
+    if args.interactive && args.destination.onto.is_none() {
+        return Err(cli_error(
+            "`--interactive` requires a destination given by `--onto`",
+        ));
+    }
     let mut workspace_command = command.workspace_helper(ui).await?;
 
     let loc = if !args.revisions.is_empty() {
@@ -399,6 +455,9 @@ pub(crate) async fn cmd_rebase(
         writeln!(ui.status(), "No revisions to rebase.")?;
         return Ok(());
     }
+    if args.interactive {
+        return rebase_interactively(ui, &mut workspace_command, loc).await;
+    }
 
     let mut tx = workspace_command.start_transaction();
     let mut computed_move = compute_move_commits(tx.repo(), &loc).await?;
@@ -652,3 +711,359 @@ fn print_move_commits_stats(ui: &Ui, stats: &MoveCommitsStats) -> std::io::Resul
     }
     Ok(())
 }
+
+/// Action for a revision in the plan of `jj rebase --interactive`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PlanAction {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl PlanAction {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "p" | "pick" => Some(Self::Pick),
+            "r" | "reword" => Some(Self::Reword),
+            "e" | "edit" => Some(Self::Edit),
+            "s" | "squash" => Some(Self::Squash),
+            "f" | "fixup" => Some(Self::Fixup),
+            "d" | "drop" => Some(Self::Drop),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Pick => "pick",
+            Self::Reword => "reword",
+            Self::Edit => "edit",
+            Self::Squash => "squash",
+            Self::Fixup => "fixup",
+            Self::Drop => "drop",
+        }
+    }
+}
+
+/// Line of the plan of `jj rebase --interactive`.
+#[derive(Debug, PartialEq, Eq)]
+enum PlanLine {
+    Revision(PlanAction, CommitId),
+    Exec(String),
+}
+
+/// Revisions of the plan which are combined into a single new revision.
+struct PlanGroup<'a> {
+    /// Action of the first revision, which is `pick`, `reword`, or `edit`.
+    action: PlanAction,
+    commit: &'a Commit,
+    /// Revisions squashed into the first revision with `squash` or `fixup`.
+    squashed: Vec<(PlanAction, &'a Commit)>,
+}
+
+const REBASE_PLAN_HELP: &str = indoc! {r#"
+    JJ: Commands:
+    JJ: p, pick <revision> = keep the revision
+    JJ: r, reword <revision> = keep the revision, but edit its description
+    JJ: e, edit <revision> = keep the revision, and edit it in the working copy
+    JJ: s, squash <revision> = move the changes into the previous revision
+    JJ: f, fixup <revision> = like "squash", but discard the description
+    JJ: d, drop <revision> = abandon the revision
+    JJ: x, exec <command> = run the command after the whole rebase, with
+    JJ:                     $JJ_COMMIT_ID set to the previous revision
+    JJ:                     (the revision isn't checked out)
+    JJ:
+    JJ: The revisions are listed from the oldest one, and can be reordered.
+    JJ: Removing the line of a revision abandons it.
+    JJ: If the plan is emptied, the rebase is aborted.
+"#};
+
+async fn rebase_interactively(
+    ui: &Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    loc: MoveCommitsLocation,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo().clone();
+    let target_expression = match loc.target {
+        MoveCommitsTarget::Commits(ids) => RevsetExpression::commits(ids),
+        MoveCommitsTarget::Roots(ids) => RevsetExpression::commits(ids).descendants(),
+    };
+    workspace_command
+        .check_rewritable_expr(&target_expression)
+        .await?;
+    let mut commits: Vec<Commit> = target_expression
+        .evaluate(repo.as_ref())?
+        .stream()
+        .commits(repo.store())
+        .try_collect()
+        .await?;
+    commits.reverse();
+    if let Some((parent, child)) = commits
+        .iter()
+        .tuple_windows()
+        .find(|(parent, child)| child.parent_ids() != [parent.id().clone()])
+    {
+        return Err(user_error(
+            "Cannot rebase revisions interactively unless they form a linear chain",
+        )
+        .hinted(format!(
+            "Revision {} is not the only parent of revision {}.",
+            short_commit_hash(parent.id()),
+            short_commit_hash(child.id()),
+        )));
+    }
+    check_rebase_destinations(&repo, &loc.new_parent_ids, &commits[0]).await?;
+
+    let editor = TextEditor::sequence_editor_from_settings(workspace_command.settings())?;
+    let plan = editor
+        .edit_str(
+            format_rebase_plan(&commits, &loc.new_parent_ids),
+            Some(".jjrebase"),
+        )
+        .map_err(|err| err.with_name("rebase plan"))?;
+    let plan = parse_rebase_plan(&plan, &commits)?;
+    if plan.is_empty() {
+        return Err(user_error("The rebase plan is empty, nothing to do"));
+    }
+
+    let commits_by_id: HashMap<&CommitId, &Commit> =
+        commits.iter().map(|commit| (commit.id(), commit)).collect();
+    let mut groups: Vec<PlanGroup> = vec![];
+    let mut exec_commands: Vec<(Option<usize>, String)> = vec![];
+    let mut kept_ids = HashSet::new();
+    for line in plan {
+        let (action, commit) = match line {
+            PlanLine::Revision(action, id) => (action, commits_by_id[&id]),
+            PlanLine::Exec(command) => {
+                exec_commands.push((groups.len().checked_sub(1), command));
+                continue;
+            }
+        };
+        match action {
+            PlanAction::Pick | PlanAction::Reword | PlanAction::Edit => {
+                groups.push(PlanGroup {
+                    action,
+                    commit,
+                    squashed: vec![],
+                });
+            }
+            PlanAction::Squash | PlanAction::Fixup => {
+                let Some(group) = groups.last_mut() else {
+                    return Err(user_error(format!(
+                        "Cannot {} revision {} without a previous revision",
+                        action.name(),
+                        short_commit_hash(commit.id()),
+                    )));
+                };
+                group.squashed.push((action, commit));
+            }
+            PlanAction::Drop => continue,
+        }
+        kept_ids.insert(commit.id());
+    }
+    let edit_indices = groups
+        .iter()
+        .positions(|group| group.action == PlanAction::Edit)
+        .collect_vec();
+    if edit_indices.len() > 1 {
+        return Err(user_error(
+            "Only one revision can be edited in the working copy",
+        ));
+    }
+
+    let text_editor = workspace_command.text_editor()?;
+    let mut planned = Vec::with_capacity(groups.len());
+    for group in &groups {
+        planned.push(PlannedCommit {
+            commits: iter::once(group.commit)
+                .chain(group.squashed.iter().map(|(_, commit)| *commit))
+                .cloned()
+                .collect(),
+            description: plan_group_description(&text_editor, group)?,
+        });
+    }
+    let dropped = commits
+        .iter()
+        .filter(|commit| !kept_ids.contains(commit.id()))
+        .cloned()
+        .collect_vec();
+
+    let mut tx = workspace_command.start_transaction();
+    let new_commits =
+        apply_rebase_plan(tx.repo_mut(), loc.new_parent_ids.clone(), planned, &dropped).await?;
+    if let Some(&index) = edit_indices.first() {
+        tx.edit(&new_commits[index])?;
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        let num_rewritten = zip(&groups, &new_commits)
+            .filter(|(group, new_commit)| group.commit.id() != new_commit.id())
+            .count();
+        if num_rewritten > 0 {
+            writeln!(formatter, "Rebased {num_rewritten} commits.")?;
+        }
+        if !dropped.is_empty() {
+            writeln!(formatter, "Abandoned {} commits.", dropped.len())?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!("rebase {} commits interactively", commits.len()),
+    )
+    .await?;
+
+    // Unlike `git rebase`, the commands run after the transaction is finished,
+    // without checking out the revisions, since the plan is applied at once.
+    for (index, command) in exec_commands {
+        let commit_id = match index {
+            Some(index) => new_commits[index].id(),
+            None => &loc.new_parent_ids[0],
+        };
+        run_plan_command(ui, workspace_command, &command, commit_id)?;
+    }
+    Ok(())
+}
+
+fn format_rebase_plan(commits: &[Commit], new_parent_ids: &[CommitId]) -> String {
+    let mut plan = String::new();
+    for commit in commits {
+        let summary = commit
+            .description()
+            .lines()
+            .next()
+            .unwrap_or("(no description set)");
+        writeln!(plan, "pick {} {summary}", short_commit_hash(commit.id())).unwrap();
+    }
+    writeln!(
+        plan,
+        "\nJJ: Rebase {} revisions onto {}",
+        commits.len(),
+        new_parent_ids.iter().map(short_commit_hash).join(", "),
+    )
+    .unwrap();
+    plan.push_str("JJ:\n");
+    plan.push_str(REBASE_PLAN_HELP);
+    plan
+}
+
+fn parse_rebase_plan(text: &str, commits: &[Commit]) -> Result<Vec<PlanLine>, CommandError> {
+    let mut plan = vec![];
+    let mut seen_ids = HashSet::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ:") {
+            continue;
+        }
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, rest)| (name, rest.trim_start()));
+        if name == "x" || name == "exec" {
+            if rest.is_empty() {
+                return Err(user_error(format!(
+                    "Missing command in rebase plan: `{line}`"
+                )));
+            }
+            plan.push(PlanLine::Exec(rest.to_owned()));
+            continue;
+        }
+        let Some(action) = PlanAction::parse(name) else {
+            return Err(user_error(format!(
+                "Unknown action `{name}` in rebase plan: `{line}`"
+            )));
+        };
+        let prefix = rest.split_whitespace().next().unwrap_or_default();
+        let matching = commits
+            .iter()
+            .filter(|commit| !prefix.is_empty() && commit.id().hex().starts_with(prefix))
+            .collect_vec();
+        let commit = match matching.as_slice() {
+            [commit] => commit,
+            [] => {
+                return Err(user_error(format!(
+                    "Unknown revision in rebase plan: `{line}`"
+                )));
+            }
+            _ => {
+                return Err(user_error(format!(
+                    "Ambiguous revision in rebase plan: `{line}`"
+                )));
+            }
+        };
+        if !seen_ids.insert(commit.id()) {
+            return Err(user_error(format!(
+                "Revision {} appears more than once in the rebase plan",
+                short_commit_hash(commit.id()),
+            )));
+        }
+        plan.push(PlanLine::Revision(action, commit.id().clone()));
+    }
+    Ok(plan)
+}
+
+fn plan_group_description(
+    text_editor: &TextEditor,
+    group: &PlanGroup,
+) -> Result<String, CommandError> {
+    let squashed = group
+        .squashed
+        .iter()
+        .filter(|(action, _)| *action == PlanAction::Squash)
+        .map(|(_, commit)| (*commit).clone())
+        .collect_vec();
+    if group.action != PlanAction::Reword
+        && let Some(description) = try_combine_messages(&squashed, group.commit)
+    {
+        return Ok(description);
+    }
+    let mut combined = String::new();
+    if squashed.is_empty() {
+        combined.push_str(group.commit.description());
+    } else {
+        combined.push_str("JJ: Description from the destination commit:\n");
+        combined.push_str(group.commit.description());
+        for commit in &squashed {
+            combined.push_str("\nJJ: Description from source commit:\n");
+            combined.push_str(commit.description());
+        }
+    }
+    edit_description(text_editor, &combined)
+}
+
+fn run_plan_command(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    command: &str,
+    commit_id: &CommitId,
+) -> Result<(), CommandError> {
+    writeln!(ui.status(), "Executing: {command}")?;
+    let status = shell_command(command)
+        .current_dir(workspace_command.workspace_root())
+        .env("JJ_WORKSPACE_ROOT", workspace_command.workspace_root())
+        .env("JJ_COMMIT_ID", commit_id.hex())
+        .status()
+        .map_err(|err| {
+            user_error_with_message(format!("Failed to execute command '{command}'"), err)
+        })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(
+            user_error(format!("Command '{command}' failed with {status}"))
+                .hinted("The rebase has already been applied. Use `jj undo` to revert it."),
+        )
+    }
+}
+
+/// Builds a command to run the `command` line by the platform shell.
+fn shell_command(command: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut cmd = Command::new(shell);
+    cmd.arg(flag).arg(command);
+    cmd
+}
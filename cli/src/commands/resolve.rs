@@ -15,17 +15,32 @@
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
+use jj_lib::backend::BackendResult;
+use jj_lib::backend::MergedTreeValue;
+use jj_lib::conflicts::ConflictMaterializeOptions;
+use jj_lib::conflicts::choose_materialized_conflict_marker_len;
+use jj_lib::conflicts::find_conflict_line_ranges;
+use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::conflicts::try_materialize_file_conflict_value;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::rerere::RerereStore;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
 use crate::command_error::cli_error;
+use crate::command_error::user_error;
 use crate::complete;
 use crate::formatter::FormatterExt as _;
+use crate::merge_tools::pick_conflict_hunk_sides;
 use crate::ui::Ui;
 
 /// Resolve conflicted files with an external merge tool
@@ -58,6 +73,39 @@ pub(crate) struct ResolveArgs {
     #[arg(long, short)]
     list: bool,
 
+    /// Instead of resolving conflicts, list the conflicted hunks of each file
+    ///
+    /// Each line shows the file path, the hunk number as shown in conflict
+    /// markers, the line range of the conflict markers in the materialized
+    /// file, and the number and label of a side. The numbers can be passed to
+    /// `--hunk`.
+    #[arg(long, conflicts_with_all = ["list", "tool"])]
+    list_hunks: bool,
+
+    /// Resolve a conflicted hunk by picking one of its sides, e.g. `--hunk
+    /// 2=1`
+    ///
+    /// Hunks and sides are numbered from 1, as shown by `--list-hunks`. Other
+    /// hunks are left conflicted. Can be repeated for different hunks. Exactly
+    /// one conflicted file must be selected.
+    #[arg(long, value_name = "HUNK=SIDE", value_parser = parse_hunk_choice)]
+    #[arg(conflicts_with_all = ["list", "tool", "list_hunks"])]
+    hunk: Vec<(usize, usize)>,
+
+    /// Instead of resolving conflicts, list the resolutions recorded when
+    /// `merge.rerere` is enabled
+    #[arg(long, conflicts_with_all = ["list", "tool", "paths", "list_hunks", "hunk"])]
+    list_recorded: bool,
+
+    /// Instead of resolving conflicts, delete the recorded resolutions with
+    /// these IDs
+    ///
+    /// IDs can be abbreviated to unique prefixes. Use `--list-recorded` to
+    /// find them.
+    #[arg(long, value_name = "ID")]
+    #[arg(conflicts_with_all = ["list", "tool", "paths", "list_hunks", "hunk", "list_recorded"])]
+    forget: Vec<String>,
+
     /// Specify 3-way merge tool to be used
     ///
     /// The built-in merge tools `:ours` and `:theirs` can be used to choose
@@ -79,6 +127,9 @@ pub(crate) async fn cmd_resolve(
     command: &CommandHelper,
     args: &ResolveArgs,
 ) -> Result<(), CommandError> {
+    if args.list_recorded || !args.forget.is_empty() {
+        return manage_recorded_resolutions(ui, command, args).await;
+    }
     let mut workspace_command = command.workspace_helper(ui).await?;
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let matcher = fileset_expression.to_matcher();
@@ -105,15 +156,32 @@ pub(crate) async fn cmd_resolve(
         );
     }
 
+    if args.list_hunks {
+        return print_conflict_hunks(ui, &workspace_command, &tree, conflicts).await;
+    }
+
     let repo_paths = conflicts
         .iter()
         .map(|(path, _)| path.as_ref())
         .collect_vec();
     workspace_command.check_rewritable([commit.id()]).await?;
-    let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+    let (new_tree, partial_resolution_error) = if args.hunk.is_empty() {
+        let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+        merge_editor.edit_files(ui, &tree, &repo_paths).await?
+    } else {
+        if let Some((hunk, _)) = args.hunk.iter().duplicates_by(|(hunk, _)| hunk).next() {
+            return Err(user_error(format!(
+                "--hunk {hunk} was specified more than once"
+            )));
+        }
+        let [repo_path] = repo_paths[..] else {
+            return Err(user_error("--hunk requires exactly one conflicted file")
+                .hinted("Specify the path of the file to resolve."));
+        };
+        let new_tree = pick_conflict_hunk_sides(&tree, repo_path, &args.hunk).await?;
+        (new_tree, None)
+    };
     let mut tx = workspace_command.start_transaction();
-    let (new_tree, partial_resolution_error) =
-        merge_editor.edit_files(ui, &tree, &repo_paths).await?;
     let new_commit = tx
         .repo_mut()
         .rewrite_commit(&commit)
@@ -147,3 +215,121 @@ pub(crate) async fn cmd_resolve(
     }
     Ok(())
 }
+
+fn parse_hunk_choice(s: &str) -> Result<(usize, usize), String> {
+    let (hunk, side) = s
+        .split_once('=')
+        .ok_or_else(|| "expected HUNK=SIDE".to_owned())?;
+    let parse = |n: &str| n.trim().parse::<usize>().map_err(|err| err.to_string());
+    Ok((parse(hunk)?, parse(side)?))
+}
+
+async fn print_conflict_hunks(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    tree: &MergedTree,
+    conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
+) -> Result<(), CommandError> {
+    let store = tree.store();
+    let mut rows = vec![];
+    for (path, value) in conflicts {
+        let Some(file) =
+            try_materialize_file_conflict_value(store, &path, &value?, tree.labels()).await?
+        else {
+            continue;
+        };
+        let MergeResult::Conflict(hunks) =
+            files::merge_hunks(&file.contents, store.merge_options())
+        else {
+            continue;
+        };
+        // Materialize the file as it would be written to the working copy to
+        // find the line ranges of the conflict markers.
+        let marker_len = choose_materialized_conflict_marker_len(&file.contents);
+        let options = ConflictMaterializeOptions {
+            marker_style: workspace_command.env().conflict_marker_style(),
+            marker_len: Some(marker_len),
+            merge: store.merge_options().clone(),
+        };
+        let materialized =
+            materialize_merge_result_to_bytes(&file.contents, &file.labels, &options);
+        let line_ranges = find_conflict_line_ranges(&materialized, marker_len);
+        let ui_path = workspace_command.format_file_path(&path);
+        let num_conflicts = hunks.iter().filter(|hunk| !hunk.is_resolved()).count();
+        for index in 1..=num_conflicts {
+            let lines = line_ranges
+                .get(index - 1)
+                .map(|range| format!("lines {}-{}", range.start + 1, range.end))
+                .unwrap_or_default();
+            for side in 1..=file.ids.num_sides() {
+                let label = file.labels.get_add(side - 1).unwrap_or_default().to_owned();
+                rows.push((ui_path.clone(), index, lines.clone(), side, label));
+            }
+        }
+    }
+    let max_path_len = rows.iter().map(|(path, ..)| path.len()).max().unwrap_or(0);
+    let max_lines_len = rows
+        .iter()
+        .map(|(_, _, lines, ..)| lines.len())
+        .max()
+        .unwrap_or(0);
+    let mut formatter = ui.stdout_formatter();
+    for (path, index, lines, side, label) in rows {
+        write!(
+            formatter,
+            "{path:max_path_len$}    hunk {index}    {lines:max_lines_len$}    side {side}"
+        )?;
+        if label.is_empty() {
+            writeln!(formatter)?;
+        } else {
+            writeln!(formatter, ": {label}")?;
+        }
+    }
+    Ok(())
+}
+
+async fn manage_recorded_resolutions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ResolveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui).await?;
+    let rerere = RerereStore::load(workspace_command.repo_path());
+    if args.list_recorded {
+        let resolutions = rerere.list()?;
+        if resolutions.is_empty() {
+            writeln!(ui.status(), "No recorded resolutions.")?;
+        }
+        let mut formatter = ui.stdout_formatter();
+        for resolution in resolutions {
+            let short_id = resolution.id.get(..12).unwrap_or(&resolution.id);
+            write!(formatter.labeled("id"), "{short_id}")?;
+            writeln!(
+                formatter,
+                "  {}",
+                workspace_command.format_file_path(&resolution.path)
+            )?;
+        }
+        return Ok(());
+    }
+    // Look up all IDs first so nothing is deleted if any of them is invalid.
+    let resolutions: Vec<_> = args
+        .forget
+        .iter()
+        .map(|id_prefix| rerere.resolve_id_prefix(id_prefix))
+        .try_collect()
+        .map_err(user_error)?;
+    let resolutions = resolutions
+        .into_iter()
+        .unique_by(|resolution| resolution.id.clone())
+        .collect_vec();
+    for resolution in &resolutions {
+        rerere.forget(resolution)?;
+    }
+    writeln!(
+        ui.status(),
+        "Forgot {} recorded resolutions",
+        resolutions.len()
+    )?;
+    Ok(())
+}
@@ -137,11 +137,14 @@ pub(crate) async fn cmd_restore(
     }
     workspace_command.check_rewritable([to_commit.id()]).await?;
 
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let matcher = fileset_expression.to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns_with_filters(ui, &args.paths)?;
+    let to_tree = to_commit.tree();
+    let matcher = workspace_command
+        .resolve_file_filters(&fileset_expression, &[&to_tree, &from_tree])
+        .await?
+        .to_matcher();
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
-    let to_tree = to_commit.tree();
     let format_instructions = || {
         formatdoc! {"
             You are restoring changes from: {from_commits}
@@ -13,16 +13,25 @@
 // limitations under the License.
 use std::collections::HashMap;
 use std::io::Write as _;
+use std::slice;
 
+use bstr::ByteVec as _;
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use futures::StreamExt as _;
+use indexmap::IndexMap;
 use jj_lib::backend::CommitId;
+use jj_lib::codeowners::CodeOwners;
 use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::matchers::FilesMatcher;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
 use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::rewrite::CommitRewriter;
 use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehavior;
@@ -32,6 +41,7 @@ use jj_lib::rewrite::RebaseOptions;
 use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::RewriteRefsOptions;
 use jj_lib::rewrite::move_commits;
+use jj_lib::rewrite::restore_tree;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -41,7 +51,10 @@ use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::cli_util::compute_commit_location;
 use crate::cli_util::print_unmatched_explicit_paths;
+use crate::cli_util::print_updated_commits;
 use crate::command_error::CommandError;
+use crate::command_error::user_error;
+use crate::commit_templater::SplitGroup;
 use crate::complete;
 use crate::description_util::add_trailers;
 use crate::description_util::description_template;
@@ -103,6 +116,16 @@ use crate::ui::Ui;
 /// description, the second commit will not get a description, and you will be
 /// asked for a description only for the first commit.
 ///
+/// With `--by`, the revision is split into as many revisions as there are
+/// groups of changed files, without opening the diff editor. Files that don't
+/// belong to any group (such as files at the root of the repository with
+/// `--by directory`, or files not matching the given filesets) go into the
+/// last revision. The revisions form a chain, or are siblings with
+/// `--parallel/-p`. The description of each revision is generated from the
+/// `templates.split_description` template, which can refer to the new revision
+/// as `commit` and to the name of the group as `name`. By default, the
+/// original description is prefixed with the name of the group.
+///
 /// Splitting an empty commit is not supported because the same effect can be
 /// achieved with `jj new`.
 #[derive(clap::Args, Clone, Debug)]
@@ -194,9 +217,41 @@ pub(crate) struct SplitArgs {
     parallel: bool,
 
     /// Files matching any of these filesets are put in the selected changes
+    ///
+    /// With `--by fileset-list`, each fileset is put in a separate revision.
+    /// With other `--by` modes, only files matching the filesets are grouped.
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     #[arg(add = ArgValueCompleter::new(complete::modified_revision_files))]
     paths: Vec<String>,
+
+    /// Split the revision into one revision per group of changed files
+    #[arg(
+        long,
+        value_name = "GROUPS",
+        conflicts_with_all = [
+            "interactive",
+            "tool",
+            "onto",
+            "insert_after",
+            "insert_before",
+            "message_paragraphs",
+            "editor",
+        ],
+    )]
+    by: Option<SplitBy>,
+}
+
+/// How to group changed files with `jj split --by`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+enum SplitBy {
+    /// Group files by the directory containing them
+    Directory,
+    /// Group files by the top-level directory containing them
+    TopLevel,
+    /// Group files by the first fileset matching them
+    FilesetList,
+    /// Group files by their owners in the `CODEOWNERS` file
+    Owner,
 }
 
 impl SplitArgs {
@@ -277,6 +332,9 @@ pub(crate) async fn cmd_split(
     args: &SplitArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui).await?;
+    if let Some(by) = args.by {
+        return split_by_groups(ui, &mut workspace_command, args, by).await;
+    }
     let ResolvedSplitArgs {
         target_commit,
         matcher,
@@ -405,7 +463,10 @@ pub(crate) async fn cmd_split(
         )
         .await?
     } else {
-        rewrite_descendants(&mut tx, &target, first_commit, second_commit, parallel).await?
+        let new_commits = [first_commit.clone(), second_commit.clone()];
+        let num_rebased =
+            rewrite_descendants(&mut tx, &target.commit, &new_commits, parallel).await?;
+        (first_commit, second_commit, num_rebased)
     };
     if let Some(mut formatter) = ui.status_formatter() {
         if num_rebased > 0 {
@@ -494,53 +555,204 @@ async fn move_first_commit(
     Ok((first_commit, second_commit, num_rebased))
 }
 
+/// Rebases the descendants of the split `target_commit` onto the last of
+/// `new_commits`, or onto all of them if `parallel`. The working copy is moved
+/// to the last commit.
 async fn rewrite_descendants(
     tx: &mut WorkspaceCommandTransaction<'_>,
-    target: &CommitWithSelection,
-    first_commit: Commit,
-    second_commit: Commit,
+    target_commit: &Commit,
+    new_commits: &[Commit],
     parallel: bool,
-) -> Result<(Commit, Commit, usize), CommandError> {
+) -> Result<usize, CommandError> {
+    let first_commit = new_commits.first().unwrap();
+    let last_commit = new_commits.last().unwrap();
     let legacy_bookmark_behavior = tx.settings().get_bool("split.legacy-bookmark-behavior")?;
     if legacy_bookmark_behavior {
-        // Mark the commit being split as rewritten to the second commit. This
-        // moves any bookmarks pointing to the target commit to the second
+        // Mark the commit being split as rewritten to the last commit. This
+        // moves any bookmarks pointing to the target commit to the last
         // commit.
         tx.repo_mut()
-            .set_rewritten_commit(target.commit.id().clone(), second_commit.id().clone());
+            .set_rewritten_commit(target_commit.id().clone(), last_commit.id().clone());
     }
+    // The old parent is the commit the target commit was rewritten to.
+    let old_parent_id = if legacy_bookmark_behavior {
+        last_commit.id()
+    } else {
+        first_commit.id()
+    };
+    let new_parent_ids: Vec<&CommitId> = if parallel {
+        new_commits.iter().ids().collect()
+    } else {
+        vec![last_commit.id()]
+    };
     let mut num_rebased = 0;
     tx.repo_mut()
         .transform_descendants(
-            vec![target.commit.id().clone()],
+            vec![target_commit.id().clone()],
             async |mut rewriter: CommitRewriter<'_>| {
                 num_rebased += 1;
-                if parallel && legacy_bookmark_behavior {
-                    // The old_parent is the second commit due to the rewrite above.
-                    rewriter.replace_parent(
-                        second_commit.id(),
-                        [first_commit.id(), second_commit.id()],
-                    );
-                } else if parallel {
-                    rewriter
-                        .replace_parent(first_commit.id(), [first_commit.id(), second_commit.id()]);
-                } else {
-                    rewriter.replace_parent(first_commit.id(), [second_commit.id()]);
-                }
+                rewriter.replace_parent(old_parent_id, new_parent_ids.iter().copied());
                 rewriter.rebase().await?.write().await?;
                 Ok(())
             },
         )
         .await?;
-    // Move the working copy commit (@) to the second commit for any workspaces
+    // Move the working copy commit (@) to the last commit for any workspaces
     // where the target commit is the working copy commit.
     for (name, working_copy_commit) in tx.base_repo().clone().view().wc_commit_ids() {
-        if working_copy_commit == target.commit.id() {
-            tx.repo_mut().edit(name.clone(), &second_commit).await?;
+        if working_copy_commit == target_commit.id() {
+            tx.repo_mut().edit(name.clone(), last_commit).await?;
         }
     }
 
-    Ok((first_commit, second_commit, num_rebased))
+    Ok(num_rebased)
+}
+
+async fn split_by_groups(
+    ui: &mut Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    args: &SplitArgs,
+    by: SplitBy,
+) -> Result<(), CommandError> {
+    let target_commit = workspace_command
+        .resolve_single_rev(ui, &args.revision)
+        .await?;
+    workspace_command
+        .check_rewritable([target_commit.id()])
+        .await?;
+    if by == SplitBy::FilesetList && args.paths.is_empty() {
+        return Err(user_error(
+            "`--by fileset-list` requires at least one fileset",
+        ));
+    }
+    let repo = workspace_command.repo().clone();
+    let parent_tree = target_commit.parent_tree(repo.as_ref()).await?;
+    let target_tree = target_commit.tree();
+
+    // Each fileset is matched separately with `--by fileset-list`
+    let mut filesets = vec![];
+    let matcher = if by == SplitBy::FilesetList {
+        for path in &args.paths {
+            let expression = workspace_command.parse_file_patterns(ui, slice::from_ref(path))?;
+            filesets.push((path.clone(), expression.to_matcher()));
+        }
+        Box::new(EverythingMatcher)
+    } else {
+        workspace_command
+            .parse_file_patterns(ui, &args.paths)?
+            .to_matcher()
+    };
+    let codeowners = if by == SplitBy::Owner {
+        CodeOwners::load(&target_tree).await?
+    } else {
+        CodeOwners::empty()
+    };
+
+    // Groups of changed paths by name. Paths which don't belong to any group
+    // are put in the group with an empty name.
+    let mut groups: IndexMap<String, Vec<RepoPathBuf>> = filesets
+        .iter()
+        .map(|(name, _)| (name.clone(), vec![]))
+        .collect();
+    let mut diff_stream = parent_tree.diff_stream(&target_tree, &EverythingMatcher);
+    while let Some(entry) = diff_stream.next().await {
+        entry.values?;
+        let path = entry.path;
+        let name = if by == SplitBy::FilesetList {
+            filesets
+                .iter()
+                .find(|(_, matcher)| matcher.matches(&path))
+                .map_or("", |(name, _)| name)
+                .to_owned()
+        } else if !matcher.matches(&path) {
+            String::new()
+        } else {
+            let dir = path.parent().filter(|dir| !dir.is_root());
+            match by {
+                SplitBy::Directory => dir
+                    .map_or("", |dir| dir.as_internal_file_string())
+                    .to_owned(),
+                SplitBy::TopLevel => dir
+                    .and_then(|dir| dir.components().next())
+                    .map_or("", |component| component.as_internal_str())
+                    .to_owned(),
+                SplitBy::Owner => codeowners.owners(&path).join(" "),
+                SplitBy::FilesetList => unreachable!(),
+            }
+        };
+        groups.entry(name).or_default().push(path);
+    }
+    drop(diff_stream);
+    groups.retain(|_, paths| !paths.is_empty());
+    if by != SplitBy::FilesetList {
+        groups.sort_unstable_keys();
+    }
+    if let Some(paths) = groups.shift_remove("") {
+        groups.insert(String::new(), paths);
+    }
+    if groups.len() < 2 {
+        return Err(user_error(
+            "Cannot split the revision because all changes belong to the same group",
+        ));
+    }
+
+    let template_text = workspace_command
+        .settings()
+        .get_string("templates.split_description")?;
+    let mut tx = workspace_command.start_transaction();
+    let mut new_commits: Vec<Commit> = vec![];
+    for (name, paths) in &groups {
+        let (parent_ids, base_tree) = match new_commits.last() {
+            Some(commit) if !args.parallel => (vec![commit.id().clone()], commit.tree()),
+            _ => (target_commit.parent_ids().to_vec(), parent_tree.clone()),
+        };
+        let new_tree = restore_tree(
+            &target_tree,
+            &base_tree,
+            format!("split revision ({})", target_commit.conflict_label()),
+            "parents of split revision".to_owned(),
+            &FilesMatcher::new(paths),
+        )
+        .await?;
+        // The first commit replaces the original commit. The others get new
+        // change ids so that the commit being split doesn't become divergent.
+        let mut commit_builder = tx.repo_mut().rewrite_commit(&target_commit).detach();
+        commit_builder.set_parents(parent_ids).set_tree(new_tree);
+        if !new_commits.is_empty() {
+            commit_builder.clear_rewrite_source();
+            commit_builder.generate_new_change_id();
+        }
+        let group = SplitGroup {
+            commit: commit_builder.write_hidden().await?,
+            name: name.clone(),
+        };
+        let description = {
+            let language = tx.commit_template_language();
+            tx.parse_template(ui, &language, &template_text)?
+                .format_plain_text(&group)
+                .into_string_lossy()
+        };
+        commit_builder.set_description(description);
+        new_commits.push(commit_builder.write(tx.repo_mut()).await?);
+    }
+
+    let num_rebased =
+        rewrite_descendants(&mut tx, &target_commit, &new_commits, args.parallel).await?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits.")?;
+        }
+        writeln!(formatter, "Split into {} revisions:", new_commits.len())?;
+        print_updated_commits(
+            formatter.as_mut(),
+            &tx.commit_summary_template(),
+            &new_commits,
+        )?;
+    }
+    tx.finish(ui, format!("split commit {}", target_commit.id().hex()))
+        .await?;
+    Ok(())
 }
 
 /// Prompts the user to select the content they want in the first commit and
@@ -13,30 +13,38 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::iter::once;
 
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use futures::TryStreamExt as _;
 use futures::future::try_join_all;
+use indexmap::IndexMap;
 use indoc::formatdoc;
+use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
 use jj_lib::rewrite;
 use jj_lib::rewrite::CommitWithSelection;
+use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::merge_commit_trees;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::DiffSelector;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::cli_util::compute_commit_location;
 use crate::cli_util::print_unmatched_explicit_paths;
+use crate::cli_util::short_commit_hash;
 use crate::command_error::CommandError;
 use crate::command_error::user_error;
 use crate::complete;
@@ -46,6 +54,7 @@ use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
 use crate::description_util::try_combine_messages;
+use crate::text_util;
 use crate::ui::Ui;
 
 /// Move changes from a revision into another revision
@@ -76,6 +85,15 @@ use crate::ui::Ui;
 /// The name "squash" comes from the idea of combining (squashing) the changes
 /// from multiple revisions together.
 ///
+/// With the `--autosquash` option, commits whose description starts with
+/// `fixup!`, `squash!`, or `amend!` are squashed into their target revisions,
+/// similar to `git rebase --autosquash`. The target is the closest mutable
+/// ancestor whose subject (the first line of its description) or change ID
+/// matches the rest of the first line. The description of the target is kept
+/// for `fixup!` commits, the rest of the description of a `squash!` commit is
+/// appended to it, and the rest of the description of an `amend!` commit
+/// replaces it.
+///
 /// EXPERIMENTAL FEATURES
 ///
 /// An alternative squashing UI is available via the `-o`, `-A`, and `-B`
@@ -86,6 +104,9 @@ use crate::ui::Ui;
 pub(crate) struct SquashArgs {
     /// Revision to squash into its parent (default: @). Incompatible with the
     /// experimental `-o`/`-A`/`-B` options.
+    ///
+    /// With `--autosquash`, the revisions to search for fixup commits
+    /// (default: `revsets.autosquash`).
     #[arg(long, short, value_name = "REVSET")]
     #[arg(add = ArgValueCompleter::new(complete::revset_expression_mutable))]
     revision: Option<RevisionArg>,
@@ -176,6 +197,26 @@ pub(crate) struct SquashArgs {
     /// The source revision will not be abandoned
     #[arg(long, short)]
     keep_emptied: bool,
+
+    /// Squash `fixup!`, `squash!`, and `amend!` commits into their targets
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "from",
+            "into",
+            "onto",
+            "insert_after",
+            "insert_before",
+            "message_paragraphs",
+            "use_destination_message",
+            "editor",
+            "interactive",
+            "tool",
+            "paths",
+            "keep_emptied",
+        ],
+    )]
+    autosquash: bool,
 }
 
 #[instrument(skip_all)]
@@ -188,6 +229,9 @@ pub(crate) async fn cmd_squash(
         args.onto.is_some() || args.insert_after.is_some() || args.insert_before.is_some();
 
     let mut workspace_command = command.workspace_helper(ui).await?;
+    if args.autosquash {
+        return squash_fixup_commits(ui, &mut workspace_command, args.revision.as_ref()).await;
+    }
 
     let mut sources: Vec<Commit>;
     let pre_existing_destination;
@@ -512,3 +556,195 @@ async fn select_diff(
     }
     Ok(source_commits)
 }
+
+/// Kind of a commit to squash with `--autosquash`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FixupKind {
+    /// Keep the description of the target.
+    Fixup,
+    /// Append the rest of the description to the description of the target.
+    Squash,
+    /// Replace the description of the target with the rest of the description.
+    Amend,
+}
+
+impl FixupKind {
+    fn strip_prefix(line: &str) -> Option<(Self, &str)> {
+        [
+            (Self::Fixup, "fixup!"),
+            (Self::Squash, "squash!"),
+            (Self::Amend, "amend!"),
+        ]
+        .into_iter()
+        .find_map(|(kind, prefix)| Some((kind, line.strip_prefix(prefix)?.trim_start())))
+    }
+
+    /// Parses the kind and the subject of the target from a description.
+    fn parse(description: &str) -> Option<(Self, &str)> {
+        let first_line = description.lines().next()?;
+        let (kind, mut subject) = Self::strip_prefix(first_line)?;
+        // `fixup! fixup! subject` refers to the same target as `fixup! subject`
+        while let Some((_, rest)) = Self::strip_prefix(subject) {
+            subject = rest;
+        }
+        let subject = subject.trim_end();
+        (!subject.is_empty()).then_some((kind, subject))
+    }
+
+    fn apply(self, target_description: &str, fixup_description: &str) -> String {
+        let body = fixup_description
+            .split_once('\n')
+            .map_or("", |(_, body)| body)
+            .trim();
+        match self {
+            Self::Fixup => target_description.to_owned(),
+            Self::Squash if !body.is_empty() => join_message_paragraphs(&[
+                target_description.trim_end().to_owned(),
+                body.to_owned(),
+            ]),
+            Self::Amend if !body.is_empty() => text_util::complete_newline(body),
+            Self::Squash | Self::Amend => target_description.to_owned(),
+        }
+    }
+}
+
+fn is_fixup_target(commit: &Commit, subject: &str) -> bool {
+    let is_change_id = subject.bytes().all(|b| (b'k'..=b'z').contains(&b));
+    commit.description().lines().next() == Some(subject)
+        || is_change_id && commit.change_id().reverse_hex().starts_with(subject)
+}
+
+async fn squash_fixup_commits(
+    ui: &mut Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    revision: Option<&RevisionArg>,
+) -> Result<(), CommandError> {
+    let candidates = if let Some(revision) = revision {
+        workspace_command.parse_revset(ui, revision)?
+    } else {
+        let revs = workspace_command
+            .settings()
+            .get_string("revsets.autosquash")?;
+        workspace_command.parse_revset(ui, &RevisionArg::from(revs))?
+    };
+    let mut fixups: Vec<Commit> = candidates.evaluate_to_commits()?.try_collect().await?;
+    fixups.retain(|commit| FixupKind::parse(commit.description()).is_some());
+    // Visit the oldest commits first so that fixups of fixups are resolved
+    fixups.reverse();
+
+    // Map from target commit to the fixup commits to squash into it
+    let mut groups: IndexMap<CommitId, (Commit, Vec<(FixupKind, Commit)>)> = IndexMap::new();
+    let mut fixup_targets: HashMap<CommitId, CommitId> = HashMap::new();
+    for fixup in fixups {
+        let (kind, subject) = FixupKind::parse(fixup.description()).unwrap();
+        let ancestors = workspace_command.attach_revset_evaluator(
+            workspace_command
+                .env()
+                .immutable_expression()
+                .range(&RevsetExpression::commit(fixup.id().clone()).parents()),
+        );
+        let mut ancestors = ancestors.evaluate_to_commits()?;
+        let mut target = None;
+        while let Some(commit) = ancestors.try_next().await? {
+            if is_fixup_target(&commit, subject) {
+                target = Some(commit);
+                break;
+            }
+        }
+        drop(ancestors);
+        let Some(mut target) = target else {
+            writeln!(
+                ui.warning_default(),
+                "No target found for fixup commit {}, leaving it as is.",
+                short_commit_hash(fixup.id())
+            )?;
+            continue;
+        };
+        if let Some(target_id) = fixup_targets.get(target.id()) {
+            target = groups[target_id].0.clone();
+        }
+        fixup_targets.insert(fixup.id().clone(), target.id().clone());
+        groups
+            .entry(target.id().clone())
+            .or_insert_with(|| (target, vec![]))
+            .1
+            .push((kind, fixup));
+    }
+    if groups.is_empty() {
+        writeln!(ui.status(), "No fixup commits to squash.")?;
+        return Ok(());
+    }
+    workspace_command
+        .check_rewritable(groups.keys().chain(fixup_targets.keys()))
+        .await?;
+
+    let mut tx = workspace_command.start_transaction();
+    // Descendants are rebased after each squash, so keep track of the latest
+    // version of each commit.
+    let mut rewritten: HashMap<CommitId, Commit> = HashMap::new();
+    let latest_commit = |rewritten: &HashMap<CommitId, Commit>, commit: &Commit| {
+        let mut commit = commit;
+        while let Some(new_commit) = rewritten.get(commit.id()) {
+            commit = new_commit;
+        }
+        commit.clone()
+    };
+    for (destination, fixups) in groups.values() {
+        let destination = latest_commit(&rewritten, destination);
+        let mut sources = vec![];
+        for (_, fixup) in fixups {
+            let commit = latest_commit(&rewritten, fixup);
+            sources.push(CommitWithSelection {
+                selected_tree: commit.tree(),
+                parent_tree: commit.parent_tree(tx.repo()).await?,
+                commit,
+            });
+        }
+        let description = fixups.iter().fold(
+            destination.description().to_owned(),
+            |description, (kind, fixup)| kind.apply(&description, fixup.description()),
+        );
+        let squashed = rewrite::squash_commits(tx.repo_mut(), &sources, &destination, false)
+            .await?
+            .expect("fully selected sources should be squashed");
+        let new_commit = squashed
+            .commit_builder
+            .set_description(description)
+            .write()
+            .await?;
+        rewritten.insert(destination.id().clone(), new_commit);
+        tx.repo_mut()
+            .rebase_descendants_with_options(
+                &RevsetExpression::none(),
+                &RebaseOptions::default(),
+                |old_commit, rebased_commit| {
+                    if let RebasedCommit::Rewritten(new_commit) = rebased_commit {
+                        rewritten.insert(old_commit.id().clone(), new_commit);
+                    }
+                },
+            )
+            .await?;
+    }
+
+    let new_ids: HashSet<&CommitId> = rewritten.values().ids().collect();
+    let num_rebased = rewritten
+        .keys()
+        .filter(|id| {
+            !new_ids.contains(id) && !groups.contains_key(*id) && !fixup_targets.contains_key(*id)
+        })
+        .count();
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Squashed {} fixup commits into {} commits.",
+            fixup_targets.len(),
+            groups.len()
+        )?;
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits.")?;
+        }
+    }
+    tx.finish(ui, format!("autosquash {} commits", fixup_targets.len()))
+        .await?;
+    Ok(())
+}
@@ -14,9 +14,11 @@
 
 use std::collections::BTreeMap;
 
+use futures::StreamExt as _;
 use futures::TryStreamExt as _;
 use itertools::Itertools as _;
 use jj_lib::commit::Commit;
+use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopyRecords;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
@@ -36,8 +38,10 @@ use crate::cli_util::print_snapshot_stats;
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::command_error::CommandError;
 use crate::diff_util::DiffFormat;
+use crate::diff_util::diff_status;
 use crate::diff_util::get_copy_records;
 use crate::formatter::FormatterExt as _;
+use crate::output_format::write_json_value;
 use crate::ui::Ui;
 
 /// Show high-level repo status [default alias: st]
@@ -82,6 +86,33 @@ pub(crate) async fn cmd_status(
         .transpose()?;
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let matcher = fileset_expression.to_matcher();
+
+    if command.output_format().is_some() {
+        let output = if let Some(wc_commit) = &maybe_wc_commit {
+            let status =
+                collect_working_copy_status(repo.as_ref(), wc_commit, snapshot_stats).await?;
+            print_unmatched_explicit_paths(
+                ui,
+                &workspace_command,
+                &fileset_expression,
+                [&status.tree],
+            )?;
+            let mut copy_records = CopyRecords::default();
+            for parent in &status.parents {
+                let records =
+                    get_copy_records(repo.store(), parent.id(), status.commit.id(), &matcher)
+                        .await?;
+                copy_records.add_records(records);
+            }
+            StatusOutput::new(repo.as_ref(), &status, &matcher, &copy_records).await?
+        } else {
+            StatusOutput::without_working_copy(repo.as_ref())
+        };
+        ui.request_pager();
+        write_json_value(ui.stdout(), &output)?;
+        return Ok(());
+    }
+
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -249,6 +280,91 @@ pub(crate) async fn cmd_status(
     Ok(())
 }
 
+/// Status report printed by `--output-format`.
+#[derive(serde::Serialize)]
+struct StatusOutput {
+    working_copy: Option<Commit>,
+    parents: Vec<Commit>,
+    changes: Vec<StatusChangeOutput>,
+    untracked: Vec<RepoPathBuf>,
+    conflicts: Vec<RepoPathBuf>,
+    conflicted_bookmarks: Vec<String>,
+    conflicted_remote_bookmarks: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct StatusChangeOutput {
+    status: &'static str,
+    path: RepoPathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<RepoPathBuf>,
+}
+
+impl StatusOutput {
+    async fn new(
+        repo: &dyn Repo,
+        status: &WorkingCopyStatus,
+        matcher: &dyn Matcher,
+        copy_records: &CopyRecords,
+    ) -> Result<Self, CommandError> {
+        let mut changes = vec![];
+        let mut tree_diff =
+            status
+                .parent_tree
+                .diff_stream_with_copies(&status.tree, matcher, copy_records);
+        while let Some(CopiesTreeDiffEntry { path, values }) = tree_diff.next().await {
+            let values = values?;
+            let source = path.copy_operation().map(|_| path.source().to_owned());
+            changes.push(StatusChangeOutput {
+                status: diff_status(&path, &values).label(),
+                path: path.target().to_owned(),
+                source,
+            });
+        }
+        let conflicts = if status.commit.has_conflict() {
+            status
+                .tree
+                .conflicts_matching(matcher)
+                .map(|(path, _)| path)
+                .collect()
+        } else {
+            vec![]
+        };
+        Ok(Self {
+            working_copy: Some(status.commit.clone()),
+            parents: status.parents.clone(),
+            changes,
+            untracked: status
+                .untracked_paths_matching(matcher)
+                .map(|path| path.to_owned())
+                .collect(),
+            conflicts,
+            ..Self::without_working_copy(repo)
+        })
+    }
+
+    fn without_working_copy(repo: &dyn Repo) -> Self {
+        let view = repo.view();
+        Self {
+            working_copy: None,
+            parents: vec![],
+            changes: vec![],
+            untracked: vec![],
+            conflicts: vec![],
+            conflicted_bookmarks: view
+                .local_bookmarks()
+                .filter(|(_, target)| target.has_conflict())
+                .map(|(name, _)| name.as_symbol().to_string())
+                .collect(),
+            conflicted_remote_bookmarks: view
+                .all_remote_bookmarks()
+                .filter(|(_, remote_ref)| remote_ref.target.has_conflict())
+                .map(|(symbol, _)| symbol.to_string())
+                .collect(),
+        }
+    }
+}
+
 struct WorkingCopyStatus {
     commit: Commit,
     parents: Vec<Commit>,
@@ -20,6 +20,7 @@ use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::commit_templater::WorkspaceRef;
 use crate::complete;
+use crate::output_format::JsonListWriter;
 use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
 
@@ -69,13 +70,23 @@ pub async fn cmd_workspace_list(
     };
 
     let repo = workspace_command.repo();
-    let mut formatter = ui.stdout_formatter();
-
+    let mut ws_refs = Vec::new();
     for (name, wc_commit_id) in repo.view().wc_commit_ids() {
         let commit = repo.store().get_commit_async(wc_commit_id).await?;
-        let ws_ref = WorkspaceRef::new(name.clone(), commit);
+        ws_refs.push(WorkspaceRef::new(name.clone(), commit));
+    }
 
-        template.format(&ws_ref, formatter.as_mut())?;
+    if let Some(output_format) = command.output_format() {
+        let mut writer = JsonListWriter::new(ui.stdout(), output_format);
+        for ws_ref in &ws_refs {
+            writer.write_item(ws_ref)?;
+        }
+        writer.finish()?;
+    } else {
+        let mut formatter = ui.stdout_formatter();
+        for ws_ref in &ws_refs {
+            template.format(ws_ref, formatter.as_mut())?;
+        }
     }
 
     Ok(())
@@ -15,18 +15,21 @@
 //! Template environment for `jj log`, `jj evolog` and similar.
 
 use std::any::Any;
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::cmp::max;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
 use std::io;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use bstr::BString;
+use bstr::ByteSlice as _;
 use futures::StreamExt as _;
 use futures::TryStreamExt as _;
 use futures::stream::BoxStream;
@@ -36,7 +39,9 @@ use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeValue;
 use jj_lib::backend::Timestamp;
+use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::codeowners::CodeOwners;
 use jj_lib::commit::Commit;
 use jj_lib::conflict_labels::ConflictLabels;
 use jj_lib::conflicts;
@@ -44,6 +49,8 @@ use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyRecords;
+use jj_lib::diff_presentation::unified::DiffLineType;
+use jj_lib::diff_presentation::unified::unified_diff_hunks;
 use jj_lib::evolution::CommitEvolutionEntry;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
@@ -55,6 +62,7 @@ use jj_lib::id_prefix::IdPrefixIndex;
 use jj_lib::index::IndexResult;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
+use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::LocalRemoteRefTarget;
@@ -355,6 +363,16 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, diagnostics, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::TreeDiffHunk(property) => {
+                let table = &self.build_fn_table.tree_diff_hunk_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TreeDiffHunkList(property) => {
+                let table = &self.build_fn_table.tree_diff_hunk_list_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
             CommitTemplatePropertyKind::TreeEntry(property) => {
                 let table = &self.build_fn_table.tree_entry_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
@@ -396,6 +414,18 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, diagnostics, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::FileSearchMatch(property) => {
+                let type_name = "FileSearchMatch";
+                let table = &self.build_fn_table.file_search_match_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::SplitGroup(property) => {
+                let type_name = "SplitGroup";
+                let table = &self.build_fn_table.split_group_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
             CommitTemplatePropertyKind::Trailer(property) => {
                 let table = &self.build_fn_table.trailer_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
@@ -464,6 +494,8 @@ pub enum CommitTemplatePropertyKind<'repo> {
     TreeDiff(BoxedTemplateProperty<'repo, TreeDiff>),
     TreeDiffEntry(BoxedTemplateProperty<'repo, TreeDiffEntry>),
     TreeDiffEntryList(BoxedTemplateProperty<'repo, Vec<TreeDiffEntry>>),
+    TreeDiffHunk(BoxedTemplateProperty<'repo, TreeDiffHunk>),
+    TreeDiffHunkList(BoxedTemplateProperty<'repo, Vec<TreeDiffHunk>>),
     TreeEntry(BoxedTemplateProperty<'repo, TreeEntry>),
     TreeEntryList(BoxedTemplateProperty<'repo, Vec<TreeEntry>>),
     DiffStats(BoxedTemplateProperty<'repo, DiffStatsFormatted<'repo>>),
@@ -471,6 +503,8 @@ pub enum CommitTemplatePropertyKind<'repo> {
     DiffStatEntryList(BoxedTemplateProperty<'repo, Vec<DiffStatEntry>>),
     CryptographicSignatureOpt(BoxedTemplateProperty<'repo, Option<CryptographicSignature>>),
     AnnotationLine(BoxedTemplateProperty<'repo, AnnotationLine>),
+    FileSearchMatch(BoxedTemplateProperty<'repo, FileSearchMatch>),
+    SplitGroup(BoxedTemplateProperty<'repo, SplitGroup>),
     Trailer(BoxedTemplateProperty<'repo, Trailer>),
     TrailerList(BoxedTemplateProperty<'repo, Vec<Trailer>>),
 }
@@ -498,6 +532,8 @@ template_builder::impl_property_wrappers!(<'repo> CommitTemplatePropertyKind<'re
     TreeDiff(TreeDiff),
     TreeDiffEntry(TreeDiffEntry),
     TreeDiffEntryList(Vec<TreeDiffEntry>),
+    TreeDiffHunk(TreeDiffHunk),
+    TreeDiffHunkList(Vec<TreeDiffHunk>),
     TreeEntry(TreeEntry),
     TreeEntryList(Vec<TreeEntry>),
     DiffStats(DiffStatsFormatted<'repo>),
@@ -505,6 +541,8 @@ template_builder::impl_property_wrappers!(<'repo> CommitTemplatePropertyKind<'re
     DiffStatEntryList(Vec<DiffStatEntry>),
     CryptographicSignatureOpt(Option<CryptographicSignature>),
     AnnotationLine(AnnotationLine),
+    FileSearchMatch(FileSearchMatch),
+    SplitGroup(SplitGroup),
     Trailer(Trailer),
     TrailerList(Vec<Trailer>),
 });
@@ -546,6 +584,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::TreeDiff(_) => "TreeDiff",
             Self::TreeDiffEntry(_) => "TreeDiffEntry",
             Self::TreeDiffEntryList(_) => "List<TreeDiffEntry>",
+            Self::TreeDiffHunk(_) => "TreeDiffHunk",
+            Self::TreeDiffHunkList(_) => "List<TreeDiffHunk>",
             Self::TreeEntry(_) => "TreeEntry",
             Self::TreeEntryList(_) => "List<TreeEntry>",
             Self::DiffStats(_) => "DiffStats",
@@ -553,6 +593,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::DiffStatEntryList(_) => "List<DiffStatEntry>",
             Self::CryptographicSignatureOpt(_) => "Option<CryptographicSignature>",
             Self::AnnotationLine(_) => "AnnotationLine",
+            Self::FileSearchMatch(_) => "FileSearchMatch",
+            Self::SplitGroup(_) => "SplitGroup",
             Self::Trailer(_) => "Trailer",
             Self::TrailerList(_) => "List<Trailer>",
         }
@@ -604,6 +646,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::TreeDiff(_) => Err(self),
             Self::TreeDiffEntry(_) => Err(self),
             Self::TreeDiffEntryList(property) => Ok(property.map(|l| !l.is_empty()).into_dyn()),
+            Self::TreeDiffHunk(_) => Err(self),
+            Self::TreeDiffHunkList(property) => Ok(property.map(|l| !l.is_empty()).into_dyn()),
             Self::TreeEntry(_) => Err(self),
             Self::TreeEntryList(property) => Ok(property.map(|l| !l.is_empty()).into_dyn()),
             Self::DiffStats(_) => Err(self),
@@ -613,6 +657,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
                 Ok(property.map(|sig| sig.is_some()).into_dyn())
             }
             Self::AnnotationLine(_) => Err(self),
+            Self::FileSearchMatch(_) => Err(self),
+            Self::SplitGroup(_) => Err(self),
             Self::Trailer(_) => Err(self),
             Self::TrailerList(property) => Ok(property.map(|l| !l.is_empty()).into_dyn()),
         }
@@ -658,13 +704,17 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::TreeDiff(_) => None,
             Self::TreeDiffEntry(_) => None,
             Self::TreeDiffEntryList(_) => None,
+            Self::TreeDiffHunk(_) => None,
+            Self::TreeDiffHunkList(_) => None,
             Self::TreeEntry(_) => None,
             Self::TreeEntryList(_) => None,
             Self::DiffStats(_) => None,
             Self::DiffStatEntry(_) => None,
             Self::DiffStatEntryList(_) => None,
             Self::CryptographicSignatureOpt(_) => None,
-            Self::AnnotationLine(_) => None,
+            Self::AnnotationLine(property) => Some(property.into_serialize()),
+            Self::FileSearchMatch(_) => None,
+            Self::SplitGroup(_) => None,
             Self::Trailer(_) => None,
             Self::TrailerList(_) => None,
         }
@@ -694,6 +744,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::TreeDiff(_) => None,
             Self::TreeDiffEntry(_) => None,
             Self::TreeDiffEntryList(_) => None,
+            Self::TreeDiffHunk(_) => None,
+            Self::TreeDiffHunkList(_) => None,
             Self::TreeEntry(_) => None,
             Self::TreeEntryList(_) => None,
             Self::DiffStats(property) => Some(property.into_template()),
@@ -701,6 +753,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             Self::DiffStatEntryList(_) => None,
             Self::CryptographicSignatureOpt(_) => None,
             Self::AnnotationLine(_) => None,
+            Self::FileSearchMatch(_) => None,
+            Self::SplitGroup(_) => None,
             Self::Trailer(property) => Some(property.into_template()),
             Self::TrailerList(property) => Some(property.into_template()),
         }
@@ -763,6 +817,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             (Self::TreeDiff(_), _) => None,
             (Self::TreeDiffEntry(_), _) => None,
             (Self::TreeDiffEntryList(_), _) => None,
+            (Self::TreeDiffHunk(_), _) => None,
+            (Self::TreeDiffHunkList(_), _) => None,
             (Self::TreeEntry(_), _) => None,
             (Self::TreeEntryList(_), _) => None,
             (Self::DiffStats(_), _) => None,
@@ -770,6 +826,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             (Self::DiffStatEntryList(_), _) => None,
             (Self::CryptographicSignatureOpt(_), _) => None,
             (Self::AnnotationLine(_), _) => None,
+            (Self::FileSearchMatch(_), _) => None,
+            (Self::SplitGroup(_), _) => None,
             (Self::Trailer(_), _) => None,
             (Self::TrailerList(_), _) => None,
         }
@@ -805,6 +863,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             (Self::TreeDiff(_), _) => None,
             (Self::TreeDiffEntry(_), _) => None,
             (Self::TreeDiffEntryList(_), _) => None,
+            (Self::TreeDiffHunk(_), _) => None,
+            (Self::TreeDiffHunkList(_), _) => None,
             (Self::TreeEntry(_), _) => None,
             (Self::TreeEntryList(_), _) => None,
             (Self::DiffStats(_), _) => None,
@@ -812,6 +872,8 @@ impl<'repo> CoreTemplatePropertyVar<'repo> for CommitTemplatePropertyKind<'repo>
             (Self::DiffStatEntryList(_), _) => None,
             (Self::CryptographicSignatureOpt(_), _) => None,
             (Self::AnnotationLine(_), _) => None,
+            (Self::FileSearchMatch(_), _) => None,
+            (Self::SplitGroup(_), _) => None,
             (Self::Trailer(_), _) => None,
             (Self::TrailerList(_), _) => None,
         }
@@ -842,6 +904,8 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub tree_diff_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiff>,
     pub tree_diff_entry_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiffEntry>,
     pub tree_diff_entry_list_methods: CommitTemplateBuildMethodFnMap<'repo, Vec<TreeDiffEntry>>,
+    pub tree_diff_hunk_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiffHunk>,
+    pub tree_diff_hunk_list_methods: CommitTemplateBuildMethodFnMap<'repo, Vec<TreeDiffHunk>>,
     pub tree_entry_methods: CommitTemplateBuildMethodFnMap<'repo, TreeEntry>,
     pub tree_entry_list_methods: CommitTemplateBuildMethodFnMap<'repo, Vec<TreeEntry>>,
     pub diff_stats_methods: CommitTemplateBuildMethodFnMap<'repo, DiffStats>,
@@ -850,6 +914,8 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub cryptographic_signature_methods:
         CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
     pub annotation_line_methods: CommitTemplateBuildMethodFnMap<'repo, AnnotationLine>,
+    pub file_search_match_methods: CommitTemplateBuildMethodFnMap<'repo, FileSearchMatch>,
+    pub split_group_methods: CommitTemplateBuildMethodFnMap<'repo, SplitGroup>,
     pub trailer_methods: CommitTemplateBuildMethodFnMap<'repo, Trailer>,
     pub trailer_list_methods: CommitTemplateBuildMethodFnMap<'repo, Vec<Trailer>>,
 }
@@ -873,6 +939,8 @@ impl CommitTemplateBuildFnTable<'_> {
             tree_diff_methods: HashMap::new(),
             tree_diff_entry_methods: HashMap::new(),
             tree_diff_entry_list_methods: HashMap::new(),
+            tree_diff_hunk_methods: HashMap::new(),
+            tree_diff_hunk_list_methods: HashMap::new(),
             tree_entry_methods: HashMap::new(),
             tree_entry_list_methods: HashMap::new(),
             diff_stats_methods: HashMap::new(),
@@ -880,6 +948,8 @@ impl CommitTemplateBuildFnTable<'_> {
             diff_stat_entry_list_methods: HashMap::new(),
             cryptographic_signature_methods: HashMap::new(),
             annotation_line_methods: HashMap::new(),
+            file_search_match_methods: HashMap::new(),
+            split_group_methods: HashMap::new(),
             trailer_methods: HashMap::new(),
             trailer_list_methods: HashMap::new(),
         }
@@ -903,6 +973,8 @@ impl CommitTemplateBuildFnTable<'_> {
             tree_diff_methods,
             tree_diff_entry_methods,
             tree_diff_entry_list_methods,
+            tree_diff_hunk_methods,
+            tree_diff_hunk_list_methods,
             tree_entry_methods,
             tree_entry_list_methods,
             diff_stats_methods,
@@ -910,6 +982,8 @@ impl CommitTemplateBuildFnTable<'_> {
             diff_stat_entry_list_methods,
             cryptographic_signature_methods,
             annotation_line_methods,
+            file_search_match_methods,
+            split_group_methods,
             trailer_methods,
             trailer_list_methods,
         } = other;
@@ -942,6 +1016,11 @@ impl CommitTemplateBuildFnTable<'_> {
             &mut self.tree_diff_entry_list_methods,
             tree_diff_entry_list_methods,
         );
+        merge_fn_map(&mut self.tree_diff_hunk_methods, tree_diff_hunk_methods);
+        merge_fn_map(
+            &mut self.tree_diff_hunk_list_methods,
+            tree_diff_hunk_list_methods,
+        );
         merge_fn_map(&mut self.tree_entry_methods, tree_entry_methods);
         merge_fn_map(&mut self.tree_entry_list_methods, tree_entry_list_methods);
         merge_fn_map(&mut self.diff_stats_methods, diff_stats_methods);
@@ -955,6 +1034,11 @@ impl CommitTemplateBuildFnTable<'_> {
             cryptographic_signature_methods,
         );
         merge_fn_map(&mut self.annotation_line_methods, annotation_line_methods);
+        merge_fn_map(
+            &mut self.file_search_match_methods,
+            file_search_match_methods,
+        );
+        merge_fn_map(&mut self.split_group_methods, split_group_methods);
         merge_fn_map(&mut self.trailer_methods, trailer_methods);
         merge_fn_map(&mut self.trailer_list_methods, trailer_list_methods);
     }
@@ -980,6 +1064,8 @@ impl CommitTemplateBuildFnTable<'_> {
             tree_diff_methods: builtin_tree_diff_methods(),
             tree_diff_entry_methods: builtin_tree_diff_entry_methods(),
             tree_diff_entry_list_methods: template_builder::builtin_unformattable_list_methods(),
+            tree_diff_hunk_methods: builtin_tree_diff_hunk_methods(),
+            tree_diff_hunk_list_methods: template_builder::builtin_unformattable_list_methods(),
             tree_entry_methods: builtin_tree_entry_methods(),
             tree_entry_list_methods: template_builder::builtin_unformattable_list_methods(),
             diff_stats_methods: builtin_diff_stats_methods(),
@@ -987,6 +1073,8 @@ impl CommitTemplateBuildFnTable<'_> {
             diff_stat_entry_list_methods: template_builder::builtin_unformattable_list_methods(),
             cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
             annotation_line_methods: builtin_annotation_line_methods(),
+            file_search_match_methods: builtin_file_search_match_methods(),
+            split_group_methods: builtin_split_group_methods(),
             trailer_methods: builtin_trailer_methods(),
             trailer_list_methods: builtin_trailer_list_methods(),
         }
@@ -1000,6 +1088,7 @@ pub struct CommitKeywordCache<'repo> {
     tags_index: OnceCell<Rc<CommitRefsIndex>>,
     git_refs_index: OnceCell<Rc<CommitRefsIndex>>,
     is_immutable_fn: OnceCell<Rc<RevsetContainingFn<'repo>>>,
+    codeowners: Rc<CodeOwnersCache>,
 }
 
 impl<'repo> CommitKeywordCache<'repo> {
@@ -1032,6 +1121,30 @@ impl<'repo> CommitKeywordCache<'repo> {
             Ok(revset.containing_fn().into())
         })
     }
+
+    pub fn codeowners(&self) -> &Rc<CodeOwnersCache> {
+        &self.codeowners
+    }
+}
+
+/// `CODEOWNERS` rules loaded from trees.
+#[derive(Debug, Default)]
+pub struct CodeOwnersCache {
+    loaded: RefCell<HashMap<Merge<TreeId>, Rc<CodeOwners>>>,
+}
+
+impl CodeOwnersCache {
+    /// Returns `CODEOWNERS` rules in the `tree`, loading them if needed.
+    pub fn get(&self, tree: &MergedTree) -> BackendResult<Rc<CodeOwners>> {
+        if let Some(codeowners) = self.loaded.borrow().get(tree.tree_ids()) {
+            return Ok(codeowners.clone());
+        }
+        let codeowners = Rc::new(CodeOwners::load(tree).block_on()?);
+        self.loaded
+            .borrow_mut()
+            .insert(tree.tree_ids().clone(), codeowners.clone());
+        Ok(codeowners)
+    }
 }
 
 /// Builtin functions for the commit template language.
@@ -1359,7 +1472,13 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
                 let tree = commit.tree();
                 let entries: Vec<_> = tree
                     .entries_matching(&*matcher)
-                    .map(|(path, value)| value.map(|value| TreeEntry { path, value }))
+                    .map(|(path, value)| {
+                        value.map(|value| TreeEntry {
+                            path,
+                            value,
+                            tree: tree.clone(),
+                        })
+                    })
                     .try_collect()?;
                 Ok(entries)
             });
@@ -1374,7 +1493,13 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
                 let tree = commit.tree();
                 let entries: Vec<_> = tree
                     .conflicts()
-                    .map(|(path, value)| value.map(|value| TreeEntry { path, value }))
+                    .map(|(path, value)| {
+                        value.map(|value| TreeEntry {
+                            path,
+                            value,
+                            tree: tree.clone(),
+                        })
+                    })
                     .try_collect()?;
                 Ok(entries)
             });
@@ -1422,6 +1547,12 @@ fn expect_fileset_literal(
         diagnostics.extend_with(inner_diagnostics, |diag| {
             TemplateParseError::expression("In fileset expression", node.span).with_source(diag)
         });
+        if expression.has_filters() {
+            return Err(TemplateParseError::expression(
+                "File predicates are not supported in templates",
+                node.span,
+            ));
+        }
         Ok(expression)
     })
 }
@@ -2336,7 +2467,10 @@ impl TreeDiff {
 
     async fn collect_entries(&self) -> BackendResult<Vec<TreeDiffEntry>> {
         self.diff_stream()
-            .map(TreeDiffEntry::from_backend_entry_with_copies)
+            .map(|entry| {
+                let trees = Diff::new(&self.from_tree, &self.to_tree);
+                TreeDiffEntry::from_backend_entry_with_copies(entry, trees)
+            })
             .try_collect()
             .await
     }
@@ -2563,13 +2697,19 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
 pub struct TreeDiffEntry {
     pub path: CopiesTreeDiffEntryPath,
     pub values: Diff<MergedTreeValue>,
+    /// Trees the entry was compared between.
+    pub trees: Diff<MergedTree>,
 }
 
 impl TreeDiffEntry {
-    pub fn from_backend_entry_with_copies(entry: CopiesTreeDiffEntry) -> BackendResult<Self> {
+    pub fn from_backend_entry_with_copies(
+        entry: CopiesTreeDiffEntry,
+        trees: Diff<&MergedTree>,
+    ) -> BackendResult<Self> {
         Ok(Self {
             path: entry.path,
             values: entry.values?,
+            trees: trees.map(|tree| tree.clone()),
         })
     }
 
@@ -2581,6 +2721,7 @@ impl TreeDiffEntry {
         TreeEntry {
             path: self.path.source.map_or(self.path.target, |(path, _)| path),
             value: self.values.before,
+            tree: self.trees.before,
         }
     }
 
@@ -2588,8 +2729,46 @@ impl TreeDiffEntry {
         TreeEntry {
             path: self.path.target,
             value: self.values.after,
+            tree: self.trees.after,
         }
     }
+
+    /// Computes hunks of changed lines. Binary files have no hunks.
+    async fn hunks(
+        self,
+        store: &Store,
+        context: usize,
+        options: &diff_util::LineDiffOptions,
+        marker_style: ConflictMarkerStyle,
+    ) -> BackendResult<Vec<TreeDiffHunk>> {
+        let contents =
+            diff_util::materialize_diff_contents(store, &self.path, self.values, marker_style)
+                .await?;
+        if contents.before.is_binary || contents.after.is_binary {
+            return Ok(vec![]);
+        }
+        let contents = Diff::new(
+            contents.before.contents.as_bstr(),
+            contents.after.contents.as_bstr(),
+        );
+        let hunks = unified_diff_hunks(contents, context, options.compare_mode, options.algorithm)
+            .into_iter()
+            .map(|hunk| TreeDiffHunk {
+                left_line_range: hunk.left_line_range,
+                right_line_range: hunk.right_line_range,
+                lines: hunk
+                    .lines
+                    .into_iter()
+                    .map(|(line_type, tokens)| {
+                        let line = tokens.iter().map(|(_, data)| *data).collect_vec().concat();
+                        let line = line.strip_suffix(b"\n").unwrap_or(&line);
+                        (line_type, BString::from(line))
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(hunks)
+    }
 }
 
 fn format_diff_path(
@@ -2633,6 +2812,24 @@ fn builtin_tree_diff_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'r
             Ok(out_property.into_dyn_wrapped())
         },
     );
+    map.insert(
+        "owners",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let cache = language.keyword_cache.codeowners().clone();
+            // Both source and target paths are reviewed if renamed.
+            let out_property = self_property.and_then(move |entry| {
+                let codeowners = cache.get(&entry.trees.after)?;
+                Ok([entry.path.source(), entry.path.target()]
+                    .into_iter()
+                    .flat_map(|path| codeowners.owners(path))
+                    .unique()
+                    .map(str::to_owned)
+                    .collect_vec())
+            });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
     map.insert(
         "status_char",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -2641,6 +2838,38 @@ fn builtin_tree_diff_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'r
             Ok(out_property.into_dyn_wrapped())
         },
     );
+    map.insert(
+        "hunks",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let ([], [context_node]) = function.expect_named_arguments(&["context"])?;
+            let context_property = context_node
+                .map(|node| {
+                    template_builder::expect_usize_expression(
+                        language,
+                        diagnostics,
+                        build_ctx,
+                        node,
+                    )
+                })
+                .transpose()?;
+            let options = diff_util::UnifiedDiffOptions::from_settings(language.settings())
+                .map_err(|err| {
+                    let message = "Failed to load diff settings";
+                    TemplateParseError::expression(message, function.name_span).with_source(err)
+                })?;
+            let store = language.repo.store().clone();
+            let conflict_marker_style = language.conflict_marker_style;
+            let out_property =
+                (self_property, context_property).and_then(move |(entry, context)| {
+                    let context = context.unwrap_or(options.context);
+                    let hunks = entry
+                        .hunks(&store, context, &options.line_diff, conflict_marker_style)
+                        .block_on()?;
+                    Ok(hunks)
+                });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
     map.insert(
         "source",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -2660,11 +2889,160 @@ fn builtin_tree_diff_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'r
     map
 }
 
+/// Hunk of changed lines in a [`TreeDiffEntry`], with surrounding context.
+#[derive(Clone, Debug)]
+pub struct TreeDiffHunk {
+    /// 0-based range of lines in the left (or old) file.
+    pub left_line_range: Range<usize>,
+    /// 0-based range of lines in the right (or new) file.
+    pub right_line_range: Range<usize>,
+    /// Lines in this hunk without the trailing newline.
+    pub lines: Vec<(DiffLineType, BString)>,
+}
+
+impl TreeDiffHunk {
+    fn lines_of_type(&self, line_type: DiffLineType) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter(|(ty, _)| *ty == line_type)
+            .map(|(_, line)| line.to_str_lossy().into_owned())
+            .collect()
+    }
+
+    /// 1-based start line number of the left range.
+    fn old_start(&self) -> usize {
+        to_hunk_line_number(&self.left_line_range)
+    }
+
+    /// 1-based start line number of the right range.
+    fn new_start(&self) -> usize {
+        to_hunk_line_number(&self.right_line_range)
+    }
+
+    /// Formats `@@ -l,s +l,s @@` header of unified diff.
+    fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start(),
+            self.left_line_range.len(),
+            self.new_start(),
+            self.right_line_range.len()
+        )
+    }
+}
+
+/// Converts 0-based line range to the start line number in unified diff.
+///
+/// Empty range starts at the preceding line, or 0 if the range is at the start
+/// of the file.
+fn to_hunk_line_number(range: &Range<usize>) -> usize {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
+}
+
+fn builtin_tree_diff_hunk_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, TreeDiffHunk> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<TreeDiffHunk>::new();
+    map.insert(
+        "old_start",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|hunk| Ok(i64::try_from(hunk.old_start())?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "old_len",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|hunk| Ok(i64::try_from(hunk.left_line_range.len())?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "new_start",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|hunk| Ok(i64::try_from(hunk.new_start())?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "new_len",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|hunk| Ok(i64::try_from(hunk.right_line_range.len())?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "header",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|hunk| hunk.header());
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "lines",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|hunk| {
+                hunk.lines
+                    .iter()
+                    .map(|(line_type, line)| {
+                        let sigil = match line_type {
+                            DiffLineType::Context => ' ',
+                            DiffLineType::Removed => '-',
+                            DiffLineType::Added => '+',
+                        };
+                        format!("{sigil}{}", line.to_str_lossy())
+                    })
+                    .collect_vec()
+            });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "context",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|hunk| hunk.lines_of_type(DiffLineType::Context));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "removed",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|hunk| hunk.lines_of_type(DiffLineType::Removed));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "added",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|hunk| hunk.lines_of_type(DiffLineType::Added));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map
+}
+
 /// [`MergedTree`] entry.
 #[derive(Clone, Debug)]
 pub struct TreeEntry {
     pub path: RepoPathBuf,
     pub value: MergedTreeValue,
+    /// Tree containing the entry.
+    pub tree: MergedTree,
 }
 
 fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, TreeEntry> {
@@ -2679,6 +3057,19 @@ fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo,
             Ok(out_property.into_dyn_wrapped())
         },
     );
+    map.insert(
+        "owners",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let cache = language.keyword_cache.codeowners().clone();
+            let out_property = self_property.and_then(move |entry| {
+                let codeowners = cache.get(&entry.tree)?;
+                let owners = codeowners.owners(&entry.path);
+                Ok(owners.into_iter().map(str::to_owned).collect_vec())
+            });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
     map.insert(
         "conflict",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -2932,15 +3323,23 @@ fn builtin_cryptographic_signature_methods<'repo>()
     map
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct AnnotationLine {
     pub commit: Commit,
+    #[serde(serialize_with = "serialize_bstring_lossy")]
     pub content: BString,
     pub line_number: usize,
     pub original_line_number: usize,
     pub first_line_in_hunk: bool,
 }
 
+fn serialize_bstring_lossy<S>(content: &BString, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    content.to_str_lossy().serialize(serializer)
+}
+
 fn builtin_annotation_line_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, AnnotationLine>
 {
     let mut map = CommitTemplateBuildMethodFnMap::<AnnotationLine>::new();
@@ -2988,6 +3387,81 @@ fn builtin_annotation_line_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'r
     map
 }
 
+/// Line matched by `jj file search`.
+#[derive(Debug, Clone)]
+pub struct FileSearchMatch {
+    pub commit: Commit,
+    pub path: RepoPathBuf,
+    pub content: BString,
+    pub line_number: usize,
+}
+
+fn builtin_file_search_match_methods<'repo>()
+-> CommitTemplateBuildMethodFnMap<'repo, FileSearchMatch> {
+    let mut map = CommitTemplateBuildMethodFnMap::<FileSearchMatch>::new();
+    map.insert(
+        "commit",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|m| m.commit);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "path",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|m| m.path);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "content",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|m| m.content);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "line_number",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|m| Ok(i64::try_from(m.line_number)?));
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map
+}
+
+/// Revision created by `jj split --by` for a group of changed files.
+#[derive(Debug, Clone)]
+pub struct SplitGroup {
+    pub commit: Commit,
+    pub name: String,
+}
+
+fn builtin_split_group_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, SplitGroup> {
+    let mut map = CommitTemplateBuildMethodFnMap::<SplitGroup>::new();
+    map.insert(
+        "commit",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|group| group.commit);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "name",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|group| group.name);
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map
+}
+
 impl Template for Trailer {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         write!(formatter, "{}: {}", self.key, self.value)
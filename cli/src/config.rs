@@ -771,6 +771,7 @@ pub fn default_config_layers() -> Vec<ConfigLayer> {
     let mut layers = vec![
         parse(include_str!("config/colors.toml")),
         parse(include_str!("config/hints.toml")),
+        parse(include_str!("config/hunk_headers.toml")),
         parse(include_str!("config/merge_tools.toml")),
         parse(include_str!("config/misc.toml")),
         parse(include_str!("config/revsets.toml")),
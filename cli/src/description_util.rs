@@ -16,6 +16,7 @@ use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit_builder::DetachedCommitBuilder;
 use jj_lib::config::ConfigGetError;
+use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::file_util::IoResultExt as _;
 use jj_lib::file_util::PathError;
 use jj_lib::settings::UserSettings;
@@ -80,6 +81,14 @@ impl TextEditor {
         Ok(Self { editor })
     }
 
+    /// Returns the editor for rebase plans, which defaults to `ui.editor`.
+    pub fn sequence_editor_from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        match settings.get("ui.sequence-editor").optional()? {
+            Some(editor) => Ok(Self { editor }),
+            None => Self::from_settings(settings),
+        }
+    }
+
     /// Opens the given `path` in editor.
     pub fn edit_file(&self, path: impl AsRef<Path>) -> Result<(), TextEditError> {
         let mut cmd = self.editor.to_command();
@@ -15,18 +15,22 @@
 use std::borrow::Cow;
 use std::cmp::max;
 use std::cmp::min;
+use std::collections::BTreeSet;
 use std::future;
 use std::io;
 use std::iter;
 use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
 
 use bstr::BStr;
 use bstr::BString;
 use clap_complete::ArgValueCandidates;
 use futures::StreamExt as _;
 use futures::TryStreamExt as _;
+use futures::stream;
 use futures::stream::BoxStream;
 use itertools::Itertools as _;
 use jj_lib::backend::BackendError;
@@ -44,19 +48,26 @@ use jj_lib::conflicts::ConflictMaterializeOptions;
 use jj_lib::conflicts::MaterializedTreeDiffEntry;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::conflicts::materialize_tree_value;
 use jj_lib::conflicts::materialized_diff_stream;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::diff::ContentDiff;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::diff::DiffHunk;
 use jj_lib::diff::DiffHunkKind;
 use jj_lib::diff_presentation::DiffTokenType;
 use jj_lib::diff_presentation::FileContent;
 use jj_lib::diff_presentation::LineCompareMode;
 use jj_lib::diff_presentation::diff_by_line;
+use jj_lib::diff_presentation::diff_hunks_by_word;
 use jj_lib::diff_presentation::file_content_for_diff;
+use jj_lib::diff_presentation::funcname::FuncnameFinder;
+use jj_lib::diff_presentation::funcname::FuncnameMatcher;
+use jj_lib::diff_presentation::moved::MovedLines;
+use jj_lib::diff_presentation::moved::MovedLinesDetector;
 use jj_lib::diff_presentation::unified::DiffLineType;
 use jj_lib::diff_presentation::unified::UnifiedDiffError;
 use jj_lib::diff_presentation::unified::git_diff_part;
@@ -67,6 +78,10 @@ use jj_lib::files::ConflictDiffHunk;
 use jj_lib::files::DiffLineHunkSide;
 use jj_lib::files::DiffLineIterator;
 use jj_lib::files::DiffLineNumber;
+use jj_lib::fileset;
+use jj_lib::fileset::FilesetAliasesMap;
+use jj_lib::fileset::FilesetDiagnostics;
+use jj_lib::fileset::FilesetParseContext;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
 use jj_lib::merge::Merge;
@@ -75,12 +90,15 @@ use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::InvalidRepoPathError;
 use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::rewrite::rebase_to_dest_parent;
 use jj_lib::settings::UserSettings;
 use jj_lib::store::Store;
+use regex::bytes::Regex;
 use thiserror::Error;
 use tracing::instrument;
+use unicode_width::UnicodeWidthChar as _;
 use unicode_width::UnicodeWidthStr as _;
 
 use crate::command_error::CommandError;
@@ -103,7 +121,7 @@ use crate::ui::Ui;
 #[derive(clap::Args, Clone, Debug)]
 #[command(next_help_heading = "Diff Formatting Options")]
 #[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types", "name_only"])))]
-#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words"])))]
+#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "side_by_side"])))]
 pub struct DiffFormatArgs {
     /// For each path, show only whether it was modified, added, or deleted
     #[arg(long, short)]
@@ -138,6 +156,12 @@ pub struct DiffFormatArgs {
     #[arg(long)]
     pub color_words: bool,
 
+    /// Show a two-column diff of the left and right contents
+    ///
+    /// The width of the columns is determined by the terminal width.
+    #[arg(long)]
+    pub side_by_side: bool,
+
     /// Generate diff by external command
     ///
     /// A builtin format can also be specified as `:<name>`. For example,
@@ -158,6 +182,13 @@ pub struct DiffFormatArgs {
     /// Ignore changes in amount of whitespace when comparing lines.
     #[arg(long, conflicts_with = "ignore_all_space")] // short = 'b'
     ignore_space_change: bool,
+
+    /// Highlight blocks of lines moved within or across files
+    ///
+    /// Moved lines are shown in different colors in the `git` and
+    /// `color-words` formats, and counted in the `stat` summary.
+    #[arg(long)]
+    color_moved: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -169,6 +200,7 @@ pub enum DiffFormat {
     NameOnly,
     Git(Box<UnifiedDiffOptions>),
     ColorWords(Box<ColorWordsDiffOptions>),
+    SideBySide(Box<SideBySideDiffOptions>),
     Tool(Box<ExternalMergeTool>),
 }
 
@@ -180,6 +212,7 @@ enum BuiltinFormatKind {
     NameOnly,
     Git,
     ColorWords,
+    SideBySide,
 }
 
 impl BuiltinFormatKind {
@@ -193,6 +226,7 @@ impl BuiltinFormatKind {
         Self::NameOnly,
         Self::Git,
         Self::ColorWords,
+        Self::SideBySide,
     ];
 
     fn from_name(name: &str) -> Result<Self, String> {
@@ -203,6 +237,7 @@ impl BuiltinFormatKind {
             "name-only" => Ok(Self::NameOnly),
             "git" => Ok(Self::Git),
             "color-words" => Ok(Self::ColorWords),
+            "side-by-side" => Ok(Self::SideBySide),
             _ => Err(format!("Invalid builtin diff format: {name}")),
         }
     }
@@ -226,6 +261,8 @@ impl BuiltinFormatKind {
             Some(Self::Git)
         } else if args.color_words {
             Some(Self::ColorWords)
+        } else if args.side_by_side {
+            Some(Self::SideBySide)
         } else {
             None
         }
@@ -234,7 +271,7 @@ impl BuiltinFormatKind {
     fn is_short(self) -> bool {
         match self {
             Self::Summary | Self::Stat | Self::Types | Self::NameOnly => true,
-            Self::Git | Self::ColorWords => false,
+            Self::Git | Self::ColorWords | Self::SideBySide => false,
         }
     }
 
@@ -246,6 +283,7 @@ impl BuiltinFormatKind {
             Self::NameOnly => "name-only",
             Self::Git => "git",
             Self::ColorWords => "color-words",
+            Self::SideBySide => "side-by-side",
         }
     }
 
@@ -273,6 +311,11 @@ impl BuiltinFormatKind {
                 options.merge_args(args);
                 Ok(DiffFormat::ColorWords(Box::new(options)))
             }
+            Self::SideBySide => {
+                let mut options = SideBySideDiffOptions::from_settings(settings)?;
+                options.merge_args(args);
+                Ok(DiffFormat::SideBySide(Box::new(options)))
+            }
         }
     }
 }
@@ -532,6 +575,20 @@ impl<'a> DiffRenderer<'a> {
                     )
                     .await?;
                 }
+                DiffFormat::SideBySide(options) => {
+                    let tree_diff = diff_stream();
+                    show_side_by_side_diff(
+                        *formatter.labeled("side_by_side"),
+                        store,
+                        tree_diff,
+                        conflict_labels,
+                        path_converter,
+                        options,
+                        self.conflict_marker_style,
+                        width,
+                    )
+                    .await?;
+                }
                 DiffFormat::Tool(tool) => {
                     match tool.diff_invocation_mode {
                         DiffToolMode::FileByFile => {
@@ -550,7 +607,7 @@ impl<'a> DiffRenderer<'a> {
                             .await
                         }
                         DiffToolMode::Dir => {
-                            let mut writer = formatter.raw()?;
+                            let mut writer = formatter.external_output()?;
                             generate_diff(
                                 ui,
                                 writer.as_mut(),
@@ -574,6 +631,7 @@ impl<'a> DiffRenderer<'a> {
         &self,
         formatter: &mut dyn Formatter,
         descriptions: Diff<&Merge<&str>>,
+        width: usize,
     ) -> Result<(), DiffRenderError> {
         if !descriptions.is_changed() {
             return Ok(());
@@ -608,10 +666,23 @@ impl<'a> DiffRenderer<'a> {
                         formatter,
                         descriptions,
                         Diff::new(&ConflictLabels::unlabeled(), &ConflictLabels::unlabeled()),
+                        no_moved_lines(),
+                        None,
                         options,
                         &materialize_options,
                     )?;
                 }
+                DiffFormat::SideBySide(options) => {
+                    writeln!(formatter.labeled("header"), "Modified commit description:")?;
+                    show_side_by_side_diff_hunks(
+                        formatter,
+                        descriptions,
+                        Diff::new(&ConflictLabels::unlabeled(), &ConflictLabels::unlabeled()),
+                        options,
+                        &materialize_options,
+                        width,
+                    )?;
+                }
                 DiffFormat::Tool(_) => {
                     // TODO: materialize commit description as file?
                 }
@@ -651,6 +722,7 @@ impl<'a> DiffRenderer<'a> {
         self.show_diff_commit_descriptions(
             *formatter,
             Diff::new(&from_description, &to_description),
+            width,
         )?;
         self.show_diff_trees(
             ui,
@@ -722,10 +794,19 @@ pub enum ConflictDiffMethod {
 pub struct LineDiffOptions {
     /// How equivalence of lines is tested.
     pub compare_mode: LineCompareMode,
+    /// Algorithm used to compute the line diff.
+    pub algorithm: DiffAlgorithm,
     // TODO: add --ignore-blank-lines, etc. which aren't mutually exclusive.
 }
 
 impl LineDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(Self {
+            compare_mode: LineCompareMode::default(),
+            algorithm: settings.get("diff.algorithm")?,
+        })
+    }
+
     fn merge_args(&mut self, args: &DiffFormatArgs) {
         self.compare_mode = if args.ignore_all_space {
             LineCompareMode::IgnoreAllSpace
@@ -737,6 +818,96 @@ impl LineDiffOptions {
     }
 }
 
+/// Patterns to find the enclosing function name of hunks, loaded from the
+/// `diff.hunk-headers` table.
+#[derive(Clone, Debug, Default)]
+pub struct HunkHeaderOptions {
+    /// Enabled config entries sorted by name.
+    configs: Vec<(String, HunkHeaderConfig)>,
+    matcher: Arc<FuncnameMatcher>,
+}
+
+impl HunkHeaderOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        let configs: Vec<(String, HunkHeaderConfig)> = settings
+            .table_keys("diff.hunk-headers")
+            .sorted()
+            .map(|name| {
+                let config: HunkHeaderConfig = settings.get(["diff", "hunk-headers", name])?;
+                Ok((name.to_owned(), config))
+            })
+            .filter_ok(|(_, config)| config.enabled)
+            .try_collect()?;
+        // Config files aren't relative to the cwd. Fileset aliases aren't
+        // supported since they can't be loaded without reporting diagnostics.
+        static ROOT_PATH_CONVERTER: LazyLock<RepoPathUiConverter> =
+            LazyLock::new(|| RepoPathUiConverter::Fs {
+                cwd: PathBuf::new(),
+                base: PathBuf::new(),
+            });
+        let aliases_map = FilesetAliasesMap::new();
+        let context = FilesetParseContext {
+            aliases_map: &aliases_map,
+            path_converter: &ROOT_PATH_CONVERTER,
+        };
+        let mut matcher = FuncnameMatcher::new();
+        for (name, config) in &configs {
+            let config_error =
+                |key: &str, error: Box<dyn std::error::Error + Send + Sync>| ConfigGetError::Type {
+                    name: format!("diff.hunk-headers.{name}.{key}"),
+                    error,
+                    source_path: None,
+                };
+            let mut diagnostics = FilesetDiagnostics::new();
+            let expressions: Vec<_> = config
+                .patterns
+                .iter()
+                .map(|text| fileset::parse(&mut diagnostics, text, &context))
+                .try_collect()
+                .map_err(|err| config_error("patterns", err.into()))?;
+            let expression = fileset::FilesetExpression::union_all(expressions);
+            if expression.has_filters() {
+                let message = "File predicates are not supported";
+                return Err(config_error("patterns", message.into()));
+            }
+            let regex =
+                Regex::new(&config.regex).map_err(|err| config_error("regex", err.into()))?;
+            matcher.add_rule(expression.to_matcher(), regex);
+        }
+        Ok(Self {
+            configs,
+            matcher: Arc::new(matcher),
+        })
+    }
+
+    /// Returns the header pattern for the file at `path`.
+    fn regex_for(&self, path: &RepoPath) -> Option<&Regex> {
+        self.matcher.regex_for(path)
+    }
+}
+
+impl PartialEq for HunkHeaderOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.configs == other.configs
+    }
+}
+
+impl Eq for HunkHeaderOptions {}
+
+/// Entry of the `diff.hunk-headers` config table.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct HunkHeaderConfig {
+    patterns: Vec<String>,
+    regex: String,
+    #[serde(default = "default_hunk_header_enabled")]
+    enabled: bool,
+}
+
+fn default_hunk_header_enabled() -> bool {
+    true
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ColorWordsDiffOptions {
     /// How conflicts are processed and rendered.
@@ -747,6 +918,10 @@ pub struct ColorWordsDiffOptions {
     pub line_diff: LineDiffOptions,
     /// Maximum number of removed/added word alternation to inline.
     pub max_inline_alternation: Option<usize>,
+    /// Whether to highlight moved lines.
+    pub color_moved: bool,
+    /// Patterns to find function names shown in section separators.
+    pub hunk_headers: HunkHeaderOptions,
 }
 
 impl ColorWordsDiffOptions {
@@ -765,8 +940,10 @@ impl ColorWordsDiffOptions {
         Ok(Self {
             conflict: settings.get("diff.color-words.conflict")?,
             context: settings.get("diff.color-words.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
             max_inline_alternation,
+            color_moved: settings.get("diff.color-moved")?,
+            hunk_headers: HunkHeaderOptions::from_settings(settings)?,
         })
     }
 
@@ -775,6 +952,7 @@ impl ColorWordsDiffOptions {
             self.context = context;
         }
         self.line_diff.merge_args(args);
+        self.color_moved |= args.color_moved;
     }
 }
 
@@ -782,6 +960,8 @@ fn show_color_words_diff_hunks<T: AsRef<[u8]>>(
     formatter: &mut dyn Formatter,
     contents: Diff<&Merge<T>>,
     conflict_labels: Diff<&ConflictLabels>,
+    moved_lines: Diff<&BTreeSet<usize>>,
+    funcname_regex: Option<&Regex>,
     options: &ColorWordsDiffOptions,
     materialize_options: &ConflictMaterializeOptions,
 ) -> io::Result<()> {
@@ -790,7 +970,15 @@ fn show_color_words_diff_hunks<T: AsRef<[u8]>>(
     if let (Some(left), Some(right)) = (contents.before.as_resolved(), contents.after.as_resolved())
     {
         let contents = Diff::new(left, right).map(BStr::new);
-        show_color_words_resolved_hunks(formatter, contents, line_number, labels, options)?;
+        show_color_words_resolved_hunks(
+            formatter,
+            contents,
+            line_number,
+            labels,
+            moved_lines,
+            funcname_regex,
+            options,
+        )?;
         return Ok(());
     }
     match options.conflict {
@@ -803,6 +991,8 @@ fn show_color_words_diff_hunks<T: AsRef<[u8]>>(
                 contents.as_ref().map(BStr::new),
                 line_number,
                 labels,
+                moved_lines,
+                funcname_regex,
                 options,
             )?;
         }
@@ -831,6 +1021,7 @@ fn show_color_words_conflict_hunks(
     let line_diff = diff_by_line(
         itertools::chain(contents.before, contents.after),
         &options.line_diff.compare_mode,
+        options.line_diff.algorithm,
     );
     // Matching entries shouldn't appear consecutively in diff of two inputs.
     // However, if the inputs have conflicts, there may be a hunk that can be
@@ -856,6 +1047,7 @@ fn show_color_words_conflict_hunks(
                     options,
                     num_after,
                     num_before,
+                    None,
                 )?;
                 contexts.clear();
                 emitted = true;
@@ -867,6 +1059,7 @@ fn show_color_words_conflict_hunks(
                         Diff::new(left, right),
                         line_number,
                         labels,
+                        no_moved_lines(),
                         options,
                     )?
                 } else {
@@ -892,6 +1085,7 @@ fn show_color_words_conflict_hunks(
         options,
         num_after,
         num_before,
+        None,
     )
 }
 
@@ -942,8 +1136,15 @@ fn show_color_words_unresolved_hunk(
             false => labels.invert(),
         };
         // Individual hunk pair may be largely the same, so diff it again.
-        let new_line_number =
-            show_color_words_resolved_hunks(formatter, contents, line_number, labels, options)?;
+        let new_line_number = show_color_words_resolved_hunks(
+            formatter,
+            contents,
+            line_number,
+            labels,
+            no_moved_lines(),
+            None,
+            options,
+        )?;
         // Take max to assign unique line numbers to trailing hunks. The line
         // numbers can't be real anyway because preceding conflict hunks might
         // have been resolved.
@@ -960,9 +1161,17 @@ fn show_color_words_resolved_hunks(
     contents: Diff<&BStr>,
     mut line_number: DiffLineNumber,
     labels: Diff<&str>,
+    moved_lines: Diff<&BTreeSet<usize>>,
+    funcname_regex: Option<&Regex>,
     options: &ColorWordsDiffOptions,
 ) -> io::Result<DiffLineNumber> {
-    let line_diff = diff_by_line(contents.into_array(), &options.line_diff.compare_mode);
+    let mut funcname_finder =
+        funcname_regex.map(|regex| FuncnameFinder::new(contents.before, regex));
+    let line_diff = diff_by_line(
+        contents.into_array(),
+        &options.line_diff.compare_mode,
+        options.line_diff.algorithm,
+    );
     // Matching entries shouldn't appear consecutively in diff of two inputs.
     let mut context: Option<Diff<&BStr>> = None;
     let mut emitted = false;
@@ -987,6 +1196,7 @@ fn show_color_words_resolved_hunks(
                     options,
                     num_after,
                     num_before,
+                    funcname_finder.as_mut(),
                 )?;
                 context = None;
                 emitted = true;
@@ -995,6 +1205,7 @@ fn show_color_words_resolved_hunks(
                     hunk_contents,
                     line_number,
                     labels,
+                    moved_lines,
                     options,
                 )?;
             }
@@ -1011,10 +1222,15 @@ fn show_color_words_resolved_hunks(
         options,
         num_after,
         num_before,
+        None,
     )
 }
 
 /// Prints `num_after` lines, ellipsis, and `num_before` lines.
+///
+/// If `funcname_finder` is specified, the ellipsis is followed by the function
+/// name of the `num_before` lines.
+#[expect(clippy::too_many_arguments)]
 fn show_color_words_context_lines(
     formatter: &mut dyn Formatter,
     contexts: &[Diff<&BStr>],
@@ -1023,8 +1239,9 @@ fn show_color_words_context_lines(
     options: &ColorWordsDiffOptions,
     num_after: usize,
     num_before: usize,
+    funcname_finder: Option<&mut FuncnameFinder>,
 ) -> io::Result<DiffLineNumber> {
-    const SKIPPED_CONTEXT_LINE: &str = "    ...\n";
+    const SKIPPED_CONTEXT_LINE: &str = "    ...";
     let extract = |after: bool| -> (Vec<&[u8]>, Vec<&[u8]>, u32) {
         let mut lines = contexts
             .iter()
@@ -1072,6 +1289,7 @@ fn show_color_words_context_lines(
                 Diff::new(&left, &right).map(BStr::new),
                 line_number,
                 labels,
+                no_moved_lines(),
                 options,
             )
         }
@@ -1081,7 +1299,6 @@ fn show_color_words_context_lines(
     let (right_after, mut right_before, num_right_skipped) = extract(true);
     line_number = show(formatter, [&left_after, &right_after], line_number)?;
     if num_left_skipped > 0 || num_right_skipped > 0 {
-        write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
         line_number.left += num_left_skipped;
         line_number.right += num_right_skipped;
         if left_before.len() > num_before {
@@ -1092,6 +1309,16 @@ fn show_color_words_context_lines(
             right_before.pop();
             line_number.right += 1;
         }
+        write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+        let funcname = funcname_finder.and_then(|finder| {
+            let next_line_index = line_number.left - 1;
+            finder.find(next_line_index.try_into().unwrap())
+        });
+        if let Some(funcname) = funcname {
+            write!(formatter, " ")?;
+            formatter.write_all(funcname)?;
+        }
+        writeln!(formatter)?;
     }
     left_before.reverse();
     right_before.reverse();
@@ -1104,20 +1331,37 @@ fn show_color_words_diff_lines(
     contents: Diff<&BStr>,
     mut line_number: DiffLineNumber,
     labels: Diff<&str>,
+    moved_lines: Diff<&BTreeSet<usize>>,
     options: &ColorWordsDiffOptions,
 ) -> io::Result<DiffLineNumber> {
     let word_diff_hunks = ContentDiff::by_word(contents.into_array())
         .hunks()
         .collect_vec();
-    let can_inline = if formatter.maybe_color() {
-        match options.max_inline_alternation {
-            None => true,     // unlimited
-            Some(0) => false, // no need to count alternation
-            Some(max_num) => {
-                let groups = split_diff_hunks_by_matching_newline(&word_diff_hunks);
-                groups.map(count_diff_alternation).max().unwrap_or(0) <= max_num
-            }
-        }
+    // Line numbers are 1-based, whereas moved line indices are 0-based.
+    let is_moved =
+        |lines: &BTreeSet<usize>, line_number: u32| lines.contains(&(line_number as usize - 1));
+    let has_moved_lines = {
+        let num_left_lines = contents.before.split_inclusive(|b| *b == b'\n').count();
+        let num_right_lines = contents.after.split_inclusive(|b| *b == b'\n').count();
+        let left_start = line_number.left as usize - 1;
+        let right_start = line_number.right as usize - 1;
+        moved_lines
+            .before
+            .range(left_start..left_start + num_left_lines)
+            .next()
+            .is_some()
+            || moved_lines
+                .after
+                .range(right_start..right_start + num_right_lines)
+                .next()
+                .is_some()
+    };
+    let can_inline = if has_moved_lines {
+        // Moved lines are labeled as a whole, which can't be expressed by
+        // inline word hunks.
+        false
+    } else if formatter.maybe_color() {
+        can_inline_word_diff_hunks(&word_diff_hunks, options)
     } else {
         // Inline word hunks rely on color labels to distinguish sides. Without
         // color support, show separate before/after lines instead.
@@ -1150,7 +1394,8 @@ fn show_color_words_diff_lines(
                 Diff::new(Some(line_number.left), None),
                 labels,
             )?;
-            show_color_words_single_sided_line(formatter, tokens, labels.before)?;
+            let moved = is_moved(moved_lines.before, line_number.left);
+            show_color_words_single_sided_line(formatter, tokens, labels.before, moved)?;
             line_number.left += 1;
         }
         for tokens in &lines.after {
@@ -1159,7 +1404,8 @@ fn show_color_words_diff_lines(
                 Diff::new(None, Some(line_number.right)),
                 labels,
             )?;
-            show_color_words_single_sided_line(formatter, tokens, labels.after)?;
+            let moved = is_moved(moved_lines.after, line_number.right);
+            show_color_words_single_sided_line(formatter, tokens, labels.after, moved)?;
             line_number.right += 1;
         }
     }
@@ -1222,8 +1468,11 @@ fn show_color_words_single_sided_line(
     formatter: &mut dyn Formatter,
     tokens: &[(DiffTokenType, &[u8])],
     label: &str,
+    moved: bool,
 ) -> io::Result<()> {
-    show_diff_line_tokens(*formatter.labeled(label), tokens)?;
+    with_moved_label(*formatter.labeled(label), moved, |formatter| {
+        show_diff_line_tokens(formatter, tokens)
+    })?;
     let (_, data) = tokens.last().expect("diff line must not be empty");
     if !data.ends_with(b"\n") {
         writeln!(formatter)?;
@@ -1231,6 +1480,19 @@ fn show_color_words_single_sided_line(
     Ok(())
 }
 
+/// Returns true if the word diff hunks don't alternate more than
+/// `max_inline_alternation`.
+fn can_inline_word_diff_hunks(diff_hunks: &[DiffHunk], options: &ColorWordsDiffOptions) -> bool {
+    match options.max_inline_alternation {
+        None => true,     // unlimited
+        Some(0) => false, // no need to count alternation
+        Some(max_num) => {
+            let groups = split_diff_hunks_by_matching_newline(diff_hunks);
+            groups.map(count_diff_alternation).max().unwrap_or(0) <= max_num
+        }
+    }
+}
+
 /// Counts number of diff-side alternation, ignoring matching hunks.
 ///
 /// This function is meant to measure visual complexity of diff hunks. It's easy
@@ -1283,6 +1545,103 @@ async fn diff_content(
     .await
 }
 
+/// Materializes the file contents of a diff entry to be compared line by line.
+///
+/// Conflicts are materialized without labels, as in diff stats.
+pub async fn materialize_diff_contents(
+    store: &Store,
+    path: &CopiesTreeDiffEntryPath,
+    values: Diff<MergedTreeValue>,
+    marker_style: ConflictMarkerStyle,
+) -> BackendResult<Diff<FileContent<BString>>> {
+    let materialize_options = ConflictMaterializeOptions {
+        marker_style,
+        marker_len: None,
+        merge: store.merge_options().clone(),
+    };
+    let conflict_labels = ConflictLabels::unlabeled();
+    let before =
+        materialize_tree_value(store, path.source(), values.before, &conflict_labels).await?;
+    let after =
+        materialize_tree_value(store, path.target(), values.after, &conflict_labels).await?;
+    Ok(Diff::new(
+        diff_content(path.source(), before, &materialize_options).await?,
+        diff_content(path.target(), after, &materialize_options).await?,
+    ))
+}
+
+/// Buffers `tree_diff` entries, and detects lines moved across the files.
+///
+/// Returns the buffered entries to be rendered, and the moved lines keyed by
+/// target path. Files which can't be materialized or are binary are excluded
+/// from the detection.
+async fn detect_moved_lines<'a>(
+    store: &Store,
+    tree_diff: BoxStream<'a, CopiesTreeDiffEntry>,
+    conflict_labels: Diff<&ConflictLabels>,
+    line_diff: &LineDiffOptions,
+    materialize_options: &ConflictMaterializeOptions,
+) -> (BoxStream<'a, CopiesTreeDiffEntry>, MovedLines<RepoPathBuf>) {
+    let entries: Vec<CopiesTreeDiffEntry> = tree_diff.collect().await;
+    let entries_to_detect = entries
+        .iter()
+        .filter_map(|entry| {
+            let values = entry.values.as_ref().ok()?;
+            Some(CopiesTreeDiffEntry {
+                path: entry.path.clone(),
+                values: Ok(values.clone()),
+            })
+        })
+        .collect_vec();
+    let contents: Vec<(RepoPathBuf, Diff<BString>)> = materialized_diff_stream(
+        store,
+        stream::iter(entries_to_detect).boxed(),
+        conflict_labels,
+    )
+    .filter_map(async |MaterializedTreeDiffEntry { path, values }| {
+        let values = values.ok()?;
+        let left = diff_content(path.source(), values.before, materialize_options)
+            .await
+            .ok()?;
+        let right = diff_content(path.target(), values.after, materialize_options)
+            .await
+            .ok()?;
+        if left.is_binary || right.is_binary {
+            return None;
+        }
+        Some((
+            path.target().to_owned(),
+            Diff::new(left.contents, right.contents),
+        ))
+    })
+    .collect()
+    .await;
+    let mut detector = MovedLinesDetector::new(&line_diff.compare_mode, line_diff.algorithm);
+    for (path, contents) in &contents {
+        detector.add_file(path.clone(), contents.as_ref().map(|c| c.as_slice()));
+    }
+    (stream::iter(entries).boxed(), detector.detect())
+}
+
+/// Returns empty sets of moved lines for contents not subject to detection.
+fn no_moved_lines() -> Diff<&'static BTreeSet<usize>> {
+    static EMPTY: BTreeSet<usize> = BTreeSet::new();
+    Diff::new(&EMPTY, &EMPTY)
+}
+
+/// Calls `f` with the formatter labeled as "moved" if `moved` is true.
+fn with_moved_label(
+    formatter: &mut dyn Formatter,
+    moved: bool,
+    f: impl FnOnce(&mut dyn Formatter) -> io::Result<()>,
+) -> io::Result<()> {
+    if moved {
+        f(*formatter.labeled("moved"))
+    } else {
+        f(formatter)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 struct DiffContentAsMerge {
     file_content: Merge<BString>,
@@ -1388,6 +1747,65 @@ pub async fn show_color_words_diff(
     path_converter: &RepoPathUiConverter,
     options: &ColorWordsDiffOptions,
     marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let (tree_diff, moved_lines) = if options.color_moved {
+        let materialize_options = ConflictMaterializeOptions {
+            marker_style,
+            marker_len: None,
+            merge: store.merge_options().clone(),
+        };
+        detect_moved_lines(
+            store,
+            tree_diff,
+            conflict_labels,
+            &options.line_diff,
+            &materialize_options,
+        )
+        .await
+    } else {
+        (tree_diff, MovedLines::empty())
+    };
+    show_file_contents_diff(
+        formatter,
+        store,
+        tree_diff,
+        conflict_labels,
+        path_converter,
+        marker_style,
+        |formatter, path, contents, conflict_labels, materialize_options| {
+            show_color_words_diff_hunks(
+                formatter,
+                contents,
+                conflict_labels,
+                moved_lines.get(path),
+                options.hunk_headers.regex_for(path),
+                options,
+                materialize_options,
+            )
+        },
+    )
+    .await
+}
+
+/// Prints header and content hunks of each file.
+///
+/// This is the common part of the color-words and side-by-side formats. The
+/// `show_hunks` function is called with the target path and the contents of
+/// each text file.
+async fn show_file_contents_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
+    conflict_labels: Diff<&ConflictLabels>,
+    path_converter: &RepoPathUiConverter,
+    marker_style: ConflictMarkerStyle,
+    mut show_hunks: impl FnMut(
+        &mut dyn Formatter,
+        &RepoPath,
+        Diff<&Merge<BString>>,
+        Diff<&ConflictLabels>,
+        &ConflictMaterializeOptions,
+    ) -> io::Result<()>,
 ) -> Result<(), DiffRenderError> {
     let materialize_options = ConflictMaterializeOptions {
         marker_style,
@@ -1437,14 +1855,14 @@ pub async fn show_color_words_diff(
             } else if right_content.is_binary {
                 writeln!(formatter.labeled("binary"), "    (binary)")?;
             } else {
-                show_color_words_diff_hunks(
+                show_hunks(
                     formatter,
+                    right_path,
                     Diff::new(&empty_content(), &right_content.contents.file_content),
                     Diff::new(
                         &ConflictLabels::unlabeled(),
                         &right_content.contents.conflict_labels,
                     ),
-                    options,
                     &materialize_options,
                 )?;
             }
@@ -1508,8 +1926,9 @@ pub async fn show_color_words_diff(
             if left_content.is_binary || right_content.is_binary {
                 writeln!(formatter.labeled("binary"), "    (binary)")?;
             } else if left_content.contents != right_content.contents {
-                show_color_words_diff_hunks(
+                show_hunks(
                     formatter,
+                    right_path,
                     Diff::new(
                         &left_content.contents.file_content,
                         &right_content.contents.file_content,
@@ -1518,7 +1937,6 @@ pub async fn show_color_words_diff(
                         &left_content.contents.conflict_labels,
                         &right_content.contents.conflict_labels,
                     ),
-                    options,
                     &materialize_options,
                 )?;
             }
@@ -1534,14 +1952,14 @@ pub async fn show_color_words_diff(
             } else if left_content.is_binary {
                 writeln!(formatter.labeled("binary"), "    (binary)")?;
             } else {
-                show_color_words_diff_hunks(
+                show_hunks(
                     formatter,
+                    right_path,
                     Diff::new(&left_content.contents.file_content, &empty_content()),
                     Diff::new(
                         &left_content.contents.conflict_labels,
                         &ConflictLabels::unlabeled(),
                     ),
-                    options,
                     &materialize_options,
                 )?;
             }
@@ -1550,6 +1968,324 @@ pub async fn show_color_words_diff(
     Ok(())
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SideBySideDiffOptions {
+    /// Number of context lines to show.
+    pub context: usize,
+    /// How lines are compared, and how changed words are highlighted. The
+    /// context and color-moved options are unused.
+    pub color_words: ColorWordsDiffOptions,
+}
+
+impl SideBySideDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(Self {
+            context: settings.get("diff.side-by-side.context")?,
+            color_words: ColorWordsDiffOptions::from_settings(settings)?,
+        })
+    }
+
+    fn merge_args(&mut self, args: &DiffFormatArgs) {
+        if let Some(context) = args.context {
+            self.context = context;
+        }
+        self.color_words.line_diff.merge_args(args);
+    }
+}
+
+#[expect(clippy::too_many_arguments)]
+pub async fn show_side_by_side_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
+    conflict_labels: Diff<&ConflictLabels>,
+    path_converter: &RepoPathUiConverter,
+    options: &SideBySideDiffOptions,
+    marker_style: ConflictMarkerStyle,
+    width: usize,
+) -> Result<(), DiffRenderError> {
+    show_file_contents_diff(
+        formatter,
+        store,
+        tree_diff,
+        conflict_labels,
+        path_converter,
+        marker_style,
+        |formatter, _path, contents, conflict_labels, materialize_options| {
+            show_side_by_side_diff_hunks(
+                formatter,
+                contents,
+                conflict_labels,
+                options,
+                materialize_options,
+                width,
+            )
+        },
+    )
+    .await
+}
+
+/// Line to be printed in a column of side-by-side diff.
+struct SideBySideLine<'a> {
+    line_number: u32,
+    tokens: Cow<'a, [(DiffTokenType, &'a [u8])]>,
+}
+
+/// Row of display cells, and its display width.
+type SideBySideRow = (Vec<(DiffTokenType, String)>, usize);
+
+fn show_side_by_side_diff_hunks<T: AsRef<[u8]>>(
+    formatter: &mut dyn Formatter,
+    contents: Diff<&Merge<T>>,
+    conflict_labels: Diff<&ConflictLabels>,
+    options: &SideBySideDiffOptions,
+    materialize_options: &ConflictMaterializeOptions,
+    width: usize,
+) -> io::Result<()> {
+    // Conflicts are materialized so the conflict markers can be aligned with
+    // the lines of the other side.
+    let contents = contents
+        .zip(conflict_labels)
+        .map(|(side, labels)| materialize_merge_result_to_bytes(side, labels, materialize_options));
+    // Each column consists of "NNNN M " prefix and text. The columns are
+    // separated by " │ ".
+    let column_width = max(width.saturating_sub(17) / 2, 10);
+    let line_diff_options = &options.color_words.line_diff;
+    let line_diff = diff_by_line(
+        contents.as_ref().map(BStr::new).into_array(),
+        &line_diff_options.compare_mode,
+        line_diff_options.algorithm,
+    );
+    let hunks = line_diff.hunks().collect_vec();
+    let mut line_number = DiffLineNumber { left: 1, right: 1 };
+    for (i, hunk) in hunks.iter().enumerate() {
+        let &[left, right] = hunk.contents.as_slice() else {
+            panic!("hunk contents should have two sides")
+        };
+        match hunk.kind {
+            DiffHunkKind::Matching => {
+                // Lines may differ in whitespace, but the numbers of lines
+                // should be the same.
+                let lines = iter::zip(
+                    left.split_inclusive(|b| *b == b'\n'),
+                    right.split_inclusive(|b| *b == b'\n'),
+                )
+                .collect_vec();
+                let num_after = if i > 0 { options.context } else { 0 };
+                let num_before = if i + 1 < hunks.len() {
+                    options.context
+                } else {
+                    0
+                };
+                let (after_lines, before_lines) = if num_after + num_before < lines.len() {
+                    (&lines[..num_after], &lines[lines.len() - num_before..])
+                } else {
+                    (&lines[..], &lines[..0])
+                };
+                let mut formatter = formatter.labeled("context");
+                line_number = show_side_by_side_context_lines(
+                    *formatter,
+                    after_lines,
+                    line_number,
+                    column_width,
+                )?;
+                let num_skipped = lines.len() - after_lines.len() - before_lines.len();
+                if num_skipped > 0 {
+                    writeln!(formatter, "    ...")?;
+                    let num_skipped: u32 = num_skipped.try_into().unwrap();
+                    line_number.left += num_skipped;
+                    line_number.right += num_skipped;
+                }
+                line_number = show_side_by_side_context_lines(
+                    *formatter,
+                    before_lines,
+                    line_number,
+                    column_width,
+                )?;
+            }
+            DiffHunkKind::Different => {
+                // Paired lines are diffed again to highlight changed words
+                // unless the words alternate too much.
+                let word_diff_hunks =
+                    diff_hunks_by_word([left, right], &line_diff_options.compare_mode);
+                let lines = if can_inline_word_diff_hunks(&word_diff_hunks, &options.color_words) {
+                    unzip_diff_hunks_to_lines(&word_diff_hunks)
+                } else {
+                    unzip_diff_hunks_to_lines([DiffHunk::different([left, right])])
+                };
+                for pair in lines.before.iter().zip_longest(&lines.after) {
+                    let (left_tokens, right_tokens) = pair.left_and_right();
+                    let left_line = left_tokens.map(|tokens| SideBySideLine {
+                        line_number: line_number.left,
+                        tokens: Cow::Borrowed(tokens),
+                    });
+                    let right_line = right_tokens.map(|tokens| SideBySideLine {
+                        line_number: line_number.right,
+                        tokens: Cow::Borrowed(tokens),
+                    });
+                    show_side_by_side_line_pair(
+                        formatter,
+                        Diff::new(left_line.as_ref(), right_line.as_ref()),
+                        false,
+                        column_width,
+                    )?;
+                    line_number.left += u32::from(left_tokens.is_some());
+                    line_number.right += u32::from(right_tokens.is_some());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn show_side_by_side_context_lines(
+    formatter: &mut dyn Formatter,
+    lines: &[(&[u8], &[u8])],
+    mut line_number: DiffLineNumber,
+    column_width: usize,
+) -> io::Result<DiffLineNumber> {
+    for &(left, right) in lines {
+        let line_pair = Diff::new(
+            SideBySideLine {
+                line_number: line_number.left,
+                tokens: Cow::Owned(vec![(DiffTokenType::Matching, left)]),
+            },
+            SideBySideLine {
+                line_number: line_number.right,
+                tokens: Cow::Owned(vec![(DiffTokenType::Matching, right)]),
+            },
+        );
+        show_side_by_side_line_pair(formatter, line_pair.as_ref().map(Some), true, column_width)?;
+        line_number.left += 1;
+        line_number.right += 1;
+    }
+    Ok(line_number)
+}
+
+/// Prints left and right lines in columns. Long lines are wrapped.
+fn show_side_by_side_line_pair(
+    formatter: &mut dyn Formatter,
+    lines: Diff<Option<&SideBySideLine>>,
+    is_context: bool,
+    column_width: usize,
+) -> io::Result<()> {
+    let labels = Diff::new("removed", "added");
+    let markers = if is_context {
+        Diff::new(" ", " ")
+    } else {
+        Diff::new("-", "+")
+    };
+    let rows = lines.map(|line| {
+        line.map_or_else(Vec::new, |line| {
+            wrap_side_by_side_tokens(&line.tokens, column_width)
+        })
+    });
+    let num_rows = max(rows.before.len(), rows.after.len());
+    for i in 0..num_rows {
+        let first_row = i == 0;
+        let left_width = show_side_by_side_cell(
+            formatter,
+            lines
+                .before
+                .filter(|_| first_row)
+                .map(|line| line.line_number),
+            lines.before.filter(|_| first_row).map(|_| markers.before),
+            labels.before,
+            rows.before.get(i),
+            is_context,
+        )?;
+        write!(formatter, "{:pad$} │", "", pad = column_width - left_width)?;
+        if let Some(row) = rows.after.get(i) {
+            write!(formatter, " ")?;
+            show_side_by_side_cell(
+                formatter,
+                lines
+                    .after
+                    .filter(|_| first_row)
+                    .map(|line| line.line_number),
+                lines.after.filter(|_| first_row).map(|_| markers.after),
+                labels.after,
+                Some(row),
+                is_context,
+            )?;
+        }
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
+
+/// Prints line number, marker, and text of a column. Returns the display
+/// width of the text.
+fn show_side_by_side_cell(
+    formatter: &mut dyn Formatter,
+    line_number: Option<u32>,
+    marker: Option<&str>,
+    label: &str,
+    row: Option<&SideBySideRow>,
+    is_context: bool,
+) -> io::Result<usize> {
+    if let Some(line_number) = line_number {
+        write!(
+            formatter.labeled(label).labeled("line_number"),
+            "{line_number:>4}"
+        )?;
+    } else {
+        write!(formatter, "    ")?;
+    }
+    write!(formatter, " ")?;
+    match marker {
+        Some(marker) if !is_context => write!(formatter.labeled(label), "{marker}")?,
+        _ => write!(formatter, " ")?,
+    }
+    write!(formatter, " ")?;
+    let Some((segments, width)) = row else {
+        return Ok(0);
+    };
+    for (token_type, text) in segments {
+        if is_context {
+            write!(formatter, "{text}")?;
+        } else {
+            let mut formatter = formatter.labeled(label);
+            match token_type {
+                DiffTokenType::Matching => write!(formatter, "{text}")?,
+                DiffTokenType::Different => write!(formatter.labeled("token"), "{text}")?,
+            }
+        }
+    }
+    Ok(*width)
+}
+
+/// Splits line tokens into rows of the given display width. Tabs are expanded
+/// to spaces.
+fn wrap_side_by_side_tokens(tokens: &[(DiffTokenType, &[u8])], width: usize) -> Vec<SideBySideRow> {
+    const TAB_WIDTH: usize = 4;
+    let mut rows: Vec<SideBySideRow> = vec![(vec![], 0)];
+    for &(token_type, data) in tokens {
+        let data = data.strip_suffix(b"\n").unwrap_or(data);
+        for c in String::from_utf8_lossy(data).chars() {
+            let (_, row_width) = rows.last().unwrap();
+            let (text, char_width) = if c == '\t' {
+                let n = TAB_WIDTH - row_width % TAB_WIDTH;
+                (" ".repeat(n), n)
+            } else {
+                (c.to_string(), c.width().unwrap_or(0))
+            };
+            if row_width + char_width > width && *row_width > 0 {
+                rows.push((vec![], 0));
+            }
+            let (segments, row_width) = rows.last_mut().unwrap();
+            match segments.last_mut() {
+                Some((last_type, last_text)) if *last_type == token_type => {
+                    last_text.push_str(&text);
+                }
+                _ => segments.push((token_type, text)),
+            }
+            *row_width += char_width;
+        }
+    }
+    rows
+}
+
 #[expect(clippy::too_many_arguments)]
 pub async fn show_file_by_file_diff(
     ui: &Ui,
@@ -1629,7 +2365,7 @@ pub async fn show_file_by_file_diff(
             "width" => width.to_string(),
         };
 
-        let mut writer = formatter.raw()?;
+        let mut writer = formatter.external_output()?;
         invoke_external_diff(ui, writer.as_mut(), tool, temp_dir.path(), patterns)
             .map_err(DiffRenderError::DiffGenerate)?;
     }
@@ -1644,6 +2380,10 @@ pub struct UnifiedDiffOptions {
     pub show_path_prefix: bool,
     /// How lines are tokenized and compared.
     pub line_diff: LineDiffOptions,
+    /// Whether to highlight moved lines.
+    pub color_moved: bool,
+    /// Patterns to find function names shown in hunk headers.
+    pub hunk_headers: HunkHeaderOptions,
 }
 
 impl UnifiedDiffOptions {
@@ -1651,7 +2391,9 @@ impl UnifiedDiffOptions {
         Ok(Self {
             context: settings.get("diff.git.context")?,
             show_path_prefix: settings.get("diff.git.show-path-prefix")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
+            color_moved: settings.get("diff.color-moved")?,
+            hunk_headers: HunkHeaderOptions::from_settings(settings)?,
         })
     }
 
@@ -1660,12 +2402,15 @@ impl UnifiedDiffOptions {
             self.context = context;
         }
         self.line_diff.merge_args(args);
+        self.color_moved |= args.color_moved;
     }
 }
 
 fn show_unified_diff_hunks(
     formatter: &mut dyn Formatter,
     contents: Diff<&BStr>,
+    moved_lines: Diff<&BTreeSet<usize>>,
+    funcname_regex: Option<&Regex>,
     options: &UnifiedDiffOptions,
 ) -> io::Result<()> {
     // "If the chunk size is 0, the first number is one lower than one would
@@ -1683,23 +2428,57 @@ fn show_unified_diff_hunks(
         }
     }
 
-    for hunk in unified_diff_hunks(contents, options.context, options.line_diff.compare_mode) {
-        writeln!(
-            formatter.labeled("hunk_header"),
-            "@@ -{},{} +{},{} @@",
-            to_line_number(hunk.left_line_range.clone()),
-            hunk.left_line_range.len(),
-            to_line_number(hunk.right_line_range.clone()),
-            hunk.right_line_range.len()
-        )?;
+    let mut funcname_finder =
+        funcname_regex.map(|regex| FuncnameFinder::new(contents.before, regex));
+    for hunk in unified_diff_hunks(
+        contents,
+        options.context,
+        options.line_diff.compare_mode,
+        options.line_diff.algorithm,
+    ) {
+        let funcname = funcname_finder
+            .as_mut()
+            .and_then(|finder| finder.find(hunk.left_line_range.start));
+        {
+            let mut formatter = formatter.labeled("hunk_header");
+            write!(
+                formatter,
+                "@@ -{},{} +{},{} @@",
+                to_line_number(hunk.left_line_range.clone()),
+                hunk.left_line_range.len(),
+                to_line_number(hunk.right_line_range.clone()),
+                hunk.right_line_range.len()
+            )?;
+            if let Some(funcname) = funcname {
+                write!(formatter, " ")?;
+                formatter.write_all(funcname)?;
+            }
+            writeln!(formatter)?;
+        }
+        let mut left_line = hunk.left_line_range.start;
+        let mut right_line = hunk.right_line_range.start;
         for (line_type, tokens) in &hunk.lines {
-            let (label, sigil) = match line_type {
-                DiffLineType::Context => ("context", " "),
-                DiffLineType::Removed => ("removed", "-"),
-                DiffLineType::Added => ("added", "+"),
+            let (label, sigil, moved) = match line_type {
+                DiffLineType::Context => {
+                    left_line += 1;
+                    right_line += 1;
+                    ("context", " ", false)
+                }
+                DiffLineType::Removed => {
+                    let moved = moved_lines.before.contains(&left_line);
+                    left_line += 1;
+                    ("removed", "-", moved)
+                }
+                DiffLineType::Added => {
+                    let moved = moved_lines.after.contains(&right_line);
+                    right_line += 1;
+                    ("added", "+", moved)
+                }
             };
-            write!(formatter.labeled(label), "{sigil}")?;
-            show_diff_line_tokens(*formatter.labeled(label), tokens)?;
+            with_moved_label(*formatter.labeled(label), moved, |formatter| {
+                write!(formatter, "{sigil}")?;
+                show_diff_line_tokens(formatter, tokens)
+            })?;
             let (_, content) = tokens.last().expect("hunk line must not be empty");
             if !content.ends_with(b"\n") {
                 write!(formatter, "\n\\ No newline at end of file\n")?;
@@ -1735,6 +2514,18 @@ pub async fn show_git_diff(
         marker_len: None,
         merge: store.merge_options().clone(),
     };
+    let (tree_diff, moved_lines) = if options.color_moved {
+        detect_moved_lines(
+            store,
+            tree_diff,
+            conflict_labels,
+            &options.line_diff,
+            &materialize_options,
+        )
+        .await
+    } else {
+        (tree_diff, MovedLines::empty())
+    };
     let mut diff_stream = materialized_diff_stream(store, tree_diff, conflict_labels);
     while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
         let left_path = path.source();
@@ -1813,6 +2604,8 @@ pub async fn show_git_diff(
             show_unified_diff_hunks(
                 formatter,
                 Diff::new(&left_part.content.contents, &right_part.content.contents).map(BStr::new),
+                moved_lines.get(path.target()),
+                options.hunk_headers.regex_for(path.target()),
                 options,
             )?;
         }
@@ -1846,7 +2639,13 @@ fn show_git_diff_texts<T: AsRef<[u8]>>(
             materialize_options,
         )),
     });
-    show_unified_diff_hunks(formatter, contents.as_ref().map(Cow::as_ref), options)
+    show_unified_diff_hunks(
+        formatter,
+        contents.as_ref().map(Cow::as_ref),
+        no_moved_lines(),
+        None,
+        options,
+    )
 }
 
 #[instrument(skip_all)]
@@ -1902,24 +2701,30 @@ pub struct DiffStatOptions {
     /// How many characters to use at most, for the bar portion.
     /// If None, there is no width limit.
     pub max_bar_width: Option<usize>,
+    /// Whether to count moved lines.
+    pub color_moved: bool,
 }
 
 impl DiffStatOptions {
     pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
         Ok(Self {
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
             max_bar_width: settings.get("diff.stat.max-bar-width").optional()?,
+            color_moved: settings.get("diff.color-moved")?,
         })
     }
 
     fn merge_args(&mut self, args: &DiffFormatArgs) {
         self.line_diff.merge_args(args);
+        self.color_moved |= args.color_moved;
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct DiffStats {
     entries: Vec<DiffStatEntry>,
+    /// Number of moved lines if detection is enabled.
+    total_moved: Option<usize>,
 }
 
 impl DiffStats {
@@ -1936,7 +2741,7 @@ impl DiffStats {
             merge: store.merge_options().clone(),
         };
         let conflict_labels = ConflictLabels::unlabeled();
-        let entries = materialized_diff_stream(
+        let entries_and_contents: Vec<_> = materialized_diff_stream(
             store,
             tree_diff,
             Diff::new(&conflict_labels, &conflict_labels),
@@ -1955,11 +2760,33 @@ impl DiffStats {
                 Diff::new(&left_content, &right_content),
                 options,
             );
-            BackendResult::Ok(stat)
+            // Keep text contents only if needed for moved-line detection.
+            let contents = (options.color_moved && stat.added_removed.is_some())
+                .then(|| Diff::new(left_content.contents, right_content.contents));
+            BackendResult::Ok((stat, contents))
         })
         .try_collect()
         .await?;
-        Ok(Self { entries })
+        let total_moved = options.color_moved.then(|| {
+            let mut detector = MovedLinesDetector::new(
+                &options.line_diff.compare_mode,
+                options.line_diff.algorithm,
+            );
+            for (index, (_, contents)) in entries_and_contents.iter().enumerate() {
+                if let Some(contents) = contents {
+                    detector.add_file(index, contents.as_ref().map(|c| c.as_slice()));
+                }
+            }
+            detector.detect().count_total()
+        });
+        let entries = entries_and_contents
+            .into_iter()
+            .map(|(stat, _)| stat)
+            .collect();
+        Ok(Self {
+            entries,
+            total_moved,
+        })
     }
 
     /// List of stats per file.
@@ -1982,6 +2809,11 @@ impl DiffStats {
             .filter_map(|stat| stat.added_removed.map(|(_, removed)| removed))
             .sum()
     }
+
+    /// Total number of moved lines, or `None` if detection is disabled.
+    pub fn count_total_moved(&self) -> Option<usize> {
+        self.total_moved
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -2037,6 +2869,7 @@ fn get_diff_stat_entry(
         let diff = diff_by_line(
             contents.map(|content| &content.contents).into_array(),
             &options.line_diff.compare_mode,
+            options.line_diff.algorithm,
         );
         let mut added = 0;
         let mut removed = 0;
@@ -2194,8 +3027,9 @@ pub fn show_diff_stats(
     let total_added = stats.count_total_added();
     let total_removed = stats.count_total_removed();
     let total_files = stats.entries().len();
-    writeln!(
-        formatter.labeled("stat-summary"),
+    let mut formatter = formatter.labeled("stat-summary");
+    write!(
+        formatter,
         "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
         total_files,
         if total_files == 1 { "" } else { "s" },
@@ -2204,6 +3038,15 @@ pub fn show_diff_stats(
         total_removed,
         if total_removed == 1 { "" } else { "s" },
     )?;
+    if let Some(total_moved) = stats.count_total_moved() {
+        write!(
+            formatter,
+            ", {} line{} moved",
+            total_moved,
+            if total_moved == 1 { "" } else { "s" },
+        )?;
+    }
+    writeln!(formatter)?;
     Ok(())
 }
 
@@ -2259,10 +3102,11 @@ pub async fn show_names(
 pub async fn show_templated(
     formatter: &mut dyn Formatter,
     mut tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
+    trees: Diff<&MergedTree>,
     template: &TemplateRenderer<'_, commit_templater::TreeDiffEntry>,
 ) -> Result<(), DiffRenderError> {
     while let Some(entry) = tree_diff.next().await {
-        let entry = commit_templater::TreeDiffEntry::from_backend_entry_with_copies(entry)?;
+        let entry = commit_templater::TreeDiffEntry::from_backend_entry_with_copies(entry, trees)?;
         template.format(&entry, formatter)?;
     }
     Ok(())
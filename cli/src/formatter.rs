@@ -47,6 +47,21 @@ pub trait Formatter: Write {
     fn pop_label(&mut self);
 
     fn maybe_color(&self) -> bool;
+
+    /// Returns true if hyperlinks written as OSC 8 escape sequences through
+    /// [`Self::raw()`] can be rendered.
+    fn maybe_hyperlink(&self) -> bool {
+        self.maybe_color()
+    }
+
+    /// Returns a writer for text produced by an external program, such as a
+    /// diff tool. The text may contain ANSI escape sequences.
+    ///
+    /// Unlike [`Self::raw()`], the text may be transformed to fit in the
+    /// output format.
+    fn external_output(&mut self) -> io::Result<Box<dyn Write + '_>> {
+        self.raw()
+    }
 }
 
 impl<T: Formatter + ?Sized> Formatter for &mut T {
@@ -65,6 +80,14 @@ impl<T: Formatter + ?Sized> Formatter for &mut T {
     fn maybe_color(&self) -> bool {
         <T as Formatter>::maybe_color(self)
     }
+
+    fn maybe_hyperlink(&self) -> bool {
+        <T as Formatter>::maybe_hyperlink(self)
+    }
+
+    fn external_output(&mut self) -> io::Result<Box<dyn Write + '_>> {
+        <T as Formatter>::external_output(self)
+    }
 }
 
 impl<T: Formatter + ?Sized> Formatter for Box<T> {
@@ -83,6 +106,14 @@ impl<T: Formatter + ?Sized> Formatter for Box<T> {
     fn maybe_color(&self) -> bool {
         <T as Formatter>::maybe_color(self)
     }
+
+    fn maybe_hyperlink(&self) -> bool {
+        <T as Formatter>::maybe_hyperlink(self)
+    }
+
+    fn external_output(&mut self) -> io::Result<Box<dyn Write + '_>> {
+        <T as Formatter>::external_output(self)
+    }
 }
 
 /// [`Formatter`] adapters.
@@ -183,6 +214,7 @@ enum FormatterFactoryKind {
     PlainText,
     Sanitized,
     Color { rules: Arc<Rules>, debug: bool },
+    Html { rules: Arc<Rules> },
 }
 
 impl FormatterFactory {
@@ -202,6 +234,12 @@ impl FormatterFactory {
         Ok(Self { kind })
     }
 
+    pub fn html(config: &StackedConfig) -> Result<Self, ConfigGetError> {
+        let rules = Arc::new(rules_from_config(config)?);
+        let kind = FormatterFactoryKind::Html { rules };
+        Ok(Self { kind })
+    }
+
     pub fn new_formatter<'output, W: Write + 'output>(
         &self,
         output: W,
@@ -212,9 +250,29 @@ impl FormatterFactory {
             FormatterFactoryKind::Color { rules, debug } => {
                 Box::new(ColorFormatter::new(output, rules.clone(), *debug))
             }
+            FormatterFactoryKind::Html { rules } => {
+                Box::new(HtmlFormatter::new(output, rules.clone()))
+            }
+        }
+    }
+
+    /// Creates a formatter for the top-level output stream.
+    ///
+    /// Unlike [`Self::new_formatter()`], HTML output is wrapped in a `<pre>`
+    /// element.
+    pub fn new_stream_formatter<'output, W: Write + 'output>(
+        &self,
+        output: W,
+    ) -> Box<dyn Formatter + 'output> {
+        match &self.kind {
+            FormatterFactoryKind::Html { rules } => {
+                Box::new(HtmlFormatter::new(output, rules.clone()).wrapped_in_pre())
+            }
+            _ => self.new_formatter(output),
         }
     }
 
+    /// Returns true if the output may contain ANSI color escape sequences.
     pub fn maybe_color(&self) -> bool {
         matches!(self.kind, FormatterFactoryKind::Color { .. })
     }
@@ -357,35 +415,7 @@ impl<W: Write> ColorFormatter<W> {
         if let Some(cached) = self.cached_styles.get(&self.labels) {
             cached.clone()
         } else {
-            // We use the reverse list of matched indices as a measure of how well the rule
-            // matches the actual labels. For example, for rule "a d" and the actual labels
-            // "a b c d", we'll get [3,0]. We compare them by Rust's default Vec comparison.
-            // That means "a d" will trump both rule "d" (priority [3]) and rule
-            // "a b c" (priority [2,1,0]).
-            let mut matched_styles = vec![];
-            for (labels, style) in self.rules.as_ref() {
-                let mut labels_iter = self.labels.iter().enumerate();
-                // The indexes in the current label stack that match the required label.
-                let mut matched_indices = vec![];
-                for required_label in labels {
-                    for (label_index, label) in &mut labels_iter {
-                        if label == required_label {
-                            matched_indices.push(label_index);
-                            break;
-                        }
-                    }
-                }
-                if matched_indices.len() == labels.len() {
-                    matched_indices.reverse();
-                    matched_styles.push((style, matched_indices));
-                }
-            }
-            matched_styles.sort_by_key(|(_, indices)| indices.clone());
-
-            let mut style = Style::default();
-            for (matched_style, _) in matched_styles {
-                style.merge(matched_style);
-            }
+            let style = style_for_labels(&self.rules, &self.labels);
             self.cached_styles
                 .insert(self.labels.clone(), style.clone());
             style
@@ -483,6 +513,39 @@ impl<W: Write> ColorFormatter<W> {
     }
 }
 
+fn style_for_labels(rules: &Rules, labels: &[String]) -> Style {
+    // We use the reverse list of matched indices as a measure of how well the rule
+    // matches the actual labels. For example, for rule "a d" and the actual labels
+    // "a b c d", we'll get [3,0]. We compare them by Rust's default Vec comparison.
+    // That means "a d" will trump both rule "d" (priority [3]) and rule
+    // "a b c" (priority [2,1,0]).
+    let mut matched_styles = vec![];
+    for (rule_labels, style) in rules {
+        let mut labels_iter = labels.iter().enumerate();
+        // The indexes in the current label stack that match the required label.
+        let mut matched_indices = vec![];
+        for required_label in rule_labels {
+            for (label_index, label) in &mut labels_iter {
+                if label == required_label {
+                    matched_indices.push(label_index);
+                    break;
+                }
+            }
+        }
+        if matched_indices.len() == rule_labels.len() {
+            matched_indices.reverse();
+            matched_styles.push((style, matched_indices));
+        }
+    }
+    matched_styles.sort_by_key(|(_, indices)| indices.clone());
+
+    let mut style = Style::default();
+    for (matched_style, _) in matched_styles {
+        style.merge(matched_style);
+    }
+    style
+}
+
 fn rules_from_config(config: &StackedConfig) -> Result<Rules, ConfigGetError> {
     config
         .table_keys("colors")
@@ -659,6 +722,405 @@ impl<W: Write> Drop for ColorFormatter<W> {
     }
 }
 
+/// Formatter that renders labeled text as HTML.
+///
+/// Each run of text is wrapped in a `<span>` element whose classes are the
+/// active labels. The style configured for the labels is inlined in the
+/// `style` attribute, so the output can be rendered without a style sheet.
+/// Hyperlinks are rendered as `<a>` elements, and other escape sequences are
+/// removed. The output is an HTML fragment, which is supposed to be embedded
+/// in a `<pre>` element.
+#[derive(Clone, Debug)]
+pub struct HtmlFormatter<W: Write> {
+    output: W,
+    rules: Arc<Rules>,
+    /// The stack of currently applied labels.
+    labels: Vec<String>,
+    cached_css: HashMap<Vec<String>, String>,
+    /// The labels of the `<span>` element we last opened, or `None` if no
+    /// element is open.
+    current_labels: Option<Vec<String>>,
+    /// Escape sequence which has been partially written through `raw()`.
+    pending_escape: Vec<u8>,
+    /// Whether an `<a>` element is open.
+    link_open: bool,
+    pre: HtmlPreState,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum HtmlPreState {
+    None,
+    Pending,
+    Open,
+}
+
+impl<W: Write> HtmlFormatter<W> {
+    pub fn new(output: W, rules: Arc<Rules>) -> Self {
+        Self {
+            output,
+            rules,
+            labels: vec![],
+            cached_css: HashMap::new(),
+            current_labels: None,
+            pending_escape: vec![],
+            link_open: false,
+            pre: HtmlPreState::None,
+        }
+    }
+
+    pub fn for_config(output: W, config: &StackedConfig) -> Result<Self, ConfigGetError> {
+        let rules = rules_from_config(config)?;
+        Ok(Self::new(output, Arc::new(rules)))
+    }
+
+    /// Wraps the output in a `<pre>` element. The element is opened when the
+    /// first content is written.
+    pub fn wrapped_in_pre(mut self) -> Self {
+        self.pre = HtmlPreState::Pending;
+        self
+    }
+
+    fn requested_css(&mut self) -> String {
+        if let Some(cached) = self.cached_css.get(&self.labels) {
+            cached.clone()
+        } else {
+            let css = css_for_style(&style_for_labels(&self.rules, &self.labels));
+            self.cached_css.insert(self.labels.clone(), css.clone());
+            css
+        }
+    }
+
+    fn open_pre(&mut self) -> io::Result<()> {
+        if self.pre == HtmlPreState::Pending {
+            self.output.write_all(b"<pre>")?;
+            self.pre = HtmlPreState::Open;
+        }
+        Ok(())
+    }
+
+    fn close_span(&mut self) -> io::Result<()> {
+        if self.current_labels.take().is_some() {
+            self.output.write_all(b"</span>")?;
+        }
+        Ok(())
+    }
+
+    fn write_new_span(&mut self) -> io::Result<()> {
+        self.open_pre()?;
+        if self.current_labels.as_ref() == Some(&self.labels) {
+            return Ok(());
+        }
+        self.close_span()?;
+        if !self.labels.is_empty() {
+            let classes = self.labels.join(" ");
+            write!(self.output, "<span class=\"")?;
+            write_html_escaped(&mut self.output, classes.as_bytes())?;
+            let css = self.requested_css();
+            if css.is_empty() {
+                write!(self.output, "\">")?;
+            } else {
+                write!(self.output, "\" style=\"{css}\">")?;
+            }
+            self.current_labels = Some(self.labels.clone());
+        }
+        Ok(())
+    }
+
+    /// Writes `data` containing escape sequences. OSC 8 hyperlinks are
+    /// translated to `<a>` elements, and the other escape sequences are
+    /// removed. The remainder is escaped if `escape_text` is true.
+    fn write_with_escapes(&mut self, data: &[u8], escape_text: bool) -> io::Result<()> {
+        let mut text_start = 0;
+        for (i, &b) in data.iter().enumerate() {
+            if self.pending_escape.is_empty() && b != b'\x1b' {
+                continue;
+            }
+            if text_start < i {
+                self.write_text(&data[text_start..i], escape_text)?;
+            }
+            text_start = i + 1;
+            self.pending_escape.push(b);
+            if let Some(osc) = complete_escape_sequence(&self.pending_escape) {
+                let osc = osc.map(|osc| osc.to_vec());
+                self.pending_escape.clear();
+                if let Some(url) = osc.as_deref().and_then(parse_osc8_hyperlink) {
+                    self.write_hyperlink(url)?;
+                }
+            }
+        }
+        if text_start < data.len() {
+            self.write_text(&data[text_start..], escape_text)?;
+        }
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &[u8], escape_text: bool) -> io::Result<()> {
+        self.write_new_span()?;
+        if escape_text {
+            write_html_escaped(&mut self.output, text)
+        } else {
+            self.output.write_all(text)
+        }
+    }
+
+    /// Opens `<a>` element if `url` is not empty, or closes it otherwise.
+    fn write_hyperlink(&mut self, url: &[u8]) -> io::Result<()> {
+        self.open_pre()?;
+        // Don't nest <a> and <span> elements across boundaries
+        self.close_span()?;
+        if self.link_open {
+            self.output.write_all(b"</a>")?;
+            self.link_open = false;
+        }
+        if !url.is_empty() {
+            self.output.write_all(b"<a href=\"")?;
+            write_html_escaped(&mut self.output, url)?;
+            self.output.write_all(b"\">")?;
+            self.link_open = true;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `Some` if the `escape` sequence starting with ESC is complete. If
+/// the sequence is OSC, its content is returned.
+fn complete_escape_sequence(escape: &[u8]) -> Option<Option<&[u8]>> {
+    match escape {
+        [] | [_] => None,
+        // CSI: parameter and intermediate bytes followed by a final byte
+        [_, b'[', rest @ ..] => match rest.last() {
+            Some(0x40..=0x7e) => Some(None),
+            _ => None,
+        },
+        // OSC: terminated by BEL or ST
+        [_, b']', rest @ ..] => rest
+            .strip_suffix(b"\x07")
+            .or_else(|| rest.strip_suffix(b"\x1b\\"))
+            .map(Some),
+        // nF: intermediate bytes followed by a final byte
+        [_, 0x20..=0x2f, .., 0x20..=0x2f] | [_, 0x20..=0x2f] => None,
+        [_, 0x20..=0x2f, ..] => Some(None),
+        // Other two-byte sequences
+        [_, _] => Some(None),
+        _ => unreachable!(),
+    }
+}
+
+/// Parses OSC 8 `8;params;url` content into URL.
+fn parse_osc8_hyperlink(osc: &[u8]) -> Option<&[u8]> {
+    let rest = osc.strip_prefix(b"8;")?;
+    let pos = rest.iter().position(|&b| b == b';')?;
+    Some(&rest[pos + 1..])
+}
+
+fn css_for_style(style: &Style) -> String {
+    let (fg, bg) = if style.reverse.unwrap_or_default() {
+        // The terminal default colors are unknown. Use the system colors of
+        // the HTML document instead.
+        (
+            Some(style.bg.map_or("Canvas".to_owned(), css_for_color)),
+            Some(style.fg.map_or("CanvasText".to_owned(), css_for_color)),
+        )
+    } else {
+        (style.fg.map(css_for_color), style.bg.map(css_for_color))
+    };
+    let mut declarations = vec![];
+    if let Some(color) = fg {
+        declarations.push(format!("color: {color}"));
+    }
+    if let Some(color) = bg {
+        declarations.push(format!("background-color: {color}"));
+    }
+    if style.bold.unwrap_or_default() {
+        declarations.push("font-weight: bold".to_owned());
+    }
+    if style.dim.unwrap_or_default() {
+        declarations.push("opacity: 0.5".to_owned());
+    }
+    if style.italic.unwrap_or_default() {
+        declarations.push("font-style: italic".to_owned());
+    }
+    let decorations = [
+        (style.underline, "underline"),
+        (style.crossed_out, "line-through"),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| enabled.unwrap_or_default())
+    .map(|(_, decoration)| decoration)
+    .collect_vec();
+    if !decorations.is_empty() {
+        declarations.push(format!("text-decoration: {}", decorations.join(" ")));
+    }
+    declarations.join("; ")
+}
+
+fn css_for_color(color: Color) -> String {
+    // Colors of the xterm default palette
+    const BASIC_COLORS: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return "inherit".to_owned(),
+        Color::Black => 0,
+        Color::DarkRed => 1,
+        Color::DarkGreen => 2,
+        Color::DarkYellow => 3,
+        Color::DarkBlue => 4,
+        Color::DarkMagenta => 5,
+        Color::DarkCyan => 6,
+        Color::Grey => 7,
+        Color::DarkGrey => 8,
+        Color::Red => 9,
+        Color::Green => 10,
+        Color::Yellow => 11,
+        Color::Blue => 12,
+        Color::Magenta => 13,
+        Color::Cyan => 14,
+        Color::White => 15,
+        Color::AnsiValue(n) => n,
+        Color::Rgb { r, g, b } => return format!("#{r:02x}{g:02x}{b:02x}"),
+    };
+    let (r, g, b) = match index {
+        0..16 => BASIC_COLORS[usize::from(index)],
+        16..232 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        232.. => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    };
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn write_html_escaped(output: &mut impl Write, buf: &[u8]) -> io::Result<()> {
+    let mut start = 0;
+    for (i, b) in buf.iter().enumerate() {
+        let escaped: &[u8] = match b {
+            b'&' => b"&amp;",
+            b'<' => b"&lt;",
+            b'>' => b"&gt;",
+            b'"' => b"&quot;",
+            b'\x1b' => "␛".as_bytes(),
+            _ => continue,
+        };
+        output.write_all(&buf[start..i])?;
+        output.write_all(escaped)?;
+        start = i + 1;
+    }
+    output.write_all(&buf[start..])
+}
+
+impl<W: Write> Write for HtmlFormatter<W> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, Error> {
+        // Like ColorFormatter, close the element at the end of each line so
+        // each line can be processed independently.
+        for line in data.split_inclusive(|b| *b == b'\n') {
+            let (content, newline) = match line.strip_suffix(b"\n") {
+                Some(content) => (content, true),
+                None => (line, false),
+            };
+            if !content.is_empty() {
+                self.write_new_span()?;
+                write_html_escaped(&mut self.output, content)?;
+            }
+            if newline {
+                self.open_pre()?;
+                self.close_span()?;
+                self.output.write_all(b"\n")?;
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        // Don't open <pre> element for empty output
+        if self.pre != HtmlPreState::Pending {
+            self.write_new_span()?;
+        }
+        self.output.flush()
+    }
+}
+
+impl<W: Write> Formatter for HtmlFormatter<W> {
+    fn raw(&mut self) -> io::Result<Box<dyn Write + '_>> {
+        Ok(Box::new(HtmlEscapeSequenceWriter {
+            formatter: self,
+            escape_text: false,
+        }))
+    }
+
+    fn push_label(&mut self, label: &str) {
+        self.labels.push(label.to_owned());
+    }
+
+    fn pop_label(&mut self) {
+        self.labels.pop();
+    }
+
+    fn maybe_color(&self) -> bool {
+        false
+    }
+
+    fn maybe_hyperlink(&self) -> bool {
+        true
+    }
+
+    fn external_output(&mut self) -> io::Result<Box<dyn Write + '_>> {
+        Ok(Box::new(HtmlEscapeSequenceWriter {
+            formatter: self,
+            escape_text: true,
+        }))
+    }
+}
+
+impl<W: Write> Drop for HtmlFormatter<W> {
+    fn drop(&mut self) {
+        // Close the elements if the formatter was dropped without flushing.
+        self.close_span().ok();
+        if self.link_open {
+            self.output.write_all(b"</a>").ok();
+        }
+        if self.pre == HtmlPreState::Open {
+            self.output.write_all(b"</pre>\n").ok();
+        }
+    }
+}
+
+/// Writer that processes escape sequences written to [`HtmlFormatter`].
+struct HtmlEscapeSequenceWriter<'a, W: Write> {
+    formatter: &'a mut HtmlFormatter<W>,
+    escape_text: bool,
+}
+
+impl<W: Write> Write for HtmlEscapeSequenceWriter<'_, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.formatter.write_with_escapes(data, self.escape_text)?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.formatter.output.flush()
+    }
+}
+
 /// Like buffered formatter, but records `push`/`pop_label()` calls.
 ///
 /// This allows you to manipulate the recorded data without losing labels.
@@ -671,6 +1133,7 @@ pub struct FormatRecorder {
     data: Vec<u8>,
     ops: Vec<(usize, FormatOp)>,
     maybe_color: bool,
+    maybe_hyperlink: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -686,6 +1149,18 @@ impl FormatRecorder {
             data: vec![],
             ops: vec![],
             maybe_color,
+            maybe_hyperlink: maybe_color,
+        }
+    }
+
+    /// Creates new buffer which inherits the capabilities of the given
+    /// `formatter`.
+    pub fn for_formatter(formatter: &dyn Formatter) -> Self {
+        Self {
+            data: vec![],
+            ops: vec![],
+            maybe_color: formatter.maybe_color(),
+            maybe_hyperlink: formatter.maybe_hyperlink(),
         }
     }
 
@@ -695,6 +1170,7 @@ impl FormatRecorder {
             data: data.into(),
             ops: vec![],
             maybe_color: false,
+            maybe_hyperlink: false,
         }
     }
 
@@ -779,6 +1255,10 @@ impl Formatter for FormatRecorder {
     fn maybe_color(&self) -> bool {
         self.maybe_color
     }
+
+    fn maybe_hyperlink(&self) -> bool {
+        self.maybe_hyperlink
+    }
 }
 
 fn write_sanitized(output: &mut impl Write, buf: &[u8]) -> Result<(), Error> {
@@ -1571,6 +2051,112 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_html_formatter() -> TestResult {
+        // Labels are emitted as classes, and the styles are inlined. Each line
+        // is wrapped separately.
+        let config = config_from_string(indoc! {r##"
+            colors.outer = "green"
+            colors."outer inner" = { fg = "#123456", bg = "ansi-color-196", bold = true }
+            colors.styled = { italic = true, underline = true, crossed-out = true }
+            colors.reversed = { fg = "bright blue", reverse = true }
+        "##});
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = HtmlFormatter::for_config(&mut output, &config)?;
+        writeln!(formatter, "<plain> & \"quoted\"")?;
+        formatter.push_label("outer");
+        write!(formatter, "before ")?;
+        formatter.push_label("inner");
+        write!(formatter, "inside\nnext <line>")?;
+        formatter.pop_label();
+        writeln!(formatter, " after")?;
+        formatter.pop_label();
+        formatter.push_label("styled");
+        write!(formatter, "styled")?;
+        formatter.pop_label();
+        formatter.push_label("reversed");
+        write!(formatter, "reversed")?;
+        formatter.pop_label();
+        formatter.push_label("unknown");
+        write!(formatter, "\x1b[1mnot bold\n\n")?;
+        drop(formatter);
+        insta::assert_snapshot!(to_snapshot_string(output), @r#"
+        &lt;plain&gt; &amp; &quot;quoted&quot;
+        <span class="outer" style="color: #00cd00">before </span><span class="outer inner" style="color: #123456; background-color: #ff0000; font-weight: bold">inside</span>
+        <span class="outer inner" style="color: #123456; background-color: #ff0000; font-weight: bold">next &lt;line&gt;</span><span class="outer" style="color: #00cd00"> after</span>
+        <span class="styled" style="font-style: italic; text-decoration: underline line-through">styled</span><span class="reversed" style="color: Canvas; background-color: #5c5cff">reversed</span><span class="unknown">␛[1mnot bold</span>
+
+        [EOF]
+        "#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_html_formatter_dropped() -> TestResult {
+        // An open element should be closed when the formatter is dropped.
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = HtmlFormatter::new(&mut output, Arc::new(vec![]));
+        formatter.push_label("outer");
+        write!(formatter, "inside")?;
+        drop(formatter);
+        insta::assert_snapshot!(
+            to_snapshot_string(output),
+            @r#"
+        <span class="outer">inside</span>[EOF]
+        "#,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_html_formatter_raw_escapes() -> TestResult {
+        // OSC 8 hyperlinks are translated to <a> elements, and the other
+        // escape sequences are removed. Sequences may be split across writes.
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = HtmlFormatter::new(&mut output, Arc::new(vec![]));
+        assert!(!formatter.maybe_color());
+        assert!(formatter.maybe_hyperlink());
+        formatter.push_label("outer");
+        write!(formatter.raw()?, "\x1b]8;;https://example.com/?a=1&b=\"2\"")?;
+        write!(formatter.raw()?, "\x1b\\")?;
+        write!(formatter, "link")?;
+        write!(formatter.raw()?, "\x1b]8;;\x07\x1b[1m")?;
+        write!(formatter, " text")?;
+        formatter.pop_label();
+        write!(
+            formatter.external_output()?,
+            "\x1b[31m<tool>\x1b[0m & \x1b(B"
+        )?;
+        drop(formatter);
+        insta::assert_snapshot!(to_snapshot_string(output), @r#"
+        <a href="https://example.com/?a=1&amp;b=&quot;2&quot;"><span class="outer">link</span></a><span class="outer"> text</span>&lt;tool&gt; &amp; [EOF]
+        "#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_html_formatter_wrapped_in_pre() -> TestResult {
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = HtmlFormatter::new(&mut output, Arc::new(vec![])).wrapped_in_pre();
+        formatter.flush()?;
+        formatter.push_label("outer");
+        writeln!(formatter, "inside")?;
+        drop(formatter);
+        insta::assert_snapshot!(to_snapshot_string(output), @r#"
+        <pre><span class="outer">inside</span>
+        </pre>
+        [EOF]
+        "#);
+
+        // Empty output shouldn't be wrapped.
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = HtmlFormatter::new(&mut output, Arc::new(vec![])).wrapped_in_pre();
+        formatter.flush()?;
+        drop(formatter);
+        assert!(output.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_labeled_scope() -> TestResult {
         let config = config_from_string(indoc! {"
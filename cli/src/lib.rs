@@ -32,6 +32,7 @@ pub mod graphlog;
 pub mod merge_tools;
 pub mod movement_util;
 pub mod operation_templater;
+pub mod output_format;
 mod progress;
 pub mod revset_util;
 pub mod template_builder;
@@ -9,8 +9,6 @@ use std::sync::Arc;
 
 use bstr::BString;
 use itertools::Itertools as _;
-use jj_lib::backend::CopyId;
-use jj_lib::backend::TreeValue;
 use jj_lib::conflicts;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::ConflictMaterializeOptions;
@@ -37,6 +35,7 @@ use super::diff_working_copies::DiffType;
 use super::diff_working_copies::check_out_trees;
 use super::diff_working_copies::new_utf8_temp_dir;
 use super::diff_working_copies::set_readonly_recursively;
+use super::tree_value_from_file_ids;
 use crate::config::CommandNameAndArgs;
 use crate::config::find_all_variables;
 use crate::config::interpolate_variables;
@@ -191,7 +190,7 @@ async fn run_mergetool_external_single_file(
 ) -> Result<(), ConflictResolveError> {
     let MergeToolFile {
         repo_path,
-        conflict,
+        conflict: _,
         file,
     } = merge_tool_file;
 
@@ -317,18 +316,7 @@ async fn run_mergetool_external_single_file(
         ));
     }
 
-    let new_tree_value = match new_file_ids.into_resolved() {
-        Ok(file_id) => {
-            let executable = file.executable.expect("should have been resolved");
-            Merge::resolved(file_id.map(|id| TreeValue::File {
-                id,
-                executable,
-                copy_id: CopyId::placeholder(),
-            }))
-        }
-        // Update the file ids only, leaving the executable flags unchanged
-        Err(file_ids) => conflict.with_new_file_ids(&file_ids),
-    };
+    let new_tree_value = tree_value_from_file_ids(merge_tool_file, new_file_ids);
     tree_builder.set_or_remove(repo_path.to_owned(), new_tree_value);
     Ok(())
 }
@@ -15,20 +15,26 @@
 mod builtin;
 mod diff_working_copies;
 mod external;
+mod three_pane;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use futures::future::try_join_all;
 use jj_lib::backend::BackendError;
 use jj_lib::backend::CopyId;
+use jj_lib::backend::FileId;
 use jj_lib::backend::MergedTreeValue;
 use jj_lib::backend::TreeValue;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::config::ConfigNamePathBuf;
+use jj_lib::conflicts;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedFileConflictValue;
 use jj_lib::conflicts::try_materialize_file_conflict_value;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Diff;
@@ -54,6 +60,7 @@ use self::external::ExternalToolError;
 use self::external::edit_diff_external;
 pub use self::external::generate_diff;
 pub use self::external::invoke_external_diff;
+use self::three_pane::edit_merge_three_pane;
 use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
@@ -105,6 +112,18 @@ pub enum ConflictResolveError {
     ConflictTooComplicated { path: RepoPathBuf, sides: usize },
     #[error("{path:?} has conflicts in executable bit\n{summary}", summary = summary.trim_end())]
     ExecutableConflict { path: RepoPathBuf, summary: String },
+    #[error("The file {path:?} has no conflicted hunk {index}. It has {count} conflicted hunks.")]
+    HunkNotFound {
+        path: RepoPathBuf,
+        index: usize,
+        count: usize,
+    },
+    #[error("The conflict at {path:?} has no side {side}. It has {sides} sides.")]
+    SideNotFound {
+        path: RepoPathBuf,
+        side: usize,
+        sides: usize,
+    },
     #[error(
         "The output file is either unchanged or empty after the editor quit (run with --debug to \
          see the exact invocation)."
@@ -346,13 +365,6 @@ impl MergeToolFile {
                     path: repo_path.to_owned(),
                     summary: conflict.describe(tree.labels()),
                 })?;
-        // We only support conflicts with 2 sides (3-way conflicts)
-        if file.ids.num_sides() > 2 {
-            return Err(ConflictResolveError::ConflictTooComplicated {
-                path: repo_path.to_owned(),
-                sides: file.ids.num_sides(),
-            });
-        }
         if file.executable.is_none() {
             return Err(ConflictResolveError::ExecutableConflict {
                 path: repo_path.to_owned(),
@@ -365,12 +377,58 @@ impl MergeToolFile {
             file,
         })
     }
+
+    fn check_num_sides(&self) -> Result<(), ConflictResolveError> {
+        // We only support conflicts with 2 sides (3-way conflicts)
+        if self.file.ids.num_sides() > 2 {
+            return Err(ConflictResolveError::ConflictTooComplicated {
+                path: self.repo_path.clone(),
+                sides: self.file.ids.num_sides(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Builds the new tree value of a conflicted file from the updated file ids.
+fn tree_value_from_file_ids(
+    merge_tool_file: &MergeToolFile,
+    new_file_ids: Merge<Option<FileId>>,
+) -> MergedTreeValue {
+    match new_file_ids.into_resolved() {
+        Ok(file_id) => {
+            let executable = merge_tool_file
+                .file
+                .executable
+                .expect("should have been resolved");
+            Merge::resolved(file_id.map(|id| TreeValue::File {
+                id,
+                executable,
+                copy_id: CopyId::placeholder(),
+            }))
+        }
+        // Update the file ids only, leaving the executable flags unchanged
+        Err(file_ids) => merge_tool_file.conflict.with_new_file_ids(&file_ids),
+    }
+}
+
+/// How the `:builtin` merge editor presents conflicts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinMergeMode {
+    /// Select between the sides of conflicted hunks.
+    Select,
+    /// Show the sides and bases of each conflicted hunk above the editable
+    /// result.
+    #[serde(rename = "3-pane")]
+    ThreePane,
 }
 
 /// Configured 3-way merge editor.
 #[derive(Clone, Debug)]
 pub struct MergeEditor {
     tool: MergeTool,
+    builtin_mode: BuiltinMergeMode,
     path_converter: RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
 }
@@ -386,7 +444,7 @@ impl MergeEditor {
     ) -> Result<Self, MergeToolConfigError> {
         let tool = MergeTool::get_tool_config(settings, name)?
             .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
-        Self::new_inner(name, tool, path_converter, conflict_marker_style)
+        Self::new_inner(name, tool, settings, path_converter, conflict_marker_style)
     }
 
     /// Loads the default 3-way merge editor from the settings.
@@ -403,12 +461,13 @@ impl MergeEditor {
             None
         }
         .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_merge_args(&args)));
-        Self::new_inner(&args, tool, path_converter, conflict_marker_style)
+        Self::new_inner(&args, tool, settings, path_converter, conflict_marker_style)
     }
 
     fn new_inner(
         name: impl ToString,
         tool: MergeTool,
+        settings: &UserSettings,
         path_converter: RepoPathUiConverter,
         conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Self, MergeToolConfigError> {
@@ -421,6 +480,7 @@ impl MergeEditor {
         }
         Ok(Self {
             tool,
+            builtin_mode: settings.get("ui.builtin-merge-mode")?,
             path_converter,
             conflict_marker_style,
         })
@@ -439,12 +499,23 @@ impl MergeEditor {
                 .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path)),
         )
         .await?;
+        let is_three_pane =
+            self.tool == MergeTool::Builtin && self.builtin_mode == BuiltinMergeMode::ThreePane;
+        if !is_three_pane {
+            for merge_tool_file in &merge_tool_files {
+                merge_tool_file.check_num_sides()?;
+            }
+        }
 
         match &self.tool {
             MergeTool::Builtin => {
-                let tree = edit_merge_builtin(tree, &merge_tool_files)
-                    .await
-                    .map_err(Box::new)?;
+                let tree = match self.builtin_mode {
+                    BuiltinMergeMode::Select => edit_merge_builtin(tree, &merge_tool_files).await,
+                    BuiltinMergeMode::ThreePane => {
+                        edit_merge_three_pane(tree, &merge_tool_files).await
+                    }
+                }
+                .map_err(Box::new)?;
                 Ok((tree, None))
             }
             MergeTool::Ours => {
@@ -492,6 +563,55 @@ async fn pick_conflict_side(
     tree_builder.write_tree().await
 }
 
+/// Resolves conflicted hunks of the file at `repo_path` by picking sides.
+///
+/// `choices` maps 1-based hunk numbers, as shown in conflict markers, to
+/// 1-based side numbers.
+pub async fn pick_conflict_hunk_sides(
+    tree: &MergedTree,
+    repo_path: &RepoPath,
+    choices: &[(usize, usize)],
+) -> Result<MergedTree, ConflictResolveError> {
+    let merge_tool_file = MergeToolFile::from_tree_and_path(tree, repo_path).await?;
+    let file = &merge_tool_file.file;
+    let store = tree.store();
+    let hunks = match files::merge_hunks(&file.contents, store.merge_options()) {
+        MergeResult::Resolved(_) => {
+            return Err(ConflictResolveError::NotAConflict(repo_path.to_owned()));
+        }
+        MergeResult::Conflict(hunks) => hunks,
+    };
+    let count = hunks.iter().filter(|hunk| !hunk.is_resolved()).count();
+    let sides = file.ids.num_sides();
+    let mut side_indices = HashMap::new();
+    for &(index, side) in choices {
+        if !(1..=count).contains(&index) {
+            return Err(ConflictResolveError::HunkNotFound {
+                path: repo_path.to_owned(),
+                index,
+                count,
+            });
+        }
+        if !(1..=sides).contains(&side) {
+            return Err(ConflictResolveError::SideNotFound {
+                path: repo_path.to_owned(),
+                side,
+                sides,
+            });
+        }
+        side_indices.insert(index - 1, side - 1);
+    }
+    let hunks = conflicts::pick_hunk_sides(hunks, &side_indices);
+    let new_file_ids =
+        conflicts::update_from_hunks(&file.unsimplified_ids, store, repo_path, hunks).await?;
+    let mut tree_builder = MergedTreeBuilder::new(tree.clone());
+    tree_builder.set_or_remove(
+        repo_path.to_owned(),
+        tree_value_from_file_ids(&merge_tool_file, new_file_ids),
+    );
+    Ok(tree_builder.write_tree().await?)
+}
+
 #[cfg(test)]
 mod tests {
     use jj_lib::config::ConfigLayer;
@@ -0,0 +1,1069 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Built-in merge editor which shows the sides and bases of each conflicted
+//! hunk above the editable result.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+
+use bstr::BString;
+use crossterm::ExecutableCommand as _;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use crossterm::event::{self};
+use crossterm::terminal::EnterAlternateScreen;
+use crossterm::terminal::LeaveAlternateScreen;
+use crossterm::terminal::disable_raw_mode;
+use crossterm::terminal::enable_raw_mode;
+use itertools::Itertools as _;
+use jj_lib::conflicts;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree_builder::MergedTreeBuilder;
+use jj_lib::tree_merge::MergeOptions;
+use ratatui::Terminal;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Position;
+use ratatui::layout::Rect;
+use ratatui::prelude::CrosstermBackend;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Widget as _;
+
+use super::MergeToolFile;
+use super::builtin::BuiltinToolError;
+use super::tree_value_from_file_ids;
+
+/// Conflicted file being edited.
+#[derive(Clone, Debug)]
+struct FileState {
+    /// Path for displaying purposes.
+    path: String,
+    /// Titles of the panes showing the terms of the conflicted hunks.
+    term_titles: Vec<String>,
+    /// Hunks computed by merging the file contents.
+    hunks: Vec<Merge<BString>>,
+    /// Lines of the result for each hunk, or `None` if the conflicted hunk is
+    /// unresolved. Lines include the line terminator.
+    result: Vec<Option<Vec<String>>>,
+}
+
+impl FileState {
+    fn new(
+        path: String,
+        labels: &[String],
+        hunks: Vec<Merge<BString>>,
+    ) -> Result<Self, BuiltinToolError> {
+        let num_sides = hunks.iter().map(|hunk| hunk.num_sides()).max().unwrap_or(1);
+        let term_titles = (0..num_sides * 2 - 1)
+            .map(|index| {
+                let name = if index % 2 == 0 {
+                    format!("side #{}", index / 2 + 1)
+                } else if num_sides == 2 {
+                    "base".to_owned()
+                } else {
+                    format!("base #{}", index / 2 + 1)
+                };
+                match labels.get(index).filter(|label| !label.is_empty()) {
+                    Some(label) => format!("{name}: {label}"),
+                    None => name,
+                }
+            })
+            .collect();
+        let result = hunks
+            .iter()
+            .map(|hunk| match hunk.as_resolved() {
+                Some(content) => decode_lines(content, "unchanged hunk").map(Some),
+                None => {
+                    for term in hunk {
+                        decode_lines(term, "conflicting hunk")?;
+                    }
+                    Ok(None)
+                }
+            })
+            .try_collect()?;
+        Ok(Self {
+            path,
+            term_titles,
+            hunks,
+            result,
+        })
+    }
+
+    /// Returns the edited hunks. Unresolved hunks are left conflicted.
+    fn to_hunks(&self) -> Vec<Merge<BString>> {
+        itertools::zip_eq(&self.hunks, &self.result)
+            .map(|(hunk, lines)| match lines {
+                Some(lines) => Merge::resolved(lines.concat().into()),
+                None => hunk.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns rows of the result pane.
+    fn result_rows(&self) -> Vec<ResultRow> {
+        let mut rows = vec![];
+        for (hunk_index, (hunk, lines)) in self.hunks.iter().zip(&self.result).enumerate() {
+            match lines {
+                None => rows.push(ResultRow::Unresolved { hunk_index }),
+                Some(lines) if lines.is_empty() && !hunk.is_resolved() => {
+                    rows.push(ResultRow::Empty { hunk_index });
+                }
+                Some(lines) => rows.extend((0..lines.len()).map(|line_index| ResultRow::Text {
+                    hunk_index,
+                    line_index,
+                })),
+            }
+        }
+        rows
+    }
+}
+
+fn decode_lines(content: &[u8], item: &'static str) -> Result<Vec<String>, BuiltinToolError> {
+    let content =
+        str::from_utf8(content).map_err(|source| BuiltinToolError::DecodeUtf8 { source, item })?;
+    Ok(content.split_inclusive('\n').map(str::to_owned).collect())
+}
+
+fn strip_newline(line: &str) -> &str {
+    line.strip_suffix('\n').unwrap_or(line)
+}
+
+fn byte_offset(line: &str, col: usize) -> usize {
+    line.char_indices()
+        .nth(col)
+        .map_or(strip_newline(line).len(), |(offset, _)| offset)
+        .min(strip_newline(line).len())
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ResultRow {
+    Text {
+        hunk_index: usize,
+        line_index: usize,
+    },
+    /// Conflicted hunk resolved to nothing.
+    Empty {
+        hunk_index: usize,
+    },
+    Unresolved {
+        hunk_index: usize,
+    },
+}
+
+impl ResultRow {
+    fn hunk_index(self) -> usize {
+        match self {
+            Self::Text { hunk_index, .. }
+            | Self::Empty { hunk_index }
+            | Self::Unresolved { hunk_index } => hunk_index,
+        }
+    }
+
+    fn line_index(self) -> usize {
+        match self {
+            Self::Text { line_index, .. } => line_index,
+            Self::Empty { .. } | Self::Unresolved { .. } => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Focus {
+    /// Pane showing a term of the current conflict. The index is the position
+    /// of the term in `Merge::as_slice()`.
+    Term(usize),
+    Result,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    Normal,
+    Insert,
+}
+
+#[derive(Clone, Debug)]
+struct State {
+    files: Vec<FileState>,
+    /// `(file_index, hunk_index)` of each conflicted hunk.
+    conflicts: Vec<(usize, usize)>,
+    current_conflict: usize,
+    focus: Focus,
+    mode: Mode,
+    /// Cursor line in the focused term pane.
+    term_cursor: usize,
+    /// Selected lines in the focused term pane.
+    selected_lines: BTreeSet<usize>,
+    /// Cursor in the result pane of the current file.
+    result_hunk: usize,
+    result_line: usize,
+    result_col: usize,
+}
+
+impl State {
+    fn new(files: Vec<FileState>) -> Self {
+        let conflicts = files
+            .iter()
+            .enumerate()
+            .flat_map(|(file_index, file)| {
+                file.hunks
+                    .iter()
+                    .positions(|hunk| !hunk.is_resolved())
+                    .map(move |hunk_index| (file_index, hunk_index))
+            })
+            .collect_vec();
+        let mut state = Self {
+            files,
+            conflicts,
+            current_conflict: 0,
+            focus: Focus::Term(0),
+            mode: Mode::Normal,
+            term_cursor: 0,
+            selected_lines: BTreeSet::new(),
+            result_hunk: 0,
+            result_line: 0,
+            result_col: 0,
+        };
+        state.go_to_conflict(0);
+        state
+    }
+
+    fn current_file_index(&self) -> usize {
+        self.conflicts[self.current_conflict].0
+    }
+
+    fn current_file(&self) -> &FileState {
+        &self.files[self.current_file_index()]
+    }
+
+    fn current_hunk(&self) -> &Merge<BString> {
+        let (file_index, hunk_index) = self.conflicts[self.current_conflict];
+        &self.files[file_index].hunks[hunk_index]
+    }
+
+    fn num_terms(&self) -> usize {
+        self.current_hunk().as_slice().len()
+    }
+
+    fn term_lines(&self, term_index: usize) -> Vec<&str> {
+        let Some(term) = self.current_hunk().as_slice().get(term_index) else {
+            return vec![];
+        };
+        // Validated by FileState::new()
+        let term = str::from_utf8(term).unwrap();
+        term.split_inclusive('\n').collect()
+    }
+
+    fn go_to_conflict(&mut self, conflict_index: usize) {
+        self.current_conflict = conflict_index;
+        // Conflicts may have different numbers of terms
+        if let Focus::Term(term_index) = self.focus {
+            self.focus = Focus::Term(term_index.min(self.num_terms() - 1));
+        }
+        self.term_cursor = 0;
+        self.selected_lines.clear();
+        self.result_hunk = self.conflicts[conflict_index].1;
+        self.result_line = 0;
+        self.result_col = 0;
+    }
+
+    fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+        self.term_cursor = 0;
+        self.selected_lines.clear();
+    }
+
+    fn result_mut(&mut self) -> &mut Option<Vec<String>> {
+        let (file_index, hunk_index) = self.conflicts[self.current_conflict];
+        &mut self.files[file_index].result[hunk_index]
+    }
+
+    fn take_side(&mut self, side: usize) {
+        let Some(content) = self.current_hunk().get_add(side) else {
+            return;
+        };
+        // Validated by FileState::new()
+        let lines = decode_lines(content, "conflicting hunk").unwrap();
+        *self.result_mut() = Some(lines);
+        self.result_hunk = self.conflicts[self.current_conflict].1;
+        self.result_line = 0;
+        self.result_col = 0;
+    }
+
+    /// Appends the selected lines, or the line under the cursor, of the
+    /// focused term to the result of the current conflict.
+    fn append_selected_lines(&mut self) {
+        let Focus::Term(term_index) = self.focus else {
+            return;
+        };
+        let term_lines = self.term_lines(term_index);
+        let indices = if self.selected_lines.is_empty() {
+            BTreeSet::from([self.term_cursor])
+        } else {
+            self.selected_lines.clone()
+        };
+        let mut new_lines = indices
+            .iter()
+            .filter_map(|&index| term_lines.get(index))
+            .map(|&line| line.to_owned())
+            .collect_vec();
+        let result = self.result_mut().get_or_insert_default();
+        // Don't join lines if the term didn't end with newline.
+        if let Some(last) = result.last_mut()
+            && !last.ends_with('\n')
+            && !new_lines.is_empty()
+        {
+            last.push('\n');
+        }
+        result.append(&mut new_lines);
+        self.selected_lines.clear();
+    }
+
+    fn unresolve(&mut self) {
+        *self.result_mut() = None;
+        self.result_hunk = self.conflicts[self.current_conflict].1;
+        self.result_line = 0;
+        self.result_col = 0;
+    }
+
+    fn move_term_cursor(&mut self, delta: isize) {
+        let Focus::Term(term_index) = self.focus else {
+            return;
+        };
+        let num_lines = self.term_lines(term_index).len();
+        self.term_cursor = self
+            .term_cursor
+            .saturating_add_signed(delta)
+            .min(num_lines.saturating_sub(1));
+    }
+
+    fn result_row_index(&self, rows: &[ResultRow]) -> usize {
+        rows.iter()
+            .position(|row| {
+                row.hunk_index() == self.result_hunk && row.line_index() == self.result_line
+            })
+            .unwrap_or(0)
+    }
+
+    fn move_result_cursor(&mut self, delta: isize) {
+        let rows = self.current_file().result_rows();
+        let row_index = self
+            .result_row_index(&rows)
+            .saturating_add_signed(delta)
+            .min(rows.len().saturating_sub(1));
+        let Some(row) = rows.get(row_index) else {
+            return;
+        };
+        self.result_hunk = row.hunk_index();
+        self.result_line = row.line_index();
+        // Move to the conflict under the cursor
+        let file_index = self.current_file_index();
+        if let Some(conflict_index) = self
+            .conflicts
+            .iter()
+            .position(|&conflict| conflict == (file_index, self.result_hunk))
+        {
+            self.current_conflict = conflict_index;
+        }
+        self.clamp_result_col();
+    }
+
+    fn current_result_line(&self) -> Option<&str> {
+        let file = self.current_file();
+        let lines = file.result[self.result_hunk].as_ref()?;
+        lines.get(self.result_line).map(|line| line.as_str())
+    }
+
+    fn clamp_result_col(&mut self) {
+        let len = self
+            .current_result_line()
+            .map_or(0, |line| strip_newline(line).chars().count());
+        self.result_col = self.result_col.min(len);
+    }
+
+    /// Returns the lines of the hunk under the result cursor, resolving the
+    /// conflict or creating a line if needed.
+    fn editable_result_lines(&mut self) -> &mut Vec<String> {
+        let file_index = self.current_file_index();
+        let hunk_index = self.result_hunk;
+        let lines = self.files[file_index].result[hunk_index].get_or_insert_default();
+        if lines.is_empty() {
+            lines.push("\n".to_owned());
+            self.result_line = 0;
+            self.result_col = 0;
+        }
+        lines
+    }
+
+    fn insert_char(&mut self, ch: char) {
+        let (line_index, col) = (self.result_line, self.result_col);
+        let lines = self.editable_result_lines();
+        let line = &mut lines[line_index];
+        line.insert(byte_offset(line, col), ch);
+        self.result_col += 1;
+    }
+
+    fn insert_newline(&mut self) {
+        let (line_index, col) = (self.result_line, self.result_col);
+        let lines = self.editable_result_lines();
+        let line = &mut lines[line_index];
+        let rest = line.split_off(byte_offset(line, col));
+        line.push('\n');
+        lines.insert(line_index + 1, rest);
+        self.result_line += 1;
+        self.result_col = 0;
+    }
+
+    fn delete_char_backward(&mut self) {
+        let (line_index, col) = (self.result_line, self.result_col);
+        let lines = self.editable_result_lines();
+        if col > 0 {
+            let line = &mut lines[line_index];
+            let end = byte_offset(line, col);
+            let start = byte_offset(line, col - 1);
+            line.replace_range(start..end, "");
+            self.result_col -= 1;
+        } else if line_index > 0 {
+            let line = lines.remove(line_index);
+            let prev_line = &mut lines[line_index - 1];
+            if prev_line.ends_with('\n') {
+                prev_line.pop();
+            }
+            let prev_len = prev_line.chars().count();
+            prev_line.push_str(&line);
+            self.result_line -= 1;
+            self.result_col = prev_len;
+        }
+    }
+
+    fn handle_key_event(&mut self, event: KeyEvent) {
+        match self.mode {
+            Mode::Normal => self.handle_normal_key_event(event),
+            Mode::Insert => self.handle_insert_key_event(event),
+        }
+    }
+
+    fn handle_normal_key_event(&mut self, event: KeyEvent) {
+        let num_terms = self.num_terms();
+        match (event.code, event.modifiers) {
+            (KeyCode::Tab, _) => {
+                let focus = match self.focus {
+                    Focus::Term(index) if index + 1 < num_terms => Focus::Term(index + 1),
+                    Focus::Term(_) => Focus::Result,
+                    Focus::Result => Focus::Term(0),
+                };
+                self.set_focus(focus);
+            }
+            (KeyCode::BackTab, _) => {
+                let focus = match self.focus {
+                    Focus::Term(0) => Focus::Result,
+                    Focus::Term(index) => Focus::Term(index - 1),
+                    Focus::Result => Focus::Term(num_terms - 1),
+                };
+                self.set_focus(focus);
+            }
+            (KeyCode::Down | KeyCode::Char('j'), KeyModifiers::NONE) => match self.focus {
+                Focus::Term(_) => self.move_term_cursor(1),
+                Focus::Result => self.move_result_cursor(1),
+            },
+            (KeyCode::Up | KeyCode::Char('k'), KeyModifiers::NONE) => match self.focus {
+                Focus::Term(_) => self.move_term_cursor(-1),
+                Focus::Result => self.move_result_cursor(-1),
+            },
+            (KeyCode::Char('n'), KeyModifiers::NONE)
+                if self.current_conflict + 1 < self.conflicts.len() =>
+            {
+                self.go_to_conflict(self.current_conflict + 1);
+            }
+            (KeyCode::Char('p'), KeyModifiers::NONE) if self.current_conflict > 0 => {
+                self.go_to_conflict(self.current_conflict - 1);
+            }
+            (KeyCode::Char(' '), KeyModifiers::NONE) if matches!(self.focus, Focus::Term(_)) => {
+                if !self.selected_lines.remove(&self.term_cursor) {
+                    self.selected_lines.insert(self.term_cursor);
+                }
+                self.move_term_cursor(1);
+            }
+            (KeyCode::Char('a'), KeyModifiers::NONE) => self.append_selected_lines(),
+            (KeyCode::Char(ch @ '1'..='9'), KeyModifiers::NONE) => {
+                self.take_side(ch.to_digit(10).unwrap() as usize - 1);
+            }
+            (KeyCode::Char('u'), KeyModifiers::NONE) => self.unresolve(),
+            (KeyCode::Char('i'), KeyModifiers::NONE) => {
+                self.set_focus(Focus::Result);
+                self.mode = Mode::Insert;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_insert_key_event(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Char(ch) if !event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.insert_char(ch);
+            }
+            KeyCode::Enter => self.insert_newline(),
+            KeyCode::Backspace => self.delete_char_backward(),
+            KeyCode::Left => self.result_col = self.result_col.saturating_sub(1),
+            KeyCode::Right => {
+                self.result_col += 1;
+                self.clamp_result_col();
+            }
+            KeyCode::Up => self.move_result_cursor(-1),
+            KeyCode::Down => self.move_result_cursor(1),
+            _ => {}
+        }
+    }
+}
+
+const HELP_ITEMS_NORMAL: &[(&str, &str)] = &[
+    ("tab", "next pane"),
+    ("↓/j ↑/k", "move"),
+    ("n/p", "next/prev conflict"),
+    ("space", "select line"),
+    ("a", "append lines"),
+    ("1-9", "take side"),
+    ("u", "unresolve"),
+    ("i", "edit result"),
+    ("c", "confirm"),
+    ("q", "quit"),
+];
+
+const HELP_ITEMS_INSERT: &[(&str, &str)] = &[("esc", "stop editing")];
+
+fn help_line(mode: Mode) -> Line<'static> {
+    let items = match mode {
+        Mode::Normal => HELP_ITEMS_NORMAL,
+        Mode::Insert => HELP_ITEMS_INSERT,
+    };
+    let mut spans = Vec::new();
+    for (i, (key, desc)) in items.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" • "));
+        }
+        spans.push(Span::styled(*key, Style::default().fg(Color::Magenta)));
+        spans.push(Span::raw(format!(" {desc}")));
+    }
+    Line::from(spans)
+}
+
+fn focused_block(title: String, is_focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title);
+    if is_focused {
+        block.border_style(Style::default().fg(Color::Yellow))
+    } else {
+        block
+    }
+}
+
+/// First visible row to keep `cursor` visible in a pane of `height` rows.
+fn scroll_offset(cursor: usize, height: u16) -> usize {
+    cursor.saturating_sub(usize::from(height).saturating_sub(1))
+}
+
+/// Renders the UI, and returns the position of the terminal cursor in insert
+/// mode.
+fn render(state: &State, buf: &mut Buffer, area: Rect) -> Option<Position> {
+    let [terms_area, result_area, help_area] = Layout::vertical([
+        Constraint::Fill(2),
+        Constraint::Fill(3),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+    help_line(state.mode).render(help_area, buf);
+
+    let file = state.current_file();
+    let num_terms = state.num_terms();
+    let term_areas = Layout::horizontal(vec![Constraint::Fill(1); num_terms]).split(terms_area);
+    for (term_index, &term_area) in term_areas.iter().enumerate() {
+        let is_focused = state.focus == Focus::Term(term_index);
+        let block = focused_block(file.term_titles[term_index].clone(), is_focused);
+        let inner = block.inner(term_area);
+        block.render(term_area, buf);
+        let offset = if is_focused {
+            scroll_offset(state.term_cursor, inner.height)
+        } else {
+            0
+        };
+        let lines = state.term_lines(term_index);
+        for (row, (line_index, line)) in lines
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(usize::from(inner.height))
+            .enumerate()
+        {
+            let is_cursor = is_focused && line_index == state.term_cursor;
+            let is_selected = is_focused && state.selected_lines.contains(&line_index);
+            let marker = match (is_cursor, is_selected) {
+                (true, true) => "▶●",
+                (true, false) => "▶ ",
+                (false, true) => " ●",
+                (false, false) => "  ",
+            };
+            let style = if is_cursor {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::raw(marker),
+                Span::styled(strip_newline(line), style),
+            ])
+            .render(inner.rows().nth(row).unwrap(), buf);
+        }
+    }
+
+    let (_, current_hunk) = state.conflicts[state.current_conflict];
+    let num_conflicts = state.conflicts.len();
+    let title = format!(
+        "{} (conflict {} of {num_conflicts})",
+        file.path,
+        state.current_conflict + 1,
+    );
+    let is_focused = state.focus == Focus::Result;
+    let block = focused_block(title, is_focused);
+    let inner = block.inner(result_area);
+    block.render(result_area, buf);
+    let rows = file.result_rows();
+    let cursor_row = state.result_row_index(&rows);
+    let offset = scroll_offset(cursor_row, inner.height);
+    let mut cursor_position = None;
+    for (y, (row_index, row)) in rows
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(usize::from(inner.height))
+        .enumerate()
+    {
+        let row_area = inner.rows().nth(y).unwrap();
+        let is_cursor = is_focused && row_index == cursor_row;
+        let gutter = match (is_cursor, row.hunk_index() == current_hunk) {
+            (true, _) => "▶",
+            (false, true) => "┃",
+            (false, false) => " ",
+        };
+        let content = match *row {
+            ResultRow::Text {
+                hunk_index,
+                line_index,
+            } => {
+                let lines = file.result[hunk_index].as_ref().unwrap();
+                Span::raw(strip_newline(&lines[line_index]))
+            }
+            ResultRow::Empty { .. } => {
+                Span::styled("(empty)", Style::default().fg(Color::DarkGray))
+            }
+            ResultRow::Unresolved { hunk_index } => {
+                let conflict_index = state
+                    .conflicts
+                    .iter()
+                    .position(|&(file_index, hunk)| {
+                        file_index == state.current_file_index() && hunk == hunk_index
+                    })
+                    .unwrap();
+                Span::styled(
+                    format!("<<<<<<< conflict {} unresolved", conflict_index + 1),
+                    Style::default().fg(Color::Red),
+                )
+            }
+        };
+        Line::from(vec![Span::raw(gutter), Span::raw(" "), content]).render(row_area, buf);
+        if is_cursor && state.mode == Mode::Insert {
+            cursor_position = Some(Position::new(
+                row_area.x + 2 + state.result_col as u16,
+                row_area.y,
+            ));
+        }
+    }
+    cursor_position
+}
+
+fn run_tui<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    mut state: State,
+) -> Result<State, BuiltinToolError> {
+    let map_err =
+        |err: io::Error| BuiltinToolError::Record(scm_record::RecordError::RenderFrame(err.into()));
+    loop {
+        terminal
+            .draw(|frame| {
+                let area = frame.area();
+                if let Some(position) = render(&state, frame.buffer_mut(), area) {
+                    frame.set_cursor_position(position);
+                }
+            })
+            .map_err(|err| {
+                BuiltinToolError::Record(scm_record::RecordError::RenderFrame(
+                    err.to_string().into(),
+                ))
+            })?;
+        let event = event::read().map_err(map_err)?;
+        let Event::Key(event) = event else {
+            continue;
+        };
+        // On Windows, we get Press and Release (and maybe Repeat) events, but on
+        // Linux we only get Press.
+        if event.is_release() {
+            continue;
+        }
+        if state.mode == Mode::Normal {
+            match (event.code, event.modifiers) {
+                (KeyCode::Char('q'), KeyModifiers::NONE)
+                | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    return Err(BuiltinToolError::Record(scm_record::RecordError::Cancelled));
+                }
+                (KeyCode::Char('c'), KeyModifiers::NONE) => return Ok(state),
+                _ => {}
+            }
+        }
+        state.handle_key_event(event);
+    }
+}
+
+/// Puts the terminal in raw mode on the alternate screen, and restores it when
+/// dropped, so that the terminal isn't left broken if the TUI panics.
+struct TerminalGuard {
+    active: bool,
+}
+
+impl TerminalGuard {
+    fn new() -> io::Result<Self> {
+        io::stdout().execute(EnterAlternateScreen)?;
+        let guard = Self { active: true };
+        enable_raw_mode()?;
+        Ok(guard)
+    }
+
+    /// Restores the terminal, reporting errors unlike `drop()`.
+    fn restore(mut self) -> io::Result<()> {
+        self.active = false;
+        disable_raw_mode()?;
+        io::stdout().execute(LeaveAlternateScreen)?;
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.active {
+            disable_raw_mode().ok();
+            io::stdout().execute(LeaveAlternateScreen).ok();
+        }
+    }
+}
+
+fn make_file_state(
+    merge_tool_file: &MergeToolFile,
+    options: &MergeOptions,
+) -> Result<Option<FileState>, BuiltinToolError> {
+    let file = &merge_tool_file.file;
+    let MergeResult::Conflict(hunks) = files::merge_hunks(&file.contents, options) else {
+        return Ok(None);
+    };
+    let path = merge_tool_file
+        .repo_path
+        .to_fs_path_unchecked(Path::new(""))
+        .display()
+        .to_string();
+    FileState::new(path, file.labels.as_slice(), hunks).map(Some)
+}
+
+/// Resolves conflicts in the three-pane merge editor.
+pub async fn edit_merge_three_pane(
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+) -> Result<MergedTree, BuiltinToolError> {
+    let store = tree.store();
+    let mut edited_files = vec![];
+    let mut file_states = vec![];
+    for merge_tool_file in merge_tool_files {
+        if let Some(file_state) = make_file_state(merge_tool_file, store.merge_options())? {
+            edited_files.push(merge_tool_file);
+            file_states.push(file_state);
+        }
+    }
+    if file_states.is_empty() {
+        return Ok(tree.clone());
+    }
+
+    let set_up_err = |err: io::Error| {
+        BuiltinToolError::Record(scm_record::RecordError::SetUpTerminal(err.into()))
+    };
+    let guard = TerminalGuard::new().map_err(set_up_err)?;
+    let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .map_err(set_up_err)
+        .and_then(|mut terminal| run_tui(&mut terminal, State::new(file_states)));
+    guard.restore().map_err(|err| {
+        BuiltinToolError::Record(scm_record::RecordError::CleanUpTerminal(err.into()))
+    })?;
+    let state = result?;
+
+    let mut tree_builder = MergedTreeBuilder::new(tree.clone());
+    for (merge_tool_file, file_state) in edited_files.iter().zip(&state.files) {
+        let new_file_ids = conflicts::update_from_hunks(
+            &merge_tool_file.file.unsimplified_ids,
+            store,
+            &merge_tool_file.repo_path,
+            file_state.to_hunks(),
+        )
+        .await?;
+        tree_builder.set_or_remove(
+            merge_tool_file.repo_path.clone(),
+            tree_value_from_file_ids(merge_tool_file, new_file_ids),
+        );
+    }
+    Ok(tree_builder.write_tree().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(terms: &[&str]) -> Merge<BString> {
+        Merge::from_vec(terms.iter().map(|&term| BString::from(term)).collect_vec())
+    }
+
+    fn resolved(content: &str) -> Merge<BString> {
+        Merge::resolved(content.into())
+    }
+
+    fn new_state(hunks: Vec<Merge<BString>>, labels: &[&str]) -> State {
+        let labels = labels.iter().map(|&label| label.to_owned()).collect_vec();
+        let file = FileState::new("file".to_owned(), &labels, hunks).unwrap();
+        State::new(vec![file])
+    }
+
+    fn press(state: &mut State, keys: &str) {
+        for ch in keys.chars() {
+            let code = match ch {
+                '\t' => KeyCode::Tab,
+                '\n' => KeyCode::Enter,
+                '\x08' => KeyCode::Backspace,
+                '\x1b' => KeyCode::Esc,
+                ch => KeyCode::Char(ch),
+            };
+            state.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn result_text(state: &State) -> String {
+        state.files[0]
+            .to_hunks()
+            .iter()
+            .map(|hunk| match hunk.as_resolved() {
+                Some(content) => content.to_string(),
+                None => "<conflict>\n".to_owned(),
+            })
+            .collect()
+    }
+
+    fn render_to_string(state: &State, width: u16, height: u16) -> String {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        render(state, &mut buf, area);
+        let mut res = String::new();
+        for y in 0..height {
+            let line: String = (0..width).map(|x| buf[(x, y)].symbol()).collect();
+            res.push_str(line.trim_end());
+            res.push('\n');
+        }
+        res
+    }
+
+    #[test]
+    fn test_take_side_and_unresolve() {
+        let mut state = new_state(
+            vec![
+                resolved("1\n"),
+                hunk(&["a1\n", "base1\n", "b1\n"]),
+                resolved("2\n"),
+                hunk(&["a2\n", "base2\n", "b2\n"]),
+            ],
+            &[],
+        );
+        assert_eq!(result_text(&state), "1\n<conflict>\n2\n<conflict>\n");
+        press(&mut state, "2");
+        assert_eq!(result_text(&state), "1\nb1\n2\n<conflict>\n");
+        press(&mut state, "n1");
+        assert_eq!(result_text(&state), "1\nb1\n2\na2\n");
+        press(&mut state, "u");
+        assert_eq!(result_text(&state), "1\nb1\n2\n<conflict>\n");
+        // There's no side #3
+        press(&mut state, "3");
+        assert_eq!(result_text(&state), "1\nb1\n2\n<conflict>\n");
+    }
+
+    #[test]
+    fn test_append_lines() {
+        let mut state = new_state(vec![hunk(&["a1\na2\na3\n", "base\n", "b1\nb2\n"])], &[]);
+        // Select the first and third lines of side #1
+        press(&mut state, " j a");
+        assert_eq!(result_text(&state), "a1\na3\n");
+        // Append the line under the cursor of side #2
+        press(&mut state, "\t\tja");
+        assert_eq!(result_text(&state), "a1\na3\nb2\n");
+    }
+
+    #[test]
+    fn test_append_lines_without_newline() {
+        let mut state = new_state(vec![hunk(&["a", "base", "b"])], &[]);
+        press(&mut state, "a\t\ta");
+        assert_eq!(result_text(&state), "a\nb");
+    }
+
+    #[test]
+    fn test_go_to_conflict_with_fewer_terms() {
+        let mut state = new_state(
+            vec![
+                hunk(&["a\n", "base1\n", "b\n", "base2\n", "c\n"]),
+                resolved("1\n"),
+                hunk(&["d\n", "base\n", "e\n"]),
+            ],
+            &[],
+        );
+        // Focus side #3 of the first conflict, then move to the second conflict
+        // which has only 2 sides
+        press(&mut state, "\t\t\t\tn");
+        assert_eq!(state.focus, Focus::Term(2));
+        press(&mut state, "ja");
+        assert_eq!(result_text(&state), "<conflict>\n1\ne\n");
+        // Move back to the first conflict
+        press(&mut state, "p\t\t1");
+        assert_eq!(state.focus, Focus::Term(4));
+        assert_eq!(result_text(&state), "a\n1\ne\n");
+        insta::assert_snapshot!(render_to_string(&state, 80, 10), @"
+        ┌side #1───────┐┌base #1───────┐┌side #2───────┐┌base #2───────┐┌side #3───────┐
+        │  a           ││  base1       ││  b           ││  base2       ││▶ c           │
+        │              ││              ││              ││              ││              │
+        └──────────────┘└──────────────┘└──────────────┘└──────────────┘└──────────────┘
+        ┌file (conflict 1 of 2)────────────────────────────────────────────────────────┐
+        │┃ a                                                                           │
+        │  1                                                                           │
+        │  e                                                                           │
+        └──────────────────────────────────────────────────────────────────────────────┘
+        tab next pane • ↓/j ↑/k move • n/p next/prev conflict • space select line • a ap
+        ");
+    }
+
+    #[test]
+    fn test_edit_result() {
+        let mut state = new_state(
+            vec![
+                resolved("1\n"),
+                hunk(&["a\n", "base\n", "b\n"]),
+                resolved("2\n"),
+            ],
+            &[],
+        );
+        // Type into the unresolved conflict
+        press(&mut state, "ixy\nz\x1b");
+        assert_eq!(result_text(&state), "1\nxy\nz\n2\n");
+        // Join lines
+        press(&mut state, "i\x08\x08w\x1b");
+        assert_eq!(result_text(&state), "1\nxyw\n2\n");
+        // Edit unconflicted lines
+        state.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        press(&mut state, "i0\x1b");
+        assert_eq!(result_text(&state), "1\nxyw\n20\n");
+        // Take a side after editing
+        press(&mut state, "\t1");
+        assert_eq!(result_text(&state), "1\na\n20\n");
+    }
+
+    #[test]
+    fn test_render_two_sided_conflict() {
+        let mut state = new_state(
+            vec![
+                resolved("1\n"),
+                hunk(&["a1\na2\n", "base\n", "b\n"]),
+                resolved("2\n"),
+                hunk(&["c\n", "base\n", "d\n"]),
+            ],
+            &["left", "base", "right"],
+        );
+        press(&mut state, " ");
+        insta::assert_snapshot!(render_to_string(&state, 72, 16), @"
+        ┌side #1: left─────────┐┌base: base────────────┐┌side #2: right────────┐
+        │ ●a1                  ││  base                ││  b                   │
+        │▶ a2                  ││                      ││                      │
+        │                      ││                      ││                      │
+        │                      ││                      ││                      │
+        └──────────────────────┘└──────────────────────┘└──────────────────────┘
+        ┌file (conflict 1 of 2)────────────────────────────────────────────────┐
+        │  1                                                                   │
+        │┃ <<<<<<< conflict 1 unresolved                                       │
+        │  2                                                                   │
+        │  <<<<<<< conflict 2 unresolved                                       │
+        │                                                                      │
+        │                                                                      │
+        │                                                                      │
+        └──────────────────────────────────────────────────────────────────────┘
+        tab next pane • ↓/j ↑/k move • n/p next/prev conflict • space select lin
+        ");
+        press(&mut state, "a");
+        press(&mut state, "\t\t\t");
+        insta::assert_snapshot!(render_to_string(&state, 72, 16), @"
+        ┌side #1: left─────────┐┌base: base────────────┐┌side #2: right────────┐
+        │  a1                  ││  base                ││  b                   │
+        │  a2                  ││                      ││                      │
+        │                      ││                      ││                      │
+        │                      ││                      ││                      │
+        └──────────────────────┘└──────────────────────┘└──────────────────────┘
+        ┌file (conflict 1 of 2)────────────────────────────────────────────────┐
+        │  1                                                                   │
+        │▶ a1                                                                  │
+        │  2                                                                   │
+        │  <<<<<<< conflict 2 unresolved                                       │
+        │                                                                      │
+        │                                                                      │
+        │                                                                      │
+        └──────────────────────────────────────────────────────────────────────┘
+        tab next pane • ↓/j ↑/k move • n/p next/prev conflict • space select lin
+        ");
+    }
+
+    #[test]
+    fn test_render_multi_sided_conflict() {
+        let state = new_state(
+            vec![hunk(&["a\n", "base1\n", "b\n", "base2\n", "c\n"])],
+            &[],
+        );
+        insta::assert_snapshot!(render_to_string(&state, 80, 10), @"
+        ┌side #1───────┐┌base #1───────┐┌side #2───────┐┌base #2───────┐┌side #3───────┐
+        │▶ a           ││  base1       ││  b           ││  base2       ││  c           │
+        │              ││              ││              ││              ││              │
+        └──────────────┘└──────────────┘└──────────────┘└──────────────┘└──────────────┘
+        ┌file (conflict 1 of 1)────────────────────────────────────────────────────────┐
+        │┃ <<<<<<< conflict 1 unresolved                                               │
+        │                                                                              │
+        │                                                                              │
+        └──────────────────────────────────────────────────────────────────────────────┘
+        tab next pane • ↓/j ↑/k move • n/p next/prev conflict • space select line • a ap
+        ");
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Machine-readable output of listing commands.
+
+use std::io;
+use std::io::Write;
+
+/// Machine-readable output format selected by `--output-format`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// A single JSON array containing all items
+    Json,
+    /// One JSON object per line
+    Ndjson,
+}
+
+/// Writes serialized items in the selected [`OutputFormat`].
+///
+/// In `json` format, items are wrapped in an array, one item per line. In
+/// `ndjson` format, each item is terminated by a newline. [`finish()`] must be
+/// called to close the array.
+///
+/// [`finish()`]: Self::finish
+pub struct JsonListWriter<W> {
+    writer: W,
+    format: OutputFormat,
+    count: usize,
+}
+
+impl<W: Write> JsonListWriter<W> {
+    /// Creates new writer which emits items to the given `writer`.
+    pub fn new(writer: W, format: OutputFormat) -> Self {
+        Self {
+            writer,
+            format,
+            count: 0,
+        }
+    }
+
+    /// Serializes the given `item` and writes it to the output.
+    pub fn write_item<T: serde::Serialize + ?Sized>(&mut self, item: &T) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                let separator = if self.count == 0 { "[\n" } else { ",\n" };
+                self.writer.write_all(separator.as_bytes())?;
+                serde_json::to_writer(&mut self.writer, item)?;
+            }
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut self.writer, item)?;
+                self.writer.write_all(b"\n")?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Terminates the output.
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Json if self.count == 0 => self.writer.write_all(b"[]\n")?,
+            OutputFormat::Json => self.writer.write_all(b"\n]\n")?,
+            OutputFormat::Ndjson => {}
+        }
+        self.writer.flush()
+    }
+}
+
+/// Writes single serialized `value` followed by a newline.
+///
+/// Commands which report a single object rather than a list of items emit the
+/// same output in both formats.
+pub fn write_json_value<T: serde::Serialize + ?Sized>(
+    mut writer: impl Write,
+    value: &T,
+) -> io::Result<()> {
+    serde_json::to_writer(&mut writer, value)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_items(format: OutputFormat, items: &[i32]) -> String {
+        let mut output = Vec::new();
+        let mut writer = JsonListWriter::new(&mut output, format);
+        for item in items {
+            writer.write_item(item).unwrap();
+        }
+        writer.finish().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_list_writer() {
+        assert_eq!(write_items(OutputFormat::Json, &[]), "[]\n");
+        assert_eq!(write_items(OutputFormat::Json, &[1]), "[\n1\n]\n");
+        assert_eq!(write_items(OutputFormat::Json, &[1, 2]), "[\n1,\n2\n]\n");
+        assert_eq!(write_items(OutputFormat::Ndjson, &[]), "");
+        assert_eq!(write_items(OutputFormat::Ndjson, &[1, 2]), "1\n2\n");
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io;
 use std::iter;
 use std::path::Path;
@@ -925,6 +926,28 @@ impl<'a, P: CoreTemplatePropertyVar<'a>> Expression<P> {
         self.property.try_into_eq(other.property)
     }
 
+    /// Transforms into a key property to compare list items. Values of types
+    /// other than `Integer` and `Timestamp` are compared as strings.
+    fn try_into_list_item_key(self) -> Option<BoxedTemplateProperty<'a, ListItemKey>> {
+        let property = match self.property.try_into_integer() {
+            Ok(property) => return Some(property.map(ListItemKey::Integer).into_dyn()),
+            Err(property) => property,
+        };
+        let property = match property.try_into_timestamp() {
+            Ok(property) => {
+                let key = |timestamp: Timestamp| ListItemKey::Integer(timestamp.timestamp.0);
+                return Some(property.map(key).into_dyn());
+            }
+            Err(property) => property,
+        };
+        let expression = Self {
+            property,
+            labels: self.labels,
+        };
+        let property = expression.try_into_byte_stringify()?;
+        Some(property.map(ListItemKey::String).into_dyn())
+    }
+
     pub fn try_into_cmp(self, other: Self) -> Option<BoxedTemplateProperty<'a, Ordering>> {
         self.property.try_into_cmp(other.property)
     }
@@ -2205,9 +2228,284 @@ where
             Ok(L::Property::wrap_property(out_property.into_dyn()))
         },
     );
+    map.insert(
+        "sort_by",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let [lambda_node] = function.expect_exact_arguments()?;
+            let item_placeholder = PropertyPlaceholder::new();
+            let item_key = build_item_key_expression(
+                language,
+                diagnostics,
+                build_ctx,
+                &item_placeholder,
+                Some(lambda_node),
+                function,
+            )?;
+            let out_property = self_property.and_then(move |items| {
+                let mut keyed_items = extract_item_keys(items, &item_placeholder, &item_key)?;
+                // Stable sort to preserve the original order of equal items
+                keyed_items.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+                Ok(keyed_items.into_iter().map(|(_, item)| item).collect_vec())
+            });
+            Ok(L::Property::wrap_property(out_property.into_dyn()))
+        },
+    );
+    map.insert(
+        "unique",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let ([], [lambda_node]) = function.expect_arguments()?;
+            let item_placeholder = PropertyPlaceholder::new();
+            let item_key = build_item_key_expression(
+                language,
+                diagnostics,
+                build_ctx,
+                &item_placeholder,
+                lambda_node,
+                function,
+            )?;
+            let out_property = self_property.and_then(move |items| {
+                let keyed_items = extract_item_keys(items, &item_placeholder, &item_key)?;
+                let mut seen = HashSet::new();
+                Ok(keyed_items
+                    .into_iter()
+                    .filter_map(|(key, item)| seen.insert(key).then_some(item))
+                    .collect_vec())
+            });
+            Ok(L::Property::wrap_property(out_property.into_dyn()))
+        },
+    );
+    map.insert(
+        "group_by",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let mapped_groups = build_group_by_operation(
+                language,
+                diagnostics,
+                build_ctx,
+                self_property,
+                function,
+            )?;
+            Ok(L::Property::wrap_any_list(mapped_groups))
+        },
+    );
+    map.insert(
+        "sum",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let [lambda_node] = function.expect_exact_arguments()?;
+            let item_placeholder = PropertyPlaceholder::new();
+            let item_value =
+                template_parser::catch_aliases(diagnostics, lambda_node, |diagnostics, node| {
+                    let lambda = template_parser::expect_lambda(node)?;
+                    build_lambda_expression(
+                        build_ctx,
+                        lambda,
+                        &[&|| item_placeholder.clone().into_dyn_wrapped()],
+                        |build_ctx, body| {
+                            expect_integer_expression(language, diagnostics, build_ctx, body)
+                        },
+                    )
+                })?;
+            let out_property = self_property.and_then(move |items| {
+                items.into_iter().try_fold(0_i64, |total, item| {
+                    let value = item_placeholder.with_value(item, || item_value.extract())?;
+                    total
+                        .checked_add(value)
+                        .ok_or_else(|| TemplatePropertyError("Attempt to add with overflow".into()))
+                })
+            });
+            Ok(out_property.into_dyn_wrapped())
+        },
+    );
+    map.insert(
+        "min",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let ([], [lambda_node]) = function.expect_arguments()?;
+            let item_placeholder = PropertyPlaceholder::new();
+            let item_key = build_item_key_expression(
+                language,
+                diagnostics,
+                build_ctx,
+                &item_placeholder,
+                lambda_node,
+                function,
+            )?;
+            // TODO: Return `Option<T>` instead of erroring out.
+            let out_property = self_property.and_then(move |items| {
+                let keyed_items = extract_item_keys(items, &item_placeholder, &item_key)?;
+                keyed_items
+                    .into_iter()
+                    .min_by(|(key1, _), (key2, _)| key1.cmp(key2))
+                    .map(|(_, item)| item)
+                    .ok_or_else(|| TemplatePropertyError("List is empty".into()))
+            });
+            Ok(L::Property::wrap_property(out_property.into_dyn()))
+        },
+    );
+    map.insert(
+        "max",
+        |language, diagnostics, build_ctx, self_property, function| {
+            let ([], [lambda_node]) = function.expect_arguments()?;
+            let item_placeholder = PropertyPlaceholder::new();
+            let item_key = build_item_key_expression(
+                language,
+                diagnostics,
+                build_ctx,
+                &item_placeholder,
+                lambda_node,
+                function,
+            )?;
+            // TODO: Return `Option<T>` instead of erroring out.
+            let out_property = self_property.and_then(move |items| {
+                let keyed_items = extract_item_keys(items, &item_placeholder, &item_key)?;
+                // Reversed min_by() so the first maximum item is selected
+                keyed_items
+                    .into_iter()
+                    .min_by(|(key1, _), (key2, _)| key2.cmp(key1))
+                    .map(|(_, item)| item)
+                    .ok_or_else(|| TemplatePropertyError("List is empty".into()))
+            });
+            Ok(L::Property::wrap_property(out_property.into_dyn()))
+        },
+    );
     map
 }
 
+/// Key to compare list items.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum ListItemKey {
+    Integer(i64),
+    String(BString),
+}
+
+/// Builds expression that computes key of each list item. If `lambda_node`
+/// isn't specified, the item itself is used as the key.
+fn build_item_key_expression<'a, L, O>(
+    language: &L,
+    diagnostics: &mut TemplateDiagnostics,
+    build_ctx: &BuildContext<L::Property>,
+    item_placeholder: &PropertyPlaceholder<O>,
+    lambda_node: Option<&ExpressionNode>,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<BoxedTemplateProperty<'a, ListItemKey>>
+where
+    L: TemplateLanguage<'a> + ?Sized,
+    L::Property: WrapTemplateProperty<'a, O>,
+    O: Clone + 'a,
+{
+    const KEY_TYPE: &str = "Sortable";
+    let Some(lambda_node) = lambda_node else {
+        let expression =
+            Expression::<L::Property>::unlabeled(item_placeholder.clone().into_dyn_wrapped());
+        let actual_type = expression.type_name();
+        return expression.try_into_list_item_key().ok_or_else(|| {
+            TemplateParseError::expected_type(KEY_TYPE, actual_type, function.name_span)
+        });
+    };
+    template_parser::catch_aliases(diagnostics, lambda_node, |diagnostics, node| {
+        let lambda = template_parser::expect_lambda(node)?;
+        build_lambda_expression(
+            build_ctx,
+            lambda,
+            &[&|| item_placeholder.clone().into_dyn_wrapped()],
+            |build_ctx, body| {
+                expect_expression_of_type(
+                    language,
+                    diagnostics,
+                    build_ctx,
+                    body,
+                    KEY_TYPE,
+                    |expression| expression.try_into_list_item_key(),
+                )
+            },
+        )
+    })
+}
+
+/// Evaluates key of each item, and returns pairs of key and item.
+fn extract_item_keys<O: Clone>(
+    items: Vec<O>,
+    item_placeholder: &PropertyPlaceholder<O>,
+    item_key: &impl TemplateProperty<Output = ListItemKey>,
+) -> Result<Vec<(ListItemKey, O)>, TemplatePropertyError> {
+    items
+        .into_iter()
+        .map(|item| {
+            let key = item_placeholder.with_value(item.clone(), || item_key.extract())?;
+            Ok((key, item))
+        })
+        .collect()
+}
+
+/// Builds expression that groups list items by key, and applies template to
+/// each group.
+fn build_group_by_operation<'a, L, O>(
+    language: &L,
+    diagnostics: &mut TemplateDiagnostics,
+    build_ctx: &BuildContext<L::Property>,
+    self_property: BoxedTemplateProperty<'a, Vec<O>>,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<BoxedAnyProperty<'a>>
+where
+    L: TemplateLanguage<'a> + ?Sized,
+    L::Property: WrapTemplateProperty<'a, O> + WrapTemplateProperty<'a, Vec<O>>,
+    O: Clone + 'a,
+{
+    let [key_lambda_node, group_lambda_node] = function.expect_exact_arguments()?;
+    let item_placeholder = PropertyPlaceholder::new();
+    let item_key =
+        template_parser::catch_aliases(diagnostics, key_lambda_node, |diagnostics, node| {
+            let lambda = template_parser::expect_lambda(node)?;
+            build_lambda_expression(
+                build_ctx,
+                lambda,
+                &[&|| item_placeholder.clone().into_dyn_wrapped()],
+                |build_ctx, body| {
+                    expect_stringify_expression(language, diagnostics, build_ctx, body)
+                },
+            )
+        })?;
+    let group_placeholder: PropertyPlaceholder<(String, Vec<O>)> = PropertyPlaceholder::new();
+    let mapped_group =
+        template_parser::catch_aliases(diagnostics, group_lambda_node, |diagnostics, node| {
+            let lambda = template_parser::expect_lambda(node)?;
+            build_lambda_expression(
+                build_ctx,
+                lambda,
+                &[
+                    &|| {
+                        group_placeholder
+                            .clone()
+                            .map(|(key, _)| key)
+                            .into_dyn_wrapped()
+                    },
+                    &|| {
+                        group_placeholder
+                            .clone()
+                            .map(|(_, items)| items)
+                            .into_dyn_wrapped()
+                    },
+                ],
+                |build_ctx, body| expect_any_expression(language, diagnostics, build_ctx, body),
+            )
+        })?;
+    // Groups are ordered by first occurrence.
+    let groups_property = self_property.and_then(move |items| {
+        let mut groups: Vec<(String, Vec<O>)> = Vec::new();
+        let mut group_indices: HashMap<String, usize> = HashMap::new();
+        for item in items {
+            let key = item_placeholder.with_value(item.clone(), || item_key.extract())?;
+            if let Some(&index) = group_indices.get(&key) {
+                groups[index].1.push(item);
+            } else {
+                group_indices.insert(key.clone(), groups.len());
+                groups.push((key, vec![item]));
+            }
+        }
+        Ok(groups)
+    });
+    let mapped_groups = ListMapProperty::new(groups_property, group_placeholder, mapped_group);
+    Ok(Box::new(mapped_groups))
+}
+
 /// Builds expression that extracts iterable property and filters its items.
 fn build_filter_operation<'a, L, O, P, B>(
     language: &L,
@@ -2696,7 +2994,7 @@ where
         let mut fill_char_recorder;
         let recorded_fill_char = if let Some(fill_char) = &fill_char {
             let rewrap = formatter.rewrap_fn();
-            fill_char_recorder = FormatRecorder::new(formatter.maybe_color());
+            fill_char_recorder = formatter.new_recorder();
             fill_char.format(&mut rewrap(&mut fill_char_recorder))?;
             &fill_char_recorder
         } else {
@@ -2725,7 +3023,7 @@ where
         let mut ellipsis_recorder;
         let recorded_ellipsis = if let Some(ellipsis) = &ellipsis {
             let rewrap = formatter.rewrap_fn();
-            ellipsis_recorder = FormatRecorder::new(formatter.maybe_color());
+            ellipsis_recorder = formatter.new_recorder();
             ellipsis.format(&mut rewrap(&mut ellipsis_recorder))?;
             &ellipsis_recorder
         } else {
@@ -2771,7 +3069,7 @@ where
             captures_placeholder.with_value(
                 RegexCaptures::new(data.to_vec(), capture_ranges, names_map.clone()),
                 || -> io::Result<()> {
-                    let mut recorder = FormatRecorder::new(formatter.maybe_color());
+                    let mut recorder = formatter.new_recorder();
                     let rewrap = formatter.rewrap_fn();
                     write_replacement_content(&mut rewrap(&mut recorder))?;
                     recorded_replacements.push(recorder);
@@ -4323,6 +4621,85 @@ mod tests {
         insta::assert_snapshot!(env.render_ok(r#""a\nb\nc\nd".lines().skip(1).take(2).join("|")"#), @"b|c");
     }
 
+    #[test]
+    fn test_list_sort_and_aggregate_methods() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("size_hint", || literal((10, None)));
+
+        // List.sort_by(key)
+        insta::assert_snapshot!(
+            env.render_ok(r#""bb\na\nccc\nd".lines().sort_by(|s| s.len()).join("|")"#),
+            @"a|d|bb|ccc");
+        insta::assert_snapshot!(
+            env.render_ok(r#""bb\na\nccc\nd".lines().sort_by(|s| s).join("|")"#),
+            @"a|bb|ccc|d");
+        insta::assert_snapshot!(
+            env.render_ok(r#""bb\na\nccc".lines().sort_by(|s| -s.len()).join("|")"#),
+            @"ccc|bb|a");
+
+        // List.unique([key])
+        insta::assert_snapshot!(
+            env.render_ok(r#""a\nb\na\nc\nb".lines().unique().join("|")"#),
+            @"a|b|c");
+        insta::assert_snapshot!(
+            env.render_ok(r#""a\nbb\nc\ndd".lines().unique(|s| s.len()).join("|")"#),
+            @"a|bb");
+
+        // List.group_by(key, group)
+        insta::assert_snapshot!(
+            env.render_ok(
+                r#""a\nbb\nc\nddd\nee".lines().group_by(|s| s.len(), |len, items| len ++ ":" ++ items.join(",")).join(" ")"#),
+            @"1:a,c 2:bb,ee 3:ddd");
+        insta::assert_snapshot!(
+            env.render_ok(r#""".lines().group_by(|s| s, |k, items| k).join(" ")"#),
+            @"");
+
+        // List.sum(value)
+        insta::assert_snapshot!(
+            env.render_ok(r#""a\nbb\nccc".lines().sum(|s| s.len())"#),
+            @"6");
+        insta::assert_snapshot!(env.render_ok(r#""".lines().sum(|s| s.len())"#), @"0");
+
+        // List.min([key]) and List.max([key])
+        insta::assert_snapshot!(env.render_ok(r#""b\na\nc".lines().min()"#), @"a");
+        insta::assert_snapshot!(env.render_ok(r#""b\na\nc".lines().max()"#), @"c");
+        // The first item is selected if there are multiple candidates
+        insta::assert_snapshot!(
+            env.render_ok(r#""bb\na\nccc\nd\neee".lines().min(|s| s.len())"#),
+            @"a");
+        insta::assert_snapshot!(
+            env.render_ok(r#""bb\na\nccc\nd\neee".lines().max(|s| s.len())"#),
+            @"ccc");
+        insta::assert_snapshot!(env.render_ok(r#""".lines().max()"#), @"<Error: List is empty>");
+
+        // Bad key type
+        insta::assert_snapshot!(env.parse_err(r#""a".lines().sort_by(|s| size_hint)"#), @r#"
+         --> 1:25
+          |
+        1 | "a".lines().sort_by(|s| size_hint)
+          |                         ^-------^
+          |
+          = Expected expression of type `Sortable`, but actual type is `SizeHint`
+        "#);
+        insta::assert_snapshot!(env.parse_err(r#""a".lines().sum(|s| s)"#), @r#"
+         --> 1:21
+          |
+        1 | "a".lines().sum(|s| s)
+          |                     ^
+          |
+          = Expected expression of type `Integer`, but actual type is `String`
+        "#);
+        // Wrong parameter count for group_by()
+        insta::assert_snapshot!(env.parse_err(r#""a".lines().group_by(|s| s, |items| items)"#), @r#"
+         --> 1:30
+          |
+        1 | "a".lines().group_by(|s| s, |items| items)
+          |                              ^---^
+          |
+          = Expected 2 lambda parameters
+        "#);
+    }
+
     #[test]
     fn test_byte_string_method() {
         let mut env = TestTemplateEnv::new();
@@ -300,7 +300,7 @@ where
             Err(err) => return formatter.handle_error(err),
         };
 
-        if !formatter.maybe_color() {
+        if !formatter.maybe_hyperlink() {
             if let Some(fallback) = &self.fallback {
                 return fallback.format(formatter);
             }
@@ -365,7 +365,7 @@ where
 {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let rewrap = formatter.rewrap_fn();
-        let mut recorder = FormatRecorder::new(formatter.maybe_color());
+        let mut recorder = formatter.new_recorder();
         self.content.format(&mut rewrap(&mut recorder))?;
         (self.reformat)(formatter, &recorder)
     }
@@ -919,7 +919,7 @@ impl<T: Template> Template for TryList<T> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let (last, contents) = self.0.split_last().unwrap();
         if let Some(recorder) = contents.iter().find_map(|content| {
-            let mut recorder = FormatRecorder::new(formatter.maybe_color());
+            let mut recorder = formatter.new_recorder();
             let mut wrapper = TemplateFormatter::new(&mut recorder, propagate_property_error);
             content.format(&mut wrapper).is_ok().then_some(recorder)
         }) {
@@ -1110,6 +1110,15 @@ impl<'a> TemplateFormatter<'a> {
         self.formatter.maybe_color()
     }
 
+    pub fn maybe_hyperlink(&self) -> bool {
+        self.formatter.maybe_hyperlink()
+    }
+
+    /// Creates new buffer which inherits the capabilities of this formatter.
+    pub fn new_recorder(&self) -> FormatRecorder {
+        FormatRecorder::for_formatter(&*self.formatter)
+    }
+
     pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> io::Result<()> {
         self.formatter.write_fmt(args)
     }
@@ -1222,9 +1231,9 @@ fn record_non_empty_fn<T: Template + ?Sized>(
     // 1.85.0.
 ) -> impl Fn(&T) -> Option<io::Result<FormatRecorder>> + use<T> {
     let rewrap = formatter.rewrap_fn();
-    let maybe_color = formatter.maybe_color();
+    let empty_recorder = formatter.new_recorder();
     move |template| {
-        let mut recorder = FormatRecorder::new(maybe_color);
+        let mut recorder = empty_recorder.clone();
         match template.format(&mut rewrap(&mut recorder)) {
             Ok(()) if recorder.data().is_empty() => None, // omit empty content
             Ok(()) => Some(Ok(recorder)),
@@ -226,6 +226,7 @@ pub enum ColorChoice {
     Always,
     Never,
     Debug,
+    Html,
     Auto,
 }
 
@@ -235,6 +236,7 @@ impl fmt::Display for ColorChoice {
             Self::Always => "always",
             Self::Never => "never",
             Self::Debug => "debug",
+            Self::Html => "html",
             Self::Auto => "auto",
         };
         write!(f, "{s}")
@@ -250,6 +252,7 @@ fn prepare_formatter_factory(
         ColorChoice::Always => (true, false),
         ColorChoice::Never => (false, false),
         ColorChoice::Debug => (true, true),
+        ColorChoice::Html => return FormatterFactory::html(config),
         ColorChoice::Auto => (terminal, false),
     };
     if color {
@@ -440,7 +443,7 @@ impl Ui {
     /// Labels added to the returned formatter should be removed by caller.
     /// Otherwise the last color would persist.
     pub fn stdout_formatter(&self) -> Box<dyn Formatter + '_> {
-        for_outputs!(UiStdout, self.stdout(), w => self.new_formatter(w))
+        for_outputs!(UiStdout, self.stdout(), w => self.formatter_factory.new_stream_formatter(w))
     }
 
     /// Locked stderr stream.
@@ -455,7 +458,7 @@ impl Ui {
 
     /// Creates a formatter for the locked stderr stream.
     pub fn stderr_formatter(&self) -> Box<dyn Formatter + '_> {
-        for_outputs!(UiStderr, self.stderr(), w => self.new_formatter(w))
+        for_outputs!(UiStderr, self.stderr(), w => self.formatter_factory.new_stream_formatter(w))
     }
 
     /// Stderr stream to be attached to a child process.
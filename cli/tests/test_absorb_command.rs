@@ -17,6 +17,7 @@ use testutils::TestResult;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
+use crate::common::force_interactive;
 
 #[test]
 fn test_absorb_simple() {
@@ -85,6 +86,8 @@ fn test_absorb_simple() {
     let output = work_dir.run_jj(["absorb"]);
     insta::assert_snapshot!(output, @"
     ------- stderr -------
+    Warning: 1 hunk was not absorbed because it has multiple candidate destinations
+    Hint: Use `jj absorb --dry-run` to show them, or `jj absorb --pick-ambiguous` to choose their destinations.
     Nothing changed.
     [EOF]
     ");
@@ -1069,6 +1072,8 @@ fn test_absorb_interactive() -> TestResult {
     let output = work_dir.run_jj(["absorb", "-i"]);
     insta::assert_snapshot!(output, @"
     ------- stderr -------
+    Warning: 1 hunk was not absorbed because it has multiple candidate destinations
+    Hint: Use `jj absorb --dry-run` to show them, or `jj absorb --pick-ambiguous` to choose their destinations.
     Nothing changed.
     [EOF]
     ");
@@ -1124,6 +1129,176 @@ fn test_absorb_interactive() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn test_absorb_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    test_env.add_config("revset-aliases.'immutable_heads()' = 'present(main)'");
+
+    work_dir.run_jj(["describe", "-m0"]).success();
+    work_dir.write_file("file0", "0a\n");
+    work_dir
+        .run_jj(["bookmark", "set", "-r@", "main"])
+        .success();
+
+    work_dir.run_jj(["new", "-m1"]).success();
+    work_dir.write_file("file1", "1a\n1b\n");
+
+    work_dir.run_jj(["new", "-m2"]).success();
+    work_dir.write_file("file1", "1a\n1b\n2a\n2b\n");
+
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file0", "0A\n");
+    work_dir.write_file("file1", "1A\n1b\n2a\n2b\n");
+    work_dir.write_file("file2", "new\n");
+
+    let output = work_dir.run_jj(["absorb", "--dry-run"]);
+    insta::assert_snapshot!(output, @"
+    file0 @@ -1,1 +1,1 @@ not absorbed: the lines were not modified by any destination revision
+    -0a
+    +0A
+    file1 @@ -1,1 +1,1 @@ absorbed into:
+      zsuskuln 3707a4be 1
+    -1a
+    +1A
+    file2 not absorbed: the file is new
+    [EOF]
+    ");
+
+    // Hunks overlapping with changes from multiple revisions are ambiguous
+    work_dir.write_file("file1", "1a\n1B\n2A\n2b\n");
+    let output = work_dir.run_jj(["absorb", "--dry-run", "file1"]);
+    insta::assert_snapshot!(output, @"
+    file1 @@ -2,2 +2,2 @@ not absorbed: multiple candidate destination revisions:
+      zsuskuln 3707a4be 1
+      mzvwutvl 68b07c43 2
+    -1b
+    -2a
+    +1B
+    +2A
+    [EOF]
+    ");
+
+    // Nothing should be rewritten
+    insta::assert_snapshot!(get_evolog(&work_dir, "@-"), @"
+    ○  mzvwutvl 68b07c43 2
+    ○  mzvwutvl/1 82e0cb9d (hidden) (empty) 2
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_absorb_pick_ambiguous() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["describe", "-m1"]).success();
+    work_dir.write_file("file1", "1a\n1b\n");
+
+    work_dir.run_jj(["new", "-m2"]).success();
+    work_dir.write_file("file1", "1a\n1b\n2a\n2b\n");
+
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "1a\n1B\n2A\n2b\n");
+    work_dir.run_jj(["util", "snapshot"]).success();
+    let setup_opid = work_dir.current_operation_id();
+
+    // Ambiguous hunks are left in the source by default
+    let output = work_dir.run_jj(["absorb"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: 1 hunk was not absorbed because it has multiple candidate destinations
+    Hint: Use `jj absorb --dry-run` to show them, or `jj absorb --pick-ambiguous` to choose their destinations.
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Absorb into the chosen revision
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["absorb", "--pick-ambiguous"])
+            .write_stdin("1\n")
+    });
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    file1 @@ -2,2 +2,2 @@ can be absorbed into these revisions:
+    -1b
+    -2a
+    +1B
+    +2A
+    1: kkmpptxz 8105f646 2
+    s: leave it in the source revision
+    Enter the index of the destination revision: Absorbed changes into 1 revisions:
+      kkmpptxz 90869434 2
+    Working copy  (@) now at: vruxwmqv cd1b2076 (empty) (no description set)
+    Parent commit (@-)      : kkmpptxz 90869434 2
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_diffs(&work_dir, "mutable()"), @"
+    @  vruxwmqv cd1b2076 (empty) (no description set)
+    ○  kkmpptxz 90869434 2
+    │  diff --git a/file1 b/file1
+    │  index 8c5268f893..9076018fa8 100644
+    │  --- a/file1
+    │  +++ b/file1
+    │  @@ -1,2 +1,4 @@
+    │   1a
+    │  -1b
+    │  +1B
+    │  +2A
+    │  +2b
+    ○  qpvuntsm e35bcaff 1
+    │  diff --git a/file1 b/file1
+    ~  new file mode 100644
+       index 0000000000..8c5268f893
+       --- /dev/null
+       +++ b/file1
+       @@ -0,0 +1,2 @@
+       +1a
+       +1b
+    [EOF]
+    ");
+
+    // Skip the hunk
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["absorb", "--pick-ambiguous"])
+            .write_stdin("s\n")
+    });
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    file1 @@ -2,2 +2,2 @@ can be absorbed into these revisions:
+    -1b
+    -2a
+    +1B
+    +2A
+    1: kkmpptxz 8105f646 2
+    s: leave it in the source revision
+    Enter the index of the destination revision: Nothing changed.
+    [EOF]
+    ");
+
+    // Hunks conflicting with all candidates are left in the source
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    work_dir.write_file("file1", "1a\n1b\n2a\n3b\n");
+    work_dir.run_jj(["describe", "-m3"]).success();
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "1a\n1B\n2A\n3b\n");
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd).args(["absorb", "--pick-ambiguous", "--into=::@-- & mutable()"])
+    });
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: file1 @@ -2,2 +2,2 @@ was not absorbed because it conflicts with all candidate destinations
+    Nothing changed.
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_diffs(work_dir: &TestWorkDir, revision: &str) -> CommandOutput {
     let template = r#"format_commit_summary_with_refs(self, "") ++ "\n""#;
@@ -2794,6 +2794,38 @@ fn test_bookmark_list_quoted_name() {
     "#);
 }
 
+#[test]
+fn test_bookmark_list_output_format() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "foo"])
+        .success();
+    work_dir.run_jj(["new", "root()"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "bar"])
+        .success();
+
+    let output = work_dir.run_jj(["bookmark", "list", "--output-format=ndjson"]);
+    insta::assert_snapshot!(output, @r#"
+    {"name":"bar","target":["2b17ac719c7db025e2514f5708d2b0328fc6b268"]}
+    {"name":"foo","target":["e8849ae12c709f2321908879bc724fdb2ab8a781"]}
+    [EOF]
+    "#);
+
+    // Empty list is still a valid JSON document
+    let output = work_dir.run_jj(["bookmark", "list", "--output-format=json", "unknown"]);
+    insta::assert_snapshot!(output, @"
+    []
+    [EOF]
+    ------- stderr -------
+    Warning: No matching bookmarks for names: unknown
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_bookmark_list_much_remote_divergence() -> TestResult {
     let test_env = TestEnvironment::default();
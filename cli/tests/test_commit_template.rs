@@ -1399,6 +1399,79 @@ fn test_log_diff_predefined_formats() -> TestResult {
     * total_added=0 total_removed=0
     [EOF]
     ");
+
+    // files sorted by lines changed
+    let template = indoc! {r#"
+        diff.stat().files()
+          .sort_by(|e| -(e.lines_added() + e.lines_removed()))
+          .map(|e| e.path() ++ "=" ++ (e.lines_added() + e.lines_removed()))
+          .join(" ")
+        ++ " max_added=" ++ diff.stat().files().max(|e| e.lines_added()).path()
+        ++ " total=" ++ diff.stat().files().sum(|e| e.lines_added() + e.lines_removed())
+        ++ "\n"
+    "#};
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    file2=3 file1=1 rename-target=0 max_added=file2 total=4
+    [EOF]
+    ");
+
+    // custom template with hunks()
+    let template = indoc! {r#"
+        diff.files().map(|e| e.hunks(1).map(|h| concat(
+          e.path() ++ " " ++ h.header() ++ "\n",
+          separate(" ",
+            "old=" ++ h.old_start() ++ "," ++ h.old_len(),
+            "new=" ++ h.new_start() ++ "," ++ h.new_len(),
+            "context=" ++ h.context().len(),
+            "removed=" ++ h.removed().join("|"),
+            "added=" ++ h.added().join("|"),
+          ) ++ "\n",
+          h.lines().join("\n") ++ "\n",
+        )).join("")).join("")
+    "#};
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    file1 @@ -2,1 +2,2 @@
+    old=2,1 new=2,2 context=1 removed= added=c
+     b
+    +c
+    file2 @@ -1,1 +1,2 @@
+    old=1,1 new=1,2 context=0 removed=a added=b|c
+    -a
+    +b
+    +c
+    [EOF]
+    ");
+
+    // empty ranges start at the preceding line as in header()
+    let template = indoc! {r#"
+        diff.files().map(|e| e.hunks(context=0).map(|h| separate(" ",
+          e.path(),
+          h.header(),
+          "old=" ++ h.old_start() ++ "," ++ h.old_len(),
+          "new=" ++ h.new_start() ++ "," ++ h.new_len(),
+        ) ++ "\n").join("")).join("")
+    "#};
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    file1 @@ -2,0 +3,1 @@ old=2,0 new=3,1
+    file2 @@ -1,1 +1,2 @@ old=1,1 new=1,2
+    [EOF]
+    ");
+
+    // only added lines matching a pattern
+    let template = indoc! {r#"
+        diff.files().map(|e| e.hunks(context=0).map(|h|
+          h.added().filter(|l| l.contains("c")).map(|l| e.path() ++ ": " ++ l ++ "\n").join("")
+        ).join("")).join("")
+    "#};
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    file1: c
+    file2: c
+    [EOF]
+    ");
     Ok(())
 }
 
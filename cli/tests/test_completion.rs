@@ -231,6 +231,7 @@ fn test_bookmark_names() {
     --no-integrate-operation	Run the command as usual but don't integrate any operations
     --ignore-immutable	Allow rewriting immutable commits
     --at-operation	Operation to load the repo at
+    --output-format	Print machine-readable output
     --debug	Enable debug logging
     --color	When to colorize output
     --quiet	Silence non-primary command output
@@ -724,6 +725,7 @@ fn test_command_completion_short_name() {
     --no-integrate-operation	Run the command as usual but don't integrate any operations
     --ignore-immutable	Allow rewriting immutable commits
     --at-operation	Operation to load the repo at
+    --output-format	Print machine-readable output
     --debug	Enable debug logging
     --color	When to colorize output
     --quiet	Silence non-primary command output
@@ -1437,6 +1439,7 @@ fn test_config() {
     ui.conflict-marker-style=diff-experimental
     ui.conflict-marker-style=snapshot
     ui.conflict-marker-style=git
+    ui.conflict-marker-style=zdiff3
     [EOF]
     ");
 
@@ -1647,6 +1650,7 @@ fn test_merge_tools() {
     :name-only
     :git
     :color-words
+    :side-by-side
     diffedit3
     diffedit3-ssh
     difft
@@ -116,6 +116,48 @@ fn test_config_list_inline_table() {
     ");
 }
 
+#[test]
+fn test_config_list_output_format() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+    [test-table]
+    x = true
+    y = { foo = "abc", bar = [1, 2] }
+    "#,
+    );
+    let output = test_env.run_jj_in(
+        ".",
+        ["config", "list", "test-table", "--output-format=json"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    [
+    {"name":"test-table.x","value":true,"source":"user","path":"$TEST_ENV/config/config0002.toml","is_overridden":false},
+    {"name":"test-table.y.foo","value":"abc","source":"user","path":"$TEST_ENV/config/config0002.toml","is_overridden":false},
+    {"name":"test-table.y.bar","value":[1,2],"source":"user","path":"$TEST_ENV/config/config0002.toml","is_overridden":false}
+    ]
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(
+        ".",
+        ["config", "list", "test-table.x", "--output-format=ndjson"],
+    );
+    insta::assert_snapshot!(output, @r#"
+    {"name":"test-table.x","value":true,"source":"user","path":"$TEST_ENV/config/config0002.toml","is_overridden":false}
+    [EOF]
+    "#);
+
+    // No warning is printed for an empty list
+    let output = test_env.run_jj_in(
+        ".",
+        ["config", "list", "nonexistent", "--output-format=json"],
+    );
+    insta::assert_snapshot!(output, @"
+    []
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_config_list_array() {
     let test_env = TestEnvironment::default();
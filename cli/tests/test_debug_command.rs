@@ -167,6 +167,12 @@ fn test_debug_index() {
     === Changed paths ===
     Indexed commits: none
     Stats per level:
+    === Commit metadata ===
+    Indexed commits: none
+    Stats per level:
+    === Trigrams ===
+    Indexed commits: none
+    Stats per level:
     [EOF]
     ");
 
@@ -197,6 +203,96 @@ fn test_debug_index() {
         Number of changed paths: 0
         Number of paths: 0
         Name: [hash]
+    === Commit metadata ===
+    Indexed commits: none
+    Stats per level:
+    === Trigrams ===
+    Indexed commits: none
+    Stats per level:
+    [EOF]
+    ");
+
+    // Enable commit-metadata index
+    let output = work_dir.run_jj(["debug", "index-commit-metadata"]);
+    assert_snapshot!(output, @r"
+    ------- stderr -------
+    Finished indexing 0..2 commits.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["debug", "index"]);
+    assert_snapshot!(filter_index_stats(output), @"
+    === Commits ===
+    Number of commits: 2
+    Number of merges: 0
+    Max generation number: 1
+    Number of heads: 1
+    Number of changes: 2
+    Stats per level:
+      Level 0:
+        Number of commits: 2
+        Name: [hash]
+    === Changed paths ===
+    Indexed commits: 1..2
+    Stats per level:
+      Level 0:
+        Number of commits: 1
+        Number of changed paths: 0
+        Number of paths: 0
+        Name: [hash]
+    === Commit metadata ===
+    Indexed commits: 0..2
+    Stats per level:
+      Level 0:
+        Number of commits: 2
+        Number of text bytes: 60
+        Name: [hash]
+    === Trigrams ===
+    Indexed commits: none
+    Stats per level:
+    [EOF]
+    ");
+
+    // Enable trigram index
+    let output = work_dir.run_jj(["debug", "index-trigrams"]);
+    assert_snapshot!(output, @r"
+    ------- stderr -------
+    Finished indexing 0..2 commits.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["debug", "index"]);
+    assert_snapshot!(filter_index_stats(output), @"
+    === Commits ===
+    Number of commits: 2
+    Number of merges: 0
+    Max generation number: 1
+    Number of heads: 1
+    Number of changes: 2
+    Stats per level:
+      Level 0:
+        Number of commits: 2
+        Name: [hash]
+    === Changed paths ===
+    Indexed commits: 1..2
+    Stats per level:
+      Level 0:
+        Number of commits: 1
+        Number of changed paths: 0
+        Number of paths: 0
+        Name: [hash]
+    === Commit metadata ===
+    Indexed commits: 0..2
+    Stats per level:
+      Level 0:
+        Number of commits: 2
+        Number of text bytes: 60
+        Name: [hash]
+    === Trigrams ===
+    Indexed commits: 0..2
+    Stats per level:
+      Level 0:
+        Number of commits: 2
+        Number of trigrams: 0
+        Name: [hash]
     [EOF]
     ");
 }
@@ -226,6 +322,12 @@ fn test_debug_reindex() {
     === Changed paths ===
     Indexed commits: none
     Stats per level:
+    === Commit metadata ===
+    Indexed commits: none
+    Stats per level:
+    === Trigrams ===
+    Indexed commits: none
+    Stats per level:
     [EOF]
     ");
     let output = work_dir.run_jj(["debug", "reindex"]);
@@ -249,6 +351,12 @@ fn test_debug_reindex() {
     === Changed paths ===
     Indexed commits: none
     Stats per level:
+    === Commit metadata ===
+    Indexed commits: none
+    Stats per level:
+    === Trigrams ===
+    Indexed commits: none
+    Stats per level:
     [EOF]
     ");
 }
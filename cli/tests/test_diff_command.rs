@@ -376,6 +376,7 @@ fn test_diff_basic() {
     error: the argument '--template <TEMPLATE>' cannot be used with:
       --git
       --color-words
+      --side-by-side
 
     Usage: jj diff --template <TEMPLATE> [FILESETS]...
 
@@ -1898,6 +1899,511 @@ fn test_color_words_diff_missing_newline() {
     ");
 }
 
+#[test]
+fn test_diff_side_by_side() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+            fn main() {
+                let x = 1;
+                println!(\"{x}\");
+            a
+            b
+            c
+            d
+            e
+            f
+            }
+        "},
+    );
+    work_dir.write_file("file2", "removed\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+            fn main() {
+                let x = 2;
+            \tprintln!(\"a long line which should be wrapped at the column width {x}\");
+            a
+            b
+            c
+            d
+            e
+            f
+                return;
+            }
+        "},
+    );
+    work_dir.remove_file("file2");
+    work_dir.write_file("file3", "added\n");
+
+    let output =
+        work_dir.run_jj_with(|cmd| cmd.args(["diff", "--side-by-side"]).env("COLUMNS", "60"));
+    insta::assert_snapshot!(output, @r#"
+    Modified regular file file1:
+       1   fn main() {           │    1   fn main() {
+       2 -     let x = 1;        │    2 +     let x = 2;
+       3 -     println!("{x}");  │    3 +     println!("a long 
+                                 │        line which should be 
+                                 │        wrapped at the column
+                                 │         width {x}");
+       4   a                     │    4   a
+       5   b                     │    5   b
+       6   c                     │    6   c
+       7   d                     │    7   d
+       8   e                     │    8   e
+       9   f                     │    9   f
+                                 │   10 +     return;
+      10   }                     │   11   }
+    Removed regular file file2:
+       1 - removed               │
+    Added regular file file3:
+                                 │    1 + added
+    [EOF]
+    "#);
+
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args(["diff", "--side-by-side", "--context=0", "file1"])
+            .env("COLUMNS", "60")
+    });
+    insta::assert_snapshot!(output, @r#"
+    Modified regular file file1:
+        ...
+       2 -     let x = 1;        │    2 +     let x = 2;
+       3 -     println!("{x}");  │    3 +     println!("a long 
+                                 │        line which should be 
+                                 │        wrapped at the column
+                                 │         width {x}");
+        ...
+                                 │   10 +     return;
+        ...
+    [EOF]
+    "#);
+
+    // Changed words are highlighted
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args([
+            "diff",
+            "--side-by-side",
+            "--color=always",
+            "--context=0",
+            "file1",
+        ])
+        .env("COLUMNS", "100")
+    });
+    insta::assert_snapshot!(output, @r#"
+    [38;5;3mModified regular file file1:[39m
+        ...
+    [38;5;1m   2[39m [38;5;1m-[39m [38;5;1m    let x = [4m1[24m;[39m                            │ [38;5;2m   2[39m [38;5;2m+[39m [38;5;2m    let x = [4m2[24m;[39m
+    [38;5;1m   3[39m [38;5;1m-[39m [4m[38;5;1m    [24mprintln!("{x}");[39m                      │ [38;5;2m   3[39m [38;5;2m+[39m [4m[38;5;2m    [24mprintln!("[4ma long line which should be[24m[39m
+                                                     │        [4m[38;5;2m wrapped at the column width [24m{x}");[39m
+        ...
+                                                     │ [38;5;2m  10[39m [38;5;2m+[39m [4m[38;5;2m    return;[24m[39m
+        ...
+    [EOF]
+    "#);
+
+    // Whitespace changes aren't highlighted if whitespace is ignored
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args([
+            "diff",
+            "--side-by-side",
+            "--color=always",
+            "--context=0",
+            "--ignore-all-space",
+            "file1",
+        ])
+        .env("COLUMNS", "100")
+    });
+    insta::assert_snapshot!(output, @r#"
+    [38;5;3mModified regular file file1:[39m
+        ...
+    [38;5;1m   2[39m [38;5;1m-[39m [38;5;1m    let x = [4m1[24m;[39m                            │ [38;5;2m   2[39m [38;5;2m+[39m [38;5;2m    let x = [4m2[24m;[39m
+    [38;5;1m   3[39m [38;5;1m-[39m [38;5;1m    println!("{x}");[39m                      │ [38;5;2m   3[39m [38;5;2m+[39m [38;5;2m    println!("[4ma long line which should be[24m[39m
+                                                     │        [4m[38;5;2m wrapped at the column width [24m{x}");[39m
+        ...
+                                                     │ [38;5;2m  10[39m [38;5;2m+[39m [4m[38;5;2m    return;[24m[39m
+        ...
+    [EOF]
+    "#);
+
+    // Changed words aren't highlighted if they alternate too much
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args([
+            "diff",
+            "--side-by-side",
+            "--color=always",
+            "--context=0",
+            "--config=diff.color-words.max-inline-alternation=0",
+            "file1",
+        ])
+        .env("COLUMNS", "100")
+    });
+    insta::assert_snapshot!(output, @r#"
+    [38;5;3mModified regular file file1:[39m
+        ...
+    [38;5;1m   2[39m [38;5;1m-[39m [4m[38;5;1m    let x = 1;[24m[39m                            │ [38;5;2m   2[39m [38;5;2m+[39m [4m[38;5;2m    let x = 2;[24m[39m
+    [38;5;1m   3[39m [38;5;1m-[39m [4m[38;5;1m    println!("{x}");[24m[39m                      │ [38;5;2m   3[39m [38;5;2m+[39m [4m[38;5;2m    println!("a long line which should be[24m[39m
+                                                     │        [4m[38;5;2m wrapped at the column width {x}");[24m[39m
+        ...
+                                                     │ [38;5;2m  10[39m [38;5;2m+[39m [4m[38;5;2m    return;[24m[39m
+        ...
+    [EOF]
+    "#);
+
+    // Conflict markers are aligned with the lines of the other side
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "a\nbase\nc\n")]);
+    create_commit_with_files(&work_dir, "left", &["base"], &[("file", "a\nleft\nc\n")]);
+    create_commit_with_files(&work_dir, "right", &["base"], &[("file", "a\nright\nc\n")]);
+    create_commit_with_files(&work_dir, "conflict", &["left", "right"], &[]);
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args(["diff", "--side-by-side", "--from=base", "--to=conflict"])
+            .env("COLUMNS", "60")
+    });
+    insta::assert_snapshot!(output, @r#"
+    Created conflict in file:
+       1   a                     │    1   a
+       2 - base                  │    2 + <<<<<<< conflict 1 of
+                                 │         1
+                                 │    3 + %%%%%%% diff from: vr
+                                 │        uxwmqv 70795054 "base
+                                 │        "
+                                 │    4 + \\\\\\\        to: zn
+                                 │        kkpsqq 754d4b68 "left
+                                 │        "
+                                 │    5 + -base
+                                 │    6 + +left
+                                 │    7 + +++++++ kmkuslsw a67e
+                                 │        7ef5 "right"
+                                 │    8 + right
+                                 │    9 + >>>>>>> conflict 1 of
+                                 │         1 ends
+       3   c                     │   10   c
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_diff_color_moved() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+            fn foo() {
+                bar();
+            }
+
+            fn main() {
+                foo();
+            }
+        "},
+    );
+    work_dir.write_file("file2", "a\nb\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file(
+        "file1",
+        indoc! {"
+            fn main() {
+                foo();
+            }
+        "},
+    );
+    work_dir.write_file(
+        "file2",
+        indoc! {"
+            a
+            fn foo() {
+                bar();
+            }
+            b
+            x
+        "},
+    );
+
+    // Moved lines are labeled differently from the other changed lines
+    let output = work_dir.run_jj(["diff", "--git", "--color-moved", "--color=debug"]);
+    insta::assert_snapshot!(output, @"
+    [1m<<diff git file_header::diff --git a/file1 b/file1>>[0m
+    [1m<<diff git file_header::index f6f017d5b5..6c1c810023 100644>>[0m
+    [1m<<diff git file_header::--- a/file1>>[0m
+    [1m<<diff git file_header::+++ b/file1>>[0m
+    [38;5;6m<<diff git hunk_header::@@ -1,7 +1,3 @@>>[39m
+    [38;5;5m<<diff git removed moved::->>[4m<<diff git removed moved token::fn foo() {>>[24m[39m
+    [38;5;5m<<diff git removed moved::->>[4m<<diff git removed moved token::    bar();>>[24m[39m
+    [38;5;5m<<diff git removed moved::->>[4m<<diff git removed moved token::}>>[24m[39m
+    [38;5;1m<<diff git removed::->>[4m<<diff git removed token::>>[24m[39m
+    <<diff git context:: fn main() {>>
+    <<diff git context::     foo();>>
+    <<diff git context:: }>>
+    [1m<<diff git file_header::diff --git a/file2 b/file2>>[0m
+    [1m<<diff git file_header::index 422c2b7ab3..82c2653c2b 100644>>[0m
+    [1m<<diff git file_header::--- a/file2>>[0m
+    [1m<<diff git file_header::+++ b/file2>>[0m
+    [38;5;6m<<diff git hunk_header::@@ -1,2 +1,6 @@>>[39m
+    <<diff git context:: a>>
+    [38;5;6m<<diff git added moved::+>>[4m<<diff git added moved token::fn foo() {>>[24m[39m
+    [38;5;6m<<diff git added moved::+>>[4m<<diff git added moved token::    bar();>>[24m[39m
+    [38;5;6m<<diff git added moved::+>>[4m<<diff git added moved token::}>>[24m[39m
+    <<diff git context:: b>>
+    [38;5;2m<<diff git added::+>>[4m<<diff git added token::x>>[24m[39m
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--color-words", "--color-moved", "--color=debug"]);
+    insta::assert_snapshot!(output, @"
+    [38;5;3m<<diff color_words header::Modified regular file file1:>>[39m
+    [38;5;1m<<diff color_words removed line_number::   1>>[39m<<diff color_words::     : >>[4m[38;5;5m<<diff color_words removed moved token::fn foo() {>>[24m[39m
+    [38;5;1m<<diff color_words removed line_number::   2>>[39m<<diff color_words::     : >>[4m[38;5;5m<<diff color_words removed moved token::    bar();>>[24m[39m
+    [38;5;1m<<diff color_words removed line_number::   3>>[39m<<diff color_words::     : >>[4m[38;5;5m<<diff color_words removed moved token::}>>[24m[39m
+    [38;5;1m<<diff color_words removed line_number::   4>>[39m<<diff color_words::     : >>[4m[38;5;1m<<diff color_words removed token::>>[24m[39m
+    [2m[38;5;1m<<diff color_words context removed line_number::   5>>[0m<<diff color_words context:: >>[2m[38;5;2m<<diff color_words context added line_number::   1>>[0m<<diff color_words context::: fn main() {>>
+    [2m[38;5;1m<<diff color_words context removed line_number::   6>>[0m<<diff color_words context:: >>[2m[38;5;2m<<diff color_words context added line_number::   2>>[0m<<diff color_words context:::     foo();>>
+    [2m[38;5;1m<<diff color_words context removed line_number::   7>>[0m<<diff color_words context:: >>[2m[38;5;2m<<diff color_words context added line_number::   3>>[0m<<diff color_words context::: }>>
+    [38;5;3m<<diff color_words header::Modified regular file file2:>>[39m
+    [2m[38;5;1m<<diff color_words context removed line_number::   1>>[0m<<diff color_words context:: >>[2m[38;5;2m<<diff color_words context added line_number::   1>>[0m<<diff color_words context::: a>>
+    <<diff color_words::     >>[38;5;2m<<diff color_words added line_number::   2>>[39m<<diff color_words::: >>[4m[38;5;6m<<diff color_words added moved token::fn foo() {>>[24m[39m
+    <<diff color_words::     >>[38;5;2m<<diff color_words added line_number::   3>>[39m<<diff color_words::: >>[4m[38;5;6m<<diff color_words added moved token::    bar();>>[24m[39m
+    <<diff color_words::     >>[38;5;2m<<diff color_words added line_number::   4>>[39m<<diff color_words::: >>[4m[38;5;6m<<diff color_words added moved token::}>>[24m[39m
+    [2m[38;5;1m<<diff color_words context removed line_number::   2>>[0m<<diff color_words context:: >>[2m[38;5;2m<<diff color_words context added line_number::   5>>[0m<<diff color_words context::: b>>
+    <<diff color_words::     >>[38;5;2m<<diff color_words added line_number::   6>>[39m<<diff color_words::: >>[4m[38;5;2m<<diff color_words added token::x>>[24m[39m
+    [EOF]
+    ");
+
+    // Without color, the output is the same as the normal diff
+    let output = work_dir.run_jj(["diff", "--git", "--color-moved"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file1 b/file1
+    index f6f017d5b5..6c1c810023 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,3 @@
+    -fn foo() {
+    -    bar();
+    -}
+    -
+     fn main() {
+         foo();
+     }
+    diff --git a/file2 b/file2
+    index 422c2b7ab3..82c2653c2b 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,2 +1,6 @@
+     a
+    +fn foo() {
+    +    bar();
+    +}
+     b
+    +x
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--stat", "--color-moved"]);
+    insta::assert_snapshot!(output, @"
+    file1 | 4 ----
+    file2 | 4 ++++
+    2 files changed, 4 insertions(+), 4 deletions(-), 3 lines moved
+    [EOF]
+    ");
+
+    // Can be enabled by config
+    let output = work_dir.run_jj(["diff", "--stat", "--config=diff.color-moved=true"]);
+    insta::assert_snapshot!(output, @"
+    file1 | 4 ----
+    file2 | 4 ++++
+    2 files changed, 4 insertions(+), 4 deletions(-), 3 lines moved
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_algorithm() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "a\nb\nc\na\nb\nb\na\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file", "c\nb\na\nb\na\nc\n");
+
+    // There are no unique lines, so the default algorithm finds nothing in
+    // common.
+    let output = work_dir.run_jj(["diff", "--git"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file b/file
+    index 4eca417796..5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+    -c
+    -a
+    -b
+    -b
+    -a
+    +c
+    +b
+    +a
+    +b
+    +a
+    +c
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--git", "--config=diff.algorithm=minimal"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file b/file
+    index 4eca417796..5640cf335e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,7 +1,6 @@
+    -a
+    +c
+     b
+    -c
+     a
+     b
+    -b
+     a
+    +c
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--stat", "--config=diff.algorithm=histogram"]);
+    insta::assert_snapshot!(output, @"
+    file | 7 +++----
+    1 file changed, 3 insertions(+), 4 deletions(-)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_hunk_headers() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let rust_source = indoc! {"
+        struct Foo;
+
+        impl Foo {
+            fn foo(&self) {
+                a();
+                b();
+                c();
+                d();
+                e();
+            }
+        }
+    "};
+    work_dir.write_file("file.rs", rust_source);
+    work_dir.write_file("file.txt", rust_source);
+    work_dir.run_jj(["new"]).success();
+    let rust_source = rust_source.replace("e();", "f();");
+    work_dir.write_file("file.rs", &rust_source);
+    work_dir.write_file("file.txt", &rust_source);
+
+    // Built-in pattern applies to Rust files
+    let output = work_dir.run_jj(["diff", "--git", "--context=1"]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file.rs b/file.rs
+    index 6949aaa9c2..6966cc257a 100644
+    --- a/file.rs
+    +++ b/file.rs
+    @@ -8,3 +8,3 @@ fn foo(&self) {
+             d();
+    -        e();
+    +        f();
+         }
+    diff --git a/file.txt b/file.txt
+    index 6949aaa9c2..6966cc257a 100644
+    --- a/file.txt
+    +++ b/file.txt
+    @@ -8,3 +8,3 @@
+             d();
+    -        e();
+    +        f();
+         }
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--color-words", "--context=1", "file.rs"]);
+    insta::assert_snapshot!(output, @"
+    Modified regular file file.rs:
+        ... fn foo(&self) {
+       8    8:         d();
+       9     :         e();
+            9:         f();
+      10   10:     }
+      11   11: }
+    [EOF]
+    ");
+
+    // Custom pattern
+    let output = work_dir.run_jj([
+        "diff",
+        "--git",
+        "--context=1",
+        "file.txt",
+        "--config=diff.hunk-headers.text.patterns=['glob:*.txt']",
+        "--config=diff.hunk-headers.text.regex='^impl (.*) \\{'",
+    ]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file.txt b/file.txt
+    index 6949aaa9c2..6966cc257a 100644
+    --- a/file.txt
+    +++ b/file.txt
+    @@ -8,3 +8,3 @@ Foo
+             d();
+    -        e();
+    +        f();
+         }
+    [EOF]
+    ");
+
+    // Built-in pattern can be disabled
+    let output = work_dir.run_jj([
+        "diff",
+        "--git",
+        "--context=1",
+        "file.rs",
+        "--config=diff.hunk-headers.rust.enabled=false",
+    ]);
+    insta::assert_snapshot!(output, @"
+    diff --git a/file.rs b/file.rs
+    index 6949aaa9c2..6966cc257a 100644
+    --- a/file.rs
+    +++ b/file.rs
+    @@ -8,3 +8,3 @@
+             d();
+    -        e();
+    +        f();
+         }
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--git", "--config=diff.hunk-headers.rust.regex='('"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Config error: Invalid type or value for diff.hunk-headers.rust.regex
+    Caused by: regex parse error:
+        (
+        ^
+    error: unclosed group
+    For help, see https://docs.jj-vcs.dev/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_diff_ignore_whitespace() {
     let test_env = TestEnvironment::default();
@@ -305,3 +305,22 @@ fn test_annotate_with_template() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_annotate_output_format() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file.txt", "line1\n");
+    work_dir.run_jj(["describe", "-m=initial"]).success();
+    work_dir.run_jj(["new", "-m=next"]).success();
+    append_to_file(&work_dir.root().join("file.txt"), "line2");
+
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "--output-format=ndjson"]);
+    insta::assert_snapshot!(output, @r#"
+    {"commit":{"commit_id":"a5daff010c738867fa0b3712826be7946521e098","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"initial\n","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"}},"content":"line1\n","line_number":1,"original_line_number":1,"first_line_in_hunk":true}
+    {"commit":{"commit_id":"51ff26d7d7fc631532a6a24bb2c94c48aebfb19e","parents":["a5daff010c738867fa0b3712826be7946521e098"],"change_id":"kkmpptxzrspxrzommnulwmwkkqwworpl","description":"next\n","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:10+07:00"}},"content":"line2\n","line_number":2,"original_line_number":2,"first_line_in_hunk":true}
+    [EOF]
+    "#);
+}
@@ -92,4 +92,216 @@ fn test_file_list() {
     Warning: No matching entries for paths: file3
     [EOF]
     ");
+
+    // Can filter by file name, substring, or regex
+    let output = work_dir.run_jj(["file", "list", "name:file"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    dir/file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "substring-i:EXEC"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    conflict-exec-file
+    exec-file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "regex:'^[a-e].*-file$'"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    conflict-exec-file
+    conflict-file
+    exec-file
+    [EOF]
+    ");
+
+    // Can list files in directory non-recursively
+    let output = work_dir.run_jj(["file", "list", "files-in:."]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    conflict-exec-file
+    conflict-file
+    exec-file
+    [EOF]
+    ");
+
+    // File name shouldn't contain path separator
+    let output = work_dir.run_jj(["file", "list", "name:dir/file"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    ------- stderr -------
+    Error: Failed to parse fileset: Invalid file pattern
+    Caused by:
+    1:  --> 1:1
+      |
+    1 | name:dir/file
+      | ^-----------^
+      |
+      = Invalid file pattern
+    2: File name must not contain path separator: dir/file
+    Hint: Use substring: or regex: pattern to match path components.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_file_list_predicates() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(".gitignore", "*.log\n");
+    work_dir.write_file("exec-file", "content1");
+    work_dir.write_file("binary-file", b"\0binary");
+    work_dir.write_file("large-file", "x".repeat(2048));
+    work_dir.write_file("conflict-file", "content1");
+    work_dir
+        .run_jj(["file", "chmod", "x", "exec-file"])
+        .success();
+    work_dir.write_file("forced.log", "");
+    work_dir
+        .run_jj(["file", "track", "--include-ignored", "forced.log"])
+        .success();
+
+    work_dir.run_jj(["new", "root()"]).success();
+    work_dir.write_file("conflict-file", "content2");
+    work_dir.run_jj(["new", "visible_heads()"]).success();
+
+    let output = work_dir.run_jj(["file", "list", "executable()"]);
+    insta::assert_snapshot!(output, @"
+    exec-file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "binary()"]);
+    insta::assert_snapshot!(output, @"
+    binary-file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "size('>1KiB')"]);
+    insta::assert_snapshot!(output, @"
+    large-file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "conflicted()"]);
+    insta::assert_snapshot!(output, @"
+    conflict-file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "ignored()"]);
+    insta::assert_snapshot!(output, @"
+    forced.log
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "glob:*-file & ~(binary() | conflicted())"]);
+    insta::assert_snapshot!(output, @"
+    exec-file
+    large-file
+    [EOF]
+    ");
+
+    // Predicates aren't supported by all commands
+    let output = work_dir.run_jj(["file", "show", "binary()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: File predicates such as `binary()` are not supported by this command
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["log", "-r", "files(binary())"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Failed to parse revset: File predicates are not supported in revsets
+    Caused by:  --> 1:7
+      |
+    1 | files(binary())
+      |       ^------^
+      |
+      = File predicates are not supported in revsets
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_file_list_codeowners() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(
+        ".github/CODEOWNERS",
+        "* @org/core\n/docs/ @org/docs\n*.rs @org/rust @alice\n",
+    );
+    work_dir.write_file("README", "");
+    work_dir.write_file("docs/guide.md", "");
+    work_dir.run_jj(["describe", "-m", "add docs"]).success();
+    work_dir.run_jj(["new", "-m", "add code"]).success();
+    work_dir.write_file("src/lib.rs", "");
+    work_dir.run_jj(["new"]).success();
+
+    let output = work_dir.run_jj(["file", "list", "owned_by('@org/rust')"]);
+    insta::assert_snapshot!(output, @"
+    src/lib.rs
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "file",
+        "list",
+        "owned_by('@org/*') & ~owned_by('@org/core')",
+    ]);
+    insta::assert_snapshot!(output, @"
+    docs/guide.md
+    src/lib.rs
+    [EOF]
+    ");
+
+    let template = r#"path ++ ": " ++ self.owners().join(" ") ++ "\n""#;
+    let output = work_dir.run_jj(["file", "list", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    .github/CODEOWNERS: @org/core
+    README: @org/core
+    docs/guide.md: @org/docs
+    src/lib.rs: @org/rust @alice
+    [EOF]
+    ");
+
+    // Owners are looked up in the tree of the listed revision, not in the
+    // working copy
+    work_dir.write_file(".github/CODEOWNERS", "* @org/core\n");
+    let output = work_dir.run_jj(["file", "list", "-r@-", "-T", template, "src"]);
+    insta::assert_snapshot!(output, @"
+    src/lib.rs: @org/rust @alice
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "-T", template, "src"]);
+    insta::assert_snapshot!(output, @"
+    src/lib.rs: @org/core
+    [EOF]
+    ");
+
+    // Teams to be requested for review
+    let template = r#"description.first_line() ++ ": " ++ self.diff().files().map(|e| e.owners()).join(" ") ++ "\n""#;
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r",
+        "touches_owner('@org/*')",
+        "-T",
+        template,
+    ]);
+    insta::assert_snapshot!(output, @"
+    : @org/core
+    add code: @org/rust @alice
+    add docs: @org/core @org/core @org/docs
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r",
+        "touches_owner('@alice')",
+        "-T",
+        "description",
+    ]);
+    insta::assert_snapshot!(output, @"
+    add code
+    [EOF]
+    ");
 }
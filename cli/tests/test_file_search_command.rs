@@ -124,6 +124,42 @@ fn test_file_search() {
     ");
 }
 
+#[test]
+fn test_file_search_with_index() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "-foo-\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file2", "-bar-\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "-foo-\n-baz-\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.run_jj(["debug", "index-changed-paths"]).success();
+    work_dir.run_jj(["debug", "index-trigrams"]).success();
+
+    // Files which can't contain matches are skipped, but the results should
+    // be the same
+    let output = work_dir.run_jj(["file", "search", "--pattern=substring:foo"]);
+    insta::assert_snapshot!(output, @"
+    file1:-foo-
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "--pattern=substring-i:BAR"]);
+    insta::assert_snapshot!(output, @"
+    file2:-bar-
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "--pattern=substring:nowhere"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["file", "search", "--pattern=substring:foo", "-r@---"]);
+    insta::assert_snapshot!(output, @"
+    file1:-foo-
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_file_search_conflicts() {
     let test_env = TestEnvironment::default();
@@ -204,4 +240,117 @@ fn test_file_search_conflicts() {
     // Doesn't list file if the pattern doesn't match
     let output = work_dir.run_jj(["file", "search", "--pattern=glob:*qux*"]);
     insta::assert_snapshot!(output.normalize_backslash(), @"");
+
+    // Lines added to the conflicted file are found in history. Lines added to
+    // multiple sides are reported once.
+    work_dir.run_jj(["new"]).success();
+    let content = work_dir.read_file("file1");
+    work_dir.write_file("file1", format!("{content}new foo\n"));
+    let output = work_dir.run_jj(["file", "search", "--pattern=glob:*foo*", "--revisions=@"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @"
+    lylxulpl file1:2: new foo
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_file_search_template() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "foo\nbar\nfoo2");
+
+    let output = work_dir.run_jj([
+        "file",
+        "search",
+        "--pattern=foo",
+        "-T",
+        r#"line_number ++ " " ++ path ++ " " ++ content"#,
+    ]);
+    insta::assert_snapshot!(output, @"
+    1 file1 foo
+    3 file1 foo2
+    [EOF]
+    ");
+
+    // Cannot be combined with --name-only
+    let output = work_dir.run_jj(["file", "search", "--pattern=foo", "-T=path", "--name-only"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    error: the argument '--template <TEMPLATE>' cannot be used with '--name-only'
+
+    Usage: jj file search --pattern <PATTERN> --template <TEMPLATE> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_file_search_revisions() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "foo\nbar\n");
+    work_dir.run_jj(["describe", "-m=first"]).success();
+    work_dir.run_jj(["new", "-m=second"]).success();
+    work_dir.write_file("file1", "foo\nbar\nfoo2\n");
+    work_dir.write_file("file2", "other foo\n");
+    work_dir.run_jj(["new", "-m=third"]).success();
+    work_dir.write_file("file1", "foo\nbar\nfoo3\n");
+    work_dir.remove_file("file2");
+
+    // Lines are attributed to the commit that added or changed them
+    let output = work_dir.run_jj(["file", "search", "--pattern=foo", "--revisions=all()"]);
+    insta::assert_snapshot!(output, @"
+    zsuskuln file1:3: foo3
+    kkmpptxz file1:3: foo2
+    kkmpptxz file2:1: other foo
+    qpvuntsm file1:1: foo
+    [EOF]
+    ");
+
+    // Can filter by path
+    let output = work_dir.run_jj([
+        "file",
+        "search",
+        "--pattern=foo",
+        "--revisions=all()",
+        "file2",
+    ]);
+    insta::assert_snapshot!(output, @"
+    kkmpptxz file2:1: other foo
+    [EOF]
+    ");
+
+    // Can specify template
+    let output = work_dir.run_jj([
+        "file",
+        "search",
+        "--pattern=glob:foo*",
+        "--revisions=@-::",
+        "-T",
+        r#"commit.description().first_line() ++ ": " ++ content"#,
+    ]);
+    insta::assert_snapshot!(output, @"
+    third: foo3
+    second: foo2
+    [EOF]
+    ");
+
+    // Cannot be combined with --revision
+    let output = work_dir.run_jj(["file", "search", "--pattern=foo", "--revisions=@", "-r=@"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    error: the argument '--revisions <REVSETS>' cannot be used with '--revision <REVSET>'
+
+    Usage: jj file search --pattern <PATTERN> --revisions <REVSETS> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
 }
@@ -124,6 +124,48 @@ fn test_git_remotes() {
     "#);
 }
 
+#[test]
+fn test_git_remote_list_output_format() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["git", "remote", "list", "--output-format=json"]);
+    insta::assert_snapshot!(output, @"
+    []
+    [EOF]
+    ");
+
+    work_dir
+        .run_jj(["git", "remote", "add", "foo", "http://example.com/repo/foo"])
+        .success();
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "add",
+            "baz",
+            "http://example.com/repo/baz",
+            "--push-url",
+            "git@example.com:repo/baz",
+        ])
+        .success();
+    let output = work_dir.run_jj(["git", "remote", "list", "--output-format=json"]);
+    insta::assert_snapshot!(output, @r#"
+    [
+    {"name":"baz","fetch_url":"http://example.com/repo/baz","push_url":"git@example.com:repo/baz"},
+    {"name":"foo","fetch_url":"http://example.com/repo/foo","push_url":"http://example.com/repo/foo"}
+    ]
+    [EOF]
+    "#);
+    let output = work_dir.run_jj(["git", "remote", "list", "--output-format=ndjson"]);
+    insta::assert_snapshot!(output, @r#"
+    {"name":"baz","fetch_url":"http://example.com/repo/baz","push_url":"git@example.com:repo/baz"}
+    {"name":"foo","fetch_url":"http://example.com/repo/foo","push_url":"http://example.com/repo/foo"}
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_git_remote_add() {
     let test_env = TestEnvironment::default();
@@ -629,6 +629,41 @@ fn test_color_config() {
     [EOF]
     ");
 
+    // Test that --color=html renders labels as HTML elements.
+    let output = work_dir.run_jj([
+        "--color=html",
+        "log",
+        "-T",
+        r#"commit_id.short() ++ " " ++ "<&>""#,
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    <pre><span class="log commit node working_copy mutable" style="color: #00cd00; font-weight: bold">@</span>  <span class="log commit commit_id short" style="color: #0000ee">e8849ae12c70</span><span class="log commit"> &lt;&amp;&gt;</span>
+    <span class="log commit node immutable" style="color: #00ffff; font-weight: bold">◆</span>  <span class="log commit commit_id short" style="color: #0000ee">000000000000</span><span class="log commit"> &lt;&amp;&gt;</span>
+    </pre>
+    [EOF]
+    "#);
+
+    // Test that --color=html renders hyperlinks as HTML elements, and doesn't
+    // emit ANSI escape sequences.
+    let output = work_dir.run_jj([
+        "--color=html",
+        "log",
+        "--no-graph",
+        "-r@",
+        "-T",
+        r#"hyperlink("https://example.com/?a&b", commit_id.short()) ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    <pre><a href="https://example.com/?a&amp;b"><span class="log commit commit_id short" style="color: #0000ee">e8849ae12c70</span></a>
+    </pre>
+    [EOF]
+    "#);
+    let output = work_dir.run_jj(["--color=html", "help", "log"]);
+    assert!(output.stdout.raw().starts_with("<pre>"));
+    assert!(!output.stdout.raw().contains('\x1b'));
+    let output = work_dir.run_jj(["--color=html", "log", "--no-such-option"]);
+    assert!(!output.stderr.raw().contains('\x1b'));
+
     // Test that color is used if it's requested in the config file
     test_env.add_config(r#"ui.color="always""#);
     let output = work_dir.run_jj(["log", "-T", "commit_id"]);
@@ -704,7 +739,7 @@ fn test_color_config() {
     insta::assert_snapshot!(output, @"
     ------- stderr -------
     error: invalid value 'foo' for '--color <WHEN>'
-      [possible values: always, never, debug, auto]
+      [possible values: always, never, debug, html, auto]
 
     For more information, try '--help'.
     [EOF]
@@ -857,6 +892,31 @@ fn test_quiet() {
     insta::assert_snapshot!(output, @"");
 }
 
+#[test]
+fn test_output_format_unsupported() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["log", "--output-format=json", "-r@", "-Tchange_id"]);
+    insta::assert_snapshot!(output, @"
+    @  qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu
+    │
+    ~
+    [EOF]
+    ------- stderr -------
+    Warning: `--output-format` is not supported by `jj log` and was ignored
+    [EOF]
+    ");
+
+    // No warning if the command supports it
+    let output = work_dir.run_jj(["workspace", "list", "--output-format=ndjson"]);
+    insta::assert_snapshot!(output, @r#"
+    {"name":"default","target":{"commit_id":"e8849ae12c709f2321908879bc724fdb2ab8a781","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:07+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:07+07:00"}}}
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_repeated_args() {
     // Repeating an argument should be harmless rather than an error, so that an
@@ -1383,9 +1443,10 @@ fn test_help() {
           --no-integrate-operation       Run the command as usual but don't integrate any operations
           --ignore-immutable             Allow rewriting immutable commits
           --at-operation <AT_OPERATION>  Operation to load the repo at [alias: --at-op]
+          --output-format <FORMAT>       Print machine-readable output [possible values: json, ndjson]
           --debug                        Enable debug logging
           --color <WHEN>                 When to colorize output [possible values: always, never, debug,
-                                         auto]
+                                         html, auto]
           --quiet                        Silence non-primary command output
           --no-pager                     Disable the pager
           --config <NAME=VALUE>          Additional configuration options (can be repeated)
@@ -236,6 +236,35 @@ fn test_op_log_limit() {
     ");
 }
 
+#[test]
+fn test_op_log_output_format() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["describe", "-m=first"]).success();
+
+    // Graph is omitted
+    let output = work_dir.run_jj(["op", "log", "--output-format=ndjson", "--limit=2"]);
+    insta::assert_snapshot!(output, @r#"
+    {"id":"3f51a118a2d1ce72a2ebd865d66666203e28f203559e5e05cd82f09b401f1908af2bc9b3082e16cf8528286ba7d54b7409affd6be9a2e11706230aaba6fcb674","parents":["f63ee16f95539ec5bda4a7178aa3ace2bf594719aa2cc3410cfc4d070cb31ae31225bef146eb2428baf760180976c936cd60891e7a9087e6836d3a315fe81030"],"time":{"start":"2001-02-03T04:05:08+07:00","end":"2001-02-03T04:05:08+07:00"},"description":"describe commit e8849ae12c709f2321908879bc724fdb2ab8a781","hostname":"host.example.com","username":"test-username","is_snapshot":false,"workspace_name":"default","attributes":{"args":"jj describe '-m=first'"}}
+    {"id":"f63ee16f95539ec5bda4a7178aa3ace2bf594719aa2cc3410cfc4d070cb31ae31225bef146eb2428baf760180976c936cd60891e7a9087e6836d3a315fe81030","parents":["00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"],"time":{"start":"2001-02-03T04:05:07+07:00","end":"2001-02-03T04:05:07+07:00"},"description":"add workspace 'default'","hostname":"host.example.com","username":"test-username","is_snapshot":false,"workspace_name":null,"attributes":{}}
+    [EOF]
+    "#);
+    let output = work_dir.run_jj([
+        "op",
+        "log",
+        "--output-format=json",
+        "--reversed",
+        "--limit=1",
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    [
+    {"id":"3f51a118a2d1ce72a2ebd865d66666203e28f203559e5e05cd82f09b401f1908af2bc9b3082e16cf8528286ba7d54b7409affd6be9a2e11706230aaba6fcb674","parents":["f63ee16f95539ec5bda4a7178aa3ace2bf594719aa2cc3410cfc4d070cb31ae31225bef146eb2428baf760180976c936cd60891e7a9087e6836d3a315fe81030"],"time":{"start":"2001-02-03T04:05:08+07:00","end":"2001-02-03T04:05:08+07:00"},"description":"describe commit e8849ae12c709f2321908879bc724fdb2ab8a781","hostname":"host.example.com","username":"test-username","is_snapshot":false,"workspace_name":"default","attributes":{"args":"jj describe '-m=first'"}}
+    ]
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_op_log_no_graph() {
     let test_env = TestEnvironment::default();
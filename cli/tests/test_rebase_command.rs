@@ -12,11 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::path::Path;
+
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
 use crate::common::create_commit;
 use crate::common::create_commit_with_files;
+use crate::common::fake_editor_path;
+use crate::common::to_toml_value;
 
 #[test]
 fn test_rebase_invalid() {
@@ -3082,9 +3087,617 @@ fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     work_dir.run_jj(["log", "-T", template])
 }
 
+#[test]
+fn test_rebase_merge_drivers() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "1\n2\n3\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "1\na\n3\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "1\nb\n3\n")]);
+    let setup_opid = work_dir.current_operation_id();
+
+    // Builtin driver
+    let mut args = vec![
+        "rebase",
+        "-r",
+        "b",
+        "-o",
+        "a",
+        "--config=merge.drivers.union.patterns=['file']",
+        "--config=merge.drivers.union.builtin='union'",
+    ];
+    let output = work_dir.run_jj(&args);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination.
+    Resolved conflicts by merge drivers in these paths:
+      file
+    Working copy  (@) now at: royxmykx 812f96c9 b | b
+    Parent commit (@-)      : zsuskuln 55331c9f a | a
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r", "b", "file"]);
+    insta::assert_snapshot!(output, @"
+    1
+    a
+    b
+    3
+    [EOF]
+    ");
+
+    // Disabled driver
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    args.push("--config=merge.drivers.union.enabled=false");
+    let output = work_dir.run_jj(&args);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination.
+    Working copy  (@) now at: royxmykx 037561e7 b | (conflict) b
+    Parent commit (@-)      : zsuskuln 55331c9f a | a
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in 1 commits:
+      royxmykx 037561e7 b | (conflict) b
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+
+    // External driver resolves the conflict
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let command = format!(
+        "--config=merge.drivers.ext.command=[{}, '%A', '%P']",
+        to_toml_value(fake_editor_path())
+    );
+    std::fs::write(
+        &edit_script,
+        [
+            "expect\n1\na\n3\n",
+            "expect-arg 0\nfile",
+            "write\nresolved\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj([
+        "rebase",
+        "-r",
+        "b",
+        "-o",
+        "a",
+        "--config=merge.drivers.ext.patterns=['glob:*']",
+        &command,
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination.
+    Resolved conflicts by merge drivers in these paths:
+      file
+    Working copy  (@) now at: royxmykx 43b7bd5b b | b
+    Parent commit (@-)      : zsuskuln 55331c9f a | a
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r", "b", "file"]);
+    insta::assert_snapshot!(output, @"
+    resolved
+    [EOF]
+    ");
+
+    // External driver fails to resolve the conflict
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    std::fs::write(&edit_script, "fail").unwrap();
+    let output = work_dir.run_jj([
+        "rebase",
+        "-r",
+        "b",
+        "-o",
+        "a",
+        "--config=merge.drivers.ext.patterns=['glob:*']",
+        &command,
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination.
+    Working copy  (@) now at: royxmykx 6b328e1a b | (conflict) b
+    Parent commit (@-)      : zsuskuln 55331c9f a | a
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in 1 commits:
+      royxmykx 6b328e1a b | (conflict) b
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+
+    // External driver can't be run
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj([
+        "rebase",
+        "-r",
+        "b",
+        "-o",
+        "a",
+        "--config=merge.drivers.ext.patterns=['glob:*']",
+        "--config=merge.drivers.ext.command=['nonexistent-merge-driver']",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination.
+    Warning: Failed to resolve conflicts in file: Failed to run merge driver nonexistent-merge-driver
+    Caused by: No such file or directory (os error 2)
+    Working copy  (@) now at: royxmykx e5b4b167 b | (conflict) b
+    Parent commit (@-)      : zsuskuln 55331c9f a | a
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in 1 commits:
+      royxmykx e5b4b167 b | (conflict) b
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+
+    // Drivers aren't run when displaying the auto-merged parents of an existing
+    // merge commit
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    work_dir.run_jj(["new", "a", "b"]).success();
+    args.pop();
+    let output = work_dir.run_jj(["diff", "--git", args[5], args[6]]);
+    insta::assert_snapshot!(output, @"");
+
+    // Drivers are run when creating a merge commit
+    let output = work_dir.run_jj(["new", "a", "b", args[5], args[6]]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Resolved conflicts by merge drivers in these paths:
+      file
+    Working copy  (@) now at: wvuyspvk 52b1c543 (no description set)
+    Parent commit (@-)      : zsuskuln 55331c9f a | a
+    Parent commit (@-)      : royxmykx 5de44e5b b | b
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_structured_merge() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let base = "{\n  \"a\": 1\n}\n";
+    let a = "{\n  \"a\": 1,\n  \"b\": 2\n}\n";
+    let b = "{\n  \"a\": 1,\n  \"c\": 3\n}\n";
+    create_commit_with_files(&work_dir, "base", &[], &[("file.json", base)]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file.json", a)]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file.json", b)]);
+
+    let output = work_dir.run_jj([
+        "rebase",
+        "-r",
+        "b",
+        "-o",
+        "a",
+        "--config=merge.drivers.json.patterns=['glob:*.json']",
+        "--config=merge.drivers.json.builtin='json'",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits to destination.
+    Resolved conflicts by merge drivers in these paths:
+      file.json
+    Working copy  (@) now at: royxmykx c5d058cf b | b
+    Parent commit (@-)      : zsuskuln 8ee86453 a | a
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r", "b", "file.json"]);
+    insta::assert_snapshot!(output, @r#"
+    {
+      "a": 1,
+      "b": 2,
+      "c": 3
+    }
+    [EOF]
+    "#);
+}
+
 #[must_use]
 fn get_long_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = "bookmarks ++ '  ' ++ change_id.shortest(8) ++ '  ' ++ commit_id.shortest(8) \
                     ++ surround(':  ', '', parents.map(|c| c.bookmarks()))";
     work_dir.run_jj(["log", "-T", template])
 }
+
+/// Runs `jj rebase --interactive` without changing the plan. Returns a map
+/// from the description to the short commit hash of each revision in the plan.
+fn dump_rebase_plan(
+    test_env: &TestEnvironment,
+    edit_script: &Path,
+    work_dir: &TestWorkDir,
+    args: &[&str],
+) -> HashMap<String, String> {
+    std::fs::write(edit_script, "dump plan").unwrap();
+    work_dir.run_jj(args).success();
+    let plan = std::fs::read_to_string(test_env.env_root().join("plan")).unwrap();
+    parse_rebase_plan(&plan)
+}
+
+/// Returns a map from the description to the short commit hash of each
+/// revision in the rebase plan.
+fn parse_rebase_plan(plan: &str) -> HashMap<String, String> {
+    plan.lines()
+        .filter_map(|line| line.strip_prefix("pick "))
+        .map(|line| {
+            let (hash, description) = line.split_once(' ').unwrap();
+            (description.to_owned(), hash.to_owned())
+        })
+        .collect()
+}
+
+#[test]
+fn test_rebase_interactive() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    create_commit(&work_dir, "d", &["c"]);
+    create_commit(&work_dir, "e", &["d"]);
+
+    // Leaving the plan unchanged doesn't rewrite anything
+    std::fs::write(&edit_script, "dump plan").unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::d", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    let plan = std::fs::read_to_string(test_env.env_root().join("plan")).unwrap();
+    insta::assert_snapshot!(plan, @r#"
+    pick 7d980be7a1d4 a
+    pick 123b4d91f6e5 b
+    pick dffaa0d4dacc c
+    pick 86be7a223919 d
+
+    JJ: Rebase 4 revisions onto 000000000000
+    JJ:
+    JJ: Commands:
+    JJ: p, pick <revision> = keep the revision
+    JJ: r, reword <revision> = keep the revision, but edit its description
+    JJ: e, edit <revision> = keep the revision, and edit it in the working copy
+    JJ: s, squash <revision> = move the changes into the previous revision
+    JJ: f, fixup <revision> = like "squash", but discard the description
+    JJ: d, drop <revision> = abandon the revision
+    JJ: x, exec <command> = run the command after the whole rebase, with
+    JJ:                     $JJ_COMMIT_ID set to the previous revision
+    JJ:                     (the revision isn't checked out)
+    JJ:
+    JJ: The revisions are listed from the oldest one, and can be reordered.
+    JJ: Removing the line of a revision abandons it.
+    JJ: If the plan is emptied, the rebase is aborted.
+    "#);
+    let hashes = parse_rebase_plan(&plan);
+
+    // Reorder, reword, fixup, and drop revisions
+    std::fs::write(
+        &edit_script,
+        [
+            &format!(
+                "write\npick {c}\nreword {a} a\nfixup {b} b\n",
+                a = hashes["a"],
+                b = hashes["b"],
+                c = hashes["c"],
+            ),
+            "next invocation\n",
+            "write\na and b\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::d", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 2 commits.
+    Abandoned 1 commits.
+    Rebased 1 descendant commits.
+    Working copy  (@) now at: znkkpsqq 0bc2b942 e | e
+    Parent commit (@-)      : royxmykx 60e12a7d c d | c
+    Added 0 files, modified 0 files, removed 3 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "-T",
+        r#"separate(" ", description.first_line(), bookmarks, diff.files().map(|f| f.path())) ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output, @"
+    @  e e e
+    │ ○  a and b a b a b
+    ├─╯
+    ○  c c d c
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_interactive_squash() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    create_commit(&work_dir, "d", &["c"]);
+
+    let hashes = dump_rebase_plan(
+        &test_env,
+        &edit_script,
+        &work_dir,
+        &["rebase", "-i", "-b", "c", "-o", "a"],
+    );
+
+    // Squashing revisions with descriptions opens an editor with the combined
+    // descriptions. The revision marked `edit` becomes the working copy.
+    std::fs::write(
+        &edit_script,
+        [
+            &format!(
+                "write\nedit {b}\nsquash {c}\n",
+                b = hashes["b"],
+                c = hashes["c"],
+            ),
+            "next invocation\n",
+            "dump description\0write\nb and c\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-b", "c", "-o", "a"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 commits.
+    Abandoned 1 commits.
+    Working copy  (@) now at: zsuskuln 41f8f3aa b c d | b and c
+    Parent commit (@-)      : rlvkpnrz 7d980be7 a | a
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let description = std::fs::read_to_string(test_env.env_root().join("description")).unwrap();
+    insta::assert_snapshot!(description, @r#"
+    JJ: Description from the destination commit:
+    b
+
+    JJ: Description from source commit:
+    c
+
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+    let output = get_log_output(&work_dir);
+    insta::assert_snapshot!(output, @"
+    @  b c d: a
+    ○  a
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_interactive_exec() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.add_paths_to_normalize(fake_editor_path(), "$FAKE_EDITOR_PATH");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+
+    // The plan is edited with `ui.sequence-editor` if set
+    let sequence_editor = to_toml_value(fake_editor_path());
+    test_env.add_config(format!(
+        "ui.editor = 'nonexistent-editor'\nui.sequence-editor = {sequence_editor}"
+    ));
+    let hashes = dump_rebase_plan(
+        &test_env,
+        &edit_script,
+        &work_dir,
+        &["rebase", "-i", "-r", "a::b", "-o", "root()"],
+    );
+
+    // The commands are run after the rebase
+    let exec_file = test_env.env_root().join("exec-output");
+    let plan = format!(
+        "write\npick {b}\nexec {editor} {exec_file}\npick {a}\n",
+        a = hashes["a"],
+        b = hashes["b"],
+        editor = fake_editor_path(),
+        exec_file = exec_file.display(),
+    );
+    std::fs::write(
+        &edit_script,
+        [&plan, "next invocation\n", "write\nexecuted\n"].join("\0"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 2 commits.
+    Working copy  (@) now at: zsuskuln 67638269 b | b
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 0 files, removed 1 files
+    Executing: $FAKE_EDITOR_PATH $TEST_ENV/exec-output
+    [EOF]
+    ");
+    let exec_output = std::fs::read_to_string(&exec_file).unwrap();
+    insta::assert_snapshot!(exec_output, @"
+    executed
+    ");
+
+    // A failing command is reported after the rebase is applied
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(
+        &edit_script,
+        [&plan, "next invocation\n", "fail"].join("\0"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 2 commits.
+    Working copy  (@) now at: zsuskuln f6b52e94 b | b
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    Added 0 files, modified 0 files, removed 1 files
+    Executing: $FAKE_EDITOR_PATH $TEST_ENV/exec-output
+    Error: Command '$FAKE_EDITOR_PATH $TEST_ENV/exec-output' failed with exit status: 1
+    Hint: The rebase has already been applied. Use `jj undo` to revert it.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_rebase_interactive_exec_shell() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+
+    let hashes = dump_rebase_plan(
+        &test_env,
+        &edit_script,
+        &work_dir,
+        &["rebase", "-i", "-r", "a::b", "-o", "root()"],
+    );
+
+    // The command is run by the shell, which expands $JJ_COMMIT_ID
+    let exec_file = test_env.env_root().join("exec-output");
+    let plan = format!(
+        "write\npick {a}\nexec echo \"a: $JJ_COMMIT_ID\" > {exec_file} && echo ok >> {exec_file}\n\
+         pick {b}\n",
+        a = hashes["a"],
+        b = hashes["b"],
+        exec_file = exec_file.display(),
+    );
+    std::fs::write(&edit_script, plan).unwrap();
+    work_dir
+        .run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"])
+        .success();
+    let commit_id = work_dir
+        .run_jj(["log", "--no-graph", "-r", "a", "-T", "commit_id"])
+        .success()
+        .stdout
+        .into_raw();
+    let exec_output = std::fs::read_to_string(&exec_file).unwrap();
+    assert_eq!(exec_output, format!("a: {commit_id}\nok\n"));
+}
+
+#[test]
+fn test_rebase_interactive_errors() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["a"]);
+
+    // The revisions must form a linear chain
+    let output = work_dir.run_jj(["rebase", "-i", "-s", "a", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot rebase revisions interactively unless they form a linear chain
+    Hint: Revision 123b4d91f6e5 is not the only parent of revision 991a7501d660.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // A destination is required
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "b", "-A", "c"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: `--interactive` requires a destination given by `--onto`
+    [EOF]
+    [exit status: 2]
+    ");
+
+    // Unknown action
+    std::fs::write(&edit_script, "write\nfoo bar\n").unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Unknown action `foo` in rebase plan: `foo bar`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Unknown revision
+    std::fs::write(&edit_script, "write\npick 000000\n").unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Unknown revision in rebase plan: `pick 000000`
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Empty plan
+    std::fs::write(&edit_script, "write\n").unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: The rebase plan is empty, nothing to do
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Squash without a previous revision
+    let hashes = dump_rebase_plan(
+        &test_env,
+        &edit_script,
+        &work_dir,
+        &["rebase", "-i", "-r", "a::b", "-o", "root()"],
+    );
+    std::fs::write(
+        &edit_script,
+        format!("write\nsquash {}\npick {}\n", hashes["b"], hashes["a"]),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b", "-o", "root()"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot squash revision 123b4d91f6e5 without a previous revision
+    [EOF]
+    [exit status: 1]
+    ");
+}
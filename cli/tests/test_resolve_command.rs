@@ -2314,3 +2314,240 @@ fn test_resolve_with_contents_of_side() -> TestResult {
     ");
     Ok(())
 }
+
+#[test]
+fn test_resolve_recorded_resolutions() -> TestResult {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config("merge.rerere = true");
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "b\n")]);
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list-recorded"]), @"
+    ------- stderr -------
+    No recorded resolutions.
+    [EOF]
+    ");
+
+    // Resolving the conflict in the working copy records the resolution
+    work_dir.write_file("file", "resolution\n");
+    work_dir.run_jj(["status"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list-recorded"]), @"
+    16481937df4c  file
+    [EOF]
+    ");
+    // Recorded resolutions aren't applied when merging parent trees for display
+    insta::assert_snapshot!(work_dir.run_jj(["diff", "--summary"]), @"
+    M file
+    [EOF]
+    ");
+
+    // The same conflict is resolved automatically
+    let output = work_dir.run_jj(["new", "b", "a"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Resolved conflicts by recorded resolutions in these paths:
+      file
+    Working copy  (@) now at: lylxulpl 5b437b5e (no description set)
+    Parent commit (@-)      : royxmykx 89d1b299 b | b
+    Parent commit (@-)      : zsuskuln 45537d53 a | a
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "file"]), @"
+    resolution
+    [EOF]
+    ");
+
+    // Unrelated conflicts aren't resolved
+    work_dir.run_jj(["new", "base"]).success();
+    work_dir.write_file("file", "c\n");
+    work_dir.run_jj(["new", "@", "a"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @"
+    file    2-sided conflict
+    [EOF]
+    ");
+
+    // Forgotten resolutions are no longer applied
+    let output = work_dir.run_jj(["resolve", "--forget", "zzz"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No recorded resolution matches ID zzz
+    [EOF]
+    [exit status: 1]
+    ");
+    let id = work_dir
+        .run_jj(["resolve", "--list-recorded"])
+        .success()
+        .stdout
+        .into_raw();
+    // Nothing is forgotten if any of the IDs is invalid
+    let output = work_dir.run_jj(["resolve", "--forget", &id[..4], "--forget", "zzz"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: No recorded resolution matches ID zzz
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list-recorded"]), @"
+    16481937df4c  file
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["resolve", "--forget", &id[..4], "--forget", &id[..6]]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Forgot 1 recorded resolutions
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list-recorded"]), @"
+    ------- stderr -------
+    No recorded resolutions.
+    [EOF]
+    ");
+    work_dir.run_jj(["new", "a", "b"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @"
+    file    2-sided conflict
+    [EOF]
+    ");
+    Ok(())
+}
+
+#[test]
+fn test_resolve_hunks() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let base = "1\nbase1\n2\n3\n4\nbase2\n5\n";
+    let a = "1\na1\n2\n3\n4\na2\n5\n";
+    let b = "1\nb1\n2\n3\n4\nb2\n5\n";
+    create_commit_with_files(&work_dir, "base", &[], &[("file", base), ("other", "x\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", a), ("other", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", b), ("other", "b\n")]);
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list-hunks"]), @r#"
+    file     hunk 1    lines 2-9      side 1: zsuskuln 5819d8e8 "a"
+    file     hunk 1    lines 2-9      side 2: royxmykx 61b01c81 "b"
+    file     hunk 2    lines 13-20    side 1: zsuskuln 5819d8e8 "a"
+    file     hunk 2    lines 13-20    side 2: royxmykx 61b01c81 "b"
+    other    hunk 1    lines 1-8      side 1: zsuskuln 5819d8e8 "a"
+    other    hunk 1    lines 1-8      side 2: royxmykx 61b01c81 "b"
+    [EOF]
+    "#);
+    let setup_opid = work_dir.current_operation_id();
+
+    // Resolve one of the hunks
+    let output = work_dir.run_jj(["resolve", "file", "--hunk", "2=1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: vruxwmqv fa6c1303 conflict | (conflict) conflict
+    Parent commit (@-)      : zsuskuln 5819d8e8 a | a
+    Parent commit (@-)      : royxmykx 61b01c81 b | b
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file     2-sided conflict
+    other    2-sided conflict
+    New conflicts appeared in 1 commits:
+      vruxwmqv fa6c1303 conflict | (conflict) conflict
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new vruxwmqv
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.read_file("file"), @r#"
+    1
+    <<<<<<< conflict 1 of 1
+    %%%%%%% diff from: rlvkpnrz 0ccfa2e1 "base"
+    \\\\\\\        to: zsuskuln 5819d8e8 "a"
+    -base1
+    +a1
+    +++++++ royxmykx 61b01c81 "b"
+    b1
+    >>>>>>> conflict 1 of 1 ends
+    2
+    3
+    4
+    a2
+    5
+    "#);
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list-hunks", "file"]), @r#"
+    file    hunk 1    lines 2-9    side 1: zsuskuln 5819d8e8 "a"
+    file    hunk 1    lines 2-9    side 2: royxmykx 61b01c81 "b"
+    [EOF]
+    "#);
+
+    // Resolve all hunks
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["resolve", "file", "--hunk", "1=2", "--hunk=2=1"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Working copy  (@) now at: vruxwmqv d20c9705 conflict | (conflict) conflict
+    Parent commit (@-)      : zsuskuln 5819d8e8 a | a
+    Parent commit (@-)      : royxmykx 61b01c81 b | b
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    other    2-sided conflict
+    New conflicts appeared in 1 commits:
+      vruxwmqv d20c9705 conflict | (conflict) conflict
+    Hint: To resolve the conflicts, start by creating a commit on top of
+    the conflicted commit:
+      jj new vruxwmqv
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you can inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.read_file("file"), @"
+    1
+    b1
+    2
+    3
+    4
+    a2
+    5
+    ");
+
+    // Invalid choices
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--hunk", "1=1"]), @"
+    ------- stderr -------
+    Error: --hunk requires exactly one conflicted file
+    Hint: Specify the path of the file to resolve.
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "file", "--hunk", "3=1"]), @r#"
+    ------- stderr -------
+    Error: Failed to resolve conflicts
+    Caused by: The file "file" has no conflicted hunk 3. It has 2 conflicted hunks.
+    [EOF]
+    [exit status: 1]
+    "#);
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "file", "--hunk", "1=3"]), @r#"
+    ------- stderr -------
+    Error: Failed to resolve conflicts
+    Caused by: The conflict at "file" has no side 3. It has 2 sides.
+    [EOF]
+    [exit status: 1]
+    "#);
+    let output = work_dir.run_jj(["resolve", "file", "--hunk", "1=1", "--hunk", "1=2"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: --hunk 1 was specified more than once
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "file", "--hunk", "1"]), @"
+    ------- stderr -------
+    error: invalid value '1' for '--hunk <HUNK=SIDE>': expected HUNK=SIDE
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
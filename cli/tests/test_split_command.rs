@@ -1881,3 +1881,257 @@ fn test_split_with_editor_without_message() -> TestResult {
     ");
     Ok(())
 }
+
+#[test]
+fn test_split_by_groups() -> TestResult {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("README", "readme\n");
+    work_dir.write_file("lib/a/file1", "a\n");
+    work_dir.write_file("lib/a/file2", "a\n");
+    work_dir.write_file("lib/b/file", "b\n");
+    work_dir.write_file("cli/file", "cli\n");
+    work_dir
+        .run_jj(["commit", "-m=Bump dependencies"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@-", "deps"])
+        .success();
+    let setup_opid = work_dir.current_operation_id();
+
+    // Split by directory. Files at the root go into the last revision.
+    let output = work_dir.run_jj(["split", "-r=@-", "--by=directory"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 descendant commits.
+    Split into 4 revisions:
+      qpvuntsm 2502b279 cli: Bump dependencies
+      zsuskuln 7179168d lib/a: Bump dependencies
+      rzvqmyuk c9738fe2 lib/b: Bump dependencies
+      luqtuptr 46e768c9 deps | Bump dependencies
+    Working copy  (@) now at: rlvkpnrz 7e00f488 (empty) (no description set)
+    Parent commit (@-)      : luqtuptr 46e768c9 deps | Bump dependencies
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @"
+    @  rlvkpnrzqnoo
+    ○  luqtuptruqys deps Bump dependencies
+    │  A README
+    ○  rzvqmyukvvwz lib/b: Bump dependencies
+    │  A lib/b/file
+    ○  zsuskulnrvyr lib/a: Bump dependencies
+    │  A lib/a/file1
+    │  A lib/a/file2
+    ○  qpvuntsmwlqt cli: Bump dependencies
+    │  A cli/file
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    // Bookmarks move to the last revision with the legacy behavior
+    insta::assert_snapshot!(work_dir.run_jj(["bookmark", "list"]), @"
+    deps: luqtuptr 46e768c9 Bump dependencies
+    [EOF]
+    ");
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj([
+        "split",
+        "-r=@-",
+        "--by=directory",
+        "--config=split.legacy-bookmark-behavior=false",
+    ]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 descendant commits.
+    Split into 4 revisions:
+      qpvuntsm fa7c1a64 deps | cli: Bump dependencies
+      vruxwmqv 1aa506dc lib/a: Bump dependencies
+      nuwvvtmy 4d6bc9c2 lib/b: Bump dependencies
+      mpvtouxk 6d734cc0 Bump dependencies
+    Working copy  (@) now at: rlvkpnrz 81db6f65 (empty) (no description set)
+    Parent commit (@-)      : mpvtouxk 6d734cc0 Bump dependencies
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["bookmark", "list"]), @"
+    deps: qpvuntsm fa7c1a64 cli: Bump dependencies
+    [EOF]
+    ");
+
+    // Split by top-level directory, in parallel
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["split", "-r=@-", "--by=top-level", "--parallel"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 descendant commits.
+    Split into 3 revisions:
+      qpvuntsm 2a715228 cli: Bump dependencies
+      kpqxywon 5c91988f lib: Bump dependencies
+      vzqnnsmr 4e34cdae deps | Bump dependencies
+    Working copy  (@) now at: rlvkpnrz 8bc69fe7 (empty) (no description set)
+    Parent commit (@-)      : qpvuntsm 2a715228 cli: Bump dependencies
+    Parent commit (@-)      : kpqxywon 5c91988f lib: Bump dependencies
+    Parent commit (@-)      : vzqnnsmr 4e34cdae deps | Bump dependencies
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @"
+    @      rlvkpnrzqnoo
+    ├─┬─╮
+    │ │ ○  vzqnnsmrxxkw deps Bump dependencies
+    │ │ │  A README
+    │ ○ │  kpqxywonksrl lib: Bump dependencies
+    │ ├─╯  A lib/a/file1
+    │ │    A lib/a/file2
+    │ │    A lib/b/file
+    ○ │  qpvuntsmwlqt cli: Bump dependencies
+    ├─╯  A cli/file
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    // Split by filesets, in the given order. Files not matching any fileset go
+    // into the last revision.
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["split", "-r=@-", "--by=fileset-list", "lib/b", "cli"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 descendant commits.
+    Split into 3 revisions:
+      qpvuntsm 04c2a8e3 lib/b: Bump dependencies
+      lylxulpl cfed70c3 cli: Bump dependencies
+      rsllmpnm 7b80bb97 deps | Bump dependencies
+    Working copy  (@) now at: rlvkpnrz baa1a201 (empty) (no description set)
+    Parent commit (@-)      : rsllmpnm 7b80bb97 deps | Bump dependencies
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @"
+    @  rlvkpnrzqnoo
+    ○  rsllmpnmslon deps Bump dependencies
+    │  A README
+    │  A lib/a/file1
+    │  A lib/a/file2
+    ○  lylxulplsnyw cli: Bump dependencies
+    │  A cli/file
+    ○  qpvuntsmwlqt lib/b: Bump dependencies
+    │  A lib/b/file
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+
+    // With other modes, only files matching the filesets are grouped
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["split", "-r=@-", "--by=directory", "lib"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Rebased 1 descendant commits.
+    Split into 3 revisions:
+      qpvuntsm a61b0d97 lib/a: Bump dependencies
+      uyznsvlq f715e243 lib/b: Bump dependencies
+      uuqyqztp 44a04e48 deps | Bump dependencies
+    Working copy  (@) now at: rlvkpnrz c438403d (empty) (no description set)
+    Parent commit (@-)      : uuqyqztp 44a04e48 deps | Bump dependencies
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @"
+    @  rlvkpnrzqnoo
+    ○  uuqyqztpptml deps Bump dependencies
+    │  A README
+    │  A cli/file
+    ○  uyznsvlquzzm lib/b: Bump dependencies
+    │  A lib/b/file
+    ○  qpvuntsmwlqt lib/a: Bump dependencies
+    │  A lib/a/file1
+    │  A lib/a/file2
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+    Ok(())
+}
+
+#[test]
+fn test_split_by_owner() -> TestResult {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(
+        "CODEOWNERS",
+        "*.rs @rust-team\ndocs/ @docs-team @rust-team\n",
+    );
+    work_dir.run_jj(["commit", "-m=Add owners"]).success();
+    work_dir.write_file("src/lib.rs", "lib\n");
+    work_dir.write_file("src/main.rs", "main\n");
+    work_dir.write_file("docs/index.md", "docs\n");
+    work_dir.write_file("Makefile", "all:\n");
+    work_dir.run_jj(["describe", "-m=Rename things"]).success();
+
+    // The description of each revision is generated from a template
+    test_env.add_config(
+        r#"templates.split_description = 'if(name, name ++ ": ") ++ commit.description().first_line() ++ " (" ++ commit.diff().files().len() ++ " files)\n"'"#,
+    );
+    let output = work_dir.run_jj(["split", "--by=owner"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Split into 3 revisions:
+      rlvkpnrz 3b1a4818 @docs-team @rust-team: Rename things (1 files)
+      zsuskuln 8f9c52b7 @rust-team: Rename things (2 files)
+      rzvqmyuk 05d1d956 Rename things (1 files)
+    Working copy  (@) now at: rzvqmyuk 05d1d956 Rename things (1 files)
+    Parent commit (@-)      : zsuskuln 8f9c52b7 @rust-team: Rename things (2 files)
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_with_summary(&work_dir), @"
+    @  rzvqmyukvvwz Rename things (1 files)
+    │  A Makefile
+    ○  zsuskulnrvyr @rust-team: Rename things (2 files)
+    │  A src/lib.rs
+    │  A src/main.rs
+    ○  rlvkpnrzqnoo @docs-team @rust-team: Rename things (1 files)
+    │  A docs/index.md
+    ○  qpvuntsmwlqt Add owners
+    │  A CODEOWNERS
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+    Ok(())
+}
+
+#[test]
+fn test_split_by_errors() -> TestResult {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("dir/file1", "a\n");
+    work_dir.write_file("dir/file2", "b\n");
+
+    let output = work_dir.run_jj(["split", "--by=directory"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: Cannot split the revision because all changes belong to the same group
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["split", "--by=fileset-list"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Error: `--by fileset-list` requires at least one fileset
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["split", "--by=directory", "-i"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    error: the argument '--by <GROUPS>' cannot be used with '--interactive'
+
+    Usage: jj split --by <GROUPS> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+    Ok(())
+}
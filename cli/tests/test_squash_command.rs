@@ -1698,6 +1698,128 @@ fn test_squash_use_destination_message() {
     ");
 }
 
+#[test]
+fn test_squash_autosquash() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    work_dir.run_jj(["commit", "-m=a"]).success();
+    work_dir.write_file("file2", "b\n");
+    work_dir.run_jj(["commit", "-m=b"]).success();
+    work_dir.write_file("file1", "a\nfixed\n");
+    work_dir.run_jj(["commit", "-m=fixup! a"]).success();
+    work_dir.write_file("file2", "b\nsquashed\n");
+    work_dir
+        .run_jj(["commit", "-m=squash! b", "-m=More about b"])
+        .success();
+    work_dir.write_file("file3", "c\n");
+    work_dir.run_jj(["commit", "-m=c"]).success();
+    work_dir.write_file("file1", "a\nfixed\namended\n");
+    work_dir
+        .run_jj(["commit", "-m=amend! fixup! a", "-m=Amended a"])
+        .success();
+    work_dir.write_file("file3", "c\nfixed\n");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=subject(c)", "-T=change_id.short()"]);
+    let change_id_c = output.stdout.raw().to_owned();
+    work_dir
+        .run_jj(["commit", &format!("-m=fixup! {change_id_c}")])
+        .success();
+    work_dir.write_file("file4", "d\n");
+    work_dir.run_jj(["commit", "-m=fixup! d"]).success();
+    let template =
+        r#"separate(" ", description.first_line(), diff.files().map(|f| f.path())) ++ "\n""#;
+    let setup_opid = work_dir.current_operation_id();
+
+    // Fixups are matched by subject or change ID. The description of the target
+    // is kept, extended, or replaced depending on the prefix.
+    let output = work_dir.run_jj(["squash", "--autosquash"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: No target found for fixup commit e021b21c92a3, leaving it as is.
+    Squashed 4 fixup commits into 3 commits.
+    Rebased 2 descendant commits.
+    Working copy  (@) now at: znkkpsqq 004dd07b (empty) (no description set)
+    Parent commit (@-)      : yostqsxw 2e3c25ff fixup! d
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    @
+    ○  fixup! d file4
+    ○  c file3
+    ○  b file2
+    ○  Amended a file1
+    ◆
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=files(file2)", "-T=description"]);
+    insta::assert_snapshot!(output, @"
+    b
+
+    More about b
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=files(file1)", "file1"]);
+    insta::assert_snapshot!(output, @"
+    a
+    fixed
+    amended
+    [EOF]
+    ");
+
+    // Only the given revisions are searched for fixup commits
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    let output = work_dir.run_jj(["squash", "--autosquash", "-r=subject(glob:'squash!*')"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Squashed 1 fixup commits into 1 commits.
+    Rebased 6 descendant commits.
+    Working copy  (@) now at: znkkpsqq acc20ae9 (empty) (no description set)
+    Parent commit (@-)      : yostqsxw ad61de30 fixup! d
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", template]);
+    insta::assert_snapshot!(output, @"
+    @
+    ○  fixup! d file4
+    ○  fixup! mzvwutvlkqwt file3
+    ○  amend! fixup! a file1
+    ○  c file3
+    ○  fixup! a file1
+    ○  b file2
+    ○  a file1
+    ◆
+    [EOF]
+    ");
+
+    // Nothing to squash
+    let output = work_dir.run_jj(["squash", "--autosquash", "-r=subject(a)"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    No fixup commits to squash.
+    [EOF]
+    ");
+
+    // Immutable targets are not considered
+    work_dir.run_jj(["op", "restore", &setup_opid]).success();
+    test_env.add_config("revset-aliases.'immutable_heads()' = 'subject(b)'");
+    let output = work_dir.run_jj(["squash", "--autosquash"]);
+    insta::assert_snapshot!(output, @"
+    ------- stderr -------
+    Warning: No target found for fixup commit 56bc34366aac, leaving it as is.
+    Warning: No target found for fixup commit 7d0a09d7e721, leaving it as is.
+    Warning: No target found for fixup commit 31841b9764c0, leaving it as is.
+    Warning: No target found for fixup commit e021b21c92a3, leaving it as is.
+    Squashed 1 fixup commits into 1 commits.
+    Rebased 3 descendant commits.
+    Working copy  (@) now at: znkkpsqq 17914e8e (empty) (no description set)
+    Parent commit (@-)      : yostqsxw 7a7c871c fixup! d
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_squash_option_exclusion() {
     let test_env = TestEnvironment::default();
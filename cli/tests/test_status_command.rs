@@ -41,6 +41,34 @@ fn test_status_copies() {
     ");
 }
 
+#[test]
+fn test_status_output_format_json() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("copy-source", "copy1\ncopy2\ncopy3\n");
+    work_dir.write_file("rename-source", "rename");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("copy-source", "copy1\ncopy2\ncopy3\nsource\n");
+    work_dir.write_file("copy-target", "copy1\ncopy2\ncopy3\ntarget\n");
+    work_dir.remove_file("rename-source");
+    work_dir.write_file("rename-target", "rename");
+
+    let output = work_dir.run_jj(["status", "--output-format=json"]);
+    insta::assert_snapshot!(output, @r#"
+    {"working_copy":{"commit_id":"c2fce84292d4777694ef912b669c3adceb01f4eb","parents":["ebf799bc17c4176f3f37570c9789c39767e8699c"],"change_id":"rlvkpnrzqnoowoytxnquwvuryrwnrmlp","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"}},"parents":[{"commit_id":"ebf799bc17c4176f3f37570c9789c39767e8699c","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"}}],"changes":[{"status":"modified","path":"copy-source"},{"status":"copied","path":"copy-target","source":"copy-source"},{"status":"renamed","path":"rename-target","source":"rename-source"}],"untracked":[],"conflicts":[],"conflicted_bookmarks":[],"conflicted_remote_bookmarks":[]}
+    [EOF]
+    "#);
+
+    // Paths restrict the reported changes
+    let output = work_dir.run_jj(["status", "--output-format=ndjson", "copy-source"]);
+    insta::assert_snapshot!(output, @r#"
+    {"working_copy":{"commit_id":"c2fce84292d4777694ef912b669c3adceb01f4eb","parents":["ebf799bc17c4176f3f37570c9789c39767e8699c"],"change_id":"rlvkpnrzqnoowoytxnquwvuryrwnrmlp","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:09+07:00"}},"parents":[{"commit_id":"ebf799bc17c4176f3f37570c9789c39767e8699c","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"}}],"changes":[{"status":"modified","path":"copy-source"}],"untracked":[],"conflicts":[],"conflicted_bookmarks":[],"conflicted_remote_bookmarks":[]}
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_status_merge() {
     let test_env = TestEnvironment::default();
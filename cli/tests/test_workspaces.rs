@@ -123,6 +123,23 @@ fn test_workspaces_add_second_and_third_workspace() {
     assert!(!test_env.env_root().join("tertiary").exists());
 }
 
+#[test]
+fn test_workspaces_list_output_format() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_dir = test_env.work_dir("main");
+    main_dir
+        .run_jj(["workspace", "add", "--name", "second", "../secondary"])
+        .success();
+
+    let output = main_dir.run_jj(["workspace", "list", "--output-format=ndjson"]);
+    insta::assert_snapshot!(output, @r#"
+    {"name":"default","target":{"commit_id":"e8849ae12c709f2321908879bc724fdb2ab8a781","parents":["0000000000000000000000000000000000000000"],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:07+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:07+07:00"}}}
+    {"name":"second","target":{"commit_id":"94f41578a9e101e2c85877f3b4eaa9f5e915783f","parents":["0000000000000000000000000000000000000000"],"change_id":"uuqppmxqsxsnvoxuwlkqvswowpyprsmr","description":"","author":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"},"committer":{"name":"Test User","email":"test.user@example.com","timestamp":"2001-02-03T04:05:08+07:00"}}}
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_workspaces_add_with_message() {
     let test_env = TestEnvironment::default();
@@ -76,6 +76,28 @@ pub fn find_nonword_ranges(text: &[u8]) -> Vec<Range<usize>> {
         .collect()
 }
 
+/// Like [`find_nonword_ranges()`], but consecutive whitespace bytes are
+/// grouped into one range.
+pub fn find_nonword_ranges_grouping_whitespace(text: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for (i, b) in text.iter().enumerate() {
+        if is_word_byte(*b) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range)
+                if range.end == i
+                    && b.is_ascii_whitespace()
+                    && text[i - 1].is_ascii_whitespace() =>
+            {
+                range.end += 1;
+            }
+            _ => ranges.push(i..i + 1),
+        }
+    }
+    ranges
+}
+
 fn bytes_ignore_all_whitespace(text: &[u8]) -> impl Iterator<Item = u8> {
     text.iter().copied().filter(|b| !b.is_ascii_whitespace())
 }
@@ -108,6 +130,25 @@ where
     state.write_usize(len);
 }
 
+/// Algorithm to find unchanged lines (or tokens) between inputs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffAlgorithm {
+    /// Anchors on the least common tokens shared by the inputs, and recurses
+    /// into the regions between them.
+    #[default]
+    Default,
+    /// Extends the least common shared token to the longest unchanged region,
+    /// and recurses into the regions before and after it. This is similar to
+    /// Git's histogram diff.
+    Histogram,
+    /// Anchors on tokens which occur exactly once in each input. Falls back to
+    /// the minimal diff if there are no such tokens.
+    Patience,
+    /// Finds the longest common subsequence by Myers' algorithm.
+    Minimal,
+}
+
 /// Compares byte sequences based on a certain equivalence property.
 ///
 /// This isn't a newtype `Wrapper<'a>(&'a [u8])` but an external comparison
@@ -423,46 +464,126 @@ fn collect_unchanged_words<C: CompareBytes, S: BuildHasher>(
     left: &LocalDiffSource,
     right: &LocalDiffSource,
     comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
 ) {
     if left.ranges.is_empty() || right.ranges.is_empty() {
         return;
     }
-
-    // Prioritize LCS-based algorithm than leading/trailing matches
-    let old_len = found_positions.len();
-    collect_unchanged_words_lcs(found_positions, left, right, comp);
-    if found_positions.len() != old_len {
-        return;
+    match algorithm {
+        DiffAlgorithm::Default => {
+            collect_unchanged_words_default(found_positions, left, right, comp);
+        }
+        DiffAlgorithm::Histogram | DiffAlgorithm::Patience | DiffAlgorithm::Minimal => {
+            // Trim leading/trailing common ranges first, which is cheap and
+            // makes the remaining regions smaller.
+            let (common_leading_len, common_trailing_len) =
+                count_common_leading_trailing_words(left, right, comp);
+            push_common_leading_trailing_words(found_positions, left, right, common_leading_len, 0);
+            let left_end = LocalWordPosition(left.ranges.len() - common_trailing_len);
+            let right_end = LocalWordPosition(right.ranges.len() - common_trailing_len);
+            let left = left.narrowed(LocalWordPosition(common_leading_len)..left_end);
+            let right = right.narrowed(LocalWordPosition(common_leading_len)..right_end);
+            if !left.ranges.is_empty() && !right.ranges.is_empty() {
+                match algorithm {
+                    DiffAlgorithm::Default => unreachable!(),
+                    DiffAlgorithm::Histogram => {
+                        collect_unchanged_words_histogram(found_positions, &left, &right, comp);
+                    }
+                    DiffAlgorithm::Patience => {
+                        collect_unchanged_words_patience(found_positions, &left, &right, comp);
+                    }
+                    DiffAlgorithm::Minimal => {
+                        collect_unchanged_words_myers(found_positions, &left, &right, comp);
+                    }
+                }
+            }
+            push_common_leading_trailing_words(
+                found_positions,
+                &left,
+                &right,
+                0,
+                common_trailing_len,
+            );
+        }
     }
+}
 
-    // Trim leading common ranges (i.e. grow previous unchanged region)
+/// Counts common leading words, and common trailing words of the rest.
+fn count_common_leading_trailing_words<C: CompareBytes, S: BuildHasher>(
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) -> (usize, usize) {
     let common_leading_len = iter::zip(left.hashed_words(), right.hashed_words())
         .take_while(|&(l, r)| comp.eq_hashed(l, r))
         .count();
     let left_hashed_words = left.hashed_words().skip(common_leading_len);
     let right_hashed_words = right.hashed_words().skip(common_leading_len);
-
-    // Trim trailing common ranges (i.e. grow next unchanged region)
     let common_trailing_len = iter::zip(left_hashed_words.rev(), right_hashed_words.rev())
         .take_while(|&(l, r)| comp.eq_hashed(l, r))
         .count();
+    (common_leading_len, common_trailing_len)
+}
 
+/// Adds `leading_len` words from the start and `trailing_len` words from the
+/// end as unchanged.
+///
+/// For the trailing words, `left` and `right` should be the sources narrowed
+/// up to the trailing words.
+fn push_common_leading_trailing_words(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    leading_len: usize,
+    trailing_len: usize,
+) {
     found_positions.extend(itertools::chain(
-        (0..common_leading_len).map(|i| {
+        (0..leading_len).map(|i| {
             (
                 left.map_to_global(LocalWordPosition(i)),
                 right.map_to_global(LocalWordPosition(i)),
             )
         }),
-        (1..=common_trailing_len).rev().map(|i| {
+        (0..trailing_len).map(|i| {
             (
-                left.map_to_global(LocalWordPosition(left.ranges.len() - i)),
-                right.map_to_global(LocalWordPosition(right.ranges.len() - i)),
+                left.map_to_global(LocalWordPosition(left.ranges.len() + i)),
+                right.map_to_global(LocalWordPosition(right.ranges.len() + i)),
             )
         }),
     ));
 }
 
+fn collect_unchanged_words_default<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) {
+    // Prioritize LCS-based algorithm than leading/trailing matches
+    let old_len = found_positions.len();
+    collect_unchanged_words_lcs(found_positions, left, right, comp);
+    if found_positions.len() != old_len {
+        return;
+    }
+
+    // Trim leading common ranges (i.e. grow previous unchanged region), and
+    // trailing common ranges (i.e. grow next unchanged region)
+    let (common_leading_len, common_trailing_len) =
+        count_common_leading_trailing_words(left, right, comp);
+    push_common_leading_trailing_words(found_positions, left, right, common_leading_len, 0);
+    push_common_leading_trailing_words(
+        found_positions,
+        &left.narrowed(
+            LocalWordPosition(0)..LocalWordPosition(left.ranges.len() - common_trailing_len),
+        ),
+        &right.narrowed(
+            LocalWordPosition(0)..LocalWordPosition(right.ranges.len() - common_trailing_len),
+        ),
+        0,
+        common_trailing_len,
+    );
+}
+
 fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
     found_positions: &mut Vec<(WordPosition, WordPosition)>,
     left: &LocalDiffSource,
@@ -496,13 +617,34 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
         return;
     };
 
+    collect_unchanged_words_between_anchors(
+        found_positions,
+        left,
+        right,
+        comp,
+        DiffAlgorithm::Default,
+        uncommon_shared_word_positions.flat_map(|(lefts, rights)| iter::zip(lefts, rights)),
+    );
+}
+
+/// Adds the LCS of the `anchors` as unchanged words, recursing into the
+/// modified areas between them.
+///
+/// The `anchors` are pairs of positions of the same word (and occurrence.)
+fn collect_unchanged_words_between_anchors<'a, C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+    algorithm: DiffAlgorithm,
+    anchors: impl IntoIterator<Item = (&'a LocalWordPosition, &'a LocalWordPosition)>,
+) {
     // [(index into ranges, serial to identify {word, occurrence #})]
-    let (mut left_positions, mut right_positions): (Vec<_>, Vec<_>) =
-        uncommon_shared_word_positions
-            .flat_map(|(lefts, rights)| iter::zip(lefts, rights))
-            .enumerate()
-            .map(|(serial, (&left_pos, &right_pos))| ((left_pos, serial), (right_pos, serial)))
-            .unzip();
+    let (mut left_positions, mut right_positions): (Vec<_>, Vec<_>) = anchors
+        .into_iter()
+        .enumerate()
+        .map(|(serial, (&left_pos, &right_pos))| ((left_pos, serial), (right_pos, serial)))
+        .unzip();
     left_positions.sort_unstable_by_key(|&(pos, _serial)| pos);
     right_positions.sort_unstable_by_key(|&(pos, _serial)| pos);
     let left_index_by_right_index: Vec<usize> = {
@@ -530,6 +672,7 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
             &left.narrowed(previous_left_position..left_position),
             &right.narrowed(previous_right_position..right_position),
             comp,
+            algorithm,
         );
         found_positions.push((
             left.map_to_global(left_position),
@@ -544,9 +687,257 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
         &left.narrowed(previous_left_position..LocalWordPosition(left.ranges.len())),
         &right.narrowed(previous_right_position..LocalWordPosition(right.ranges.len())),
         comp,
+        algorithm,
     );
 }
 
+fn collect_unchanged_words_patience<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) {
+    let left_histogram = Histogram::calculate(left, comp, 1);
+    let right_histogram = Histogram::calculate(right, comp, 1);
+    let unique_shared_word_positions = left_histogram
+        .word_to_positions
+        .iter()
+        .filter_map(|(word, left_positions)| {
+            let right_positions = right_histogram.positions_by_word(*word, comp)?;
+            let (&[left_pos], &[right_pos]) = (&left_positions[..], right_positions) else {
+                return None;
+            };
+            Some((left_pos, right_pos))
+        })
+        .collect_vec();
+    if unique_shared_word_positions.is_empty() {
+        collect_unchanged_words_myers(found_positions, left, right, comp);
+        return;
+    }
+    collect_unchanged_words_between_anchors(
+        found_positions,
+        left,
+        right,
+        comp,
+        DiffAlgorithm::Patience,
+        unique_shared_word_positions.iter().map(|(l, r)| (l, r)),
+    );
+}
+
+fn collect_unchanged_words_histogram<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) {
+    // Words occurring more often than this aren't used to find the unchanged
+    // region. The value is taken from Git.
+    const MAX_CHAIN_LENGTH: usize = 64;
+
+    let left_words = left.hashed_words().collect_vec();
+    let right_words = right.hashed_words().collect_vec();
+    let left_histogram = Histogram::calculate(left, comp, MAX_CHAIN_LENGTH);
+    let left_counts = left_words
+        .iter()
+        .map(|&word| left_histogram.positions_by_word(word, comp).unwrap().len())
+        .collect_vec();
+
+    // (occurrences, left range, right range)
+    let mut best_region: Option<(usize, Range<usize>, Range<usize>)> = None;
+    let mut has_common_words = false;
+    let mut right_index = 0;
+    while right_index < right_words.len() {
+        let Some(left_positions) = left_histogram.positions_by_word(right_words[right_index], comp)
+        else {
+            right_index += 1;
+            continue;
+        };
+        if left_positions.len() > MAX_CHAIN_LENGTH {
+            has_common_words = true;
+            right_index += 1;
+            continue;
+        }
+        let mut next_right_index = right_index + 1;
+        for &LocalWordPosition(left_index) in left_positions {
+            let (mut left_start, mut right_start) = (left_index, right_index);
+            while left_start > 0
+                && right_start > 0
+                && comp.eq_hashed(left_words[left_start - 1], right_words[right_start - 1])
+            {
+                left_start -= 1;
+                right_start -= 1;
+            }
+            let (mut left_end, mut right_end) = (left_index + 1, right_index + 1);
+            while left_end < left_words.len()
+                && right_end < right_words.len()
+                && comp.eq_hashed(left_words[left_end], right_words[right_end])
+            {
+                left_end += 1;
+                right_end += 1;
+            }
+            let occurrences = left_counts[left_start..left_end].iter().min().copied();
+            let occurrences = occurrences.unwrap();
+            let is_better = best_region
+                .as_ref()
+                .is_none_or(|(best_occurrences, best_left, _)| {
+                    occurrences < *best_occurrences
+                        || (occurrences == *best_occurrences
+                            && left_end - left_start > best_left.len())
+                });
+            if is_better {
+                best_region = Some((occurrences, left_start..left_end, right_start..right_end));
+            }
+            next_right_index = next_right_index.max(right_end);
+        }
+        right_index = next_right_index;
+    }
+
+    let Some((_, left_region, right_region)) = best_region else {
+        if has_common_words {
+            collect_unchanged_words_myers(found_positions, left, right, comp);
+        }
+        return;
+    };
+    collect_unchanged_words(
+        found_positions,
+        &left.narrowed(LocalWordPosition(0)..LocalWordPosition(left_region.start)),
+        &right.narrowed(LocalWordPosition(0)..LocalWordPosition(right_region.start)),
+        comp,
+        DiffAlgorithm::Histogram,
+    );
+    found_positions.extend(iter::zip(left_region.clone(), right_region.clone()).map(
+        |(left_index, right_index)| {
+            (
+                left.map_to_global(LocalWordPosition(left_index)),
+                right.map_to_global(LocalWordPosition(right_index)),
+            )
+        },
+    ));
+    collect_unchanged_words(
+        found_positions,
+        &left.narrowed(LocalWordPosition(left_region.end)..LocalWordPosition(left.ranges.len())),
+        &right.narrowed(LocalWordPosition(right_region.end)..LocalWordPosition(right.ranges.len())),
+        comp,
+        DiffAlgorithm::Histogram,
+    );
+}
+
+/// Finds the shortest edit script by Myers' linear-space algorithm.
+fn collect_unchanged_words_myers<C: CompareBytes, S: BuildHasher>(
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) {
+    let (common_leading_len, common_trailing_len) =
+        count_common_leading_trailing_words(left, right, comp);
+    push_common_leading_trailing_words(found_positions, left, right, common_leading_len, 0);
+    let left_end = LocalWordPosition(left.ranges.len() - common_trailing_len);
+    let right_end = LocalWordPosition(right.ranges.len() - common_trailing_len);
+    let left = left.narrowed(LocalWordPosition(common_leading_len)..left_end);
+    let right = right.narrowed(LocalWordPosition(common_leading_len)..right_end);
+    // After trimming, the edit distance is at least 2 unless one of the sides
+    // is empty. Therefore, the middle snake splits the problem into strictly
+    // smaller ones.
+    if !left.ranges.is_empty() && !right.ranges.is_empty() {
+        let left_words = left.hashed_words().collect_vec();
+        let right_words = right.hashed_words().collect_vec();
+        let (left_snake, right_snake) = find_middle_snake(&left_words, &right_words, comp);
+        collect_unchanged_words_myers(
+            found_positions,
+            &left.narrowed(LocalWordPosition(0)..LocalWordPosition(left_snake.start)),
+            &right.narrowed(LocalWordPosition(0)..LocalWordPosition(right_snake.start)),
+            comp,
+        );
+        found_positions.extend(iter::zip(left_snake.clone(), right_snake.clone()).map(
+            |(left_index, right_index)| {
+                (
+                    left.map_to_global(LocalWordPosition(left_index)),
+                    right.map_to_global(LocalWordPosition(right_index)),
+                )
+            },
+        ));
+        collect_unchanged_words_myers(
+            found_positions,
+            &left.narrowed(LocalWordPosition(left_snake.end)..LocalWordPosition(left.ranges.len())),
+            &right.narrowed(
+                LocalWordPosition(right_snake.end)..LocalWordPosition(right.ranges.len()),
+            ),
+            comp,
+        );
+    }
+    push_common_leading_trailing_words(found_positions, &left, &right, 0, common_trailing_len);
+}
+
+/// Finds the middle snake of the shortest edit script between `left` and
+/// `right`. Returns the ranges of matching words in the snake.
+///
+/// See "An O(ND) Difference Algorithm and Its Variations" by Eugene W. Myers.
+fn find_middle_snake<C: CompareBytes, S: BuildHasher>(
+    left: &[HashedWord],
+    right: &[HashedWord],
+    comp: &WordComparator<C, S>,
+) -> (Range<usize>, Range<usize>) {
+    let n = left.len() as isize;
+    let m = right.len() as isize;
+    let delta = n - m;
+    let max_d = (n + m + 1) / 2;
+    // Diagonal k is stored at index k + offset. Diagonals of the backward
+    // search are relative to the end of the inputs.
+    let offset = max_d + 1;
+    let mut forward = vec![0_isize; (2 * offset + 1) as usize];
+    let mut backward = vec![0_isize; (2 * offset + 1) as usize];
+    let at = |k: isize| (k + offset) as usize;
+    for d in 0..=max_d {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let start_x = x;
+            while x < n && x - k < m && comp.eq_hashed(left[x as usize], right[(x - k) as usize]) {
+                x += 1;
+            }
+            forward[at(k)] = x;
+            let backward_k = delta - k;
+            if delta % 2 != 0
+                && (-(d - 1)..=d - 1).contains(&backward_k)
+                && x + backward[at(backward_k)] >= n
+            {
+                let left_snake = start_x as usize..x as usize;
+                let right_snake = (start_x - k) as usize..(x - k) as usize;
+                return (left_snake, right_snake);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let start_x = x;
+            while x < n
+                && x - k < m
+                && comp.eq_hashed(
+                    left[(n - 1 - x) as usize],
+                    right[(m - 1 - (x - k)) as usize],
+                )
+            {
+                x += 1;
+            }
+            backward[at(k)] = x;
+            let forward_k = delta - k;
+            if delta % 2 == 0 && (-d..=d).contains(&forward_k) && x + forward[at(forward_k)] >= n {
+                let left_snake = (n - x) as usize..(n - start_x) as usize;
+                let right_snake = (m - (x - k)) as usize..(m - (start_x - k)) as usize;
+                return (left_snake, right_snake);
+            }
+        }
+    }
+    unreachable!("middle snake should be found within (n + m + 1) / 2 steps")
+}
+
 /// Intersects two sorted sequences of `(base, other)` word positions by
 /// `base`. `base` positions should refer to the same source text.
 fn intersect_unchanged_words(
@@ -612,6 +1003,17 @@ impl<'input> ContentDiff<'input> {
         inputs: impl IntoIterator<Item = &'input T>,
         tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
         compare: impl CompareBytes,
+    ) -> Self {
+        Self::for_tokenizer_with_algorithm(inputs, tokenizer, compare, DiffAlgorithm::Default)
+    }
+
+    /// Compares `inputs` tokenized by the `tokenizer` using the given
+    /// `algorithm`.
+    pub fn for_tokenizer_with_algorithm<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
+        compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         let mut inputs = inputs.into_iter().map(BStr::new);
         let base_input = inputs.next().expect("inputs must not be empty");
@@ -639,6 +1041,7 @@ impl<'input> ContentDiff<'input> {
             &base_token_ranges,
             &other_token_ranges,
             compare,
+            algorithm,
         )
     }
 
@@ -648,6 +1051,7 @@ impl<'input> ContentDiff<'input> {
         base_token_ranges: &[Range<usize>],
         other_token_ranges: &[Vec<Range<usize>>],
         compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         assert_eq!(other_inputs.len(), other_token_ranges.len());
         let comp = WordComparator::new(compare);
@@ -680,6 +1084,7 @@ impl<'input> ContentDiff<'input> {
                     &base_source.local(),
                     &first_other_source.local(),
                     &comp,
+                    algorithm,
                 );
                 if tail_other_sources.is_empty() {
                     unchanged_regions.extend(first_positions.iter().map(
@@ -706,6 +1111,7 @@ impl<'input> ContentDiff<'input> {
                                 &base_source.local(),
                                 &other_source.local(),
                                 &comp,
+                                algorithm,
                             );
                             intersect_unchanged_words(current_positions, &new_positions)
                         },
@@ -755,6 +1161,19 @@ impl<'input> ContentDiff<'input> {
         ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly)
     }
 
+    /// Compares `inputs` line by line using the given `algorithm`.
+    pub fn by_line_with_algorithm<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        algorithm: DiffAlgorithm,
+    ) -> Self {
+        ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesExactly,
+            algorithm,
+        )
+    }
+
     /// Compares `inputs` word by word.
     ///
     /// The `inputs` is usually a changed hunk (e.g. a `DiffHunk::Different`)
@@ -1082,6 +1501,15 @@ mod tests {
         assert_eq!(find_word_ranges("⊢".as_bytes()), vec![0..3]);
     }
 
+    #[test]
+    fn test_find_nonword_ranges_grouping_whitespace() {
+        assert_eq!(find_nonword_ranges_grouping_whitespace(b""), no_ranges());
+        assert_eq!(
+            find_nonword_ranges_grouping_whitespace(b"a  +-\t b \n"),
+            vec![1..3, 3..4, 4..5, 5..7, 8..10]
+        );
+    }
+
     #[test]
     fn test_find_lcs_empty() {
         let empty: Vec<(usize, usize)> = vec![];
@@ -1200,7 +1628,13 @@ mod tests {
         let left = DiffSource::new(left_text, left_ranges, &comp);
         let right = DiffSource::new(right_text, right_ranges, &comp);
         let mut positions = Vec::new();
-        collect_unchanged_words(&mut positions, &left.local(), &right.local(), &comp);
+        collect_unchanged_words(
+            &mut positions,
+            &left.local(),
+            &right.local(),
+            &comp,
+            DiffAlgorithm::Default,
+        );
         positions
             .into_iter()
             .map(|(left_pos, right_pos)| (left.range_at(left_pos), right.range_at(right_pos)))
@@ -1486,6 +1920,142 @@ mod tests {
         );
     }
 
+    fn diff_lines_with_algorithm(
+        left: &'static str,
+        right: &'static str,
+        algorithm: DiffAlgorithm,
+    ) -> Vec<DiffHunk<'static>> {
+        ContentDiff::by_line_with_algorithm([left, right], algorithm)
+            .hunks()
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_algorithm_no_unique_lines() {
+        // Example from Myers' paper. There are no unique lines, so the default
+        // algorithm finds nothing in common.
+        let left = "a\nb\nc\na\nb\nb\na\n";
+        let right = "c\nb\na\nb\na\nc\n";
+        assert_eq!(
+            diff_lines_with_algorithm(left, right, DiffAlgorithm::Default),
+            vec![DiffHunk::different([left, right])]
+        );
+        assert_eq!(
+            diff_lines_with_algorithm(left, right, DiffAlgorithm::Histogram),
+            vec![
+                DiffHunk::different(["a\nb\n", ""]),
+                DiffHunk::matching(["c\n"].repeat(2)),
+                DiffHunk::different(["a\nb\n", ""]),
+                DiffHunk::matching(["b\na\n"].repeat(2)),
+                DiffHunk::different(["", "b\na\nc\n"]),
+            ]
+        );
+        // Patience falls back to the minimal diff.
+        let minimal_hunks = vec![
+            DiffHunk::different(["a\n", "c\n"]),
+            DiffHunk::matching(["b\n"].repeat(2)),
+            DiffHunk::different(["c\n", ""]),
+            DiffHunk::matching(["a\nb\n"].repeat(2)),
+            DiffHunk::different(["b\n", ""]),
+            DiffHunk::matching(["a\n"].repeat(2)),
+            DiffHunk::different(["", "c\n"]),
+        ];
+        assert_eq!(
+            diff_lines_with_algorithm(left, right, DiffAlgorithm::Patience),
+            minimal_hunks
+        );
+        assert_eq!(
+            diff_lines_with_algorithm(left, right, DiffAlgorithm::Minimal),
+            minimal_hunks
+        );
+    }
+
+    #[test]
+    fn test_diff_algorithm_insert_function() {
+        let left = "void func1() {\n    x += 1\n}\n\nvoid func2() {\n    x += 2\n}\n";
+        let right = concat!(
+            "void func1() {\n    x += 1\n}\n\n",
+            "void functhreehalves() {\n    x += 1.5\n}\n\n",
+            "void func2() {\n    x += 2\n}\n",
+        );
+        for algorithm in [
+            DiffAlgorithm::Default,
+            DiffAlgorithm::Histogram,
+            DiffAlgorithm::Patience,
+            DiffAlgorithm::Minimal,
+        ] {
+            assert_eq!(
+                diff_lines_with_algorithm(left, right, algorithm),
+                vec![
+                    DiffHunk::matching(["void func1() {\n    x += 1\n}\n\n"].repeat(2)),
+                    DiffHunk::different(["", "void functhreehalves() {\n    x += 1.5\n}\n\n"]),
+                    DiffHunk::matching(["void func2() {\n    x += 2\n}\n"].repeat(2)),
+                ],
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_algorithm_random_inputs() {
+        // Simple LCG to generate inputs deterministically
+        let mut seed: u32 = 1;
+        let mut next_random = move |max: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) % max
+        };
+        let mut random_lines = || {
+            let len = next_random(12);
+            (0..len)
+                .map(|_| ["a\n", "b\n", "c\n", "d\n"][next_random(4) as usize])
+                .collect::<String>()
+        };
+        let lcs_len = |left: &[u8], right: &[u8]| {
+            let mut table = vec![vec![0; right.len() + 1]; left.len() + 1];
+            for (i, l) in left.iter().enumerate() {
+                for (j, r) in right.iter().enumerate() {
+                    table[i + 1][j + 1] = if l == r {
+                        table[i][j] + 1
+                    } else {
+                        table[i][j + 1].max(table[i + 1][j])
+                    };
+                }
+            }
+            table[left.len()][right.len()]
+        };
+        for _ in 0..500 {
+            let left = random_lines();
+            let right = random_lines();
+            for algorithm in [
+                DiffAlgorithm::Default,
+                DiffAlgorithm::Histogram,
+                DiffAlgorithm::Patience,
+                DiffAlgorithm::Minimal,
+            ] {
+                let diff = ContentDiff::by_line_with_algorithm([&left, &right], algorithm);
+                let hunks = diff.hunks().collect_vec();
+                let joined_left = hunks.iter().map(|h| h.contents[0]).join("");
+                let joined_right = hunks.iter().map(|h| h.contents[1]).join("");
+                assert_eq!(joined_left, left, "{algorithm:?}");
+                assert_eq!(joined_right, right, "{algorithm:?}");
+                if algorithm == DiffAlgorithm::Minimal {
+                    let num_matching_lines: usize = hunks
+                        .iter()
+                        .filter(|h| h.kind == DiffHunkKind::Matching)
+                        .map(|h| h.contents[0].len() / 2)
+                        .sum();
+                    let left_lines = left.as_bytes().chunks(2).map(|c| c[0]).collect_vec();
+                    let right_lines = right.as_bytes().chunks(2).map(|c| c[0]).collect_vec();
+                    assert_eq!(
+                        num_matching_lines,
+                        lcs_len(&left_lines, &right_lines),
+                        "{left:?} {right:?}"
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_diff_ignore_all_whitespace() {
         fn diff(inputs: [&str; 2]) -> Vec<DiffHunk<'_>> {
@@ -18,9 +18,11 @@
 use std::cmp;
 use std::collections::HashMap;
 use std::ops::Range;
+use std::slice;
 use std::sync::Arc;
 
 use bstr::BString;
+use bstr::ByteSlice as _;
 use futures::StreamExt as _;
 use itertools::Itertools as _;
 use thiserror::Error;
@@ -29,6 +31,7 @@ use crate::annotate::FileAnnotator;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CommitId;
+use crate::backend::CopyId;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::commit::conflict_label_for_commits;
@@ -38,6 +41,8 @@ use crate::conflicts::materialized_diff_stream;
 use crate::copies::CopyRecords;
 use crate::diff::ContentDiff;
 use crate::diff::DiffHunkKind;
+use crate::files;
+use crate::files::MergeResult;
 use crate::matchers::Matcher;
 use crate::merge::Diff;
 use crate::merge::Merge;
@@ -91,6 +96,14 @@ pub enum AbsorbError {
     /// Error resolving commit ancestry.
     #[error(transparent)]
     RevsetEvaluation(#[from] RevsetEvaluationError),
+    /// The hunk isn't ambiguous, or the commit isn't one of its candidates.
+    #[error("Hunk {hunk_index} cannot be absorbed into commit {commit_id}")]
+    InvalidDestination {
+        /// Index of the hunk in [`SelectedTrees::hunks`].
+        hunk_index: usize,
+        /// The requested destination commit.
+        commit_id: CommitId,
+    },
 }
 
 /// An absorb 'plan' indicating which commits should be modified and what they
@@ -101,6 +114,166 @@ pub struct SelectedTrees {
     pub target_commits: HashMap<CommitId, MergedTreeBuilder>,
     /// Paths that were not absorbed for various error reasons.
     pub skipped_paths: Vec<(RepoPathBuf, String)>,
+    /// Hunks of the source changes and their destinations, in path order.
+    pub hunks: Vec<AbsorbHunk>,
+    files: Vec<SourceFile>,
+}
+
+impl SelectedTrees {
+    /// Returns the candidates of the ambiguous hunk at `hunk_index` to which
+    /// the hunk applies without conflicts.
+    pub async fn clean_candidates(
+        &self,
+        repo: &dyn Repo,
+        hunk_index: usize,
+    ) -> BackendResult<Vec<CommitId>> {
+        let hunk = &self.hunks[hunk_index];
+        let (AbsorbHunkDestination::Ambiguous(candidates), Some((file_index, selected_range))) =
+            (&hunk.destination, &hunk.selection)
+        else {
+            return Ok(vec![]);
+        };
+        let file = &self.files[*file_index];
+        let hunk_text = combine_texts(
+            &file.left_text,
+            &file.right_text,
+            slice::from_ref(selected_range),
+        );
+        let mut clean_candidates = vec![];
+        for commit_id in candidates {
+            let commit = repo.store().get_commit_async(commit_id).await?;
+            let value = commit.tree().path_value(&file.path).await?;
+            let Ok(Some(TreeValue::File { id, .. })) = value.into_resolved() else {
+                continue;
+            };
+            let destination_text = repo.store().read_file_contents(&file.path, &id).await?;
+            let contents = Merge::from_vec(vec![
+                destination_text.as_slice(),
+                file.left_text.as_slice(),
+                hunk_text.as_slice(),
+            ]);
+            let options = repo.store().merge_options();
+            if let MergeResult::Resolved(_) = files::merge_hunks(&contents, options) {
+                clean_candidates.push(commit_id.clone());
+            }
+        }
+        Ok(clean_candidates)
+    }
+
+    /// Absorbs the ambiguous hunk at `hunk_index` into the given commit,
+    /// which must be one of the candidates of the hunk.
+    pub async fn absorb_hunk(
+        &mut self,
+        repo: &dyn Repo,
+        source: &AbsorbSource,
+        hunk_index: usize,
+        commit_id: &CommitId,
+    ) -> Result<(), AbsorbError> {
+        let hunk = &mut self.hunks[hunk_index];
+        let (AbsorbHunkDestination::Ambiguous(candidates), Some((file_index, selected_range))) =
+            (&hunk.destination, hunk.selection.clone())
+        else {
+            return Err(AbsorbError::InvalidDestination {
+                hunk_index,
+                commit_id: commit_id.clone(),
+            });
+        };
+        if !candidates.contains(commit_id) {
+            return Err(AbsorbError::InvalidDestination {
+                hunk_index,
+                commit_id: commit_id.clone(),
+            });
+        }
+        hunk.destination = AbsorbHunkDestination::Commits(vec![commit_id.clone()]);
+        let file = &mut self.files[file_index];
+        let ranges = file.selected_ranges.entry(commit_id.clone()).or_default();
+        let index = ranges.partition_point(|(range, _)| range.start < selected_range.0.start);
+        ranges.insert(index, selected_range);
+        let tree_builder = self
+            .target_commits
+            .entry(commit_id.clone())
+            .or_insert_with(|| MergedTreeBuilder::new(source.parent_tree.clone()));
+        file.write_selected(repo, tree_builder, commit_id).await?;
+        Ok(())
+    }
+}
+
+/// Hunk of the source changes.
+#[derive(Clone, Debug)]
+pub struct AbsorbHunk {
+    /// Path of the file containing the hunk.
+    pub path: RepoPathBuf,
+    /// Zero-based line range of the hunk in the parent (= left) content.
+    pub left_lines: Range<usize>,
+    /// Zero-based line range of the hunk in the source (= right) content.
+    pub right_lines: Range<usize>,
+    /// Lines removed by the hunk.
+    pub left_text: BString,
+    /// Lines added by the hunk.
+    pub right_text: BString,
+    /// Where the hunk is absorbed, or why it isn't.
+    pub destination: AbsorbHunkDestination,
+    // Index of the source file and ranges of the hunk in the file contents.
+    selection: Option<(usize, SelectedRange)>,
+}
+
+/// Destination of an [`AbsorbHunk`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AbsorbHunkDestination {
+    /// The hunk is absorbed into the commits. Deleted lines may be absorbed
+    /// into multiple commits.
+    Commits(Vec<CommitId>),
+    /// The file is added by the source commit. The line ranges and texts of
+    /// the hunk are empty since the file contents aren't read.
+    NewFile,
+    /// The changed lines weren't modified by any of the destination commits.
+    NotInDestinations,
+    /// The changed lines are modified by, or adjacent to lines modified by,
+    /// multiple destination commits.
+    Ambiguous(Vec<CommitId>),
+}
+
+/// File changed by the source commit.
+#[derive(Clone, Debug)]
+struct SourceFile {
+    path: RepoPathBuf,
+    left_text: Vec<u8>,
+    right_text: Vec<u8>,
+    executable: bool,
+    copy_id: CopyId,
+    deleted: bool,
+    selected_ranges: HashMap<CommitId, Vec<SelectedRange>>,
+}
+
+impl SourceFile {
+    /// Sets the file contents with the hunks selected for the commit.
+    async fn write_selected(
+        &self,
+        repo: &dyn Repo,
+        tree_builder: &mut MergedTreeBuilder,
+        commit_id: &CommitId,
+    ) -> BackendResult<()> {
+        let ranges = &self.selected_ranges[commit_id];
+        let new_text = combine_texts(&self.left_text, &self.right_text, ranges);
+        // Since changes to be absorbed are represented as diffs relative to
+        // the source parent, we can propagate file deletion only if the
+        // whole file content is deleted at a single destination commit.
+        let new_tree_value = if new_text.is_empty() && self.deleted {
+            Merge::absent()
+        } else {
+            let id = repo
+                .store()
+                .write_file(&self.path, &mut new_text.as_slice())
+                .await?;
+            Merge::normal(TreeValue::File {
+                id,
+                executable: self.executable,
+                copy_id: self.copy_id.clone(),
+            })
+        };
+        tree_builder.set_or_remove(self.path.clone(), new_tree_value);
+        Ok(())
+    }
 }
 
 /// Builds trees to be merged into destination commits by splitting source
@@ -133,8 +306,22 @@ pub async fn split_hunks_to_trees(
                 value.executable,
                 value.copy_id,
             ),
-            // New file should have no destinations
-            Ok(None) => continue,
+            // New file should have no destinations. Its contents aren't read
+            // since they would never be absorbed.
+            Ok(None) => {
+                if let Ok(Some(_)) = to_file_value(values.after) {
+                    selected_trees.hunks.push(AbsorbHunk {
+                        path: right_path.to_owned(),
+                        left_lines: 0..0,
+                        right_lines: 0..0,
+                        left_text: BString::default(),
+                        right_text: BString::default(),
+                        destination: AbsorbHunkDestination::NewFile,
+                        selection: None,
+                    });
+                }
+                continue;
+            }
             Err(reason) => {
                 selected_trees
                     .skipped_paths
@@ -162,33 +349,64 @@ pub async fn split_hunks_to_trees(
             .compact_line_ranges()
             .filter_map(|(commit_id, range)| Some((commit_id.ok()?, range)))
             .collect_vec();
-        let diff = ContentDiff::by_line([&left_text, &right_text]);
-        let selected_ranges = split_file_hunks(&annotation_ranges, &diff);
+        let diff = ContentDiff::by_line_with_algorithm(
+            [&left_text, &right_text],
+            repo.store().merge_options().diff_algorithm,
+        );
+        let hunks = classify_file_hunks(&annotation_ranges, &diff);
+        drop(diff);
+        let mut file = SourceFile {
+            path: left_path.to_owned(),
+            left_text,
+            right_text,
+            executable,
+            copy_id,
+            deleted,
+            selected_ranges: HashMap::new(),
+        };
+        let file_index = selected_trees.files.len();
+        for (ranges, selection) in hunks {
+            let destination = match selection {
+                HunkSelection::Selected(selected) => {
+                    for &(commit_id, ref range) in &selected {
+                        file.selected_ranges
+                            .entry(commit_id.clone())
+                            .or_default()
+                            .push(range.clone());
+                    }
+                    AbsorbHunkDestination::Commits(
+                        selected
+                            .into_iter()
+                            .map(|(commit_id, _)| commit_id.clone())
+                            .unique()
+                            .collect(),
+                    )
+                }
+                HunkSelection::NotInDestinations => AbsorbHunkDestination::NotInDestinations,
+                HunkSelection::Ambiguous(candidates) => {
+                    AbsorbHunkDestination::Ambiguous(candidates.into_iter().cloned().collect())
+                }
+            };
+            let (left_range, right_range) = &ranges;
+            selected_trees.hunks.push(AbsorbHunk {
+                path: left_path.to_owned(),
+                left_lines: line_range(&file.left_text, left_range),
+                right_lines: line_range(&file.right_text, right_range),
+                left_text: file.left_text[left_range.clone()].into(),
+                right_text: file.right_text[right_range.clone()].into(),
+                destination,
+                selection: Some((file_index, ranges)),
+            });
+        }
         // Build trees containing parent (= left) contents + selected hunks
-        for (&commit_id, ranges) in &selected_ranges {
+        for commit_id in file.selected_ranges.keys() {
             let tree_builder = selected_trees
                 .target_commits
                 .entry(commit_id.clone())
                 .or_insert_with(|| MergedTreeBuilder::new(left_tree.clone()));
-            let new_text = combine_texts(&left_text, &right_text, ranges);
-            // Since changes to be absorbed are represented as diffs relative to
-            // the source parent, we can propagate file deletion only if the
-            // whole file content is deleted at a single destination commit.
-            let new_tree_value = if new_text.is_empty() && deleted {
-                Merge::absent()
-            } else {
-                let id = repo
-                    .store()
-                    .write_file(left_path, &mut new_text.as_slice())
-                    .await?;
-                Merge::normal(TreeValue::File {
-                    id,
-                    executable,
-                    copy_id: copy_id.clone(),
-                })
-            };
-            tree_builder.set_or_remove(left_path.to_owned(), new_tree_value);
+            file.write_selected(repo, tree_builder, commit_id).await?;
         }
+        selected_trees.files.push(file);
     }
 
     Ok(selected_trees)
@@ -198,21 +416,50 @@ type SelectedRange = (Range<usize>, Range<usize>);
 
 /// Maps `diff` hunks to commits based on the left `annotation_ranges`. The
 /// `annotation_ranges` should be compacted.
+#[cfg(test)]
 fn split_file_hunks<'a>(
-    mut annotation_ranges: &[(&'a CommitId, Range<usize>)],
+    annotation_ranges: &[(&'a CommitId, Range<usize>)],
     diff: &ContentDiff,
 ) -> HashMap<&'a CommitId, Vec<SelectedRange>> {
-    debug_assert!(annotation_ranges.iter().all(|(_, range)| !range.is_empty()));
     let mut selected_ranges: HashMap<&CommitId, Vec<_>> = HashMap::new();
-    let mut diff_hunk_ranges = diff
+    for (_, selection) in classify_file_hunks(annotation_ranges, diff) {
+        if let HunkSelection::Selected(selected) = selection {
+            for (commit_id, range) in selected {
+                selected_ranges.entry(commit_id).or_default().push(range);
+            }
+        }
+    }
+    selected_ranges
+}
+
+/// Destination of a hunk determined by [`classify_file_hunks()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum HunkSelection<'a> {
+    /// Ranges of the hunk to be absorbed into each commit.
+    Selected(Vec<(&'a CommitId, SelectedRange)>),
+    /// No annotation range overlaps with the hunk.
+    NotInDestinations,
+    /// The hunk overlaps with or is adjacent to annotation ranges of the
+    /// candidate commits, but can't be mapped unambiguously.
+    Ambiguous(Vec<&'a CommitId>),
+}
+
+/// Maps each of the `diff` hunks to commits based on the left
+/// `annotation_ranges`. The `annotation_ranges` should be compacted.
+fn classify_file_hunks<'a>(
+    mut annotation_ranges: &[(&'a CommitId, Range<usize>)],
+    diff: &ContentDiff,
+) -> Vec<(SelectedRange, HunkSelection<'a>)> {
+    debug_assert!(annotation_ranges.iter().all(|(_, range)| !range.is_empty()));
+    let mut hunks = vec![];
+    let diff_hunk_ranges = diff
         .hunk_ranges()
         .filter(|hunk| hunk.kind == DiffHunkKind::Different);
-    while !annotation_ranges.is_empty() {
-        let Some(hunk) = diff_hunk_ranges.next() else {
-            break;
-        };
+    for hunk in diff_hunk_ranges {
         let [left_range, right_range]: &[_; 2] = hunk.ranges[..].try_into().unwrap();
         assert!(!left_range.is_empty() || !right_range.is_empty());
+        let candidates = overlapped_commits(annotation_ranges, left_range);
+        let mut selected = vec![];
         if right_range.is_empty() {
             // If the hunk is pure deletion, it can be mapped to multiple
             // overlapped annotation ranges unambiguously.
@@ -227,24 +474,22 @@ fn split_file_hunks<'a>(
                 .count();
             let maybe_overlapped_ranges = annotation_ranges.get(..pre_overlap + 1);
             annotation_ranges = &annotation_ranges[pre_overlap..];
-            let Some(overlapped_ranges) = maybe_overlapped_ranges else {
-                continue;
-            };
-            // Ensure that the ranges are contiguous and include the start.
-            let all_covered = overlapped_ranges
-                .iter()
-                .try_fold(left_range.start, |prev_end, (_, cur)| {
-                    (cur.start <= prev_end).then_some(cur.end)
-                })
-                .inspect(|&last_end| assert!(left_range.end <= last_end))
-                .is_some();
-            if all_covered {
-                for (commit_id, cur_range) in overlapped_ranges {
-                    let start = cmp::max(cur_range.start, left_range.start);
-                    let end = cmp::min(cur_range.end, left_range.end);
-                    assert!(start < end);
-                    let selected = selected_ranges.entry(commit_id).or_default();
-                    selected.push((start..end, right_range.clone()));
+            if let Some(overlapped_ranges) = maybe_overlapped_ranges {
+                // Ensure that the ranges are contiguous and include the start.
+                let all_covered = overlapped_ranges
+                    .iter()
+                    .try_fold(left_range.start, |prev_end, (_, cur)| {
+                        (cur.start <= prev_end).then_some(cur.end)
+                    })
+                    .inspect(|&last_end| assert!(left_range.end <= last_end))
+                    .is_some();
+                if all_covered {
+                    for (commit_id, cur_range) in overlapped_ranges {
+                        let start = cmp::max(cur_range.start, left_range.start);
+                        let end = cmp::min(cur_range.end, left_range.end);
+                        assert!(start < end);
+                        selected.push((*commit_id, (start..end, right_range.clone())));
+                    }
                 }
             }
         } else {
@@ -255,23 +500,59 @@ fn split_file_hunks<'a>(
                 .take_while(|(_, range)| range.end < left_range.end)
                 .count();
             annotation_ranges = &annotation_ranges[skip..];
-            let Some((commit_id, cur_range)) = annotation_ranges.first() else {
-                continue;
-            };
-            let contained = cur_range.start <= left_range.start && left_range.end <= cur_range.end;
-            // If the hunk is pure insertion, it can be mapped to two distinct
-            // annotation ranges, which is ambiguous.
-            let ambiguous = cur_range.end == left_range.start
-                && annotation_ranges
-                    .get(1)
-                    .is_some_and(|(_, next_range)| next_range.start == left_range.end);
-            if contained && !ambiguous {
-                let selected = selected_ranges.entry(commit_id).or_default();
-                selected.push((left_range.clone(), right_range.clone()));
+            if let Some((commit_id, cur_range)) = annotation_ranges.first() {
+                let contained =
+                    cur_range.start <= left_range.start && left_range.end <= cur_range.end;
+                // If the hunk is pure insertion, it can be mapped to two distinct
+                // annotation ranges, which is ambiguous.
+                let ambiguous = cur_range.end == left_range.start
+                    && annotation_ranges
+                        .get(1)
+                        .is_some_and(|(_, next_range)| next_range.start == left_range.end);
+                if contained && !ambiguous {
+                    selected.push((*commit_id, (left_range.clone(), right_range.clone())));
+                }
             }
         }
+        let selection = if !selected.is_empty() {
+            HunkSelection::Selected(selected)
+        } else if candidates.is_empty() {
+            HunkSelection::NotInDestinations
+        } else {
+            HunkSelection::Ambiguous(candidates)
+        };
+        hunks.push(((left_range.clone(), right_range.clone()), selection));
     }
-    selected_ranges
+    hunks
+}
+
+/// Returns commits of the annotation ranges overlapping with the
+/// `left_range`. If the range is empty (i.e. pure insertion), the adjacent
+/// annotation ranges are included.
+fn overlapped_commits<'a>(
+    annotation_ranges: &[(&'a CommitId, Range<usize>)],
+    left_range: &Range<usize>,
+) -> Vec<&'a CommitId> {
+    annotation_ranges
+        .iter()
+        .filter(|(_, range)| {
+            if left_range.is_empty() {
+                range.start <= left_range.start && left_range.end <= range.end
+            } else {
+                range.start < left_range.end && left_range.start < range.end
+            }
+        })
+        .map(|(commit_id, _)| *commit_id)
+        .unique()
+        .collect()
+}
+
+/// Converts the byte `range` of the `text` to zero-based line range.
+fn line_range(text: &[u8], range: &Range<usize>) -> Range<usize> {
+    let start = text[..range.start].find_iter("\n").count();
+    let lines = &text[range.clone()];
+    let incomplete_line = !lines.is_empty() && !lines.ends_with(b"\n");
+    start..start + lines.find_iter("\n").count() + usize::from(incomplete_line)
 }
 
 /// Constructs new text by replacing `text1` range with `text2` range for each
@@ -1134,6 +1415,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_classify_file_hunks() {
+        let commit_id1 = &CommitId::from_hex("111111");
+        let commit_id2 = &CommitId::from_hex("222222");
+
+        assert_eq!(
+            classify_file_hunks(
+                &[(commit_id1, 0..6), /* 6..9, */ (commit_id2, 9..15)],
+                &ContentDiff::by_line(["1a\n1b\n0a\n2a\n2b\n", "1A\n1b\n0A\n2a\n2X\n2b\n3X\n"])
+            ),
+            vec![
+                // modify line in first range
+                (
+                    (0..3, 0..3),
+                    HunkSelection::Selected(vec![(commit_id1, (0..3, 0..3))])
+                ),
+                // modify masked line
+                ((6..9, 6..9), HunkSelection::NotInDestinations),
+                // insert line in second range
+                (
+                    (12..12, 12..15),
+                    HunkSelection::Selected(vec![(commit_id2, (12..12, 12..15))])
+                ),
+                // append line to second range
+                (
+                    (15..15, 18..21),
+                    HunkSelection::Selected(vec![(commit_id2, (15..15, 18..21))])
+                ),
+            ]
+        );
+
+        // insert line between contiguous ranges (ambiguous)
+        assert_eq!(
+            classify_file_hunks(
+                &[(commit_id1, 0..3), (commit_id2, 3..6)],
+                &ContentDiff::by_line(["1a\n2a\n", "1a\nX\n2a\n"])
+            ),
+            vec![(
+                (3..3, 3..5),
+                HunkSelection::Ambiguous(vec![commit_id1, commit_id2])
+            )]
+        );
+        // modify lines across contiguous ranges (ambiguous)
+        assert_eq!(
+            classify_file_hunks(
+                &[(commit_id1, 0..3), (commit_id2, 3..6)],
+                &ContentDiff::by_line(["1a\n2a\n", "1A\n2A\n"])
+            ),
+            vec![(
+                (0..6, 0..6),
+                HunkSelection::Ambiguous(vec![commit_id1, commit_id2])
+            )]
+        );
+    }
+
+    #[test]
+    fn test_line_range() {
+        let text = b"1\n2\n3";
+        assert_eq!(line_range(text, &(0..0)), 0..0);
+        assert_eq!(line_range(text, &(0..2)), 0..1);
+        assert_eq!(line_range(text, &(2..4)), 1..2);
+        assert_eq!(line_range(text, &(4..4)), 2..2);
+        assert_eq!(line_range(text, &(2..5)), 1..3);
+    }
+
     #[test]
     fn test_combine_texts() {
         assert_eq!(combine_texts(b"", b"", &[]), "");
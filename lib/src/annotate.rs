@@ -39,11 +39,13 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::conflicts::materialize_tree_value;
 use crate::diff::ContentDiff;
+use crate::diff::DiffAlgorithm;
 use crate::diff::DiffHunkKind;
 use crate::files::FileMergeHunkLevel;
 use crate::fileset::FilesetExpression;
 use crate::graph::GraphEdge;
 use crate::merge::SameChange;
+use crate::merge_driver::MergeDrivers;
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
 use crate::repo_path::RepoPath;
@@ -363,6 +365,7 @@ async fn process_commit(
         copy_same_lines_with(
             &current_source.text,
             &parent_source.text,
+            repo.store().merge_options().diff_algorithm,
             |current_start, parent_start, count| {
                 new_current_line_map
                     .extend(current_lines.peeking_take_while(|&(cur, _)| cur < current_start));
@@ -415,9 +418,10 @@ async fn process_commit(
 fn copy_same_lines_with(
     current_contents: &[u8],
     parent_contents: &[u8],
+    algorithm: DiffAlgorithm,
     mut copy: impl FnMut(usize, usize, usize),
 ) {
-    let diff = ContentDiff::by_line([current_contents, parent_contents]);
+    let diff = ContentDiff::by_line_with_algorithm([current_contents, parent_contents], algorithm);
     let mut current_line_counter: usize = 0;
     let mut parent_line_counter: usize = 0;
     for hunk in diff.hunks() {
@@ -465,6 +469,9 @@ async fn get_file_contents(
                 merge: MergeOptions {
                     hunk_level: FileMergeHunkLevel::Line,
                     same_change: SameChange::Accept,
+                    diff_algorithm: store.merge_options().diff_algorithm,
+                    drivers: MergeDrivers::default(),
+                    rerere: None,
                 },
             };
             Ok(materialize_merge_result_to_bytes(
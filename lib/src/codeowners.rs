@@ -0,0 +1,421 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser and matcher of `CODEOWNERS` files.
+//!
+//! Both GitHub and GitLab flavors are supported. In GitLab syntax, rules can
+//! be grouped by `[Section]` headers, and the owners of a file are collected
+//! from all sections.
+
+use bstr::ByteSlice as _;
+use globset::GlobBuilder;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use itertools::Itertools as _;
+
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+
+/// Locations where `CODEOWNERS` file is looked up, in order of precedence.
+pub const CODEOWNERS_PATHS: &[&str] = &[
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+/// Parsed `CODEOWNERS` file.
+#[derive(Clone, Debug, Default)]
+pub struct CodeOwners {
+    sections: Vec<CodeOwnersSection>,
+}
+
+#[derive(Clone, Debug)]
+struct CodeOwnersSection {
+    name: Option<String>,
+    rules: Vec<CodeOwnersRule>,
+    // Globs of all rules, and the index of the rule each glob belongs to.
+    globs: GlobSet,
+    glob_rule_indices: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct CodeOwnersRule {
+    /// Owners of the matched files. Empty if the files are explicitly unowned.
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// Creates empty rules which own nothing.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Parses `CODEOWNERS` file content.
+    ///
+    /// Lines that can't be parsed are skipped as GitHub does.
+    pub fn parse(input: &[u8]) -> Self {
+        let mut builders: Vec<SectionBuilder> = vec![SectionBuilder::new(None, vec![])];
+        let mut current = 0;
+        for line in input.lines() {
+            let Ok(line) = line.to_str() else {
+                continue;
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, default_owners)) = parse_section_header(line) {
+                // Sections of the same name are merged.
+                current = if let Some(index) = builders.iter().position(|builder| {
+                    builder
+                        .name
+                        .as_ref()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(&name))
+                }) {
+                    builders[index].default_owners = default_owners;
+                    index
+                } else {
+                    builders.push(SectionBuilder::new(Some(name), default_owners));
+                    builders.len() - 1
+                };
+                continue;
+            }
+            let (pattern, owners) = split_pattern(line);
+            builders[current].add_rule(pattern, parse_owners(owners));
+        }
+        let sections = builders
+            .into_iter()
+            .filter(|builder| !builder.rules.is_empty())
+            .filter_map(|builder| builder.build())
+            .collect();
+        Self { sections }
+    }
+
+    /// Loads `CODEOWNERS` file from the `tree`.
+    ///
+    /// The first resolved file found in [`CODEOWNERS_PATHS`] is used. Returns
+    /// empty rules if there's no such file.
+    pub async fn load(tree: &MergedTree) -> BackendResult<Self> {
+        for path in CODEOWNERS_PATHS {
+            let path = RepoPathBuf::from_internal_string(*path).unwrap();
+            if let Some(Some(TreeValue::File { id, .. })) =
+                tree.path_value(&path).await?.as_resolved()
+            {
+                let content = tree.store().read_file_contents(&path, id).await?;
+                return Ok(Self::parse(&content));
+            }
+        }
+        Ok(Self::empty())
+    }
+
+    /// Returns true if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Returns owners of the file `path`.
+    ///
+    /// Within each section, the last matching rule takes precedence. Owners
+    /// found in multiple sections are combined in order of first occurrence.
+    pub fn owners(&self, path: &RepoPath) -> Vec<&str> {
+        let path = path.as_internal_file_string();
+        let mut owners: Vec<&str> = Vec::new();
+        for section in &self.sections {
+            let Some(rule) = section.matched_rule(path) else {
+                continue;
+            };
+            for owner in &rule.owners {
+                if !owners.contains(&owner.as_str()) {
+                    owners.push(owner);
+                }
+            }
+        }
+        owners
+    }
+
+    /// Returns true if the file `path` is owned by an owner satisfying the
+    /// `predicate`.
+    pub fn is_owned_by(&self, path: &RepoPath, predicate: impl Fn(&str) -> bool) -> bool {
+        self.owners(path).into_iter().any(predicate)
+    }
+
+    /// Returns section names. The unnamed (GitHub-style) section is omitted.
+    pub fn section_names(&self) -> impl Iterator<Item = &str> {
+        self.sections
+            .iter()
+            .filter_map(|section| section.name.as_deref())
+    }
+}
+
+impl CodeOwnersSection {
+    fn matched_rule(&self, path: &str) -> Option<&CodeOwnersRule> {
+        let index = self
+            .globs
+            .matches(path)
+            .into_iter()
+            .map(|glob_index| self.glob_rule_indices[glob_index])
+            .max()?;
+        Some(&self.rules[index])
+    }
+}
+
+struct SectionBuilder {
+    name: Option<String>,
+    default_owners: Vec<String>,
+    rules: Vec<CodeOwnersRule>,
+    globs: GlobSetBuilder,
+    glob_rule_indices: Vec<usize>,
+}
+
+impl SectionBuilder {
+    fn new(name: Option<String>, default_owners: Vec<String>) -> Self {
+        Self {
+            name,
+            default_owners,
+            rules: vec![],
+            globs: GlobSetBuilder::new(),
+            glob_rule_indices: vec![],
+        }
+    }
+
+    fn add_rule(&mut self, pattern: &str, owners: Vec<String>) {
+        let Some(globs) = pattern_to_globs(pattern) else {
+            return;
+        };
+        // Rules without explicit owners inherit the section defaults.
+        let owners = if owners.is_empty() {
+            self.default_owners.clone()
+        } else {
+            owners
+        };
+        let index = self.rules.len();
+        for glob in globs {
+            self.globs.add(glob);
+            self.glob_rule_indices.push(index);
+        }
+        self.rules.push(CodeOwnersRule { owners });
+    }
+
+    fn build(self) -> Option<CodeOwnersSection> {
+        Some(CodeOwnersSection {
+            name: self.name,
+            rules: self.rules,
+            globs: self.globs.build().ok()?,
+            glob_rule_indices: self.glob_rule_indices,
+        })
+    }
+}
+
+/// Parses GitLab section header such as `^[Section name][2] @owner`.
+fn parse_section_header(line: &str) -> Option<(String, Vec<String>)> {
+    let rest = line.strip_prefix('^').unwrap_or(line);
+    let (name, rest) = rest.strip_prefix('[')?.split_once(']')?;
+    // Optional number of required approvals
+    let rest = rest
+        .strip_prefix('[')
+        .and_then(|rest| {
+            let (count, rest) = rest.split_once(']')?;
+            count.chars().all(|c| c.is_ascii_digit()).then_some(rest)
+        })
+        .unwrap_or(rest);
+    let name = name.trim();
+    if name.is_empty() || !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+    Some((name.to_owned(), parse_owners(rest)))
+}
+
+/// Splits line into pattern and owners at the first unescaped whitespace.
+fn split_pattern(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c.is_whitespace() {
+            return (&line[..i], &line[i..]);
+        }
+    }
+    (line, "")
+}
+
+fn parse_owners(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .take_while(|owner| !owner.starts_with('#'))
+        .map(str::to_owned)
+        .unique()
+        .collect()
+}
+
+/// Translates gitignore-like `pattern` into globs matching repository paths.
+fn pattern_to_globs(pattern: &str) -> Option<Vec<globset::Glob>> {
+    if pattern.starts_with('!') {
+        return None; // negation isn't supported
+    }
+    let (pattern, dir_only) = match pattern.strip_suffix('/') {
+        Some(dir) => (dir, true),
+        None => (pattern, false),
+    };
+    let (pattern, anchored) = match pattern.strip_prefix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, pattern.contains('/')),
+    };
+    let base = match (pattern, anchored) {
+        ("", _) => return Some(vec![build_glob("**")?]), // "/" matches everything
+        (_, true) => pattern.to_owned(),
+        (_, false) => format!("**/{pattern}"),
+    };
+    let mut globs = vec![];
+    if !dir_only {
+        globs.push(build_glob(&base)?);
+    }
+    // Files in matching directory are also owned, except for "dir/*" which
+    // only matches direct children.
+    let direct_children_only = pattern == "*" || pattern.ends_with("/*");
+    if dir_only || !direct_children_only {
+        globs.push(build_glob(&format!("{base}/**"))?);
+    }
+    Some(globs)
+}
+
+fn build_glob(pattern: &str) -> Option<globset::Glob> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .backslash_escape(true)
+        .build()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_path(value: &str) -> &RepoPath {
+        RepoPath::from_internal_string(value).unwrap()
+    }
+
+    fn owners<'a>(codeowners: &'a CodeOwners, path: &str) -> Vec<&'a str> {
+        codeowners.owners(repo_path(path))
+    }
+
+    #[test]
+    fn test_codeowners_empty() {
+        let codeowners = CodeOwners::parse(b"");
+        assert!(codeowners.is_empty());
+        assert!(owners(&codeowners, "foo").is_empty());
+
+        let codeowners = CodeOwners::parse(b"# comment\n\n");
+        assert!(codeowners.is_empty());
+    }
+
+    #[test]
+    fn test_codeowners_last_match_wins() {
+        let codeowners = CodeOwners::parse(
+            b"\
+* @global-owner1 @global-owner2
+*.js @js-owner # inline comment
+/build/logs/ @doctocat
+docs/* docs@example.com
+apps/ @octocat
+/scripts/ @doctocat @octocat
+/apps/github
+",
+        );
+        assert_eq!(
+            owners(&codeowners, "README.md"),
+            ["@global-owner1", "@global-owner2"]
+        );
+        assert_eq!(owners(&codeowners, "src/main.js"), ["@js-owner"]);
+        assert_eq!(owners(&codeowners, "build/logs/out.txt"), ["@doctocat"]);
+        assert_eq!(owners(&codeowners, "build/logs/a/b.js"), ["@doctocat"]);
+        assert_eq!(
+            owners(&codeowners, "sub/build/logs/out.txt"),
+            ["@global-owner1", "@global-owner2"]
+        );
+        // "docs/*" doesn't match nested files
+        assert_eq!(owners(&codeowners, "docs/intro.md"), ["docs@example.com"]);
+        assert_eq!(
+            owners(&codeowners, "docs/build/intro.md"),
+            ["@global-owner1", "@global-owner2"]
+        );
+        // "apps/" matches directory at any level
+        assert_eq!(owners(&codeowners, "apps/foo"), ["@octocat"]);
+        assert_eq!(owners(&codeowners, "src/apps/foo"), ["@octocat"]);
+        assert_eq!(
+            owners(&codeowners, "scripts/run.sh"),
+            ["@doctocat", "@octocat"]
+        );
+        // Rule without owners makes the file unowned
+        assert!(owners(&codeowners, "apps/github/main.rs").is_empty());
+    }
+
+    #[test]
+    fn test_codeowners_pattern_matching() {
+        let codeowners = CodeOwners::parse(
+            b"\
+docs @a
+/src/**/test @b
+path\\ with\\ space @c
+!negated @d
+",
+        );
+        // Unanchored pattern matches both files and directories
+        assert_eq!(owners(&codeowners, "docs"), ["@a"]);
+        assert_eq!(owners(&codeowners, "sub/docs/file"), ["@a"]);
+        assert_eq!(owners(&codeowners, "src/test/file"), ["@b"]);
+        assert_eq!(owners(&codeowners, "src/a/b/test/file"), ["@b"]);
+        assert!(owners(&codeowners, "lib/test/file").is_empty());
+        assert_eq!(owners(&codeowners, "path with space"), ["@c"]);
+        assert!(owners(&codeowners, "negated").is_empty());
+    }
+
+    #[test]
+    fn test_codeowners_gitlab_sections() {
+        let codeowners = CodeOwners::parse(
+            b"\
+* @fallback
+
+[Docs] @docs-team
+*.md
+/README.md @readme-owner
+
+^[Optional][2] @infra
+/ci/
+
+[docs]
+/guide/*.md @guide-owner
+",
+        );
+        assert_eq!(
+            codeowners.section_names().collect_vec(),
+            ["Docs", "Optional"]
+        );
+        assert_eq!(owners(&codeowners, "src/lib.rs"), ["@fallback"]);
+        assert_eq!(owners(&codeowners, "src/a.md"), ["@fallback", "@docs-team"]);
+        assert_eq!(
+            owners(&codeowners, "README.md"),
+            ["@fallback", "@readme-owner"]
+        );
+        assert_eq!(
+            owners(&codeowners, "guide/b.md"),
+            ["@fallback", "@guide-owner"]
+        );
+        assert_eq!(owners(&codeowners, "ci/build.yml"), ["@fallback", "@infra"]);
+    }
+}
@@ -14,9 +14,12 @@
 
 #![expect(missing_docs)]
 
+use std::collections::HashMap;
 use std::io;
 use std::io::Write;
 use std::iter::zip;
+use std::mem;
+use std::ops::Range;
 use std::pin::Pin;
 
 use bstr::BStr;
@@ -312,6 +315,10 @@ pub enum ConflictMarkerStyle {
     Snapshot,
     /// Style which replicates Git's "diff3" style to support external tools.
     Git,
+    /// Similar to "git", but lines common to all sides at the start and end of
+    /// a conflict are moved out of the conflict markers, like Git's "zdiff3"
+    /// style.
+    Zdiff3,
 }
 
 impl ConflictMarkerStyle {
@@ -521,6 +528,14 @@ fn materialize_conflict_hunks(
             conflict_index += 1;
             let conflict_info = format!("conflict {conflict_index} of {num_conflicts}");
 
+            let (common_prefix, hunk, common_suffix) =
+                if conflict_marker_style == ConflictMarkerStyle::Zdiff3 {
+                    split_common_side_lines(hunk)
+                } else {
+                    (BString::default(), hunk, BString::default())
+                };
+            output.write_all(&common_prefix)?;
+
             // If any side doesn't have the ending EOL, we remove the ending EOL from the
             // conflict end marker line and "spread" the ending EOL to every side as a
             // separator, so that contents without an ending EOL won't be concatenated with
@@ -537,7 +552,7 @@ fn materialize_conflict_hunks(
 
             match (conflict_marker_style, sides.as_slice()) {
                 // 2-sided conflicts can use Git-style conflict markers
-                (ConflictMarkerStyle::Git, [left, base, right]) => {
+                (ConflictMarkerStyle::Git | ConflictMarkerStyle::Zdiff3, [left, base, right]) => {
                     materialize_git_style_conflict(
                         left,
                         base,
@@ -562,11 +577,124 @@ fn materialize_conflict_hunks(
             if all_sides_have_ending_eol {
                 output.write_all(eol)?;
             }
+            output.write_all(&common_suffix)?;
         }
     }
     Ok(())
 }
 
+/// Splits off the lines common to all sides at the start and end of a conflict
+/// hunk. Returns the common leading lines, the hunk without them, and the
+/// common trailing lines. The bases are left untouched.
+fn split_common_side_lines(hunk: Merge<BString>) -> (BString, Merge<BString>, BString) {
+    let side_lines = hunk
+        .adds()
+        .map(|side| side.lines_with_terminator().collect_vec())
+        .collect_vec();
+    let min_num_lines = side_lines.iter().map(Vec::len).min().unwrap_or(0);
+    // Leading lines must be terminated so the conflict start marker is placed on
+    // its own line.
+    let num_prefix_lines = (0..min_num_lines)
+        .take_while(|&i| {
+            side_lines[0][i].ends_with(b"\n") && side_lines.iter().map(|lines| lines[i]).all_equal()
+        })
+        .count();
+    let mut num_suffix_lines = (0..min_num_lines - num_prefix_lines)
+        .take_while(|&i| {
+            side_lines
+                .iter()
+                .map(|lines| lines[lines.len() - 1 - i])
+                .all_equal()
+        })
+        .count();
+    // Trailing lines can only follow the conflict end marker if it ends with an
+    // EOL, which requires every remaining term to end with one.
+    if hunk
+        .removes()
+        .any(|base| base.last().is_some_and(|last| *last != b'\n'))
+    {
+        num_suffix_lines = 0;
+    }
+    let prefix: BString = side_lines[0][..num_prefix_lines].concat().into();
+    let suffix: BString = side_lines[0][side_lines[0].len() - num_suffix_lines..]
+        .concat()
+        .into();
+    let (removes, adds) = hunk.into_removes_adds();
+    let (prefix_len, suffix_len) = (prefix.len(), suffix.len());
+    let adds = adds.map(|side| BString::from(&side[prefix_len..side.len() - suffix_len]));
+    (prefix, Merge::from_removes_adds(removes, adds), suffix)
+}
+
+/// Moves lines which were moved out of conflict markers by the "zdiff3" style
+/// back into the sides of the matching conflicts in `old_hunks`. Without this,
+/// the lines would also be added to the bases of the parsed conflicts.
+///
+/// Conflicts are matched by position, so the sides of edited conflicts are
+/// restored as well. If some conflicts were resolved or added, the remaining
+/// conflicts are matched in order by their contents instead.
+fn restore_common_side_lines(
+    mut new_hunks: Vec<Merge<BString>>,
+    old_hunks: &[Merge<BString>],
+) -> Vec<Merge<BString>> {
+    let old_conflicts = old_hunks
+        .iter()
+        .filter(|hunk| !hunk.is_resolved())
+        .map(|hunk| split_common_side_lines(hunk.clone()))
+        .collect_vec();
+    let new_conflict_indices = new_hunks
+        .iter()
+        .positions(|hunk| !hunk.is_resolved())
+        .collect_vec();
+    let pairs = if old_conflicts.len() == new_conflict_indices.len() {
+        zip(new_conflict_indices, old_conflicts).collect_vec()
+    } else {
+        let mut old_conflicts = old_conflicts.into_iter();
+        new_conflict_indices
+            .into_iter()
+            .filter_map(|index| {
+                // Look ahead without consuming so an unmatched conflict doesn't
+                // prevent the following conflicts from being matched.
+                let pos = old_conflicts
+                    .as_slice()
+                    .iter()
+                    .position(|(_, old_hunk, _)| *old_hunk == new_hunks[index])?;
+                Some((index, old_conflicts.nth(pos).unwrap()))
+            })
+            .collect_vec()
+    };
+    for (index, (prefix, _, suffix)) in pairs {
+        let mut restored_prefix = BString::default();
+        if let Some(resolved) = index
+            .checked_sub(1)
+            .and_then(|prev| new_hunks[prev].as_resolved())
+            && !prefix.is_empty()
+            && resolved.ends_with(&prefix)
+        {
+            new_hunks[index - 1] =
+                Merge::resolved(BString::from(&resolved[..resolved.len() - prefix.len()]));
+            restored_prefix = prefix;
+        }
+        let mut restored_suffix = BString::default();
+        if let Some(resolved) = new_hunks.get(index + 1).and_then(Merge::as_resolved)
+            && !suffix.is_empty()
+            && resolved.starts_with(&suffix)
+        {
+            new_hunks[index + 1] = Merge::resolved(BString::from(&resolved[suffix.len()..]));
+            restored_suffix = suffix;
+        }
+        let hunk = mem::replace(&mut new_hunks[index], Merge::resolved(BString::default()));
+        let (removes, adds) = hunk.into_removes_adds();
+        let adds = adds.map(|side| {
+            [&restored_prefix[..], &side[..], &restored_suffix[..]]
+                .concat()
+                .into()
+        });
+        new_hunks[index] = Merge::from_removes_adds(removes, adds);
+    }
+    new_hunks.retain(|hunk| hunk.as_resolved().is_none_or(|content| !content.is_empty()));
+    new_hunks
+}
+
 #[derive(Debug)]
 struct HunkTerm {
     contents: BString,
@@ -899,6 +1027,30 @@ pub fn parse_conflict(
     }
 }
 
+/// Finds the conflict marker blocks in materialized `input`, and returns the
+/// line ranges (0-based, including the start and end marker lines) of each.
+///
+/// Like [`parse_conflict()`], conflict markers shorter than the expected
+/// length are ignored.
+pub fn find_conflict_line_ranges(input: &[u8], expected_marker_len: usize) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut conflict_start = None;
+    for (index, line) in input.lines_with_terminator().enumerate() {
+        match parse_conflict_marker(line, expected_marker_len) {
+            Some(ConflictMarkerLineChar::ConflictStart) => {
+                conflict_start = Some(index);
+            }
+            Some(ConflictMarkerLineChar::ConflictEnd) => {
+                if let Some(start) = conflict_start.take() {
+                    ranges.push(start..index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
 /// This method handles parsing both JJ-style and Git-style conflict markers,
 /// meaning that switching conflict marker styles won't prevent existing files
 /// with other conflict marker styles from being parsed successfully. The
@@ -1071,7 +1223,11 @@ pub async fn update_from_content(
         content,
         simplified_file_ids.num_sides(),
         conflict_marker_len,
-    );
+    )
+    .map(|new_hunks| match &old_hunks {
+        MergeResult::Resolved(_) => new_hunks,
+        MergeResult::Conflict(old_hunks) => restore_common_side_lines(new_hunks, old_hunks),
+    });
 
     // Check if the new hunks are unchanged. This makes sure that unchanged file
     // conflicts aren't updated to partially-resolved contents.
@@ -1089,7 +1245,53 @@ pub async fn update_from_content(
         let file_id = store.write_file(path, &mut &content[..]).await?;
         return Ok(Merge::normal(file_id));
     };
+    update_from_hunks(file_ids, store, path, hunks).await
+}
+
+/// Resolves conflicted `hunks` by picking sides. `choices` maps the index of
+/// a conflicted hunk, as numbered in conflict markers starting from 0, to the
+/// index of the side to pick.
+///
+/// Panics if a side index is out of range.
+pub fn pick_hunk_sides(
+    hunks: Vec<Merge<BString>>,
+    choices: &HashMap<usize, usize>,
+) -> Vec<Merge<BString>> {
+    let mut conflict_index = 0;
+    hunks
+        .into_iter()
+        .map(|hunk| {
+            if hunk.is_resolved() {
+                return hunk;
+            }
+            let choice = choices.get(&conflict_index);
+            conflict_index += 1;
+            match choice {
+                Some(&side) => Merge::resolved(hunk.get_add(side).unwrap().clone()),
+                None => hunk,
+            }
+        })
+        .collect()
+}
 
+/// Writes the contents of the (possibly conflicted) `hunks` and returns an
+/// updated version of `file_ids`. The `hunks` should have the arity of the
+/// simplified `file_ids`.
+pub async fn update_from_hunks(
+    file_ids: &Merge<Option<FileId>>,
+    store: &Store,
+    path: &RepoPath,
+    hunks: Vec<Merge<BString>>,
+) -> BackendResult<Merge<Option<FileId>>> {
+    if hunks.iter().all(|hunk| hunk.is_resolved()) {
+        let content: BString = hunks
+            .iter()
+            .flat_map(|hunk| hunk.as_resolved().unwrap().iter().copied())
+            .collect();
+        let file_id = store.write_file(path, &mut content.as_slice()).await?;
+        return Ok(Merge::normal(file_id));
+    }
+    let simplified_file_ids = file_ids.simplify();
     let mut contents = simplified_file_ids.map(|_| vec![]);
     for hunk in hunks {
         if let Some(slice) = hunk.as_resolved() {
@@ -1139,7 +1341,9 @@ mod tests {
     use test_case::test_matrix;
 
     use super::*;
+    use crate::diff::DiffAlgorithm;
     use crate::files::FileMergeHunkLevel;
+    use crate::merge_driver::MergeDrivers;
 
     #[test]
     fn test_resolve_file_executable() {
@@ -1369,6 +1573,9 @@ mod tests {
             merge: MergeOptions {
                 hunk_level: FileMergeHunkLevel::Line,
                 same_change: SameChange::Accept,
+                diff_algorithm: DiffAlgorithm::Default,
+                drivers: MergeDrivers::default(),
+                rerere: None,
             },
         };
         let actual_contents = String::from_utf8(
@@ -1433,6 +1640,9 @@ mod tests {
             merge: MergeOptions {
                 hunk_level: FileMergeHunkLevel::Line,
                 same_change: SameChange::Keep,
+                diff_algorithm: DiffAlgorithm::Default,
+                drivers: MergeDrivers::default(),
+                rerere: None,
             },
         };
         let merge = merge.map(|content| BString::from(*content));
@@ -1441,4 +1651,34 @@ mod tests {
         let hunks = parse_conflict(&materialized, 2, 7).unwrap();
         assert_eq!(hunks, vec![merge]);
     }
+
+    #[test]
+    fn test_pick_hunk_sides() {
+        let hunk = |terms: [&str; 3]| Merge::from_vec(terms.map(BString::from).to_vec());
+        let hunks = vec![
+            Merge::resolved(BString::from("1\n")),
+            hunk(["a1\n", "base1\n", "b1\n"]),
+            Merge::resolved(BString::from("2\n")),
+            hunk(["a2\n", "base2\n", "b2\n"]),
+        ];
+        assert_eq!(pick_hunk_sides(hunks.clone(), &HashMap::new()), hunks);
+        assert_eq!(
+            pick_hunk_sides(hunks.clone(), &HashMap::from([(1, 0)])),
+            vec![
+                hunks[0].clone(),
+                hunks[1].clone(),
+                hunks[2].clone(),
+                Merge::resolved(BString::from("a2\n")),
+            ]
+        );
+        assert_eq!(
+            pick_hunk_sides(hunks.clone(), &HashMap::from([(0, 1), (1, 0)])),
+            vec![
+                hunks[0].clone(),
+                Merge::resolved(BString::from("b1\n")),
+                hunks[2].clone(),
+                Merge::resolved(BString::from("a2\n")),
+            ]
+        );
+    }
 }
@@ -0,0 +1,689 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Index of per-commit descriptions and author/committer identities.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use blake2::Blake2b512;
+use digest::Digest as _;
+use either::Either;
+use itertools::Itertools as _;
+use tempfile::NamedTempFile;
+
+use super::entry::GlobalCommitPosition;
+use super::readonly::ReadonlyIndexLoadError;
+use crate::commit::Commit;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::object_id::ObjectId as _;
+use crate::object_id::id_type;
+
+/// Current format version of the commit-metadata index segment file.
+const FILE_FORMAT_VERSION: u32 = 0;
+
+/// Number of text fields stored per commit.
+const NUM_FIELDS: usize = 5;
+
+id_type!(pub(super) CommitMetadataIndexSegmentId { hex() });
+
+/// Commit position within a commit-metadata index segment.
+///
+/// This may be different from `LocalCommitPosition`, which is a position
+/// relative to the start of the commit index segment.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct CommitPosition(u32);
+
+/// Text fields of a commit which can be looked up without loading the commit
+/// object from the backend.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct CommitMetadata<'a> {
+    pub description: &'a str,
+    pub author_name: &'a str,
+    pub author_email: &'a str,
+    pub committer_name: &'a str,
+    pub committer_email: &'a str,
+}
+
+impl<'a> CommitMetadata<'a> {
+    pub fn from_commit(commit: &'a Commit) -> Self {
+        let author = commit.author();
+        let committer = commit.committer();
+        Self {
+            description: commit.description(),
+            author_name: &author.name,
+            author_email: &author.email,
+            committer_name: &committer.name,
+            committer_email: &committer.email,
+        }
+    }
+
+    fn from_fields(
+        [
+            description,
+            author_name,
+            author_email,
+            committer_name,
+            committer_email,
+        ]: [&'a str; NUM_FIELDS],
+    ) -> Self {
+        Self {
+            description,
+            author_name,
+            author_email,
+            committer_name,
+            committer_email,
+        }
+    }
+
+    fn fields(&self) -> [&'a str; NUM_FIELDS] {
+        [
+            self.description,
+            self.author_name,
+            self.author_email,
+            self.committer_name,
+            self.committer_email,
+        ]
+    }
+}
+
+/// Table of per-commit text fields, which is shared by the readonly and
+/// mutable segments.
+///
+/// ```text
+/// for each commit, in commit-index order
+///   for each field (description, author name, author email, committer name,
+///                   committer email)
+///     u32: byte offset in text table
+/// u32: number of bytes of text entries (as sentinel)
+/// <arbitrary length of bytes>: text
+/// ```
+trait TextTable {
+    fn field_offset(&self, index: usize) -> usize;
+
+    fn text(&self) -> &str;
+
+    fn metadata(&self, pos: CommitPosition) -> CommitMetadata<'_> {
+        let base = pos.0 as usize * NUM_FIELDS;
+        let fields = std::array::from_fn(|i| {
+            let start = self.field_offset(base + i);
+            let end = self.field_offset(base + i + 1);
+            &self.text()[start..end]
+        });
+        CommitMetadata::from_fields(fields)
+    }
+}
+
+/// Commit-metadata index segment backed by immutable file.
+///
+/// File format:
+/// ```text
+/// u32: file format version
+///
+/// u32: number of (local) commit entries
+/// u32: number of bytes of text entries
+///
+/// for each commit, in commit-index order
+///   for each field (description, author name, author email, committer name,
+///                   committer email)
+///     u32: byte offset in text table
+/// u32: number of bytes of text entries (as sentinel)
+/// <arbitrary length of bytes>: text
+/// ```
+///
+/// * The parent segment id isn't stored in a segment file. This allows us to
+///   insert parents without rewriting the descendant segments.
+/// * Texts are stored verbatim so that any string pattern (including regex
+///   and case-insensitive patterns) can be evaluated with the same semantics
+///   as if the commit object were loaded.
+pub(super) struct ReadonlyCommitMetadataIndexSegment {
+    id: CommitMetadataIndexSegmentId,
+    num_local_commits: u32,
+    field_lookup: Vec<u8>,
+    text: String,
+}
+
+impl Debug for ReadonlyCommitMetadataIndexSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("ReadonlyCommitMetadataIndexSegment")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadonlyCommitMetadataIndexSegment {
+    pub(super) fn load(
+        dir: &Path,
+        id: CommitMetadataIndexSegmentId,
+    ) -> Result<Arc<Self>, ReadonlyIndexLoadError> {
+        let mut file = File::open(dir.join(id.hex()))
+            .map_err(|err| ReadonlyIndexLoadError::from_io_err("commit-metadata", id.hex(), err))?;
+        Self::load_from(&mut file, id)
+    }
+
+    pub(super) fn load_from(
+        file: &mut dyn Read,
+        id: CommitMetadataIndexSegmentId,
+    ) -> Result<Arc<Self>, ReadonlyIndexLoadError> {
+        let from_io_err =
+            |err| ReadonlyIndexLoadError::from_io_err("commit-metadata", id.hex(), err);
+        let read_u32 = |file: &mut dyn Read| {
+            let mut buf = [0; 4];
+            file.read_exact(&mut buf).map_err(from_io_err)?;
+            Ok(u32::from_le_bytes(buf))
+        };
+
+        let format_version = read_u32(file)?;
+        if format_version != FILE_FORMAT_VERSION {
+            return Err(ReadonlyIndexLoadError::UnexpectedVersion {
+                kind: "commit-metadata",
+                found_version: format_version,
+                expected_version: FILE_FORMAT_VERSION,
+            });
+        }
+
+        let num_local_commits = read_u32(file)?;
+        let num_text_bytes = read_u32(file)?;
+        let mut field_lookup = vec![0; (num_local_commits as usize * NUM_FIELDS + 1) * 4];
+        file.read_exact(&mut field_lookup).map_err(from_io_err)?;
+        let mut text = String::new();
+        file.read_to_string(&mut text).map_err(from_io_err)?;
+
+        let invalid_data =
+            |message| ReadonlyIndexLoadError::invalid_data("commit-metadata", id.hex(), message);
+        if text.len() != num_text_bytes as usize {
+            return Err(invalid_data("unexpected data length"));
+        }
+        let (offsets, _remainder) = field_lookup.as_chunks();
+        let offsets_valid = offsets
+            .iter()
+            .map(|&chunk: &[u8; 4]| u32::from_le_bytes(chunk) as usize)
+            .tuple_windows()
+            .all(|(start, end)| start <= end && text.is_char_boundary(end));
+        if !offsets_valid
+            || offsets.first() != Some(&0_u32.to_le_bytes())
+            || offsets.last() != Some(&num_text_bytes.to_le_bytes())
+        {
+            return Err(invalid_data("invalid text offsets"));
+        }
+
+        Ok(Arc::new(Self {
+            id,
+            num_local_commits,
+            field_lookup,
+            text,
+        }))
+    }
+
+    pub(super) fn id(&self) -> &CommitMetadataIndexSegmentId {
+        &self.id
+    }
+
+    pub(super) fn num_local_commits(&self) -> u32 {
+        self.num_local_commits
+    }
+
+    pub(super) fn num_text_bytes(&self) -> u32 {
+        self.text.len().try_into().unwrap()
+    }
+}
+
+impl TextTable for ReadonlyCommitMetadataIndexSegment {
+    fn field_offset(&self, index: usize) -> usize {
+        let offset = index * 4;
+        u32::from_le_bytes(self.field_lookup[offset..][..4].try_into().unwrap()) as usize
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Commit-metadata index segment which is not serialized to file.
+#[derive(Clone)]
+pub(super) struct MutableCommitMetadataIndexSegment {
+    field_offsets: Vec<u32>,
+    text: String,
+}
+
+impl Debug for MutableCommitMetadataIndexSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("MutableCommitMetadataIndexSegment")
+            .finish_non_exhaustive()
+    }
+}
+
+impl MutableCommitMetadataIndexSegment {
+    pub(super) fn empty() -> Self {
+        Self {
+            field_offsets: vec![0],
+            text: String::new(),
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.num_local_commits() == 0
+    }
+
+    pub(super) fn num_local_commits(&self) -> u32 {
+        ((self.field_offsets.len() - 1) / NUM_FIELDS)
+            .try_into()
+            .unwrap()
+    }
+
+    fn add_commit_metadata(&mut self, metadata: &CommitMetadata) {
+        for field in metadata.fields() {
+            self.text.push_str(field);
+            self.field_offsets
+                .push(u32::try_from(self.text.len()).unwrap());
+        }
+    }
+
+    fn extend_from_readonly_segment(&mut self, other_segment: &ReadonlyCommitMetadataIndexSegment) {
+        for pos in (0..other_segment.num_local_commits()).map(CommitPosition) {
+            self.add_commit_metadata(&other_segment.metadata(pos));
+        }
+    }
+
+    fn extend_from_mutable_segment(&mut self, other_segment: &Self) {
+        for pos in (0..other_segment.num_local_commits()).map(CommitPosition) {
+            self.add_commit_metadata(&other_segment.metadata(pos));
+        }
+    }
+
+    fn serialize_into(&self, buf: &mut Vec<u8>) {
+        buf.extend(FILE_FORMAT_VERSION.to_le_bytes());
+        buf.extend(self.num_local_commits().to_le_bytes());
+        buf.extend(u32::try_from(self.text.len()).unwrap().to_le_bytes());
+        for offset in &self.field_offsets {
+            buf.extend(offset.to_le_bytes());
+        }
+        buf.extend(self.text.as_bytes());
+    }
+
+    pub(super) fn save_in(
+        &self,
+        dir: &Path,
+    ) -> Result<Arc<ReadonlyCommitMetadataIndexSegment>, PathError> {
+        let mut buf = Vec::new();
+        self.serialize_into(&mut buf);
+        let mut hasher = Blake2b512::new();
+        hasher.update(&buf);
+
+        let file_id = CommitMetadataIndexSegmentId::from_bytes(&hasher.finalize());
+        let file_path = dir.join(file_id.hex());
+        let mut file = NamedTempFile::new_in(dir).context(dir)?;
+        file.as_file_mut().write_all(&buf).context(file.path())?;
+        persist_content_addressed_temp_file(file, &file_path).context(&file_path)?;
+
+        let segment = ReadonlyCommitMetadataIndexSegment::load_from(&mut &buf[..], file_id)
+            .expect("in-memory index data should be valid and readable");
+        Ok(segment)
+    }
+}
+
+impl TextTable for MutableCommitMetadataIndexSegment {
+    fn field_offset(&self, index: usize) -> usize {
+        self.field_offsets[index] as usize
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Index of per-commit descriptions and author/committer identities.
+#[derive(Clone, Debug)]
+pub(super) struct CompositeCommitMetadataIndex {
+    start_commit_pos: Option<GlobalCommitPosition>,
+    num_commits: u32, // cache
+    readonly_segments: Vec<Arc<ReadonlyCommitMetadataIndexSegment>>,
+    mutable_segment: Option<Box<MutableCommitMetadataIndexSegment>>,
+}
+
+impl CompositeCommitMetadataIndex {
+    /// Creates empty commit-metadata index which cannot store entries. In
+    /// other words, the commit-metadata index is disabled.
+    pub(super) fn null() -> Self {
+        Self {
+            start_commit_pos: None,
+            num_commits: 0,
+            readonly_segments: vec![],
+            mutable_segment: None,
+        }
+    }
+
+    /// Creates empty commit-metadata index which will store entries from
+    /// `start_commit_pos`.
+    pub(super) fn empty(start_commit_pos: GlobalCommitPosition) -> Self {
+        Self {
+            start_commit_pos: Some(start_commit_pos),
+            num_commits: 0,
+            readonly_segments: vec![],
+            mutable_segment: None,
+        }
+    }
+
+    pub(super) fn load(
+        dir: &Path,
+        start_commit_pos: GlobalCommitPosition,
+        ids: &[CommitMetadataIndexSegmentId],
+    ) -> Result<Self, ReadonlyIndexLoadError> {
+        let readonly_segments: Vec<_> = ids
+            .iter()
+            .map(|id| ReadonlyCommitMetadataIndexSegment::load(dir, id.clone()))
+            .try_collect()?;
+        let num_commits = readonly_segments
+            .iter()
+            .map(|segment| segment.num_local_commits())
+            .sum();
+        Ok(Self {
+            start_commit_pos: Some(start_commit_pos),
+            num_commits,
+            readonly_segments,
+            mutable_segment: None,
+        })
+    }
+
+    /// Adds mutable segment if needed.
+    pub(super) fn make_mutable(&mut self) {
+        if self.start_commit_pos.is_none() || self.mutable_segment.is_some() {
+            return;
+        }
+        self.mutable_segment = Some(Box::new(MutableCommitMetadataIndexSegment::empty()));
+    }
+
+    /// Position of the first indexed (or to-be-indexed) commit.
+    pub(super) fn start_commit_pos(&self) -> Option<GlobalCommitPosition> {
+        self.start_commit_pos
+    }
+
+    /// New commit index position which can be added to this index.
+    pub(super) fn next_mutable_commit_pos(&self) -> Option<GlobalCommitPosition> {
+        if self.mutable_segment.is_some() {
+            self.start_commit_pos
+                .map(|GlobalCommitPosition(start)| GlobalCommitPosition(start + self.num_commits))
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn num_commits(&self) -> u32 {
+        self.num_commits
+    }
+
+    pub(super) fn readonly_segments(&self) -> &[Arc<ReadonlyCommitMetadataIndexSegment>] {
+        &self.readonly_segments
+    }
+
+    /// Appends segments from the `other` index. This and the other index should
+    /// be contiguous.
+    pub(super) fn append_segments(&mut self, other: &Self) {
+        assert!(self.mutable_segment.is_none());
+        let GlobalCommitPosition(self_start_pos) =
+            self.start_commit_pos.expect("should have start pos");
+        let Some(GlobalCommitPosition(other_start_pos)) = other.start_commit_pos else {
+            return;
+        };
+        assert_eq!(self_start_pos + self.num_commits, other_start_pos);
+        self.readonly_segments
+            .extend_from_slice(&other.readonly_segments);
+        self.mutable_segment = other.mutable_segment.clone();
+        self.num_commits += other.num_commits;
+    }
+
+    /// Maps `global_pos` to segment and segment-local position.
+    fn find_segment(
+        &self,
+        global_pos: GlobalCommitPosition,
+    ) -> Option<(
+        CommitPosition,
+        Either<&ReadonlyCommitMetadataIndexSegment, &MutableCommitMetadataIndexSegment>,
+    )> {
+        let mut local_pos = u32::checked_sub(global_pos.0, self.start_commit_pos?.0)?;
+        for segment in &self.readonly_segments {
+            local_pos = match u32::checked_sub(local_pos, segment.num_local_commits()) {
+                Some(next_local_pos) => next_local_pos,
+                None => return Some((CommitPosition(local_pos), Either::Left(segment))),
+            };
+        }
+        let segment = self.mutable_segment.as_deref()?;
+        (local_pos < segment.num_local_commits())
+            .then_some((CommitPosition(local_pos), Either::Right(segment)))
+    }
+
+    /// Returns text fields of the specified commit. Returns `None` if the
+    /// commit wasn't indexed.
+    pub(super) fn metadata(&self, global_pos: GlobalCommitPosition) -> Option<CommitMetadata<'_>> {
+        let (local_pos, segment) = self.find_segment(global_pos)?;
+        Some(segment.either(|x| x.metadata(local_pos), |x| x.metadata(local_pos)))
+    }
+
+    /// Adds text fields of the next commit.
+    ///
+    /// Caller must ensure that the commit matches `next_mutable_commit_pos()`.
+    /// Panics if this index isn't mutable (i.e. `next_mutable_commit_pos()` is
+    /// `None`.)
+    pub(super) fn add_commit_metadata(&mut self, metadata: &CommitMetadata) {
+        let segment = self
+            .mutable_segment
+            .as_deref_mut()
+            .expect("should have mutable");
+        segment.add_commit_metadata(metadata);
+        self.num_commits += 1;
+    }
+
+    /// Squashes parent segments if the mutable segment has more than half the
+    /// commits of its parent segment. This is done recursively, so the stack of
+    /// index segments has O(log n) files.
+    pub(super) fn maybe_squash_with_ancestors(&mut self) {
+        let Some(mutable_segment) = self.mutable_segment.as_deref() else {
+            return;
+        };
+        let mut num_new_commits = mutable_segment.num_local_commits();
+        let mut squash_start = self.readonly_segments.len();
+        for segment in self.readonly_segments.iter().rev() {
+            if 2 * num_new_commits < segment.num_local_commits() {
+                break;
+            }
+            num_new_commits += segment.num_local_commits();
+            squash_start -= 1;
+        }
+        if squash_start == self.readonly_segments.len() {
+            return;
+        }
+        let mut squashed_segment = Box::new(MutableCommitMetadataIndexSegment::empty());
+        for segment in self.readonly_segments.drain(squash_start..) {
+            squashed_segment.extend_from_readonly_segment(&segment);
+        }
+        squashed_segment.extend_from_mutable_segment(&self.mutable_segment.take().unwrap());
+        self.mutable_segment = Some(squashed_segment);
+    }
+
+    /// Writes mutable segment if exists, turns it into readonly segment.
+    pub(super) fn save_in(&mut self, dir: &Path) -> Result<(), PathError> {
+        let Some(segment) = self.mutable_segment.take() else {
+            return Ok(());
+        };
+        if segment.is_empty() {
+            return Ok(());
+        }
+        let segment = segment.save_in(dir)?;
+        self.readonly_segments.push(segment);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::tests::TestResult;
+    use crate::tests::new_temp_dir;
+
+    fn metadata(description: &str) -> CommitMetadata<'_> {
+        CommitMetadata {
+            description,
+            author_name: "Author",
+            author_email: "author@example.com",
+            committer_name: "Committer",
+            committer_email: "committer@example.com",
+        }
+    }
+
+    #[test]
+    fn test_composite_null() {
+        let mut index = CompositeCommitMetadataIndex::null();
+        assert_eq!(index.start_commit_pos(), None);
+        assert_eq!(index.next_mutable_commit_pos(), None);
+        assert_eq!(index.metadata(GlobalCommitPosition(0)), None);
+
+        // No entries can be added to "null" index
+        index.make_mutable();
+        assert!(index.mutable_segment.is_none());
+        assert_eq!(index.num_commits(), 0);
+    }
+
+    #[test]
+    fn test_composite_empty() -> TestResult {
+        let temp_dir = new_temp_dir();
+        let mut index = CompositeCommitMetadataIndex::empty(GlobalCommitPosition(0));
+        index.make_mutable();
+        assert_eq!(
+            index.next_mutable_commit_pos(),
+            Some(GlobalCommitPosition(0))
+        );
+        assert_eq!(index.metadata(GlobalCommitPosition(0)), None);
+
+        // Empty segment shouldn't be saved on disk
+        index.save_in(temp_dir.path())?;
+        assert!(index.mutable_segment.is_none());
+        assert!(index.readonly_segments.is_empty());
+        assert_eq!(index.num_commits(), 0);
+        Ok(())
+    }
+
+    #[test_case(false, false; "mutable")]
+    #[test_case(true, false; "readonly")]
+    #[test_case(true, true; "readonly, reloaded")]
+    fn test_composite_some_commits(on_disk: bool, reload: bool) -> TestResult {
+        let temp_dir = new_temp_dir();
+        let start_commit_pos = GlobalCommitPosition(1);
+        let mut index = CompositeCommitMetadataIndex::empty(start_commit_pos);
+        index.make_mutable();
+        index.add_commit_metadata(&metadata("first\n"));
+        index.add_commit_metadata(&metadata(""));
+        index.add_commit_metadata(&CommitMetadata {
+            description: "thïrd\n\nbody\n",
+            author_name: "",
+            author_email: "",
+            committer_name: "Someone",
+            committer_email: "someone@example.com",
+        });
+        assert_eq!(
+            index.next_mutable_commit_pos(),
+            Some(GlobalCommitPosition(4))
+        );
+        assert_eq!(index.num_commits(), 3);
+        if on_disk {
+            index.save_in(temp_dir.path())?;
+            assert!(index.mutable_segment.is_none());
+            assert_eq!(index.readonly_segments.len(), 1);
+            assert_eq!(index.next_mutable_commit_pos(), None);
+        }
+        if reload {
+            let ids = index
+                .readonly_segments()
+                .iter()
+                .map(|segment| segment.id().clone())
+                .collect_vec();
+            index = CompositeCommitMetadataIndex::load(temp_dir.path(), start_commit_pos, &ids)?;
+        }
+
+        assert_eq!(index.metadata(GlobalCommitPosition(0)), None);
+        assert_eq!(
+            index.metadata(GlobalCommitPosition(1)),
+            Some(metadata("first\n"))
+        );
+        assert_eq!(index.metadata(GlobalCommitPosition(2)), Some(metadata("")));
+        assert_eq!(
+            index.metadata(GlobalCommitPosition(3)),
+            Some(CommitMetadata {
+                description: "thïrd\n\nbody\n",
+                author_name: "",
+                author_email: "",
+                committer_name: "Someone",
+                committer_email: "someone@example.com",
+            })
+        );
+        assert_eq!(index.metadata(GlobalCommitPosition(4)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_squash_segments() -> TestResult {
+        let temp_dir = new_temp_dir();
+        let mut index = CompositeCommitMetadataIndex::empty(GlobalCommitPosition(0));
+        index.make_mutable();
+        index.add_commit_metadata(&metadata("0"));
+        index.save_in(temp_dir.path())?;
+
+        index.make_mutable();
+        index.add_commit_metadata(&metadata("1"));
+        index.maybe_squash_with_ancestors();
+        index.save_in(temp_dir.path())?;
+        assert_eq!(index.readonly_segments.len(), 1);
+        assert_eq!(index.readonly_segments[0].num_local_commits(), 2);
+
+        index.make_mutable();
+        index.add_commit_metadata(&metadata("2"));
+        index.maybe_squash_with_ancestors();
+        index.save_in(temp_dir.path())?;
+        assert_eq!(index.readonly_segments.len(), 1);
+        assert_eq!(index.readonly_segments[0].num_local_commits(), 3);
+
+        index.make_mutable();
+        index.add_commit_metadata(&metadata("3"));
+        index.maybe_squash_with_ancestors();
+        index.save_in(temp_dir.path())?;
+        assert_eq!(index.readonly_segments.len(), 2);
+
+        // Squashed segments should preserve the original entries.
+        assert_eq!(index.metadata(GlobalCommitPosition(0)), Some(metadata("0")));
+        assert_eq!(index.metadata(GlobalCommitPosition(1)), Some(metadata("1")));
+        assert_eq!(index.metadata(GlobalCommitPosition(2)), Some(metadata("2")));
+        assert_eq!(index.metadata(GlobalCommitPosition(3)), Some(metadata("3")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_corrupted_offsets() {
+        let mut segment = MutableCommitMetadataIndexSegment::empty();
+        segment.add_commit_metadata(&metadata("ä"));
+        let mut buf = Vec::new();
+        segment.serialize_into(&mut buf);
+        // Make the description end in the middle of the multi-byte char
+        buf[4 * 4..][..4].copy_from_slice(&1_u32.to_le_bytes());
+        let id = CommitMetadataIndexSegmentId::from_bytes(&[0; 64]);
+        assert!(ReadonlyCommitMetadataIndexSegment::load_from(&mut &buf[..], id).is_err());
+    }
+}
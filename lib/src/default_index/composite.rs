@@ -14,7 +14,9 @@
 
 use std::cmp::Ordering;
 use std::cmp::Reverse;
+use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
+use std::collections::HashMap;
 use std::collections::binary_heap;
 use std::iter;
 use std::mem;
@@ -30,6 +32,7 @@ use ref_cast::ref_cast_custom;
 use super::bit_set::AncestorsBitSet;
 use super::bit_set::PositionsBitSet;
 use super::changed_path::CompositeChangedPathIndex;
+use super::commit_metadata::CompositeCommitMetadataIndex;
 use super::entry::CommitIndexEntry;
 use super::entry::GlobalCommitPosition;
 use super::entry::LocalCommitPosition;
@@ -37,8 +40,11 @@ use super::entry::SmallGlobalCommitPositionsVec;
 use super::entry::SmallLocalCommitPositionsVec;
 use super::mutable::MutableCommitIndexSegment;
 use super::readonly::ReadonlyCommitIndexSegment;
+use super::rev_walk::RevWalkBuilder;
 use super::rev_walk::filter_slice_by_range;
 use super::revset_engine;
+use super::trigram::CompositeTrigramIndex;
+use super::trigram::TrigramQuery;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::hex_util;
@@ -56,6 +62,7 @@ use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
 use crate::store::Store;
+use crate::str_util::StringExpression;
 
 id_type!(pub(super) CommitIndexSegmentId { hex() });
 
@@ -370,29 +377,51 @@ impl CompositeCommitIndex {
         set1: Vec<GlobalCommitPosition>,
         set2: Vec<GlobalCommitPosition>,
     ) -> Vec<GlobalCommitPosition> {
-        let mut items1 = BinaryHeap::from(set1);
-        let mut items2 = BinaryHeap::from(set2);
+        // Entries are visited in descending position order, so flags of an
+        // entry are fully propagated from its descendants before it is
+        // visited. Ancestors of found common ancestors are marked as stale,
+        // and the walk stops when no non-stale entries remain in the queue.
+        const PARENT1: u8 = 1 << 0;
+        const PARENT2: u8 = 1 << 1;
+        const STALE: u8 = 1 << 2;
+        let mut flags: HashMap<GlobalCommitPosition, u8> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        for (positions, flag) in [(set1, PARENT1), (set2, PARENT2)] {
+            for pos in positions {
+                let entry_flags = flags.entry(pos).or_default();
+                if *entry_flags == 0 {
+                    queue.push(pos);
+                }
+                *entry_flags |= flag;
+            }
+        }
+        let mut num_non_stale = queue.len();
         let mut result = Vec::new();
-        while let (Some(&pos1), Some(&pos2)) = (items1.peek(), items2.peek()) {
-            match pos1.cmp(&pos2) {
-                Ordering::Greater => shift_to_parents(
-                    &mut items1,
-                    pos1,
-                    &self.entry_by_pos(pos1).parent_positions(),
-                ),
-                Ordering::Less => shift_to_parents(
-                    &mut items2,
-                    pos2,
-                    &self.entry_by_pos(pos2).parent_positions(),
-                ),
-                Ordering::Equal => {
-                    result.push(pos1);
-                    dedup_pop(&mut items1).unwrap();
-                    dedup_pop(&mut items2).unwrap();
+        while num_non_stale > 0 {
+            let pos = queue.pop().unwrap();
+            let mut entry_flags = flags[&pos];
+            if entry_flags & STALE == 0 {
+                num_non_stale -= 1;
+                if entry_flags & (PARENT1 | PARENT2) == PARENT1 | PARENT2 {
+                    result.push(pos);
+                    entry_flags |= STALE;
                 }
             }
+            for parent_pos in self.entry_by_pos(pos).parent_positions() {
+                let parent_flags = flags.entry(parent_pos).or_default();
+                let new_parent_flags = *parent_flags | entry_flags;
+                if *parent_flags == 0 {
+                    queue.push(parent_pos);
+                    if new_parent_flags & STALE == 0 {
+                        num_non_stale += 1;
+                    }
+                } else if *parent_flags & STALE == 0 && new_parent_flags & STALE != 0 {
+                    num_non_stale -= 1;
+                }
+                *parent_flags = new_parent_flags;
+            }
         }
-        self.heads_pos(result)
+        result
     }
 
     pub(super) fn all_heads(&self) -> impl Iterator<Item = CommitId> {
@@ -525,37 +554,57 @@ enum CompositeCommitIndexSegment {
 pub(super) struct CompositeIndex {
     commits: CompositeCommitIndexSegment,
     changed_paths: CompositeChangedPathIndex,
+    commit_metadata: CompositeCommitMetadataIndex,
+    trigrams: CompositeTrigramIndex,
 }
 
 impl CompositeIndex {
     pub(super) fn from_readonly(
         commits: Arc<ReadonlyCommitIndexSegment>,
         changed_paths: CompositeChangedPathIndex,
+        commit_metadata: CompositeCommitMetadataIndex,
+        trigrams: CompositeTrigramIndex,
     ) -> Self {
         Self {
             commits: CompositeCommitIndexSegment::Readonly(commits),
             changed_paths,
+            commit_metadata,
+            trigrams,
         }
     }
 
     pub(super) fn from_mutable(
         commits: Box<MutableCommitIndexSegment>,
         changed_paths: CompositeChangedPathIndex,
+        commit_metadata: CompositeCommitMetadataIndex,
+        trigrams: CompositeTrigramIndex,
     ) -> Self {
         Self {
             commits: CompositeCommitIndexSegment::Mutable(commits),
             changed_paths,
+            commit_metadata,
+            trigrams,
         }
     }
 
     pub(super) fn into_mutable(
         self,
-    ) -> Option<(Box<MutableCommitIndexSegment>, CompositeChangedPathIndex)> {
+    ) -> Option<(
+        Box<MutableCommitIndexSegment>,
+        CompositeChangedPathIndex,
+        CompositeCommitMetadataIndex,
+        CompositeTrigramIndex,
+    )> {
         let commits = match self.commits {
             CompositeCommitIndexSegment::Readonly(_) => return None,
             CompositeCommitIndexSegment::Mutable(segment) => segment,
         };
-        Some((commits, self.changed_paths))
+        Some((
+            commits,
+            self.changed_paths,
+            self.commit_metadata,
+            self.trigrams,
+        ))
     }
 
     pub(super) fn commits(&self) -> &CompositeCommitIndex {
@@ -586,6 +635,22 @@ impl CompositeIndex {
     pub(super) fn changed_paths_mut(&mut self) -> &mut CompositeChangedPathIndex {
         &mut self.changed_paths
     }
+
+    pub(super) fn commit_metadata(&self) -> &CompositeCommitMetadataIndex {
+        &self.commit_metadata
+    }
+
+    pub(super) fn commit_metadata_mut(&mut self) -> &mut CompositeCommitMetadataIndex {
+        &mut self.commit_metadata
+    }
+
+    pub(super) fn trigrams(&self) -> &CompositeTrigramIndex {
+        &self.trigrams
+    }
+
+    pub(super) fn trigrams_mut(&mut self) -> &mut CompositeTrigramIndex {
+        &mut self.trigrams
+    }
 }
 
 impl AsCompositeIndex for CompositeIndex {
@@ -658,6 +723,47 @@ impl Index for CompositeIndex {
         Ok(Some(Box::new(paths.map(|path| path.to_owned()))))
     }
 
+    async fn paths_maybe_containing_lines(
+        &self,
+        commit_id: &CommitId,
+        text: &StringExpression,
+    ) -> IndexResult<Option<Vec<RepoPathBuf>>> {
+        // Every line in the tree was added by the commit or its ancestors, so
+        // the file must have been changed by an ancestor whose changed lines
+        // contain the trigrams.
+        let query = TrigramQuery::from_string_expression(text);
+        if query.is_all() {
+            return Ok(None);
+        }
+        let Some(pos) = self.commits().commit_id_to_pos(commit_id) else {
+            return Ok(None);
+        };
+        // Trigrams are indexed per commit, so all paths changed by a commit
+        // whose changed lines contain the trigrams are included. Large commits
+        // may match almost any query, in which case scanning the whole tree
+        // would be as fast as checking the candidates.
+        let mut paths = BTreeSet::new();
+        for pos in RevWalkBuilder::new(self)
+            .wanted_heads(vec![pos])
+            .ancestors()
+        {
+            let Some(trigrams) = self.trigrams().trigrams(pos) else {
+                return Ok(None);
+            };
+            if !query.may_match(trigrams) {
+                continue;
+            }
+            let Some(changed_paths) = self.changed_paths().changed_paths(pos) else {
+                return Ok(None);
+            };
+            paths.extend(changed_paths.map(|path| path.to_owned()));
+            if paths.len() > MAX_PATHS_MAYBE_CONTAINING_LINES {
+                return Ok(None);
+            }
+        }
+        Ok(Some(paths.into_iter().collect()))
+    }
+
     fn evaluate_revset(
         &self,
         expression: &ResolvedExpression,
@@ -668,6 +774,9 @@ impl Index for CompositeIndex {
     }
 }
 
+/// Maximum number of paths returned by `paths_maybe_containing_lines()`.
+const MAX_PATHS_MAYBE_CONTAINING_LINES: usize = 1000;
+
 pub(super) struct ChangeIdIndexImpl<I> {
     index: I,
     reachable_set: Mutex<AncestorsBitSet>,
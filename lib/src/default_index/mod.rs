@@ -21,6 +21,7 @@
 
 mod bit_set;
 mod changed_path;
+mod commit_metadata;
 mod composite;
 mod entry;
 mod mutable;
@@ -30,18 +31,23 @@ mod rev_walk_queue;
 mod revset_engine;
 mod revset_graph_iterator;
 mod store;
+mod trigram;
 
 pub use self::mutable::DefaultMutableIndex;
 pub use self::readonly::ChangedPathIndexLevelStats;
 pub use self::readonly::CommitIndexLevelStats;
+pub use self::readonly::CommitMetadataIndexLevelStats;
 pub use self::readonly::DefaultReadonlyIndex;
 pub use self::readonly::DefaultReadonlyIndexRevset;
 pub use self::readonly::IndexStats;
 pub use self::readonly::ReadonlyIndexLoadError;
+pub use self::readonly::TrigramIndexLevelStats;
 pub use self::store::DefaultChangedPathIndexProgress;
+pub use self::store::DefaultCommitMetadataIndexProgress;
 pub use self::store::DefaultIndexStore;
 pub use self::store::DefaultIndexStoreError;
 pub use self::store::DefaultIndexStoreInitError;
+pub use self::store::DefaultTrigramIndexProgress;
 
 #[cfg(test)]
 #[rustversion::attr(
@@ -60,6 +66,7 @@ mod tests {
     use test_case::test_case;
 
     use super::changed_path::CompositeChangedPathIndex;
+    use super::commit_metadata::CompositeCommitMetadataIndex;
     use super::composite::AsCompositeIndex as _;
     use super::composite::CommitIndexSegment as _;
     use super::composite::CompositeCommitIndex;
@@ -68,6 +75,7 @@ mod tests {
     use super::entry::SmallGlobalCommitPositionsVec;
     use super::mutable::MutableCommitIndexSegment;
     use super::readonly::ReadonlyCommitIndexSegment;
+    use super::trigram::CompositeTrigramIndex;
     use super::*;
     use crate::backend::ChangeId;
     use crate::backend::CommitId;
@@ -101,7 +109,14 @@ mod tests {
 
     fn get_commit_index_stats(commits: &Arc<ReadonlyCommitIndexSegment>) -> IndexStats {
         let changed_paths = CompositeChangedPathIndex::null();
-        let index = DefaultReadonlyIndex::from_segment(commits.clone(), changed_paths);
+        let commit_metadata = CompositeCommitMetadataIndex::null();
+        let trigrams = CompositeTrigramIndex::null();
+        let index = DefaultReadonlyIndex::from_segment(
+            commits.clone(),
+            changed_paths,
+            commit_metadata,
+            trigrams,
+        );
         index.stats()
     }
 
@@ -1229,6 +1244,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_common_ancestors_ancestor_of_result() -> TestResult {
+        let mut new_change_id = change_id_generator();
+        let mut index = DefaultMutableIndex::full(TEST_FIELD_LENGTHS);
+        // 5
+        // |\
+        // 4 |
+        // | |
+        // 3 |
+        // | |
+        // 2 |
+        // |/
+        // 1
+        // |
+        // 0
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let id_3 = CommitId::from_hex("333333");
+        let id_4 = CommitId::from_hex("444444");
+        let id_5 = CommitId::from_hex("555555");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), &[id_0]);
+        index.add_commit_data(id_2.clone(), new_change_id(), &[id_1.clone()]);
+        index.add_commit_data(id_3.clone(), new_change_id(), &[id_2.clone()]);
+        index.add_commit_data(id_4.clone(), new_change_id(), &[id_3.clone()]);
+        index.add_commit_data(id_5.clone(), new_change_id(), &[id_4, id_1.clone()]);
+
+        // 1 is reachable from 5 without passing 3, but it's an ancestor of 3.
+        assert_eq!(
+            common_ancestors(&index, &[id_5.clone()], &[id_3.clone()]),
+            vec![id_3.clone()]
+        );
+        assert_eq!(
+            common_ancestors(&index, &[id_5], &[id_2.clone(), id_1]),
+            vec![id_2]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_heads() -> TestResult {
         let mut new_change_id = change_id_generator();
@@ -33,6 +33,8 @@ use tempfile::NamedTempFile;
 
 use super::changed_path::CompositeChangedPathIndex;
 use super::changed_path::collect_changed_paths;
+use super::commit_metadata::CommitMetadata;
+use super::commit_metadata::CompositeCommitMetadataIndex;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CommitIndexSegment;
@@ -49,6 +51,8 @@ use super::readonly::DefaultReadonlyIndex;
 use super::readonly::FieldLengths;
 use super::readonly::OVERFLOW_FLAG;
 use super::readonly::ReadonlyCommitIndexSegment;
+use super::trigram::CompositeTrigramIndex;
+use super::trigram::collect_changed_trigrams;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
@@ -70,6 +74,7 @@ use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
 use crate::store::Store;
+use crate::str_util::StringExpression;
 
 #[derive(Clone, Debug)]
 struct MutableGraphEntry {
@@ -460,10 +465,20 @@ pub struct DefaultMutableIndex(CompositeIndex);
 impl DefaultMutableIndex {
     pub(super) fn full(lengths: FieldLengths) -> Self {
         let commits = Box::new(MutableCommitIndexSegment::full(lengths));
-        // Changed-path index isn't enabled by default.
+        // Changed-path, commit-metadata, and trigram indexes aren't enabled by
+        // default.
         let mut changed_paths = CompositeChangedPathIndex::null();
         changed_paths.make_mutable();
-        Self(CompositeIndex::from_mutable(commits, changed_paths))
+        let mut commit_metadata = CompositeCommitMetadataIndex::null();
+        commit_metadata.make_mutable();
+        let mut trigrams = CompositeTrigramIndex::null();
+        trigrams.make_mutable();
+        Self(CompositeIndex::from_mutable(
+            commits,
+            changed_paths,
+            commit_metadata,
+            trigrams,
+        ))
     }
 
     pub(super) fn incremental(parent_index: &DefaultReadonlyIndex) -> Self {
@@ -472,12 +487,26 @@ impl DefaultMutableIndex {
         ));
         let mut changed_paths = parent_index.changed_paths().clone();
         changed_paths.make_mutable();
-        Self(CompositeIndex::from_mutable(commits, changed_paths))
+        let mut commit_metadata = parent_index.commit_metadata().clone();
+        commit_metadata.make_mutable();
+        let mut trigrams = parent_index.trigrams().clone();
+        trigrams.make_mutable();
+        Self(CompositeIndex::from_mutable(
+            commits,
+            changed_paths,
+            commit_metadata,
+            trigrams,
+        ))
     }
 
     pub(super) fn into_segment(
         self,
-    ) -> (Box<MutableCommitIndexSegment>, CompositeChangedPathIndex) {
+    ) -> (
+        Box<MutableCommitIndexSegment>,
+        CompositeChangedPathIndex,
+        CompositeCommitMetadataIndex,
+        CompositeTrigramIndex,
+    ) {
         self.0.into_mutable().expect("must have mutable")
     }
 
@@ -504,6 +533,15 @@ impl DefaultMutableIndex {
         if self.0.changed_paths().next_mutable_commit_pos() == Some(new_commit_pos) {
             self.add_commit_changed_paths(commit).await?;
         }
+        if self.0.commit_metadata().next_mutable_commit_pos() == Some(new_commit_pos) {
+            self.0
+                .commit_metadata_mut()
+                .add_commit_metadata(&CommitMetadata::from_commit(commit));
+        }
+        if self.0.trigrams().next_mutable_commit_pos() == Some(new_commit_pos) {
+            let trigrams = collect_changed_trigrams(self, commit).await?;
+            self.0.trigrams_mut().add_trigrams(trigrams);
+        }
         Ok(())
     }
 
@@ -540,6 +578,28 @@ impl DefaultMutableIndex {
                 self.0.changed_paths_mut().add_changed_paths(paths);
             }
         }
+        if self.0.commit_metadata().next_mutable_commit_pos() == Some(start_commit_pos) {
+            let other_commits = other.as_composite().commits();
+            for self_pos in (start_commit_pos.0..self.num_commits()).map(GlobalCommitPosition) {
+                let entry = self.0.commits().entry_by_pos(self_pos);
+                let other_pos = other_commits.commit_id_to_pos(&entry.commit_id()).unwrap();
+                let Some(metadata) = other.commit_metadata().metadata(other_pos) else {
+                    break; // no more indexed metadata in other index
+                };
+                self.0.commit_metadata_mut().add_commit_metadata(&metadata);
+            }
+        }
+        if self.0.trigrams().next_mutable_commit_pos() == Some(start_commit_pos) {
+            let other_commits = other.as_composite().commits();
+            for self_pos in (start_commit_pos.0..self.num_commits()).map(GlobalCommitPosition) {
+                let entry = self.0.commits().entry_by_pos(self_pos);
+                let other_pos = other_commits.commit_id_to_pos(&entry.commit_id()).unwrap();
+                let Some(trigrams) = other.trigrams().trigrams(other_pos) else {
+                    break; // no more indexed trigrams in other index
+                };
+                self.0.trigrams_mut().add_trigrams(trigrams.iter());
+            }
+        }
     }
 }
 
@@ -603,6 +663,14 @@ impl Index for DefaultMutableIndex {
         self.0.changed_paths_in_commit(commit_id).await
     }
 
+    async fn paths_maybe_containing_lines(
+        &self,
+        commit_id: &CommitId,
+        text: &StringExpression,
+    ) -> IndexResult<Option<Vec<RepoPathBuf>>> {
+        self.0.paths_maybe_containing_lines(commit_id, text).await
+    }
+
     fn evaluate_revset(
         &self,
         expression: &ResolvedExpression,
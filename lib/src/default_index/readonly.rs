@@ -32,6 +32,7 @@ use smallvec::smallvec;
 use thiserror::Error;
 
 use super::changed_path::CompositeChangedPathIndex;
+use super::commit_metadata::CompositeCommitMetadataIndex;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CommitIndexSegment;
@@ -45,6 +46,7 @@ use super::entry::SmallLocalCommitPositionsVec;
 use super::mutable::DefaultMutableIndex;
 use super::revset_engine;
 use super::revset_engine::RevsetImpl;
+use super::trigram::CompositeTrigramIndex;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::graph::GraphNode;
@@ -61,6 +63,7 @@ use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
 use crate::store::Store;
+use crate::str_util::StringExpression;
 
 /// Error while loading index segment file.
 #[derive(Debug, Error)]
@@ -617,8 +620,15 @@ impl DefaultReadonlyIndex {
     pub(super) fn from_segment(
         commits: Arc<ReadonlyCommitIndexSegment>,
         changed_paths: CompositeChangedPathIndex,
+        commit_metadata: CompositeCommitMetadataIndex,
+        trigrams: CompositeTrigramIndex,
     ) -> Self {
-        Self(CompositeIndex::from_readonly(commits, changed_paths))
+        Self(CompositeIndex::from_readonly(
+            commits,
+            changed_paths,
+            commit_metadata,
+            trigrams,
+        ))
     }
 
     pub(super) fn readonly_commits(&self) -> &Arc<ReadonlyCommitIndexSegment> {
@@ -629,6 +639,14 @@ impl DefaultReadonlyIndex {
         self.0.changed_paths()
     }
 
+    pub(super) fn commit_metadata(&self) -> &CompositeCommitMetadataIndex {
+        self.0.commit_metadata()
+    }
+
+    pub(super) fn trigrams(&self) -> &CompositeTrigramIndex {
+        self.0.trigrams()
+    }
+
     pub(super) fn has_id_impl(&self, commit_id: &CommitId) -> bool {
         self.0.commits().has_id(commit_id)
     }
@@ -677,6 +695,34 @@ impl DefaultReadonlyIndex {
             })
             .collect_vec();
 
+        let commit_metadata = self.commit_metadata();
+        let commit_metadata_commits_range = commit_metadata
+            .start_commit_pos()
+            .map(|GlobalCommitPosition(start)| start..(start + commit_metadata.num_commits()));
+        let commit_metadata_levels = commit_metadata
+            .readonly_segments()
+            .iter()
+            .map(|segment| CommitMetadataIndexLevelStats {
+                num_commits: segment.num_local_commits(),
+                num_text_bytes: segment.num_text_bytes(),
+                name: segment.id().hex(),
+            })
+            .collect_vec();
+
+        let trigrams = self.trigrams();
+        let trigram_commits_range = trigrams
+            .start_commit_pos()
+            .map(|GlobalCommitPosition(start)| start..(start + trigrams.num_commits()));
+        let trigram_levels = trigrams
+            .readonly_segments()
+            .iter()
+            .map(|segment| TrigramIndexLevelStats {
+                num_commits: segment.num_local_commits(),
+                num_trigrams: segment.num_trigrams(),
+                name: segment.id().hex(),
+            })
+            .collect_vec();
+
         IndexStats {
             num_commits,
             num_merges,
@@ -686,6 +732,10 @@ impl DefaultReadonlyIndex {
             commit_levels,
             changed_path_commits_range,
             changed_path_levels,
+            commit_metadata_commits_range,
+            commit_metadata_levels,
+            trigram_commits_range,
+            trigram_levels,
         }
     }
 
@@ -770,6 +820,14 @@ impl Index for DefaultReadonlyIndex {
         self.0.changed_paths_in_commit(commit_id).await
     }
 
+    async fn paths_maybe_containing_lines(
+        &self,
+        commit_id: &CommitId,
+        text: &StringExpression,
+    ) -> IndexResult<Option<Vec<RepoPathBuf>>> {
+        self.0.paths_maybe_containing_lines(commit_id, text).await
+    }
+
     fn evaluate_revset(
         &self,
         expression: &ResolvedExpression,
@@ -825,6 +883,10 @@ pub struct IndexStats {
     pub commit_levels: Vec<CommitIndexLevelStats>,
     pub changed_path_commits_range: Option<Range<u32>>,
     pub changed_path_levels: Vec<ChangedPathIndexLevelStats>,
+    pub commit_metadata_commits_range: Option<Range<u32>>,
+    pub commit_metadata_levels: Vec<CommitMetadataIndexLevelStats>,
+    pub trigram_commits_range: Option<Range<u32>>,
+    pub trigram_levels: Vec<TrigramIndexLevelStats>,
 }
 
 #[derive(Clone, Debug)]
@@ -845,6 +907,26 @@ pub struct ChangedPathIndexLevelStats {
     pub name: String,
 }
 
+#[derive(Clone, Debug)]
+pub struct CommitMetadataIndexLevelStats {
+    /// Number of commits.
+    pub num_commits: u32,
+    /// Total size of descriptions and author/committer identities.
+    pub num_text_bytes: u32,
+    /// Index file name.
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrigramIndexLevelStats {
+    /// Number of commits.
+    pub num_commits: u32,
+    /// Sum of number of per-commit trigrams.
+    pub num_trigrams: u32,
+    /// Index file name.
+    pub name: String,
+}
+
 /// Binary search result in a sorted lookup table.
 #[derive(Clone, Copy, Debug)]
 struct PositionLookupResult {
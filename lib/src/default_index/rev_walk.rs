@@ -14,6 +14,7 @@
 
 use std::cmp::Reverse;
 use std::cmp::max;
+use std::cmp::min;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::iter::Fuse;
@@ -247,6 +248,12 @@ pub(super) trait RevWalkIndex {
     type AdjacentPositions: IntoIterator<Item = Self::Position> + AsRef<[Self::Position]>;
 
     fn adjacent_positions(&self, pos: Self::Position) -> Self::AdjacentPositions;
+
+    /// Returns the generation number of the entry, or `None` if the walk can't
+    /// be pruned by generation number.
+    fn generation_number(&self, _pos: Self::Position) -> Option<u32> {
+        None
+    }
 }
 
 impl RevWalkIndex for CompositeIndex {
@@ -256,6 +263,10 @@ impl RevWalkIndex for CompositeIndex {
     fn adjacent_positions(&self, pos: Self::Position) -> Self::AdjacentPositions {
         self.commits().entry_by_pos(pos).parent_positions()
     }
+
+    fn generation_number(&self, pos: Self::Position) -> Option<u32> {
+        Some(self.commits().entry_by_pos(pos).generation_number())
+    }
 }
 
 #[derive(Clone)]
@@ -339,10 +350,14 @@ impl<'a> RevWalkBuilder<'a> {
 
     /// Walks ancestors.
     pub fn ancestors(self) -> RevWalkAncestors<'a> {
-        self.ancestors_with_min_pos(GlobalCommitPosition::MIN)
+        self.ancestors_with_min_pos(GlobalCommitPosition::MIN, 0)
     }
 
-    fn ancestors_with_min_pos(self, min_pos: GlobalCommitPosition) -> RevWalkAncestors<'a> {
+    fn ancestors_with_min_pos(
+        self,
+        min_pos: GlobalCommitPosition,
+        min_generation: u32,
+    ) -> RevWalkAncestors<'a> {
         let index = self.index;
         let wanted_parents_range = self.wanted_parents_range;
         let mut wanted_queue = RevWalkQueue::with_min_pos(min_pos);
@@ -355,6 +370,7 @@ impl<'a> RevWalkBuilder<'a> {
                 wanted_queue,
                 unwanted_queue,
                 wanted_parents_range,
+                min_generation,
             },
         }
     }
@@ -389,18 +405,24 @@ impl<'a> RevWalkBuilder<'a> {
     ///
     /// Use this if you are only interested in descendants of the given roots.
     /// The caller still needs to filter out unwanted entries.
+    ///
+    /// Entries whose generation number is smaller than the minimum generation
+    /// of the roots can't be descendants of the roots, so the walk doesn't
+    /// visit their ancestors. This prunes long side branches that forked
+    /// before the roots.
     pub fn ancestors_until_roots(
         self,
         root_positions: impl IntoIterator<Item = GlobalCommitPosition>,
     ) -> RevWalkAncestors<'a> {
-        // We can also make it stop visiting based on the generation number. Maybe
-        // it will perform better for unbalanced branchy history.
-        // https://github.com/jj-vcs/jj/pull/1492#discussion_r1160678325
-        let min_pos = root_positions
+        let (min_pos, min_generation) = root_positions
             .into_iter()
-            .min()
-            .unwrap_or(GlobalCommitPosition::MAX);
-        self.ancestors_with_min_pos(min_pos)
+            .map(|pos| {
+                let generation = self.index.commits().entry_by_pos(pos).generation_number();
+                (pos, generation)
+            })
+            .reduce(|(pos1, gen1), (pos2, gen2)| (min(pos1, pos2), min(gen1, gen2)))
+            .unwrap_or((GlobalCommitPosition::MAX, 0));
+        self.ancestors_with_min_pos(min_pos, min_generation)
     }
 
     /// Fully consumes ancestors and walks back from the `root_positions`.
@@ -473,6 +495,8 @@ pub(super) struct RevWalkImpl<P> {
     wanted_queue: RevWalkQueue<P, ()>,
     unwanted_queue: RevWalkQueue<P, ()>,
     wanted_parents_range: Range<u32>,
+    // Ancestors of entries of lower generation aren't visited.
+    min_generation: u32,
 }
 
 impl<I: RevWalkIndex + ?Sized> RevWalk<I> for RevWalkImpl<I::Position> {
@@ -484,13 +508,19 @@ impl<I: RevWalkIndex + ?Sized> RevWalk<I> for RevWalkImpl<I::Position> {
             if flush_queue_until(&mut self.unwanted_queue, index, item.pos).is_some() {
                 continue;
             }
-            let parents = index.adjacent_positions(item.pos);
-            self.wanted_queue.extend(
-                filter_slice_by_range(parents.as_ref(), &self.wanted_parents_range)
-                    .iter()
-                    .copied(),
-                (),
-            );
+            let is_below_min_generation = self.min_generation > 0
+                && index
+                    .generation_number(item.pos)
+                    .is_some_and(|generation| generation < self.min_generation);
+            if !is_below_min_generation {
+                let parents = index.adjacent_positions(item.pos);
+                self.wanted_queue.extend(
+                    filter_slice_by_range(parents.as_ref(), &self.wanted_parents_range)
+                        .iter()
+                        .copied(),
+                    (),
+                );
+            }
             return Some(item.pos);
         }
         None
@@ -903,6 +933,69 @@ mod tests {
         assert_eq!(iter.walk.wanted_queue.len(), 0); // no ids should be queued
     }
 
+    #[test]
+    fn test_walk_ancestors_until_roots_pruned_by_generation() {
+        let mut new_change_id = change_id_generator();
+        let mut index = DefaultMutableIndex::full(TEST_FIELD_LENGTHS);
+        // 7
+        // 6
+        // 5
+        // 4
+        // | 3
+        // | 2
+        // | 1
+        // |/
+        // 0
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let id_3 = CommitId::from_hex("333333");
+        let id_4 = CommitId::from_hex("444444");
+        let id_5 = CommitId::from_hex("555555");
+        let id_6 = CommitId::from_hex("666666");
+        let id_7 = CommitId::from_hex("777777");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_2.clone(), new_change_id(), &[id_1.clone()]);
+        index.add_commit_data(id_3.clone(), new_change_id(), &[id_2.clone()]);
+        index.add_commit_data(id_4.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_5.clone(), new_change_id(), &[id_4.clone()]);
+        index.add_commit_data(id_6.clone(), new_change_id(), &[id_5.clone()]);
+        index.add_commit_data(id_7.clone(), new_change_id(), &[id_6.clone()]);
+
+        let index = index.as_composite();
+        let make_iter = |heads: &[CommitId], roots: &[CommitId]| {
+            RevWalkBuilder::new(index)
+                .wanted_heads(to_positions_vec(index, heads))
+                .ancestors_until_roots(to_positions_vec(index, roots))
+        };
+        let to_commit_id = |pos| index.commits().entry_by_pos(pos).commit_id();
+
+        // id_4 is above the root position, but its generation is lower than
+        // id_3's, so it can't be a descendant of id_3.
+        let mut iter = make_iter(&[id_7.clone()], &[id_3.clone()]);
+        assert_eq!(iter.next().map(to_commit_id), Some(id_7.clone()));
+        assert_eq!(iter.next().map(to_commit_id), Some(id_6.clone()));
+        assert_eq!(iter.next().map(to_commit_id), Some(id_5.clone()));
+        assert_eq!(iter.walk.wanted_queue.len(), 0); // id_4 shouldn't be queued
+        assert!(iter.next().is_none());
+
+        // Without roots, all ancestors are visited.
+        let iter = RevWalkBuilder::new(index)
+            .wanted_heads(to_positions_vec(index, &[id_7.clone()]))
+            .ancestors();
+        assert_eq!(
+            iter.map(to_commit_id).collect_vec(),
+            vec![
+                id_7.clone(),
+                id_6.clone(),
+                id_5.clone(),
+                id_4.clone(),
+                id_0.clone(),
+            ]
+        );
+    }
+
     #[test]
     fn test_walk_ancestors_filtered_by_generation() {
         let mut new_change_id = change_id_generator();
@@ -40,10 +40,12 @@ use super::rev_walk::PeekableRevWalk;
 use super::rev_walk::RevWalk;
 use super::rev_walk::RevWalkBuilder;
 use super::revset_graph_iterator::RevsetGraphWalk;
+use super::trigram::TrigramQuery;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
+use crate::codeowners::CodeOwners;
 use crate::commit::Commit;
 use crate::conflict_labels::ConflictLabels;
 use crate::conflicts::MaterializedTreeValue;
@@ -53,6 +55,7 @@ use crate::diff::ContentDiff;
 use crate::diff::DiffHunkKind;
 use crate::files;
 use crate::graph::GraphNode;
+use crate::matchers::EverythingMatcher;
 use crate::matchers::FilesMatcher;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
@@ -61,6 +64,7 @@ use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId as _;
 use crate::object_id::PrefixResolution;
 use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::DiffMatchSide;
 use crate::revset::GENERATION_RANGE_FULL;
 use crate::revset::ResolvedExpression;
@@ -1310,6 +1314,9 @@ fn build_predicate_fn(
         RevsetFilterPredicate::Description(expression) => {
             let matcher = Rc::new(expression.to_matcher());
             box_pure_predicate_fn(move |index, pos| {
+                if let Some(metadata) = index.commit_metadata().metadata(pos) {
+                    return Ok(matcher.is_match(metadata.description));
+                }
                 let entry = index.commits().entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matcher.is_match(commit.description()))
@@ -1318,6 +1325,11 @@ fn build_predicate_fn(
         RevsetFilterPredicate::Subject(expression) => {
             let matcher = Rc::new(expression.to_matcher());
             box_pure_predicate_fn(move |index, pos| {
+                if let Some(metadata) = index.commit_metadata().metadata(pos) {
+                    return Ok(
+                        matcher.is_match(metadata.description.lines().next().unwrap_or_default())
+                    );
+                }
                 let entry = index.commits().entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matcher.is_match(commit.description().lines().next().unwrap_or_default()))
@@ -1326,6 +1338,9 @@ fn build_predicate_fn(
         RevsetFilterPredicate::AuthorName(expression) => {
             let matcher = Rc::new(expression.to_matcher());
             box_pure_predicate_fn(move |index, pos| {
+                if let Some(metadata) = index.commit_metadata().metadata(pos) {
+                    return Ok(matcher.is_match(metadata.author_name));
+                }
                 let entry = index.commits().entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matcher.is_match(&commit.author().name))
@@ -1334,6 +1349,9 @@ fn build_predicate_fn(
         RevsetFilterPredicate::AuthorEmail(expression) => {
             let matcher = Rc::new(expression.to_matcher());
             box_pure_predicate_fn(move |index, pos| {
+                if let Some(metadata) = index.commit_metadata().metadata(pos) {
+                    return Ok(matcher.is_match(metadata.author_email));
+                }
                 let entry = index.commits().entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matcher.is_match(&commit.author().email))
@@ -1351,6 +1369,9 @@ fn build_predicate_fn(
         RevsetFilterPredicate::CommitterName(expression) => {
             let matcher = Rc::new(expression.to_matcher());
             box_pure_predicate_fn(move |index, pos| {
+                if let Some(metadata) = index.commit_metadata().metadata(pos) {
+                    return Ok(matcher.is_match(metadata.committer_name));
+                }
                 let entry = index.commits().entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matcher.is_match(&commit.committer().name))
@@ -1359,6 +1380,9 @@ fn build_predicate_fn(
         RevsetFilterPredicate::CommitterEmail(expression) => {
             let matcher = Rc::new(expression.to_matcher());
             box_pure_predicate_fn(move |index, pos| {
+                if let Some(metadata) = index.commit_metadata().metadata(pos) {
+                    return Ok(matcher.is_match(metadata.committer_email));
+                }
                 let entry = index.commits().entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matcher.is_match(&commit.committer().email))
@@ -1386,9 +1410,16 @@ fn build_predicate_fn(
         }
         RevsetFilterPredicate::DiffLines { text, files, side } => {
             let text_matcher = Rc::new(text.to_matcher());
+            let text_query = Rc::new(TrigramQuery::from_string_expression(text));
             let files_matcher: Rc<dyn Matcher> = files.to_matcher().into();
             let side = *side;
             box_pure_predicate_fn(move |index, pos| {
+                if !text_query.is_all()
+                    && let Some(trigrams) = index.trigrams().trigrams(pos)
+                    && !text_query.may_match(trigrams)
+                {
+                    return Ok(false);
+                }
                 let narrowed_files_matcher;
                 let files_matcher = if let Some(paths) = index.changed_paths().changed_paths(pos) {
                     let matched_paths = paths
@@ -1420,6 +1451,25 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id())?;
             Ok(commit.has_conflict())
         }),
+        RevsetFilterPredicate::TouchesOwner(expression) => {
+            let matcher = Rc::new(expression.to_matcher());
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.commits().entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let paths = if let Some(paths) = index.changed_paths().changed_paths(pos) {
+                    paths.map(ToOwned::to_owned).collect_vec()
+                } else {
+                    changed_paths_from_parent(&store, index, &commit).block_on()?
+                };
+                if paths.is_empty() {
+                    return Ok(false);
+                }
+                let codeowners = CodeOwners::load(&commit.tree()).block_on()?;
+                Ok(paths
+                    .iter()
+                    .any(|path| codeowners.is_owned_by(path, |owner| matcher.is_match(owner))))
+            })
+        }
         RevsetFilterPredicate::Signed => box_pure_predicate_fn(move |index, pos| {
             let entry = index.commits().entry_by_pos(pos);
             let commit = store.get_commit(&entry.commit_id())?;
@@ -1471,6 +1521,27 @@ async fn has_diff_from_parent(
     Ok(false)
 }
 
+async fn changed_paths_from_parent(
+    store: &Arc<Store>,
+    index: &CompositeIndex,
+    commit: &Commit,
+) -> BackendResult<Vec<RepoPathBuf>> {
+    let parents = commit.parents().await?;
+    let from_tree =
+        rewrite::merge_commit_trees_no_resolve_without_repo(store, index, &parents).await?;
+    let to_tree = commit.tree();
+    let mut tree_diff = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    let mut paths = Vec::new();
+    while let Some(entry) = tree_diff.next().await {
+        let mut values = entry.values?;
+        values.before = resolve_file_values(store, &entry.path, values.before).await?;
+        if values.is_changed() {
+            paths.push(entry.path);
+        }
+    }
+    Ok(paths)
+}
+
 async fn matches_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
@@ -1549,7 +1620,7 @@ fn diff_match_lines(
     }
 }
 
-async fn to_file_content(
+pub(super) async fn to_file_content(
     path: &RepoPath,
     value: MaterializedTreeValue,
 ) -> BackendResult<Merge<BString>> {
@@ -1581,8 +1652,10 @@ mod tests {
     use super::*;
     use crate::default_index::DefaultMutableIndex;
     use crate::default_index::readonly::FieldLengths;
+    use crate::diff::DiffAlgorithm;
     use crate::files::FileMergeHunkLevel;
     use crate::merge::SameChange;
+    use crate::merge_driver::MergeDrivers;
     use crate::str_util::StringPattern;
     use crate::tests::TestResult;
 
@@ -1972,6 +2045,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let diff = |needle: &str| {
             let matcher = StringPattern::substring(needle).to_matcher();
@@ -2037,6 +2113,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let diff = |needle: &str| {
             let matcher = StringPattern::substring(needle).to_matcher();
@@ -2127,6 +2206,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let diff = |needle: &str| {
             let matcher = StringPattern::substring(needle).to_matcher();
@@ -2201,6 +2283,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let diff = |needle: &str| {
             let matcher = StringPattern::substring(needle).to_matcher();
@@ -37,6 +37,9 @@ use thiserror::Error;
 use super::changed_path::ChangedPathIndexSegmentId;
 use super::changed_path::CompositeChangedPathIndex;
 use super::changed_path::collect_changed_paths;
+use super::commit_metadata::CommitMetadata;
+use super::commit_metadata::CommitMetadataIndexSegmentId;
+use super::commit_metadata::CompositeCommitMetadataIndex;
 use super::composite::AsCompositeIndex as _;
 use super::composite::CommitIndexSegmentId;
 use super::entry::GlobalCommitPosition;
@@ -45,6 +48,9 @@ use super::readonly::DefaultReadonlyIndex;
 use super::readonly::FieldLengths;
 use super::readonly::ReadonlyCommitIndexSegment;
 use super::readonly::ReadonlyIndexLoadError;
+use super::trigram::CompositeTrigramIndex;
+use super::trigram::TrigramIndexSegmentId;
+use super::trigram::collect_changed_trigrams;
 use crate::backend::BackendError;
 use crate::backend::BackendInitError;
 use crate::backend::CommitId;
@@ -140,6 +146,8 @@ impl DefaultIndexStore {
         // will be created by the other process.
         file_util::remove_dir_contents(&self.commit_segments_dir())?;
         file_util::remove_dir_contents(&self.changed_path_segments_dir())?;
+        file_util::remove_dir_contents(&self.commit_metadata_segments_dir())?;
+        file_util::remove_dir_contents(&self.trigram_segments_dir())?;
         // jj <= 0.14 created segment files in the top directory
         for entry in self.dir.read_dir().context(&self.dir)? {
             let entry = entry.context(&self.dir)?;
@@ -158,6 +166,8 @@ impl DefaultIndexStore {
             self.op_links_dir(),
             self.commit_segments_dir(),
             self.changed_path_segments_dir(),
+            self.commit_metadata_segments_dir(),
+            self.trigram_segments_dir(),
         ] {
             file_util::create_or_reuse_dir(&dir).context(&dir)?;
         }
@@ -179,6 +189,16 @@ impl DefaultIndexStore {
         self.dir.join("changed_paths")
     }
 
+    /// Directory for commit-metadata segment files.
+    fn commit_metadata_segments_dir(&self) -> PathBuf {
+        self.dir.join("commit_metadata")
+    }
+
+    /// Directory for trigram segment files.
+    fn trigram_segments_dir(&self) -> PathBuf {
+        self.dir.join("trigrams")
+    }
+
     fn load_index_at_operation(
         &self,
         op_id: &OperationId,
@@ -201,6 +221,20 @@ impl DefaultIndexStore {
             .into_iter()
             .map(ChangedPathIndexSegmentId::new)
             .collect_vec();
+        let commit_metadata_start_commit_pos = proto
+            .commit_metadata_start_commit_pos
+            .map(GlobalCommitPosition);
+        let commit_metadata_segment_ids = proto
+            .commit_metadata_segment_ids
+            .into_iter()
+            .map(CommitMetadataIndexSegmentId::new)
+            .collect_vec();
+        let trigram_start_commit_pos = proto.trigram_start_commit_pos.map(GlobalCommitPosition);
+        let trigram_segment_ids = proto
+            .trigram_segment_ids
+            .into_iter()
+            .map(TrigramIndexSegmentId::new)
+            .collect_vec();
 
         let commits = ReadonlyCommitIndexSegment::load(
             &self.commit_segments_dir(),
@@ -219,7 +253,32 @@ impl DefaultIndexStore {
         } else {
             CompositeChangedPathIndex::null()
         };
-        Ok(DefaultReadonlyIndex::from_segment(commits, changed_paths))
+        let commit_metadata = if let Some(start_commit_pos) = commit_metadata_start_commit_pos {
+            CompositeCommitMetadataIndex::load(
+                &self.commit_metadata_segments_dir(),
+                start_commit_pos,
+                &commit_metadata_segment_ids,
+            )
+            .map_err(DefaultIndexStoreError::LoadIndex)?
+        } else {
+            CompositeCommitMetadataIndex::null()
+        };
+        let trigrams = if let Some(start_commit_pos) = trigram_start_commit_pos {
+            CompositeTrigramIndex::load(
+                &self.trigram_segments_dir(),
+                start_commit_pos,
+                &trigram_segment_ids,
+            )
+            .map_err(DefaultIndexStoreError::LoadIndex)?
+        } else {
+            CompositeTrigramIndex::null()
+        };
+        Ok(DefaultReadonlyIndex::from_segment(
+            commits,
+            changed_paths,
+            commit_metadata,
+            trigrams,
+        ))
     }
 
     /// Rebuilds index for the given `operation`.
@@ -367,25 +426,12 @@ impl DefaultIndexStore {
         let index = self.load_index_at_operation(op_id, field_lengths)?;
         let old_changed_paths = index.changed_paths();
 
-        // Distribute max_commits to contiguous pre/post ranges:
-        //   ..|pre|old_changed_paths|post|
-        //   (where pre.len() + post.len() <= max_commits)
-        let pre_start;
-        let pre_end;
-        let post_start;
-        let post_end;
-        if let Some(GlobalCommitPosition(pos)) = old_changed_paths.start_commit_pos() {
-            post_start = pos + old_changed_paths.num_commits();
-            assert!(post_start <= index.num_commits());
-            post_end = u32::saturating_add(post_start, max_commits).min(index.num_commits());
-            pre_start = u32::saturating_sub(pos, max_commits - (post_end - post_start));
-            pre_end = pos;
-        } else {
-            pre_start = u32::saturating_sub(index.num_commits(), max_commits);
-            pre_end = index.num_commits();
-            post_start = pre_end;
-            post_end = pre_end;
-        }
+        let [pre_start, pre_end, post_start, post_end] = split_unindexed_range(
+            old_changed_paths.start_commit_pos(),
+            old_changed_paths.num_commits(),
+            index.num_commits(),
+            max_commits,
+        );
 
         let mut progress = DefaultChangedPathIndexProgress {
             current: 0,
@@ -444,7 +490,215 @@ impl DefaultIndexStore {
 
         // Update the operation link to point to the new segments
         let commits = index.readonly_commits().clone();
-        let index = DefaultReadonlyIndex::from_segment(commits, new_changed_paths);
+        let commit_metadata = index.commit_metadata().clone();
+        let trigrams = index.trigrams().clone();
+        let index = DefaultReadonlyIndex::from_segment(
+            commits,
+            new_changed_paths,
+            commit_metadata,
+            trigrams,
+        );
+        self.associate_index_with_operation(&index, op_id)
+            .map_err(|source| DefaultIndexStoreError::AssociateIndex {
+                op_id: op_id.to_owned(),
+                source,
+            })?;
+        emit_progress();
+        Ok(index)
+    }
+
+    /// Builds commit-metadata index for the specified operation.
+    ///
+    /// At most `max_commits` number of commits will be scanned from the latest
+    /// unindexed commit.
+    #[tracing::instrument(skip(self, store, progress_callback))]
+    pub async fn build_commit_metadata_index_at_operation(
+        &self,
+        op_id: &OperationId,
+        store: &Arc<Store>,
+        max_commits: u32,
+        mut progress_callback: impl FnMut(&DefaultCommitMetadataIndexProgress),
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        // Create directories in case the store was initialized by old jj.
+        self.ensure_base_dirs()
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        let field_lengths = FieldLengths {
+            commit_id: store.commit_id_length(),
+            change_id: store.change_id_length(),
+        };
+        let index = self.load_index_at_operation(op_id, field_lengths)?;
+        let old_commit_metadata = index.commit_metadata();
+        let [pre_start, pre_end, post_start, post_end] = split_unindexed_range(
+            old_commit_metadata.start_commit_pos(),
+            old_commit_metadata.num_commits(),
+            index.num_commits(),
+            max_commits,
+        );
+
+        let mut progress = DefaultCommitMetadataIndexProgress {
+            current: 0,
+            total: (pre_end - pre_start) + (post_end - post_start),
+        };
+        let mut emit_progress = || {
+            progress_callback(&progress);
+            progress.current += 1;
+        };
+
+        let to_index_err = |source| DefaultIndexStoreError::IndexCommits {
+            op_id: op_id.clone(),
+            source,
+        };
+        let index_commit = async |commit_metadata: &mut CompositeCommitMetadataIndex,
+                                  pos: GlobalCommitPosition| {
+            assert_eq!(commit_metadata.next_mutable_commit_pos(), Some(pos));
+            let commit_id = index.as_composite().commits().entry_by_pos(pos).commit_id();
+            let commit = store.get_commit_async(&commit_id).await?;
+            commit_metadata.add_commit_metadata(&CommitMetadata::from_commit(&commit));
+            Ok(())
+        };
+
+        // Index pre range
+        let mut new_commit_metadata =
+            CompositeCommitMetadataIndex::empty(GlobalCommitPosition(pre_start));
+        new_commit_metadata.make_mutable();
+        tracing::info!(?pre_start, ?pre_end, "indexing metadata of commits");
+        for pos in (pre_start..pre_end).map(GlobalCommitPosition) {
+            emit_progress();
+            index_commit(&mut new_commit_metadata, pos)
+                .await
+                .map_err(to_index_err)?;
+        }
+        new_commit_metadata
+            .save_in(&self.commit_metadata_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+
+        // Copy previously-indexed segments
+        new_commit_metadata.append_segments(old_commit_metadata);
+
+        // Index post range, which is usually empty
+        new_commit_metadata.make_mutable();
+        tracing::info!(?post_start, ?post_end, "indexing metadata of commits");
+        for pos in (post_start..post_end).map(GlobalCommitPosition) {
+            emit_progress();
+            index_commit(&mut new_commit_metadata, pos)
+                .await
+                .map_err(to_index_err)?;
+        }
+        new_commit_metadata.maybe_squash_with_ancestors();
+        new_commit_metadata
+            .save_in(&self.commit_metadata_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+
+        // Update the operation link to point to the new segments
+        let commits = index.readonly_commits().clone();
+        let changed_paths = index.changed_paths().clone();
+        let trigrams = index.trigrams().clone();
+        let index = DefaultReadonlyIndex::from_segment(
+            commits,
+            changed_paths,
+            new_commit_metadata,
+            trigrams,
+        );
+        self.associate_index_with_operation(&index, op_id)
+            .map_err(|source| DefaultIndexStoreError::AssociateIndex {
+                op_id: op_id.to_owned(),
+                source,
+            })?;
+        emit_progress();
+        Ok(index)
+    }
+
+    /// Builds trigram index for the specified operation.
+    ///
+    /// At most `max_commits` number of commits will be scanned from the latest
+    /// unindexed commit.
+    #[tracing::instrument(skip(self, store, progress_callback))]
+    pub async fn build_trigram_index_at_operation(
+        &self,
+        op_id: &OperationId,
+        store: &Arc<Store>,
+        max_commits: u32,
+        mut progress_callback: impl FnMut(&DefaultTrigramIndexProgress),
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        // Create directories in case the store was initialized by old jj.
+        self.ensure_base_dirs()
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        let field_lengths = FieldLengths {
+            commit_id: store.commit_id_length(),
+            change_id: store.change_id_length(),
+        };
+        let index = self.load_index_at_operation(op_id, field_lengths)?;
+        let old_trigrams = index.trigrams();
+        let [pre_start, pre_end, post_start, post_end] = split_unindexed_range(
+            old_trigrams.start_commit_pos(),
+            old_trigrams.num_commits(),
+            index.num_commits(),
+            max_commits,
+        );
+
+        let mut progress = DefaultTrigramIndexProgress {
+            current: 0,
+            total: (pre_end - pre_start) + (post_end - post_start),
+        };
+        let mut emit_progress = || {
+            progress_callback(&progress);
+            progress.current += 1;
+        };
+
+        let to_index_err = |source| DefaultIndexStoreError::IndexCommits {
+            op_id: op_id.clone(),
+            source,
+        };
+        let index_commit = async |trigrams: &mut CompositeTrigramIndex,
+                                  pos: GlobalCommitPosition| {
+            assert_eq!(trigrams.next_mutable_commit_pos(), Some(pos));
+            let commit_id = index.as_composite().commits().entry_by_pos(pos).commit_id();
+            let commit = store.get_commit_async(&commit_id).await?;
+            trigrams.add_trigrams(collect_changed_trigrams(&index, &commit).await?);
+            Ok(())
+        };
+
+        // Index pre range
+        let mut new_trigrams = CompositeTrigramIndex::empty(GlobalCommitPosition(pre_start));
+        new_trigrams.make_mutable();
+        tracing::info!(?pre_start, ?pre_end, "indexing trigrams of commits");
+        for pos in (pre_start..pre_end).map(GlobalCommitPosition) {
+            emit_progress();
+            index_commit(&mut new_trigrams, pos)
+                .await
+                .map_err(to_index_err)?;
+        }
+        new_trigrams
+            .save_in(&self.trigram_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+
+        // Copy previously-indexed segments
+        new_trigrams.append_segments(old_trigrams);
+
+        // Index post range, which is usually empty
+        new_trigrams.make_mutable();
+        tracing::info!(?post_start, ?post_end, "indexing trigrams of commits");
+        for pos in (post_start..post_end).map(GlobalCommitPosition) {
+            emit_progress();
+            index_commit(&mut new_trigrams, pos)
+                .await
+                .map_err(to_index_err)?;
+        }
+        new_trigrams.maybe_squash_with_ancestors();
+        new_trigrams
+            .save_in(&self.trigram_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+
+        // Update the operation link to point to the new segments
+        let commits = index.readonly_commits().clone();
+        let changed_paths = index.changed_paths().clone();
+        let commit_metadata = index.commit_metadata().clone();
+        let index = DefaultReadonlyIndex::from_segment(
+            commits,
+            changed_paths,
+            commit_metadata,
+            new_trigrams,
+        );
         self.associate_index_with_operation(&index, op_id)
             .map_err(|source| DefaultIndexStoreError::AssociateIndex {
                 op_id: op_id.to_owned(),
@@ -462,7 +716,7 @@ impl DefaultIndexStore {
         // Create directories in case the store was initialized by jj < 0.33.
         self.ensure_base_dirs()
             .map_err(DefaultIndexStoreError::SaveIndex)?;
-        let (commits, mut changed_paths) = index.into_segment();
+        let (commits, mut changed_paths, mut commit_metadata, mut trigrams) = index.into_segment();
         let commits = commits
             .maybe_squash_with_ancestors()
             .save_in(&self.commit_segments_dir())
@@ -471,7 +725,16 @@ impl DefaultIndexStore {
         changed_paths
             .save_in(&self.changed_path_segments_dir())
             .map_err(DefaultIndexStoreError::SaveIndex)?;
-        let index = DefaultReadonlyIndex::from_segment(commits, changed_paths);
+        commit_metadata.maybe_squash_with_ancestors();
+        commit_metadata
+            .save_in(&self.commit_metadata_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        trigrams.maybe_squash_with_ancestors();
+        trigrams
+            .save_in(&self.trigram_segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        let index =
+            DefaultReadonlyIndex::from_segment(commits, changed_paths, commit_metadata, trigrams);
         self.associate_index_with_operation(&index, op_id)
             .map_err(|source| DefaultIndexStoreError::AssociateIndex {
                 op_id: op_id.to_owned(),
@@ -498,6 +761,26 @@ impl DefaultIndexStore {
                 .iter()
                 .map(|segment| segment.id().to_bytes())
                 .collect(),
+            commit_metadata_start_commit_pos: index
+                .commit_metadata()
+                .start_commit_pos()
+                .map(|GlobalCommitPosition(start)| start),
+            commit_metadata_segment_ids: index
+                .commit_metadata()
+                .readonly_segments()
+                .iter()
+                .map(|segment| segment.id().to_bytes())
+                .collect(),
+            trigram_start_commit_pos: index
+                .trigrams()
+                .start_commit_pos()
+                .map(|GlobalCommitPosition(start)| start),
+            trigram_segment_ids: index
+                .trigrams()
+                .readonly_segments()
+                .iter()
+                .map(|segment| segment.id().to_bytes())
+                .collect(),
         };
         let dir = self.op_links_dir();
         let mut temp_file = NamedTempFile::new_in(&dir).context(&dir)?;
@@ -574,9 +857,52 @@ impl IndexStore for DefaultIndexStore {
     }
 }
 
+/// Distributes `max_commits` to contiguous pre/post ranges of the auxiliary
+/// index which has `num_indexed` commits from `indexed_start`:
+///
+/// ```text
+/// ..|pre|indexed|post|
+/// (where pre.len() + post.len() <= max_commits)
+/// ```
+///
+/// Returns `[pre_start, pre_end, post_start, post_end]`.
+fn split_unindexed_range(
+    indexed_start: Option<GlobalCommitPosition>,
+    num_indexed: u32,
+    num_commits: u32,
+    max_commits: u32,
+) -> [u32; 4] {
+    if let Some(GlobalCommitPosition(pos)) = indexed_start {
+        let post_start = pos + num_indexed;
+        assert!(post_start <= num_commits);
+        let post_end = u32::saturating_add(post_start, max_commits).min(num_commits);
+        let pre_start = u32::saturating_sub(pos, max_commits - (post_end - post_start));
+        let pre_end = pos;
+        [pre_start, pre_end, post_start, post_end]
+    } else {
+        let pre_start = u32::saturating_sub(num_commits, max_commits);
+        let pre_end = num_commits;
+        [pre_start, pre_end, pre_end, pre_end]
+    }
+}
+
 /// Progress of [`DefaultIndexStore::build_changed_path_index_at_operation()`].
 #[derive(Clone, Debug)]
 pub struct DefaultChangedPathIndexProgress {
     pub current: u32,
     pub total: u32,
 }
+
+/// Progress of [`DefaultIndexStore::build_commit_metadata_index_at_operation()`].
+#[derive(Clone, Debug)]
+pub struct DefaultCommitMetadataIndexProgress {
+    pub current: u32,
+    pub total: u32,
+}
+
+/// Progress of [`DefaultIndexStore::build_trigram_index_at_operation()`].
+#[derive(Clone, Debug)]
+pub struct DefaultTrigramIndexProgress {
+    pub current: u32,
+    pub total: u32,
+}
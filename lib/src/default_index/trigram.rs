@@ -0,0 +1,946 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Index of trigrams in lines added or removed by each commit.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write as _;
+use std::mem;
+use std::path::Path;
+use std::sync::Arc;
+
+use blake2::Blake2b512;
+use digest::Digest as _;
+use either::Either;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use tempfile::NamedTempFile;
+
+use super::entry::GlobalCommitPosition;
+use super::readonly::ReadonlyIndexLoadError;
+use super::revset_engine::to_file_content;
+use crate::backend::BackendResult;
+use crate::commit::Commit;
+use crate::conflict_labels::ConflictLabels;
+use crate::conflicts::materialize_tree_value;
+use crate::diff::ContentDiff;
+use crate::diff::DiffHunkKind;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::index::Index;
+use crate::matchers::EverythingMatcher;
+use crate::object_id::ObjectId as _;
+use crate::object_id::id_type;
+use crate::rewrite::merge_commit_trees_no_resolve_without_repo;
+use crate::str_util::StringExpression;
+use crate::str_util::StringPattern;
+use crate::tree_merge::resolve_file_values;
+
+/// Current format version of the trigram index segment file.
+const FILE_FORMAT_VERSION: u32 = 0;
+
+id_type!(pub(super) TrigramIndexSegmentId { hex() });
+
+/// Commit position within a trigram index segment.
+///
+/// This may be different from `LocalCommitPosition`, which is a position
+/// relative to the start of the commit index segment.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct CommitPosition(u32);
+
+/// Packs three consecutive bytes into a trigram value. ASCII letters are
+/// folded to lowercase so that case-insensitive patterns can be looked up.
+fn trigram(bytes: [u8; 3]) -> u32 {
+    let [b0, b1, b2] = bytes.map(|b| b.to_ascii_lowercase());
+    u32::from_be_bytes([0, b0, b1, b2])
+}
+
+/// Iterates trigrams within a line. Trigrams never span line boundaries.
+fn line_trigrams(line: &[u8]) -> impl Iterator<Item = u32> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.windows(3)
+        .map(|bytes| trigram(bytes.try_into().unwrap()))
+}
+
+fn extend_trigrams(trigrams: &mut Vec<u32>, text: &[u8]) {
+    for line in text.split_inclusive(|b| *b == b'\n') {
+        trigrams.extend(line_trigrams(line));
+    }
+}
+
+/// Sorted trigrams of a commit.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct CommitTrigrams<'a>(&'a [[u8; 4]]);
+
+impl CommitTrigrams<'_> {
+    pub fn contains(&self, trigram: u32) -> bool {
+        self.0
+            .binary_search_by_key(&trigram, |chunk| u32::from_le_bytes(*chunk))
+            .is_ok()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> {
+        self.0.iter().map(|chunk| u32::from_le_bytes(*chunk))
+    }
+}
+
+/// Condition that must hold if a commit might contain lines matching the
+/// string expression.
+///
+/// Only literal parts of the patterns are taken into account. Since the index
+/// stores ASCII-lowercased trigrams, case-sensitive and case-insensitive
+/// patterns are handled in the same way if the pattern is pure ASCII.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum TrigramQuery {
+    /// Commit may match regardless of its trigrams.
+    All,
+    /// Commit may match if it contains all of the trigrams.
+    Trigrams(Vec<u32>),
+    /// Commit may match if both conditions hold.
+    And(Box<Self>, Box<Self>),
+    /// Commit may match if either condition holds.
+    Or(Box<Self>, Box<Self>),
+}
+
+impl TrigramQuery {
+    /// Builds query for lines matching the `expression`.
+    pub fn from_string_expression(expression: &StringExpression) -> Self {
+        match expression {
+            StringExpression::Pattern(pattern) => Self::from_string_pattern(pattern),
+            // Lines not matching a pattern can be anything
+            StringExpression::NotIn(_) => Self::All,
+            StringExpression::Union(expr1, expr2) => Self::or(
+                Self::from_string_expression(expr1),
+                Self::from_string_expression(expr2),
+            ),
+            StringExpression::Intersection(expr1, expr2) => Self::and(
+                Self::from_string_expression(expr1),
+                Self::from_string_expression(expr2),
+            ),
+        }
+    }
+
+    fn from_string_pattern(pattern: &StringPattern) -> Self {
+        match pattern {
+            StringPattern::Exact(literal) | StringPattern::Substring(literal) => {
+                Self::from_literals([literal.as_str()])
+            }
+            // Only ASCII letters are case-folded in the index. Non-ASCII
+            // letters in case-insensitive patterns may match other byte
+            // sequences.
+            StringPattern::ExactI(literal) | StringPattern::SubstringI(literal) => {
+                if literal.is_ascii() {
+                    Self::from_literals([literal.as_str()])
+                } else {
+                    Self::All
+                }
+            }
+            StringPattern::Glob(pattern) => {
+                Self::from_literals(glob_literals(pattern.as_str()).iter().map(String::as_str))
+            }
+            // Case-insensitive glob is evaluated as a Unicode-aware regex, so
+            // non-ASCII characters may match ASCII literals.
+            StringPattern::GlobI(_) => Self::All,
+            // TODO: extract required literals from regex
+            StringPattern::Regex(_) | StringPattern::RegexI(_) => Self::All,
+        }
+    }
+
+    fn from_literals<'a>(literals: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut trigrams = literals
+            .into_iter()
+            .flat_map(|literal| line_trigrams(literal.as_bytes()).collect_vec())
+            .collect_vec();
+        trigrams.sort_unstable();
+        trigrams.dedup();
+        if trigrams.is_empty() {
+            Self::All
+        } else {
+            Self::Trigrams(trigrams)
+        }
+    }
+
+    fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All, query) | (query, Self::All) => query,
+            (Self::Trigrams(mut trigrams1), Self::Trigrams(trigrams2)) => {
+                trigrams1.extend(trigrams2);
+                trigrams1.sort_unstable();
+                trigrams1.dedup();
+                Self::Trigrams(trigrams1)
+            }
+            (query1, query2) => Self::And(Box::new(query1), Box::new(query2)),
+        }
+    }
+
+    fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All, _) | (_, Self::All) => Self::All,
+            (query1, query2) => Self::Or(Box::new(query1), Box::new(query2)),
+        }
+    }
+
+    /// Returns true if this query can't exclude any commits.
+    pub fn is_all(&self) -> bool {
+        matches!(self, Self::All)
+    }
+
+    /// Returns false if the commit can't contain matching lines.
+    pub fn may_match(&self, trigrams: CommitTrigrams<'_>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Trigrams(required) => required.iter().all(|&t| trigrams.contains(t)),
+            Self::And(query1, query2) => query1.may_match(trigrams) && query2.may_match(trigrams),
+            Self::Or(query1, query2) => query1.may_match(trigrams) || query2.may_match(trigrams),
+        }
+    }
+}
+
+/// Returns literal fragments which must appear in strings matching the glob
+/// pattern.
+fn glob_literals(src: &str) -> Vec<String> {
+    let mut literals = vec![];
+    let mut current = String::new();
+    let mut chars = src.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            '*' | '?' => literals.push(mem::take(&mut current)),
+            // Character classes and alternatives aren't literal. Stop here
+            // rather than parsing the nested syntax.
+            '[' | '{' => break,
+            _ => current.push(c),
+        }
+    }
+    literals.push(current);
+    literals
+}
+
+/// Table of per-commit sorted trigrams, which is shared by the readonly and
+/// mutable segments.
+///
+/// ```text
+/// for each commit, in commit-index order
+///   u32: position in trigram table
+/// u32: number of trigram entries (as sentinel)
+/// for each commit, in commit-index order
+///   for each trigram, in ascending order
+///     u32: trigram
+/// ```
+trait TrigramTable {
+    fn trigram_offset(&self, index: usize) -> usize;
+
+    fn trigram_table(&self) -> &[[u8; 4]];
+
+    fn trigrams(&self, pos: CommitPosition) -> CommitTrigrams<'_> {
+        let start = self.trigram_offset(pos.0 as usize);
+        let end = self.trigram_offset(pos.0 as usize + 1);
+        CommitTrigrams(&self.trigram_table()[start..end])
+    }
+}
+
+/// Trigram index segment backed by immutable file.
+///
+/// File format:
+/// ```text
+/// u32: file format version
+///
+/// u32: number of (local) commit entries
+/// u32: number of trigram entries
+///
+/// for each commit, in commit-index order
+///   u32: position in trigram table
+/// u32: number of trigram entries (as sentinel)
+/// for each commit, in commit-index order
+///   for each trigram, in ascending order
+///     u32: trigram
+/// ```
+///
+/// * The parent segment id isn't stored in a segment file. This allows us to
+///   insert parents without rewriting the descendant segments.
+/// * Trigrams are collected from the lines added or removed by the commit.
+///   The index can only tell that a commit doesn't match, so the commit
+///   contents have to be diffed to confirm the match.
+pub(super) struct ReadonlyTrigramIndexSegment {
+    id: TrigramIndexSegmentId,
+    num_local_commits: u32,
+    trigram_lookup: Vec<[u8; 4]>,
+    trigram_table: Vec<[u8; 4]>,
+}
+
+impl Debug for ReadonlyTrigramIndexSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("ReadonlyTrigramIndexSegment")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadonlyTrigramIndexSegment {
+    pub(super) fn load(
+        dir: &Path,
+        id: TrigramIndexSegmentId,
+    ) -> Result<Arc<Self>, ReadonlyIndexLoadError> {
+        let mut file = File::open(dir.join(id.hex()))
+            .map_err(|err| ReadonlyIndexLoadError::from_io_err("trigram", id.hex(), err))?;
+        Self::load_from(&mut file, id)
+    }
+
+    pub(super) fn load_from(
+        file: &mut dyn Read,
+        id: TrigramIndexSegmentId,
+    ) -> Result<Arc<Self>, ReadonlyIndexLoadError> {
+        let from_io_err = |err| ReadonlyIndexLoadError::from_io_err("trigram", id.hex(), err);
+        let read_u32 = |file: &mut dyn Read| {
+            let mut buf = [0; 4];
+            file.read_exact(&mut buf).map_err(from_io_err)?;
+            Ok(u32::from_le_bytes(buf))
+        };
+        let read_chunks = |file: &mut dyn Read, len: usize| {
+            let mut buf = vec![[0; 4]; len];
+            file.read_exact(buf.as_flattened_mut())
+                .map_err(from_io_err)?;
+            Ok(buf)
+        };
+
+        let format_version = read_u32(file)?;
+        if format_version != FILE_FORMAT_VERSION {
+            return Err(ReadonlyIndexLoadError::UnexpectedVersion {
+                kind: "trigram",
+                found_version: format_version,
+                expected_version: FILE_FORMAT_VERSION,
+            });
+        }
+
+        let num_local_commits = read_u32(file)?;
+        let num_trigrams = read_u32(file)?;
+        let trigram_lookup = read_chunks(file, num_local_commits as usize + 1)?;
+        let trigram_table = read_chunks(file, num_trigrams as usize)?;
+        let mut remainder = [0];
+        if file.read(&mut remainder).map_err(from_io_err)? != 0 {
+            return Err(ReadonlyIndexLoadError::invalid_data(
+                "trigram",
+                id.hex(),
+                "unexpected data length",
+            ));
+        }
+
+        let offsets_valid = trigram_lookup
+            .iter()
+            .map(|&chunk| u32::from_le_bytes(chunk))
+            .tuple_windows()
+            .all(|(start, end)| start <= end);
+        if !offsets_valid
+            || trigram_lookup.first() != Some(&0_u32.to_le_bytes())
+            || trigram_lookup.last() != Some(&num_trigrams.to_le_bytes())
+        {
+            return Err(ReadonlyIndexLoadError::invalid_data(
+                "trigram",
+                id.hex(),
+                "invalid trigram offsets",
+            ));
+        }
+
+        Ok(Arc::new(Self {
+            id,
+            num_local_commits,
+            trigram_lookup,
+            trigram_table,
+        }))
+    }
+
+    pub(super) fn id(&self) -> &TrigramIndexSegmentId {
+        &self.id
+    }
+
+    pub(super) fn num_local_commits(&self) -> u32 {
+        self.num_local_commits
+    }
+
+    pub(super) fn num_trigrams(&self) -> u32 {
+        self.trigram_table.len().try_into().unwrap()
+    }
+}
+
+impl TrigramTable for ReadonlyTrigramIndexSegment {
+    fn trigram_offset(&self, index: usize) -> usize {
+        u32::from_le_bytes(self.trigram_lookup[index]) as usize
+    }
+
+    fn trigram_table(&self) -> &[[u8; 4]] {
+        &self.trigram_table
+    }
+}
+
+/// Trigram index segment which is not serialized to file.
+#[derive(Clone)]
+pub(super) struct MutableTrigramIndexSegment {
+    trigram_offsets: Vec<u32>,
+    trigram_table: Vec<[u8; 4]>,
+}
+
+impl Debug for MutableTrigramIndexSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("MutableTrigramIndexSegment")
+            .finish_non_exhaustive()
+    }
+}
+
+impl MutableTrigramIndexSegment {
+    pub(super) fn empty() -> Self {
+        Self {
+            trigram_offsets: vec![0],
+            trigram_table: vec![],
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.num_local_commits() == 0
+    }
+
+    pub(super) fn num_local_commits(&self) -> u32 {
+        (self.trigram_offsets.len() - 1).try_into().unwrap()
+    }
+
+    fn add_trigrams(&mut self, trigrams: impl IntoIterator<Item = u32>) {
+        self.trigram_table
+            .extend(trigrams.into_iter().map(u32::to_le_bytes));
+        self.trigram_offsets
+            .push(self.trigram_table.len().try_into().unwrap());
+    }
+
+    fn extend_from_readonly_segment(&mut self, other_segment: &ReadonlyTrigramIndexSegment) {
+        for pos in (0..other_segment.num_local_commits()).map(CommitPosition) {
+            self.add_trigrams(other_segment.trigrams(pos).iter());
+        }
+    }
+
+    fn extend_from_mutable_segment(&mut self, other_segment: &Self) {
+        for pos in (0..other_segment.num_local_commits()).map(CommitPosition) {
+            self.add_trigrams(other_segment.trigrams(pos).iter());
+        }
+    }
+
+    fn serialize_into(&self, buf: &mut Vec<u8>) {
+        buf.extend(FILE_FORMAT_VERSION.to_le_bytes());
+        buf.extend(self.num_local_commits().to_le_bytes());
+        buf.extend(
+            u32::try_from(self.trigram_table.len())
+                .unwrap()
+                .to_le_bytes(),
+        );
+        for offset in &self.trigram_offsets {
+            buf.extend(offset.to_le_bytes());
+        }
+        buf.extend(self.trigram_table.as_flattened());
+    }
+
+    pub(super) fn save_in(
+        &self,
+        dir: &Path,
+    ) -> Result<Arc<ReadonlyTrigramIndexSegment>, PathError> {
+        let mut buf = Vec::new();
+        self.serialize_into(&mut buf);
+        let mut hasher = Blake2b512::new();
+        hasher.update(&buf);
+
+        let file_id = TrigramIndexSegmentId::from_bytes(&hasher.finalize());
+        let file_path = dir.join(file_id.hex());
+        let mut file = NamedTempFile::new_in(dir).context(dir)?;
+        file.as_file_mut().write_all(&buf).context(file.path())?;
+        persist_content_addressed_temp_file(file, &file_path).context(&file_path)?;
+
+        let segment = ReadonlyTrigramIndexSegment::load_from(&mut &buf[..], file_id)
+            .expect("in-memory index data should be valid and readable");
+        Ok(segment)
+    }
+}
+
+impl TrigramTable for MutableTrigramIndexSegment {
+    fn trigram_offset(&self, index: usize) -> usize {
+        self.trigram_offsets[index] as usize
+    }
+
+    fn trigram_table(&self) -> &[[u8; 4]] {
+        &self.trigram_table
+    }
+}
+
+/// Index of trigrams in lines added or removed by each commit.
+#[derive(Clone, Debug)]
+pub(super) struct CompositeTrigramIndex {
+    start_commit_pos: Option<GlobalCommitPosition>,
+    num_commits: u32, // cache
+    readonly_segments: Vec<Arc<ReadonlyTrigramIndexSegment>>,
+    mutable_segment: Option<Box<MutableTrigramIndexSegment>>,
+}
+
+impl CompositeTrigramIndex {
+    /// Creates empty trigram index which cannot store entries. In other words,
+    /// the trigram index is disabled.
+    pub(super) fn null() -> Self {
+        Self {
+            start_commit_pos: None,
+            num_commits: 0,
+            readonly_segments: vec![],
+            mutable_segment: None,
+        }
+    }
+
+    /// Creates empty trigram index which will store entries from
+    /// `start_commit_pos`.
+    pub(super) fn empty(start_commit_pos: GlobalCommitPosition) -> Self {
+        Self {
+            start_commit_pos: Some(start_commit_pos),
+            num_commits: 0,
+            readonly_segments: vec![],
+            mutable_segment: None,
+        }
+    }
+
+    pub(super) fn load(
+        dir: &Path,
+        start_commit_pos: GlobalCommitPosition,
+        ids: &[TrigramIndexSegmentId],
+    ) -> Result<Self, ReadonlyIndexLoadError> {
+        let readonly_segments: Vec<_> = ids
+            .iter()
+            .map(|id| ReadonlyTrigramIndexSegment::load(dir, id.clone()))
+            .try_collect()?;
+        let num_commits = readonly_segments
+            .iter()
+            .map(|segment| segment.num_local_commits())
+            .sum();
+        Ok(Self {
+            start_commit_pos: Some(start_commit_pos),
+            num_commits,
+            readonly_segments,
+            mutable_segment: None,
+        })
+    }
+
+    /// Adds mutable segment if needed.
+    pub(super) fn make_mutable(&mut self) {
+        if self.start_commit_pos.is_none() || self.mutable_segment.is_some() {
+            return;
+        }
+        self.mutable_segment = Some(Box::new(MutableTrigramIndexSegment::empty()));
+    }
+
+    /// Position of the first indexed (or to-be-indexed) commit.
+    pub(super) fn start_commit_pos(&self) -> Option<GlobalCommitPosition> {
+        self.start_commit_pos
+    }
+
+    /// New commit index position which can be added to this index.
+    pub(super) fn next_mutable_commit_pos(&self) -> Option<GlobalCommitPosition> {
+        if self.mutable_segment.is_some() {
+            self.start_commit_pos
+                .map(|GlobalCommitPosition(start)| GlobalCommitPosition(start + self.num_commits))
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn num_commits(&self) -> u32 {
+        self.num_commits
+    }
+
+    pub(super) fn readonly_segments(&self) -> &[Arc<ReadonlyTrigramIndexSegment>] {
+        &self.readonly_segments
+    }
+
+    /// Appends segments from the `other` index. This and the other index should
+    /// be contiguous.
+    pub(super) fn append_segments(&mut self, other: &Self) {
+        assert!(self.mutable_segment.is_none());
+        let GlobalCommitPosition(self_start_pos) =
+            self.start_commit_pos.expect("should have start pos");
+        let Some(GlobalCommitPosition(other_start_pos)) = other.start_commit_pos else {
+            return;
+        };
+        assert_eq!(self_start_pos + self.num_commits, other_start_pos);
+        self.readonly_segments
+            .extend_from_slice(&other.readonly_segments);
+        self.mutable_segment = other.mutable_segment.clone();
+        self.num_commits += other.num_commits;
+    }
+
+    /// Maps `global_pos` to segment and segment-local position.
+    fn find_segment(
+        &self,
+        global_pos: GlobalCommitPosition,
+    ) -> Option<(
+        CommitPosition,
+        Either<&ReadonlyTrigramIndexSegment, &MutableTrigramIndexSegment>,
+    )> {
+        let mut local_pos = u32::checked_sub(global_pos.0, self.start_commit_pos?.0)?;
+        for segment in &self.readonly_segments {
+            local_pos = match u32::checked_sub(local_pos, segment.num_local_commits()) {
+                Some(next_local_pos) => next_local_pos,
+                None => return Some((CommitPosition(local_pos), Either::Left(segment))),
+            };
+        }
+        let segment = self.mutable_segment.as_deref()?;
+        (local_pos < segment.num_local_commits())
+            .then_some((CommitPosition(local_pos), Either::Right(segment)))
+    }
+
+    /// Returns trigrams of lines added or removed by the specified commit.
+    /// Returns `None` if the commit wasn't indexed.
+    pub(super) fn trigrams(&self, global_pos: GlobalCommitPosition) -> Option<CommitTrigrams<'_>> {
+        let (local_pos, segment) = self.find_segment(global_pos)?;
+        Some(segment.either(|x| x.trigrams(local_pos), |x| x.trigrams(local_pos)))
+    }
+
+    /// Adds trigrams of the next commit.
+    ///
+    /// The `trigrams` must be sorted and unique. Caller must ensure that the
+    /// commit matches `next_mutable_commit_pos()`. Panics if this index isn't
+    /// mutable (i.e. `next_mutable_commit_pos()` is `None`.)
+    pub(super) fn add_trigrams(&mut self, trigrams: impl IntoIterator<Item = u32>) {
+        let segment = self
+            .mutable_segment
+            .as_deref_mut()
+            .expect("should have mutable");
+        segment.add_trigrams(trigrams);
+        self.num_commits += 1;
+    }
+
+    /// Squashes parent segments if the mutable segment has more than half the
+    /// commits of its parent segment. This is done recursively, so the stack of
+    /// index segments has O(log n) files.
+    pub(super) fn maybe_squash_with_ancestors(&mut self) {
+        let Some(mutable_segment) = self.mutable_segment.as_deref() else {
+            return;
+        };
+        let mut num_new_commits = mutable_segment.num_local_commits();
+        let mut squash_start = self.readonly_segments.len();
+        for segment in self.readonly_segments.iter().rev() {
+            if 2 * num_new_commits < segment.num_local_commits() {
+                break;
+            }
+            num_new_commits += segment.num_local_commits();
+            squash_start -= 1;
+        }
+        if squash_start == self.readonly_segments.len() {
+            return;
+        }
+        let mut squashed_segment = Box::new(MutableTrigramIndexSegment::empty());
+        for segment in self.readonly_segments.drain(squash_start..) {
+            squashed_segment.extend_from_readonly_segment(&segment);
+        }
+        squashed_segment.extend_from_mutable_segment(&self.mutable_segment.take().unwrap());
+        self.mutable_segment = Some(squashed_segment);
+    }
+
+    /// Writes mutable segment if exists, turns it into readonly segment.
+    pub(super) fn save_in(&mut self, dir: &Path) -> Result<(), PathError> {
+        let Some(segment) = self.mutable_segment.take() else {
+            return Ok(());
+        };
+        if segment.is_empty() {
+            return Ok(());
+        }
+        let segment = segment.save_in(dir)?;
+        self.readonly_segments.push(segment);
+        Ok(())
+    }
+}
+
+/// Collects sorted trigrams of lines added or removed by the `commit`.
+///
+/// All lines are collected from conflicted files since the conflict sides
+/// can't be diffed line by line.
+pub(super) async fn collect_changed_trigrams(
+    index: &dyn Index,
+    commit: &Commit,
+) -> BackendResult<Vec<u32>> {
+    let parents = commit.parents().await?;
+    if let [p] = parents.as_slice()
+        && commit.tree_ids() == p.tree_ids()
+    {
+        return Ok(vec![]);
+    }
+    tracing::trace!(
+        ?commit,
+        parents_count = parents.len(),
+        "calculating trigrams"
+    );
+    let store = commit.store();
+    let from_tree = merge_commit_trees_no_resolve_without_repo(store, index, &parents).await?;
+    let to_tree = commit.tree();
+    let mut tree_diff = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    let mut trigrams = vec![];
+    while let Some(entry) = tree_diff.next().await {
+        let mut values = entry.values?;
+        values.before = resolve_file_values(store, &entry.path, values.before).await?;
+        if !values.is_changed() {
+            continue;
+        }
+        let conflict_labels = ConflictLabels::unlabeled();
+        let left_future =
+            materialize_tree_value(store, &entry.path, values.before, &conflict_labels);
+        let right_future =
+            materialize_tree_value(store, &entry.path, values.after, &conflict_labels);
+        let (left_value, right_value) = futures::try_join!(left_future, right_future)?;
+        let lefts = to_file_content(&entry.path, left_value).await?;
+        let rights = to_file_content(&entry.path, right_value).await?;
+        if let (Some(left), Some(right)) = (lefts.as_resolved(), rights.as_resolved()) {
+            let diff = ContentDiff::by_line([left, right]);
+            for hunk in diff.hunks() {
+                if hunk.kind == DiffHunkKind::Different {
+                    for text in &hunk.contents {
+                        extend_trigrams(&mut trigrams, text);
+                    }
+                }
+            }
+        } else {
+            for text in lefts.iter().chain(rights.iter()) {
+                extend_trigrams(&mut trigrams, text);
+            }
+        }
+    }
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    Ok(trigrams)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::tests::TestResult;
+    use crate::tests::new_temp_dir;
+
+    fn trigrams_of(text: &str) -> Vec<u32> {
+        let mut trigrams = vec![];
+        extend_trigrams(&mut trigrams, text.as_bytes());
+        trigrams.sort_unstable();
+        trigrams.dedup();
+        trigrams
+    }
+
+    fn collect_trigrams(index: &CompositeTrigramIndex, pos: u32) -> Option<Vec<u32>> {
+        let trigrams = index.trigrams(GlobalCommitPosition(pos))?;
+        Some(trigrams.iter().collect())
+    }
+
+    #[test]
+    fn test_line_trigrams() {
+        assert_eq!(line_trigrams(b"ab").count(), 0);
+        assert_eq!(line_trigrams(b"ab\n").count(), 0);
+        assert_eq!(
+            line_trigrams(b"aBcD\n").collect_vec(),
+            [trigram(*b"abc"), trigram(*b"bcd")]
+        );
+        // Trigrams shouldn't span lines
+        assert_eq!(trigrams_of("ab\ncd\n"), []);
+    }
+
+    #[test]
+    fn test_glob_literals() {
+        assert_eq!(glob_literals("foo"), ["foo"]);
+        assert_eq!(glob_literals("*foo*bar?"), ["", "foo", "bar", ""]);
+        assert_eq!(glob_literals(r"foo\*bar"), ["foo*bar"]);
+        assert_eq!(glob_literals("foo[ab]bar"), ["foo"]);
+        assert_eq!(glob_literals("foo{a,b}bar"), ["foo"]);
+    }
+
+    #[test]
+    fn test_query_from_string_expression() {
+        let query = |expr: StringExpression| TrigramQuery::from_string_expression(&expr);
+        let pattern = |src: &str, kind: &str| {
+            StringExpression::pattern(StringPattern::from_str_kind(src, kind).unwrap())
+        };
+        assert_eq!(query(pattern("ab", "substring")), TrigramQuery::All);
+        assert_eq!(
+            query(pattern("Abc", "substring")),
+            TrigramQuery::Trigrams(trigrams_of("abc"))
+        );
+        assert_eq!(
+            query(pattern("ABCD", "exact-i")),
+            TrigramQuery::Trigrams(trigrams_of("abcd"))
+        );
+        assert_eq!(
+            query(pattern("Äbc", "substring")),
+            TrigramQuery::Trigrams(trigrams_of("Äbc"))
+        );
+        // Non-ASCII letters aren't case-folded in the index
+        assert_eq!(query(pattern("Äbc", "substring-i")), TrigramQuery::All);
+        assert_eq!(
+            query(pattern("*abc*de?xyz", "glob")),
+            TrigramQuery::Trigrams(trigrams_of("abc\nxyz"))
+        );
+        assert_eq!(query(pattern("abc", "regex")), TrigramQuery::All);
+        assert_eq!(
+            query(pattern("abc", "substring").negated()),
+            TrigramQuery::All
+        );
+        assert_eq!(
+            query(pattern("abc", "substring").intersection(pattern("xyz", "substring"))),
+            TrigramQuery::Trigrams(trigrams_of("abc\nxyz"))
+        );
+        assert_eq!(
+            query(pattern("abc", "substring").union(pattern("xyz", "substring"))),
+            TrigramQuery::Or(
+                Box::new(TrigramQuery::Trigrams(trigrams_of("abc"))),
+                Box::new(TrigramQuery::Trigrams(trigrams_of("xyz"))),
+            )
+        );
+        assert_eq!(
+            query(pattern("abc", "substring").union(pattern("x", "regex"))),
+            TrigramQuery::All
+        );
+    }
+
+    #[test]
+    fn test_query_may_match() {
+        let mut segment = MutableTrigramIndexSegment::empty();
+        segment.add_trigrams(trigrams_of("foo bar\n"));
+        let trigrams = segment.trigrams(CommitPosition(0));
+        let query =
+            |src: &str| TrigramQuery::from_string_expression(&StringExpression::substring(src));
+        assert!(query("FOO").may_match(trigrams));
+        assert!(query("o b").may_match(trigrams));
+        assert!(!query("baz").may_match(trigrams));
+        assert!(query("baz").or(query("bar")).may_match(trigrams));
+        assert!(!query("baz").and(query("bar")).may_match(trigrams));
+    }
+
+    #[test]
+    fn test_composite_null() {
+        let mut index = CompositeTrigramIndex::null();
+        assert_eq!(index.start_commit_pos(), None);
+        assert_eq!(index.next_mutable_commit_pos(), None);
+        assert!(index.trigrams(GlobalCommitPosition(0)).is_none());
+
+        // No entries can be added to "null" index
+        index.make_mutable();
+        assert!(index.mutable_segment.is_none());
+        assert_eq!(index.num_commits(), 0);
+    }
+
+    #[test]
+    fn test_composite_empty() -> TestResult {
+        let temp_dir = new_temp_dir();
+        let mut index = CompositeTrigramIndex::empty(GlobalCommitPosition(0));
+        index.make_mutable();
+        assert_eq!(
+            index.next_mutable_commit_pos(),
+            Some(GlobalCommitPosition(0))
+        );
+        assert!(index.trigrams(GlobalCommitPosition(0)).is_none());
+
+        // Empty segment shouldn't be saved on disk
+        index.save_in(temp_dir.path())?;
+        assert!(index.mutable_segment.is_none());
+        assert!(index.readonly_segments.is_empty());
+        assert_eq!(index.num_commits(), 0);
+        Ok(())
+    }
+
+    #[test_case(false, false; "mutable")]
+    #[test_case(true, false; "readonly")]
+    #[test_case(true, true; "readonly, reloaded")]
+    fn test_composite_some_commits(on_disk: bool, reload: bool) -> TestResult {
+        let temp_dir = new_temp_dir();
+        let start_commit_pos = GlobalCommitPosition(1);
+        let mut index = CompositeTrigramIndex::empty(start_commit_pos);
+        index.make_mutable();
+        index.add_trigrams(trigrams_of("foo\n"));
+        index.add_trigrams([]);
+        index.add_trigrams(trigrams_of("bar\nbaz\n"));
+        assert_eq!(
+            index.next_mutable_commit_pos(),
+            Some(GlobalCommitPosition(4))
+        );
+        assert_eq!(index.num_commits(), 3);
+        if on_disk {
+            index.save_in(temp_dir.path())?;
+            assert!(index.mutable_segment.is_none());
+            assert_eq!(index.readonly_segments.len(), 1);
+            assert_eq!(index.readonly_segments[0].num_trigrams(), 3);
+            assert_eq!(index.next_mutable_commit_pos(), None);
+        }
+        if reload {
+            let ids = index
+                .readonly_segments()
+                .iter()
+                .map(|segment| segment.id().clone())
+                .collect_vec();
+            index = CompositeTrigramIndex::load(temp_dir.path(), start_commit_pos, &ids)?;
+        }
+
+        assert_eq!(collect_trigrams(&index, 0), None);
+        assert_eq!(collect_trigrams(&index, 1), Some(trigrams_of("foo")));
+        assert_eq!(collect_trigrams(&index, 2), Some(vec![]));
+        assert_eq!(collect_trigrams(&index, 3), Some(trigrams_of("bar\nbaz")));
+        assert_eq!(collect_trigrams(&index, 4), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_squash_segments() -> TestResult {
+        let temp_dir = new_temp_dir();
+        let mut index = CompositeTrigramIndex::empty(GlobalCommitPosition(0));
+        index.make_mutable();
+        index.add_trigrams(trigrams_of("000"));
+        index.save_in(temp_dir.path())?;
+
+        index.make_mutable();
+        index.add_trigrams(trigrams_of("111"));
+        index.maybe_squash_with_ancestors();
+        index.save_in(temp_dir.path())?;
+        assert_eq!(index.readonly_segments.len(), 1);
+        assert_eq!(index.readonly_segments[0].num_local_commits(), 2);
+
+        index.make_mutable();
+        index.add_trigrams(trigrams_of("222"));
+        index.maybe_squash_with_ancestors();
+        index.save_in(temp_dir.path())?;
+        assert_eq!(index.readonly_segments.len(), 1);
+        assert_eq!(index.readonly_segments[0].num_local_commits(), 3);
+
+        index.make_mutable();
+        index.add_trigrams(trigrams_of("333"));
+        index.maybe_squash_with_ancestors();
+        index.save_in(temp_dir.path())?;
+        assert_eq!(index.readonly_segments.len(), 2);
+
+        // Squashed segments should preserve the original entries.
+        assert_eq!(collect_trigrams(&index, 0), Some(trigrams_of("000")));
+        assert_eq!(collect_trigrams(&index, 1), Some(trigrams_of("111")));
+        assert_eq!(collect_trigrams(&index, 2), Some(trigrams_of("222")));
+        assert_eq!(collect_trigrams(&index, 3), Some(trigrams_of("333")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_corrupted_offsets() {
+        let mut segment = MutableTrigramIndexSegment::empty();
+        segment.add_trigrams(trigrams_of("abcd"));
+        let mut buf = Vec::new();
+        segment.serialize_into(&mut buf);
+        // Make the commit entry end past the trigram table
+        buf[4 * 4..][..4].copy_from_slice(&3_u32.to_le_bytes());
+        let id = TrigramIndexSegmentId::from_bytes(&[0; 64]);
+        assert!(ReadonlyTrigramIndexSegment::load_from(&mut &buf[..], id).is_err());
+    }
+}
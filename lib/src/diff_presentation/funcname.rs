@@ -0,0 +1,205 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lookup of the enclosing function (or section) name of diff hunks, similar
+//! to Git's `xfuncname`.
+
+use bstr::BStr;
+use bstr::ByteSlice as _;
+use regex::bytes::Regex;
+
+use crate::matchers::Matcher;
+use crate::repo_path::RepoPath;
+
+/// Maximum length of the function name in bytes. Longer names are truncated.
+pub const MAX_FUNCNAME_LEN: usize = 80;
+
+/// Header patterns selected by file path.
+#[derive(Debug, Default)]
+pub struct FuncnameMatcher {
+    rules: Vec<(Box<dyn Matcher>, Regex)>,
+}
+
+impl FuncnameMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `regex` to be used for files matching `matcher`. Rules added
+    /// earlier take precedence.
+    pub fn add_rule(&mut self, matcher: Box<dyn Matcher>, regex: Regex) {
+        self.rules.push((matcher, regex));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the header pattern for the file at `path`.
+    pub fn regex_for(&self, path: &RepoPath) -> Option<&Regex> {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.matches(path))
+            .map(|(_, regex)| regex)
+    }
+}
+
+/// Finds the function names preceding lines in a file.
+///
+/// Lookups are expected to be made in ascending line order. Lines scanned by
+/// the previous lookup are not scanned again.
+#[derive(Clone, Debug)]
+pub struct FuncnameFinder<'a> {
+    lines: Vec<&'a [u8]>,
+    regex: &'a Regex,
+    /// Number of lines scanned so far, and the last function name found in
+    /// them.
+    scanned: (usize, Option<&'a BStr>),
+}
+
+impl<'a> FuncnameFinder<'a> {
+    pub fn new(text: &'a [u8], regex: &'a Regex) -> Self {
+        Self {
+            lines: text.split_inclusive(|b| *b == b'\n').collect(),
+            regex,
+            scanned: (0, None),
+        }
+    }
+
+    /// Returns the function name of the nearest line matching the pattern
+    /// before the 0-based line `index`.
+    ///
+    /// If the pattern has capture groups, the first matched group is used.
+    /// Otherwise, the whole line is used.
+    pub fn find(&mut self, index: usize) -> Option<&'a BStr> {
+        let (scanned_len, last_found) = self.scanned;
+        let end = index.min(self.lines.len());
+        if end < scanned_len {
+            // Going backwards. Scan the lines from the start.
+            self.scanned = (0, None);
+            return self.find(index);
+        }
+        let found = self.lines[scanned_len..end]
+            .iter()
+            .rev()
+            .find_map(|line| self.extract(line))
+            .or(last_found);
+        self.scanned = (end, found);
+        found
+    }
+
+    fn extract(&self, line: &'a [u8]) -> Option<&'a BStr> {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let captures = self.regex.captures(line)?;
+        let name = captures
+            .iter()
+            .skip(1)
+            .flatten()
+            .next()
+            .map_or(line, |m| m.as_bytes());
+        let name = name.trim_end();
+        let name = if name.len() > MAX_FUNCNAME_LEN {
+            // Don't split a multi-byte character.
+            let end = name
+                .char_indices()
+                .map(|(_, end, _)| end)
+                .take_while(|&end| end <= MAX_FUNCNAME_LEN)
+                .last()
+                .unwrap_or(0);
+            &name[..end]
+        } else {
+            name
+        };
+        Some(name.as_bstr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::fileset::FilePattern;
+    use crate::fileset::FilesetExpression;
+    use crate::repo_path::RepoPathUiConverter;
+
+    fn regex(pattern: &str) -> Regex {
+        Regex::new(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_funcname_matcher() {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws"),
+            base: PathBuf::from("/ws"),
+        };
+        let glob = |pattern: &str| {
+            let pattern = FilePattern::from_str_kind(&path_converter, pattern, "root-glob");
+            FilesetExpression::pattern(pattern.unwrap()).to_matcher()
+        };
+        let mut matcher = FuncnameMatcher::new();
+        assert!(matcher.is_empty());
+        matcher.add_rule(glob("*.rs"), regex("^fn"));
+        matcher.add_rule(glob("*"), regex("^def"));
+        let path = |value| RepoPath::from_internal_string(value).unwrap();
+        assert_eq!(
+            matcher.regex_for(path("a.rs")).map(Regex::as_str),
+            Some("^fn")
+        );
+        assert_eq!(
+            matcher.regex_for(path("a.py")).map(Regex::as_str),
+            Some("^def")
+        );
+        assert_eq!(matcher.regex_for(path("dir/a.rs")).map(Regex::as_str), None);
+    }
+
+    #[test]
+    fn test_funcname_finder() {
+        let text = indoc::indoc! {b"
+            fn foo() {
+                1
+            }
+
+            fn bar() {
+                2
+                3
+            }
+        "};
+        let regex = regex(r"^fn\s+(\w+)");
+        let mut finder = FuncnameFinder::new(text, &regex);
+        assert_eq!(finder.find(0), None);
+        assert_eq!(finder.find(1), Some(b"foo".as_bstr()));
+        assert_eq!(finder.find(4), Some(b"foo".as_bstr()));
+        assert_eq!(finder.find(5), Some(b"bar".as_bstr()));
+        assert_eq!(finder.find(7), Some(b"bar".as_bstr()));
+        assert_eq!(finder.find(100), Some(b"bar".as_bstr()));
+        // Going backwards
+        assert_eq!(finder.find(2), Some(b"foo".as_bstr()));
+
+        // Whole line is used if there are no capture groups
+        let regex = self::regex(r"^fn");
+        let mut finder = FuncnameFinder::new(text, &regex);
+        assert_eq!(finder.find(3), Some(b"fn foo() {".as_bstr()));
+    }
+
+    #[test]
+    fn test_funcname_finder_truncate() {
+        let text = format!("{}\nx\n", "\u{3042}".repeat(30));
+        let regex = regex(".");
+        let mut finder = FuncnameFinder::new(text.as_bytes(), &regex);
+        // 26 characters of 3 bytes each
+        assert_eq!(finder.find(1).unwrap().len(), 78);
+    }
+}
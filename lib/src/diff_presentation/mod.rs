@@ -20,20 +20,25 @@ use std::borrow::Borrow;
 use std::mem;
 
 use bstr::BString;
-use itertools::Itertools as _;
 
 use crate::backend::BackendResult;
 use crate::conflicts::MaterializedFileValue;
+use crate::diff::CompareBytes;
 use crate::diff::CompareBytesExactly;
 use crate::diff::CompareBytesIgnoreAllWhitespace;
 use crate::diff::CompareBytesIgnoreWhitespaceAmount;
 use crate::diff::ContentDiff;
+use crate::diff::DiffAlgorithm;
 use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
 use crate::diff::find_line_ranges;
+use crate::diff::find_nonword_ranges_grouping_whitespace;
+use crate::diff::find_word_ranges;
 use crate::merge::Diff;
 use crate::repo_path::RepoPath;
 
+pub mod funcname;
+pub mod moved;
 pub mod unified;
 // TODO: colored_diffs utils should also be moved from `jj_cli::diff_utils` to
 // here.
@@ -87,24 +92,75 @@ pub enum LineCompareMode {
 pub fn diff_by_line<'input, T: AsRef<[u8]> + ?Sized + 'input>(
     inputs: impl IntoIterator<Item = &'input T>,
     options: &LineCompareMode,
+    algorithm: DiffAlgorithm,
 ) -> ContentDiff<'input> {
     // TODO: If we add --ignore-blank-lines, its tokenizer will have to attach
     // blank lines to the preceding range. Maybe it can also be implemented as a
     // post-process (similar to refine_changed_regions()) that expands unchanged
     // regions across blank lines.
     match options {
-        LineCompareMode::Exact => {
-            ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly)
-        }
+        LineCompareMode::Exact => ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesExactly,
+            algorithm,
+        ),
+        LineCompareMode::IgnoreAllSpace => ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreAllWhitespace,
+            algorithm,
+        ),
+        LineCompareMode::IgnoreSpaceChange => ContentDiff::for_tokenizer_with_algorithm(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreWhitespaceAmount,
+            algorithm,
+        ),
+    }
+}
+
+/// Compares `[left, right]` contents word by word, and returns the diff hunks.
+///
+/// The contents are usually a changed hunk of the line diff. Whitespace is
+/// compared according to the `options`. Hunks which differ only in ignored
+/// whitespace are reported as matching, so the sides of matching hunks may
+/// differ.
+pub fn diff_hunks_by_word<'input>(
+    contents: [&'input [u8]; 2],
+    options: &LineCompareMode,
+) -> Vec<DiffHunk<'input>> {
+    match options {
+        LineCompareMode::Exact => ContentDiff::by_word(contents).hunks().collect(),
         LineCompareMode::IgnoreAllSpace => {
-            ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreAllWhitespace)
+            diff_hunks_by_word_with(contents, CompareBytesIgnoreAllWhitespace)
         }
         LineCompareMode::IgnoreSpaceChange => {
-            ContentDiff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreWhitespaceAmount)
+            diff_hunks_by_word_with(contents, CompareBytesIgnoreWhitespaceAmount)
         }
     }
 }
 
+fn diff_hunks_by_word_with<C: CompareBytes + Clone>(
+    contents: [&[u8]; 2],
+    compare: C,
+) -> Vec<DiffHunk<'_>> {
+    let mut diff = ContentDiff::for_tokenizer(contents, find_word_ranges, compare.clone());
+    // Unlike ContentDiff::by_word(), whitespace runs are tokenized as a whole
+    // so they can be compared ignoring the amount of whitespace.
+    diff.refine_changed_regions(find_nonword_ranges_grouping_whitespace, compare.clone());
+    diff.hunks()
+        .map(|hunk| {
+            let [left, right] = hunk.contents[..].try_into().unwrap();
+            if hunk.kind == DiffHunkKind::Different && compare.eq(left, right) {
+                DiffHunk::matching([left, right])
+            } else {
+                hunk
+            }
+        })
+        .collect()
+}
+
 /// Splits `[left, right]` hunk pairs into `[left_lines, right_lines]`.
 pub fn unzip_diff_hunks_to_lines<'content, I>(diff_hunks: I) -> Diff<Vec<DiffTokenVec<'content>>>
 where
@@ -120,13 +176,20 @@ where
         let hunk = hunk.borrow();
         match hunk.kind {
             DiffHunkKind::Matching => {
-                // TODO: add support for unmatched contexts
-                debug_assert!(hunk.contents.iter().all_equal());
-                for token in hunk.contents[0].split_inclusive(|b| *b == b'\n') {
+                // The sides may differ if they were compared ignoring
+                // whitespace.
+                let [left, right] = hunk.contents[..]
+                    .try_into()
+                    .expect("hunk should have exactly two inputs");
+                for token in left.split_inclusive(|b| *b == b'\n') {
                     left_tokens.push((DiffTokenType::Matching, token));
-                    right_tokens.push((DiffTokenType::Matching, token));
                     if token.ends_with(b"\n") {
                         left_lines.push(mem::take(&mut left_tokens));
+                    }
+                }
+                for token in right.split_inclusive(|b| *b == b'\n') {
+                    right_tokens.push((DiffTokenType::Matching, token));
+                    if token.ends_with(b"\n") {
                         right_lines.push(mem::take(&mut right_tokens));
                     }
                 }
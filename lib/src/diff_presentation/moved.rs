@@ -0,0 +1,336 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of blocks of lines moved within or across files.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher as _;
+
+use super::LineCompareMode;
+use super::diff_by_line;
+use crate::diff::CompareBytes;
+use crate::diff::CompareBytesExactly;
+use crate::diff::CompareBytesIgnoreAllWhitespace;
+use crate::diff::CompareBytesIgnoreWhitespaceAmount;
+use crate::diff::DiffAlgorithm;
+use crate::diff::DiffHunkKind;
+use crate::merge::Diff;
+
+/// Minimum number of non-blank lines in a block to be considered moved.
+///
+/// Shorter blocks such as a closing brace and a blank line are too common to
+/// be meaningful.
+pub const MIN_MOVED_LINES: usize = 3;
+
+/// Consecutive lines removed from or added to a file.
+#[derive(Clone, Debug)]
+struct LineRun<'input> {
+    file_index: usize,
+    /// 0-based index of the first line in the file.
+    start: usize,
+    lines: Vec<&'input [u8]>,
+}
+
+/// Collects removed and added lines of files, and finds lines which were
+/// moved from one place to another.
+#[derive(Clone, Debug)]
+pub struct MovedLinesDetector<'input, K> {
+    compare_mode: LineCompareMode,
+    algorithm: DiffAlgorithm,
+    keys: Vec<K>,
+    removed_runs: Vec<LineRun<'input>>,
+    added_runs: Vec<LineRun<'input>>,
+}
+
+impl<'input, K: Eq + Hash> MovedLinesDetector<'input, K> {
+    pub fn new(compare_mode: &LineCompareMode, algorithm: DiffAlgorithm) -> Self {
+        Self {
+            compare_mode: *compare_mode,
+            algorithm,
+            keys: vec![],
+            removed_runs: vec![],
+            added_runs: vec![],
+        }
+    }
+
+    /// Adds the changed lines between `contents.before` and `contents.after`
+    /// of the file identified by `key`.
+    pub fn add_file(&mut self, key: K, contents: Diff<&'input [u8]>) {
+        let file_index = self.keys.len();
+        self.keys.push(key);
+        let line_diff = diff_by_line(contents.into_array(), &self.compare_mode, self.algorithm);
+        let mut left_line = 0;
+        let mut right_line = 0;
+        for hunk in line_diff.hunks() {
+            let &[left, right] = hunk.contents.as_slice() else {
+                panic!("hunk contents should have two sides")
+            };
+            let left_lines = left.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+            let right_lines = right.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+            let (num_left, num_right) = (left_lines.len(), right_lines.len());
+            if hunk.kind == DiffHunkKind::Different {
+                if !left_lines.is_empty() {
+                    self.removed_runs.push(LineRun {
+                        file_index,
+                        start: left_line,
+                        lines: left_lines,
+                    });
+                }
+                if !right_lines.is_empty() {
+                    self.added_runs.push(LineRun {
+                        file_index,
+                        start: right_line,
+                        lines: right_lines,
+                    });
+                }
+            }
+            left_line += num_left;
+            right_line += num_right;
+        }
+    }
+
+    /// Matches removed lines against added lines, and returns the lines which
+    /// belong to moved blocks.
+    pub fn detect(self) -> MovedLines<K> {
+        match self.compare_mode {
+            LineCompareMode::Exact => self.detect_with(CompareBytesExactly),
+            LineCompareMode::IgnoreAllSpace => self.detect_with(CompareBytesIgnoreAllWhitespace),
+            LineCompareMode::IgnoreSpaceChange => {
+                self.detect_with(CompareBytesIgnoreWhitespaceAmount)
+            }
+        }
+    }
+
+    fn detect_with(self, compare: impl CompareBytes) -> MovedLines<K> {
+        let hash_line = |line: &[u8]| {
+            let mut state = DefaultHasher::new();
+            compare.hash(line, &mut state);
+            state.finish()
+        };
+        let mut added_positions: HashMap<u64, Vec<(usize, usize)>> = HashMap::new();
+        for (run_index, run) in self.added_runs.iter().enumerate() {
+            for (offset, line) in run.lines.iter().enumerate() {
+                added_positions
+                    .entry(hash_line(line))
+                    .or_default()
+                    .push((run_index, offset));
+            }
+        }
+
+        let mut files: Vec<Diff<BTreeSet<usize>>> = self
+            .keys
+            .iter()
+            .map(|_| Diff::new(BTreeSet::new(), BTreeSet::new()))
+            .collect();
+        let mut added_used: Vec<Vec<bool>> = self
+            .added_runs
+            .iter()
+            .map(|run| vec![false; run.lines.len()])
+            .collect();
+        for removed_run in &self.removed_runs {
+            let mut offset = 0;
+            while offset < removed_run.lines.len() {
+                let removed_lines = &removed_run.lines[offset..];
+                // Pick the longest block of unused added lines starting with
+                // this line.
+                let candidates = added_positions
+                    .get(&hash_line(removed_lines[0]))
+                    .map_or(&[][..], Vec::as_slice);
+                let best = candidates
+                    .iter()
+                    .map(|&(run_index, added_offset)| {
+                        let added_lines = &self.added_runs[run_index].lines[added_offset..];
+                        let used = &added_used[run_index][added_offset..];
+                        let len = removed_lines
+                            .iter()
+                            .zip(added_lines)
+                            .zip(used)
+                            .take_while(|((removed, added), used)| {
+                                !**used && compare.eq(removed, added)
+                            })
+                            .count();
+                        (len, run_index, added_offset)
+                    })
+                    .max_by_key(|&(len, run_index, added_offset)| {
+                        (len, std::cmp::Reverse((run_index, added_offset)))
+                    });
+                let Some((len, run_index, added_offset)) = best.filter(|&(len, ..)| {
+                    let non_blank_lines = removed_lines[..len]
+                        .iter()
+                        .filter(|line| !line.trim_ascii().is_empty())
+                        .count();
+                    non_blank_lines >= MIN_MOVED_LINES
+                }) else {
+                    offset += 1;
+                    continue;
+                };
+                let added_run = &self.added_runs[run_index];
+                let removed_start = removed_run.start + offset;
+                let added_start = added_run.start + added_offset;
+                files[removed_run.file_index]
+                    .before
+                    .extend(removed_start..removed_start + len);
+                files[added_run.file_index]
+                    .after
+                    .extend(added_start..added_start + len);
+                added_used[run_index][added_offset..added_offset + len].fill(true);
+                offset += len;
+            }
+        }
+
+        MovedLines {
+            files: self
+                .keys
+                .into_iter()
+                .zip(files)
+                .filter(|(_, lines)| !lines.before.is_empty() || !lines.after.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Lines which belong to moved blocks, keyed by file.
+#[derive(Clone, Debug)]
+pub struct MovedLines<K> {
+    files: HashMap<K, Diff<BTreeSet<usize>>>,
+}
+
+impl<K: Eq + Hash> MovedLines<K> {
+    /// Creates an empty set, which can be used when detection is disabled.
+    pub fn empty() -> Self {
+        Self {
+            files: HashMap::new(),
+        }
+    }
+
+    /// Returns 0-based indices of the moved `[removed, added]` lines in the
+    /// file.
+    pub fn get<Q>(&self, key: &Q) -> Diff<&BTreeSet<usize>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        static EMPTY: BTreeSet<usize> = BTreeSet::new();
+        self.files
+            .get(key)
+            .map_or(Diff::new(&EMPTY, &EMPTY), Diff::as_ref)
+    }
+
+    /// Total number of lines moved across all files.
+    pub fn count_total(&self) -> usize {
+        self.files.values().map(|lines| lines.after.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(
+        files: &[(&'static str, &'static str, &'static str)],
+        compare_mode: LineCompareMode,
+    ) -> Vec<(&'static str, Vec<usize>, Vec<usize>)> {
+        let mut detector = MovedLinesDetector::new(&compare_mode, DiffAlgorithm::Default);
+        for &(key, left, right) in files {
+            detector.add_file(key, Diff::new(left.as_bytes(), right.as_bytes()));
+        }
+        let moved = detector.detect();
+        files
+            .iter()
+            .map(|&(key, _, _)| {
+                let lines = moved.get(&key);
+                (
+                    key,
+                    lines.before.iter().copied().collect(),
+                    lines.after.iter().copied().collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_moved_across_files() {
+        let moved = detect(
+            &[
+                ("a", "fn a() {\n  foo();\n  bar();\n}\nkeep\n", "keep\n"),
+                ("b", "x\n", "x\nfn a() {\n  foo();\n  bar();\n}\n"),
+            ],
+            LineCompareMode::Exact,
+        );
+        assert_eq!(
+            moved,
+            [
+                ("a", vec![0, 1, 2, 3], vec![]),
+                ("b", vec![], vec![1, 2, 3, 4])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_moved_within_file() {
+        let moved = detect(
+            &[("a", "1\n2\n3\nw\nx\ny\nz\n", "w\nx\ny\nz\n1\n2\n3\n")],
+            LineCompareMode::Exact,
+        );
+        assert_eq!(moved, [("a", vec![0, 1, 2], vec![4, 5, 6])]);
+    }
+
+    #[test]
+    fn test_moved_too_short() {
+        // Blank lines don't count.
+        let moved = detect(
+            &[("a", "1\n\n2\nkeep\n", "keep\n"), ("b", "", "1\n\n2\n")],
+            LineCompareMode::Exact,
+        );
+        assert_eq!(moved, [("a", vec![], vec![]), ("b", vec![], vec![])]);
+    }
+
+    #[test]
+    fn test_moved_lines_used_once() {
+        // The removed block can only be matched with one of the copies.
+        let moved = detect(
+            &[
+                ("a", "1\n2\n3\n", ""),
+                ("b", "", "1\n2\n3\n"),
+                ("c", "", "1\n2\n3\n"),
+            ],
+            LineCompareMode::Exact,
+        );
+        assert_eq!(
+            moved,
+            [
+                ("a", vec![0, 1, 2], vec![]),
+                ("b", vec![], vec![0, 1, 2]),
+                ("c", vec![], vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_moved_ignore_whitespace() {
+        let files = [("a", "1\n2\n3\n", ""), ("b", "", "  1\n  2\n  3\n")];
+        assert_eq!(
+            detect(&files, LineCompareMode::Exact),
+            [("a", vec![], vec![]), ("b", vec![], vec![])]
+        );
+        assert_eq!(
+            detect(&files, LineCompareMode::IgnoreAllSpace),
+            [("a", vec![0, 1, 2], vec![]), ("b", vec![], vec![0, 1, 2])]
+        );
+    }
+}
@@ -32,6 +32,7 @@ use crate::conflicts::ConflictMaterializeOptions;
 use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::diff::ContentDiff;
+use crate::diff::DiffAlgorithm;
 use crate::diff::DiffHunkKind;
 use crate::merge::Diff;
 use crate::object_id::ObjectId as _;
@@ -183,6 +184,7 @@ pub fn unified_diff_hunks(
     contents: Diff<&BStr>,
     context: usize,
     options: LineCompareMode,
+    algorithm: DiffAlgorithm,
 ) -> Vec<UnifiedDiffHunk<'_>> {
     let mut hunks = vec![];
     let mut current_hunk = UnifiedDiffHunk {
@@ -190,7 +192,7 @@ pub fn unified_diff_hunks(
         right_line_range: 0..0,
         lines: vec![],
     };
-    let diff = diff_by_line(contents.into_array(), &options);
+    let diff = diff_by_line(contents.into_array(), &options, algorithm);
     let mut diff_hunks = diff.hunks().peekable();
     while let Some(hunk) = diff_hunks.next() {
         match hunk.kind {
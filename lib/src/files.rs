@@ -315,7 +315,10 @@ where
     // usually done for 3-way conflicts. Are there better heuristics when there are
     // more than 3 parts?
     let num_diffs = inputs.removes().len();
-    let diff = ContentDiff::by_line(inputs.removes().chain(inputs.adds()));
+    let diff = ContentDiff::by_line_with_algorithm(
+        inputs.removes().chain(inputs.adds()),
+        options.diff_algorithm,
+    );
     let hunks = resolve_diff_hunks(&diff, num_diffs, options.same_change);
     match options.hunk_level {
         FileMergeHunkLevel::Line => B::from_hunks(hunks.map(MergeHunk::Borrowed)),
@@ -492,6 +495,8 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::diff::DiffAlgorithm;
+    use crate::merge_driver::MergeDrivers;
 
     fn conflict<const N: usize>(values: [&[u8]; N]) -> Merge<BString> {
         Merge::from_vec(values.map(hunk).to_vec())
@@ -855,6 +860,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let merge_hunks = |inputs: &_| merge_hunks(inputs, &options);
         // Unchanged and empty on all sides
@@ -971,6 +979,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let merge_hunks = |inputs: &_| merge_hunks(inputs, &options);
         let merge = |inputs: &_| merge(inputs, &options);
@@ -1083,6 +1094,9 @@ mod tests {
         let options = MergeOptions {
             hunk_level: FileMergeHunkLevel::Word,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         };
         let merge = |inputs: &_| merge(inputs, &options);
         // No context line in between, but "\n" is a context word
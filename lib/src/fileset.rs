@@ -14,17 +14,28 @@
 
 //! Functional language for selecting a set of paths.
 
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::iter;
+use std::ops::Range;
 use std::path;
+use std::path::Path;
 use std::slice;
+use std::sync::Arc;
 use std::sync::LazyLock;
 
+use futures::AsyncReadExt as _;
 use globset::Glob;
 use globset::GlobBuilder;
 use itertools::Itertools as _;
 use thiserror::Error;
 
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::FileId;
+use crate::backend::MergedTreeValue;
+use crate::backend::TreeValue;
+use crate::codeowners::CodeOwners;
 use crate::dsl_util::collect_similar;
 use crate::fileset_parser;
 use crate::fileset_parser::BinaryOp;
@@ -37,20 +48,29 @@ pub use crate::fileset_parser::FilesetParseErrorKind;
 pub use crate::fileset_parser::FilesetParseResult;
 use crate::fileset_parser::FunctionCallNode;
 use crate::fileset_parser::UnaryOp;
+use crate::gitignore::GitIgnoreFile;
 use crate::matchers::DifferenceMatcher;
 use crate::matchers::EverythingMatcher;
+use crate::matchers::FilesInMatcher;
 use crate::matchers::FilesMatcher;
 use crate::matchers::GlobsMatcher;
 use crate::matchers::IntersectionMatcher;
 use crate::matchers::Matcher;
 use crate::matchers::NothingMatcher;
 use crate::matchers::PrefixMatcher;
+use crate::matchers::RegexMatcher;
 use crate::matchers::UnionMatcher;
+use crate::merged_tree::MergedTree;
 use crate::repo_path::RelativePathParseError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathComponent;
 use crate::repo_path::RepoPathUiConverter;
 use crate::repo_path::UiPathParseError;
+use crate::settings::HumanByteSize;
+use crate::str_util::StringMatcher;
+use crate::str_util::StringPattern;
+use crate::str_util::StringPatternParseError;
 
 /// Error occurred during file pattern parsing.
 #[derive(Debug, Error)]
@@ -67,6 +87,12 @@ pub enum FilePatternParseError {
     /// Failed to parse glob pattern.
     #[error(transparent)]
     GlobPattern(#[from] globset::Error),
+    /// Failed to parse regular expression.
+    #[error(transparent)]
+    RegexPattern(#[from] regex::Error),
+    /// File name pattern contains path separator.
+    #[error("File name must not contain path separator: {0}")]
+    NameWithSeparator(String),
 }
 
 /// Basic pattern to match `RepoPath`.
@@ -90,9 +116,16 @@ pub enum FilePattern {
         /// Glob pattern relative to `dir`.
         pattern: Box<Glob>,
     },
-    // TODO: add more patterns:
-    // - FilesInPath: files in directory, non-recursively?
-    // - NameGlob or SuffixGlob: file name with glob?
+    /// Matches files in directory non-recursively.
+    FilesInPath(RepoPathBuf),
+    /// Matches file paths with regular expression. File name and substring
+    /// patterns are also translated to regex.
+    PathRegex {
+        /// Prefix directory path where the `regex` will be evaluated.
+        dir: RepoPathBuf,
+        /// Unanchored regex to search in file path relative to `dir`.
+        regex: Box<regex::Regex>,
+    },
 }
 
 impl FilePattern {
@@ -110,12 +143,14 @@ impl FilePattern {
         //   * file: exact file path
         //   * prefix: path prefix (files under directory recursively)
         //   * files-in: files in directory non-recursively
-        //   * name: file name component (or suffix match?)
-        //   * substring: substring match?
-        // * string pattern syntax (+ case sensitivity?)
+        //   * name: file name component
+        //   * substring: substring match
+        // * string pattern syntax (+ "-i" suffix for case-insensitive match)
         //   * path: literal path (default) (default anchor: prefix)
         //   * glob: glob pattern (default anchor: file)
-        //   * regex?
+        //   * regex: regular expression (default anchor: substring)
+        let cwd_dir = || path_converter.parse_file_path("");
+        let root_dir = RepoPathBuf::root;
         match kind {
             "cwd" => Self::cwd_prefix_path(path_converter, input),
             "cwd-file" | "file" => Self::cwd_file_path(path_converter, input),
@@ -123,12 +158,26 @@ impl FilePattern {
             "cwd-glob-i" | "glob-i" => Self::cwd_file_glob_i(path_converter, input),
             "cwd-prefix-glob" | "prefix-glob" => Self::cwd_prefix_glob(path_converter, input),
             "cwd-prefix-glob-i" | "prefix-glob-i" => Self::cwd_prefix_glob_i(path_converter, input),
+            "cwd-files-in" | "files-in" => Self::cwd_files_in_path(path_converter, input),
+            "cwd-name" | "name" => Self::file_name_at(cwd_dir()?, input, false),
+            "cwd-name-i" | "name-i" => Self::file_name_at(cwd_dir()?, input, true),
+            "cwd-substring" | "substring" => Self::path_substring_at(cwd_dir()?, input, false),
+            "cwd-substring-i" | "substring-i" => Self::path_substring_at(cwd_dir()?, input, true),
+            "cwd-regex" | "regex" => Self::path_regex_at(cwd_dir()?, input, false),
+            "cwd-regex-i" | "regex-i" => Self::path_regex_at(cwd_dir()?, input, true),
             "root" => Self::root_prefix_path(input),
             "root-file" => Self::root_file_path(input),
             "root-glob" => Self::root_file_glob(input),
             "root-glob-i" => Self::root_file_glob_i(input),
             "root-prefix-glob" => Self::root_prefix_glob(input),
             "root-prefix-glob-i" => Self::root_prefix_glob_i(input),
+            "root-files-in" => Self::root_files_in_path(input),
+            "root-name" => Self::file_name_at(root_dir(), input, false),
+            "root-name-i" => Self::file_name_at(root_dir(), input, true),
+            "root-substring" => Self::path_substring_at(root_dir(), input, false),
+            "root-substring-i" => Self::path_substring_at(root_dir(), input, true),
+            "root-regex" => Self::path_regex_at(root_dir(), input, false),
+            "root-regex-i" => Self::path_regex_at(root_dir(), input, true),
             _ => Err(FilePatternParseError::InvalidKind(kind.to_owned())),
         }
     }
@@ -192,6 +241,15 @@ impl FilePattern {
         Self::prefix_glob_at(dir, pattern, true)
     }
 
+    /// Pattern that matches files in cwd-relative directory non-recursively.
+    pub fn cwd_files_in_path(
+        path_converter: &RepoPathUiConverter,
+        input: impl AsRef<str>,
+    ) -> Result<Self, FilePatternParseError> {
+        let path = path_converter.parse_file_path(input.as_ref())?;
+        Ok(Self::FilesInPath(path))
+    }
+
     /// Pattern that matches workspace-relative file (or exact) path.
     pub fn root_file_path(input: impl AsRef<str>) -> Result<Self, FilePatternParseError> {
         // TODO: Let caller pass in converter for root-relative paths too
@@ -235,6 +293,54 @@ impl FilePattern {
         Self::prefix_glob_at(dir, pattern, true)
     }
 
+    /// Pattern that matches files in workspace-relative directory
+    /// non-recursively.
+    pub fn root_files_in_path(input: impl AsRef<str>) -> Result<Self, FilePatternParseError> {
+        let path = RepoPathBuf::from_relative_path(input.as_ref())?;
+        Ok(Self::FilesInPath(path))
+    }
+
+    /// Pattern that matches file name under `dir` recursively.
+    pub fn file_name_at(
+        dir: RepoPathBuf,
+        input: &str,
+        icase: bool,
+    ) -> Result<Self, FilePatternParseError> {
+        if input.contains(path::is_separator) {
+            return Err(FilePatternParseError::NameWithSeparator(input.to_owned()));
+        }
+        let pattern = format!("(?:^|/){}$", regex::escape(input));
+        Self::path_regex_at(dir, &pattern, icase)
+    }
+
+    /// Pattern that matches file path containing `input` substring. The path
+    /// is relative to `dir`.
+    pub fn path_substring_at(
+        dir: RepoPathBuf,
+        input: &str,
+        icase: bool,
+    ) -> Result<Self, FilePatternParseError> {
+        Self::path_regex_at(dir, &regex::escape(input), icase)
+    }
+
+    /// Pattern that matches file path with unanchored regular expression. The
+    /// path is relative to `dir`, and uses "/" as separator.
+    pub fn path_regex_at(
+        dir: RepoPathBuf,
+        input: &str,
+        icase: bool,
+    ) -> Result<Self, FilePatternParseError> {
+        // Flags are embedded in the pattern string so that they will be
+        // preserved when compiled into RegexSet.
+        let pattern = if icase {
+            format!("(?i:{input})")
+        } else {
+            input.to_owned()
+        };
+        let regex = Box::new(regex::Regex::new(&pattern)?);
+        Ok(Self::PathRegex { dir, regex })
+    }
+
     fn file_glob_at(
         dir: RepoPathBuf,
         input: &str,
@@ -275,7 +381,8 @@ impl FilePattern {
         match self {
             Self::FilePath(path) => Some(path),
             Self::PrefixPath(path) => Some(path),
-            Self::FileGlob { .. } | Self::PrefixGlob { .. } => None,
+            Self::FilesInPath(path) => Some(path),
+            Self::FileGlob { .. } | Self::PrefixGlob { .. } | Self::PathRegex { .. } => None,
         }
     }
 }
@@ -340,6 +447,63 @@ pub enum FilesetExpression {
     Intersection(Box<Self>, Box<Self>),
     /// Matches the first expression, but not the second expression.
     Difference(Box<Self>, Box<Self>),
+    /// Matches files satisfying the predicate.
+    ///
+    /// Filter predicates depend on file contents, and must be resolved by
+    /// `FilesetExpression::resolve_filters()` before building matcher.
+    Filter(FilesetFilterPredicate),
+}
+
+/// Predicate evaluated against file entries in tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilesetFilterPredicate {
+    /// Matches executable files.
+    Executable,
+    /// Matches symbolic links.
+    Symlink,
+    /// Matches files that look like binary.
+    Binary,
+    /// Matches conflicted files.
+    Conflicted,
+    /// Matches files whose size in bytes is within the range.
+    Size(Range<u64>),
+    /// Matches files that match the `.gitignore` rules in tree.
+    Ignored,
+    /// Matches files that exist in tree. Since filters are evaluated against
+    /// files in tree, this is equivalent to `all()`.
+    Tracked,
+    /// Matches files owned by an owner matching the glob pattern according to
+    /// the `CODEOWNERS` file in tree.
+    OwnedBy(OwnerPattern),
+}
+
+/// Glob pattern of `CODEOWNERS` owners, compared by its source text.
+#[derive(Clone, Debug)]
+pub struct OwnerPattern(StringPattern);
+
+impl OwnerPattern {
+    /// Parses the glob pattern.
+    pub fn glob(src: &str) -> Result<Self, StringPatternParseError> {
+        Ok(Self(StringPattern::glob(src)?))
+    }
+
+    /// Returns the source text of the pattern.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Converts the pattern to matcher object.
+    pub fn to_matcher(&self) -> StringMatcher {
+        self.0.to_matcher()
+    }
+}
+
+impl Eq for OwnerPattern {}
+
+impl PartialEq for OwnerPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
 }
 
 impl FilesetExpression {
@@ -368,6 +532,11 @@ impl FilesetExpression {
         Self::Pattern(FilePattern::PrefixPath(path))
     }
 
+    /// Expression that matches files satisfying the `predicate`.
+    pub fn filter(predicate: FilesetFilterPredicate) -> Self {
+        Self::Filter(predicate)
+    }
+
     /// Expression that matches any of the given `expressions`.
     pub fn union_all(expressions: Vec<Self>) -> Self {
         match expressions.len() {
@@ -401,7 +570,7 @@ impl FilesetExpression {
         iter::from_fn(move || {
             let expr = stack.pop()?;
             match expr {
-                Self::None | Self::All | Self::Pattern(_) => {}
+                Self::None | Self::All | Self::Pattern(_) | Self::Filter(_) => {}
                 Self::UnionAll(exprs) => stack.extend(exprs.iter().rev()),
                 Self::Intersection(expr1, expr2) | Self::Difference(expr1, expr2) => {
                     stack.push(expr2);
@@ -425,10 +594,266 @@ impl FilesetExpression {
         })
     }
 
+    /// Returns true if this expression contains filter predicates.
+    pub fn has_filters(&self) -> bool {
+        self.dfs_pre().any(|expr| matches!(expr, Self::Filter(_)))
+    }
+
     /// Transforms the expression tree to `Matcher` object.
+    ///
+    /// Filter predicates should have been resolved by
+    /// [`FilesetExpression::resolve_filters()`]. Unresolved predicates match
+    /// nothing.
     pub fn to_matcher(&self) -> Box<dyn Matcher> {
         build_union_matcher(self.as_union_all())
     }
+
+    /// Evaluates filter predicates against the files in `trees`, and returns
+    /// new expression in which the predicates are replaced with the matched
+    /// file paths.
+    ///
+    /// A file satisfies a predicate if it does in any of the `trees`.
+    /// `base_ignores` are the ignore rules applied in addition to the
+    /// `.gitignore` files in trees.
+    pub async fn resolve_filters(
+        &self,
+        trees: &[&MergedTree],
+        base_ignores: &Arc<GitIgnoreFile>,
+    ) -> BackendResult<Self> {
+        if !self.has_filters() {
+            return Ok(self.clone());
+        }
+        let mut predicates: Vec<&FilesetFilterPredicate> = Vec::new();
+        for expr in self.dfs_pre() {
+            if let Self::Filter(predicate) = expr
+                && !predicates.contains(&predicate)
+            {
+                predicates.push(predicate);
+            }
+        }
+        // Visit only files that can possibly match the whole expression.
+        let candidate_matcher = self.to_candidate_expression(true).to_matcher();
+        let mut matched_paths = vec![BTreeSet::new(); predicates.len()];
+        for tree in trees {
+            let mut evaluator = FilterEvaluator::new(tree, base_ignores);
+            for (path, value) in tree.entries_matching(candidate_matcher.as_ref()) {
+                let value = value?;
+                let mut entry = FilterEntry::new(&path, &value);
+                for (predicate, paths) in iter::zip(&predicates, &mut matched_paths) {
+                    if !paths.contains(&path) && evaluator.matches(predicate, &mut entry).await? {
+                        paths.insert(path.clone());
+                    }
+                }
+            }
+        }
+        Ok(self.map_filters(&mut |predicate| {
+            let index = predicates.iter().position(|p| *p == predicate).unwrap();
+            let paths = matched_paths[index].iter().cloned();
+            Self::union_all(paths.map(Self::file_path).collect())
+        }))
+    }
+
+    /// Replaces filter predicates with `all()` or `none()` so the resulting
+    /// expression matches superset of files.
+    fn to_candidate_expression(&self, positive: bool) -> Self {
+        match self {
+            Self::None | Self::All | Self::Pattern(_) => self.clone(),
+            Self::UnionAll(exprs) => Self::UnionAll(
+                exprs
+                    .iter()
+                    .map(|expr| expr.to_candidate_expression(positive))
+                    .collect(),
+            ),
+            Self::Intersection(expr1, expr2) => Self::Intersection(
+                Box::new(expr1.to_candidate_expression(positive)),
+                Box::new(expr2.to_candidate_expression(positive)),
+            ),
+            Self::Difference(expr1, expr2) => Self::Difference(
+                Box::new(expr1.to_candidate_expression(positive)),
+                Box::new(expr2.to_candidate_expression(!positive)),
+            ),
+            Self::Filter(_) if positive => Self::All,
+            Self::Filter(_) => Self::None,
+        }
+    }
+
+    fn map_filters(&self, f: &mut impl FnMut(&FilesetFilterPredicate) -> Self) -> Self {
+        match self {
+            Self::None | Self::All | Self::Pattern(_) => self.clone(),
+            Self::UnionAll(exprs) => {
+                Self::UnionAll(exprs.iter().map(|expr| expr.map_filters(f)).collect())
+            }
+            Self::Intersection(expr1, expr2) => Self::Intersection(
+                Box::new(expr1.map_filters(f)),
+                Box::new(expr2.map_filters(f)),
+            ),
+            Self::Difference(expr1, expr2) => Self::Difference(
+                Box::new(expr1.map_filters(f)),
+                Box::new(expr2.map_filters(f)),
+            ),
+            Self::Filter(predicate) => f(predicate),
+        }
+    }
+}
+
+/// Tree entry to be tested by filter predicates.
+struct FilterEntry<'a> {
+    path: &'a RepoPath,
+    value: &'a MergedTreeValue,
+}
+
+impl<'a> FilterEntry<'a> {
+    fn new(path: &'a RepoPath, value: &'a MergedTreeValue) -> Self {
+        Self { path, value }
+    }
+
+    fn terms(&self) -> impl Iterator<Item = &'a TreeValue> {
+        self.value.iter().flatten()
+    }
+
+    fn file_ids(&self) -> impl Iterator<Item = &'a FileId> {
+        self.terms().filter_map(|term| match term {
+            TreeValue::File { id, .. } => Some(id),
+            _ => None,
+        })
+    }
+
+    /// Returns sizes of all file terms.
+    async fn file_sizes(&self, tree: &MergedTree) -> BackendResult<Vec<u64>> {
+        let mut sizes = Vec::new();
+        for id in self.file_ids() {
+            sizes.push(tree.store().file_size(self.path, id).await?);
+        }
+        Ok(sizes)
+    }
+
+    /// Returns up to `len` leading bytes of all file terms.
+    async fn file_prefixes(&self, tree: &MergedTree, len: u64) -> BackendResult<Vec<Vec<u8>>> {
+        let mut prefixes = Vec::new();
+        for id in self.file_ids() {
+            let reader = tree.store().read_file(self.path, id).await?;
+            let mut prefix = vec![];
+            reader
+                .take(len)
+                .read_to_end(&mut prefix)
+                .await
+                .map_err(|err| BackendError::ReadFile {
+                    path: self.path.to_owned(),
+                    id: id.clone(),
+                    source: err.into(),
+                })?;
+            prefixes.push(prefix);
+        }
+        Ok(prefixes)
+    }
+}
+
+/// Evaluates filter predicates against entries in a single tree.
+struct FilterEvaluator<'a> {
+    tree: &'a MergedTree,
+    base_ignores: &'a Arc<GitIgnoreFile>,
+    dir_ignores: HashMap<RepoPathBuf, Arc<GitIgnoreFile>>,
+    codeowners: Option<CodeOwners>,
+    owner_matchers: HashMap<String, StringMatcher>,
+}
+
+impl<'a> FilterEvaluator<'a> {
+    fn new(tree: &'a MergedTree, base_ignores: &'a Arc<GitIgnoreFile>) -> Self {
+        Self {
+            tree,
+            base_ignores,
+            dir_ignores: HashMap::new(),
+            codeowners: None,
+            owner_matchers: HashMap::new(),
+        }
+    }
+
+    async fn matches(
+        &mut self,
+        predicate: &FilesetFilterPredicate,
+        entry: &mut FilterEntry<'_>,
+    ) -> BackendResult<bool> {
+        match predicate {
+            FilesetFilterPredicate::Executable => Ok(entry.terms().any(|term| {
+                matches!(
+                    term,
+                    TreeValue::File {
+                        executable: true,
+                        ..
+                    }
+                )
+            })),
+            FilesetFilterPredicate::Symlink => Ok(entry
+                .terms()
+                .any(|term| matches!(term, TreeValue::Symlink(_)))),
+            FilesetFilterPredicate::Binary => {
+                // Same heuristic as diff: the first 8k bytes contain a null
+                // character.
+                const PEEK_SIZE: u64 = 8000;
+                let prefixes = entry.file_prefixes(self.tree, PEEK_SIZE).await?;
+                Ok(prefixes.iter().any(|prefix| prefix.contains(&b'\0')))
+            }
+            FilesetFilterPredicate::Conflicted => Ok(!entry.value.is_resolved()),
+            FilesetFilterPredicate::Size(range) => {
+                let sizes = entry.file_sizes(self.tree).await?;
+                Ok(sizes.iter().any(|size| range.contains(size)))
+            }
+            FilesetFilterPredicate::Ignored => self.is_ignored(entry.path).await,
+            FilesetFilterPredicate::Tracked => Ok(true),
+            FilesetFilterPredicate::OwnedBy(pattern) => {
+                if self.codeowners.is_none() {
+                    self.codeowners = Some(CodeOwners::load(self.tree).await?);
+                }
+                let matcher = self
+                    .owner_matchers
+                    .entry(pattern.as_str().to_owned())
+                    .or_insert_with(|| pattern.to_matcher());
+                let codeowners = self.codeowners.as_ref().unwrap();
+                Ok(codeowners.is_owned_by(entry.path, |owner| matcher.is_match(owner)))
+            }
+        }
+    }
+
+    async fn is_ignored(&mut self, path: &RepoPath) -> BackendResult<bool> {
+        let dirs = path.ancestors().skip(1).collect_vec();
+        // Check from the root directory since files in ignored directory
+        // should also be ignored.
+        for (parent_dir, dir) in dirs.iter().rev().tuple_windows() {
+            let ignores = self.ignores_in(parent_dir).await?;
+            if ignores.matches_dir(dir) {
+                return Ok(true);
+            }
+        }
+        let ignores = self.ignores_in(dirs[0]).await?;
+        Ok(ignores.matches_file(path))
+    }
+
+    /// Returns ignore rules applicable to files in the `dir`.
+    async fn ignores_in(&mut self, dir: &RepoPath) -> BackendResult<Arc<GitIgnoreFile>> {
+        let mut ignores = self.base_ignores.clone();
+        for dir in dir.ancestors().collect_vec().into_iter().rev() {
+            if let Some(cached) = self.dir_ignores.get(dir) {
+                ignores = cached.clone();
+                continue;
+            }
+            let ignore_path = dir.join(RepoPathComponent::new(".gitignore").unwrap());
+            if let Some(Some(TreeValue::File { id, .. })) =
+                self.tree.path_value(&ignore_path).await?.as_resolved()
+            {
+                let content = self
+                    .tree
+                    .store()
+                    .read_file_contents(&ignore_path, id)
+                    .await?;
+                let disk_path = Path::new(ignore_path.as_internal_file_string());
+                ignores = ignores
+                    .chain(dir, disk_path, &content)
+                    .map_err(|err| BackendError::Other(err.into()))?;
+            }
+            self.dir_ignores.insert(dir.to_owned(), ignores.clone());
+        }
+        Ok(ignores)
+    }
 }
 
 /// Transforms the union `expressions` to `Matcher` object.
@@ -440,6 +865,8 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
     let mut prefix_paths = Vec::new();
     let mut file_globs = GlobsMatcher::builder().prefix_paths(false);
     let mut prefix_globs = GlobsMatcher::builder().prefix_paths(true);
+    let mut files_in_paths = Vec::new();
+    let mut dir_regexes = Vec::new();
     let mut matchers: Vec<Option<Box<dyn Matcher>>> = Vec::new();
     for expr in expressions {
         let matcher: Box<dyn Matcher> = match expr {
@@ -452,6 +879,8 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
                     FilePattern::PrefixPath(path) => prefix_paths.push(path),
                     FilePattern::FileGlob { dir, pattern } => file_globs.add(dir, pattern),
                     FilePattern::PrefixGlob { dir, pattern } => prefix_globs.add(dir, pattern),
+                    FilePattern::FilesInPath(path) => files_in_paths.push(path),
+                    FilePattern::PathRegex { dir, regex } => dir_regexes.push((&**dir, &**regex)),
                 }
                 continue;
             }
@@ -467,6 +896,8 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
                 let m2 = build_union_matcher(expr2.as_union_all());
                 Box::new(DifferenceMatcher::new(m1, m2))
             }
+            // Filters are supposed to be resolved by caller.
+            FilesetExpression::Filter(_) => Box::new(NothingMatcher),
         };
         matchers.push(Some(matcher));
     }
@@ -483,6 +914,12 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
     if !prefix_globs.is_empty() {
         matchers.push(Some(Box::new(prefix_globs.build())));
     }
+    if !files_in_paths.is_empty() {
+        matchers.push(Some(Box::new(FilesInMatcher::new(files_in_paths))));
+    }
+    if !dir_regexes.is_empty() {
+        matchers.push(Some(Box::new(RegexMatcher::new(dir_regexes))));
+    }
     union_all_matchers(&mut matchers)
 }
 
@@ -522,9 +959,75 @@ static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, FilesetFunction>> = LazyLock
         function.expect_no_arguments()?;
         Ok(FilesetExpression::all())
     });
+    map.insert("executable", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::filter(
+            FilesetFilterPredicate::Executable,
+        ))
+    });
+    map.insert("symlink", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::filter(FilesetFilterPredicate::Symlink))
+    });
+    map.insert("binary", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::filter(FilesetFilterPredicate::Binary))
+    });
+    map.insert("conflicted", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::filter(
+            FilesetFilterPredicate::Conflicted,
+        ))
+    });
+    map.insert("size", |_diagnostics, _path_converter, function| {
+        let [arg] = function.expect_exact_arguments()?;
+        let text = fileset_parser::expect_string_literal("string", arg)?;
+        let range = parse_size_range(text).map_err(|message| {
+            FilesetParseError::expression(format!("Invalid file size: {message}"), arg.span)
+        })?;
+        Ok(FilesetExpression::filter(FilesetFilterPredicate::Size(
+            range,
+        )))
+    });
+    map.insert("ignored", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::filter(FilesetFilterPredicate::Ignored))
+    });
+    map.insert("tracked", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::filter(FilesetFilterPredicate::Tracked))
+    });
+    map.insert("owned_by", |_diagnostics, _path_converter, function| {
+        let [arg] = function.expect_exact_arguments()?;
+        let owner = fileset_parser::expect_string_literal("string", arg)?;
+        let pattern = OwnerPattern::glob(owner).map_err(|err| {
+            FilesetParseError::expression("Invalid owner pattern", arg.span).with_source(err)
+        })?;
+        Ok(FilesetExpression::filter(FilesetFilterPredicate::OwnedBy(
+            pattern,
+        )))
+    });
     map
 });
 
+/// Parses size comparison such as `">1MiB"` into range of bytes.
+fn parse_size_range(text: &str) -> Result<Range<u64>, &'static str> {
+    let (op, size) = [">=", "<=", ">", "<", "="]
+        .iter()
+        .find_map(|op| Some((*op, text.strip_prefix(op)?)))
+        .unwrap_or(("=", text));
+    let HumanByteSize(size) = size.trim().parse()?;
+    let range = match op {
+        ">=" => size..u64::MAX,
+        "<=" => 0..size.saturating_add(1),
+        ">" => size.saturating_add(1)..u64::MAX,
+        "<" => 0..size,
+        "=" => size..size.saturating_add(1),
+        _ => unreachable!(),
+    };
+    Ok(range)
+}
+
 fn resolve_function(
     diagnostics: &mut FilesetDiagnostics,
     path_converter: &RepoPathUiConverter,
@@ -1188,6 +1691,110 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_files_in_and_regex_patterns() -> TestResult {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let context = FilesetParseContext {
+            aliases_map: &FilesetAliasesMap::new(),
+            path_converter: &RepoPathUiConverter::Fs {
+                cwd: PathBuf::from("/ws/cur"),
+                base: PathBuf::from("/ws"),
+            },
+        };
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
+
+        insta::assert_debug_snapshot!(
+            parse("files-in:foo")?,
+            @r#"Pattern(FilesInPath("cur/foo"))"#);
+        insta::assert_debug_snapshot!(
+            parse("cwd-files-in:..")?,
+            @r#"Pattern(FilesInPath(""))"#);
+        insta::assert_debug_snapshot!(
+            parse("root-files-in:foo")?,
+            @r#"Pattern(FilesInPath("foo"))"#);
+
+        insta::assert_debug_snapshot!(
+            parse("name:'a.rs'")?,
+            @r#"
+        Pattern(
+            PathRegex {
+                dir: "cur",
+                regex: Regex("(?:^|/)a\\.rs$"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("root-name-i:'a.rs'")?,
+            @r#"
+        Pattern(
+            PathRegex {
+                dir: "",
+                regex: Regex("(?i:(?:^|/)a\\.rs$)"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("name:'a/b'").unwrap_err().kind(),
+            @r#"Expression("Invalid file pattern")"#);
+
+        insta::assert_debug_snapshot!(
+            parse("substring:'a.b'")?,
+            @r#"
+        Pattern(
+            PathRegex {
+                dir: "cur",
+                regex: Regex("a\\.b"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("root-substring-i:foo")?,
+            @r#"
+        Pattern(
+            PathRegex {
+                dir: "",
+                regex: Regex("(?i:foo)"),
+            },
+        )
+        "#);
+
+        insta::assert_debug_snapshot!(
+            parse(r"regex:'^foo/.*\.rs$'")?,
+            @r#"
+        Pattern(
+            PathRegex {
+                dir: "cur",
+                regex: Regex("^foo/.*\\.rs$"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("cwd-regex-i:x|root-regex:y")?,
+            @r#"
+        UnionAll(
+            [
+                Pattern(
+                    PathRegex {
+                        dir: "cur",
+                        regex: Regex("(?i:x)"),
+                    },
+                ),
+                Pattern(
+                    PathRegex {
+                        dir: "",
+                        regex: Regex("y"),
+                    },
+                ),
+            ],
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("regex:'('").unwrap_err().kind(),
+            @r#"Expression("Invalid file pattern")"#);
+        Ok(())
+    }
+
     #[test]
     fn test_parse_function() -> TestResult {
         let settings = insta_settings();
@@ -1220,6 +1827,84 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_filter_function() -> TestResult {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let context = FilesetParseContext {
+            aliases_map: &FilesetAliasesMap::new(),
+            path_converter: &RepoPathUiConverter::Fs {
+                cwd: PathBuf::from("/ws/cur"),
+                base: PathBuf::from("/ws"),
+            },
+        };
+        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &context);
+
+        insta::assert_debug_snapshot!(parse("executable()")?, @"Filter(Executable)");
+        insta::assert_debug_snapshot!(parse("conflicted()")?, @"Filter(Conflicted)");
+        insta::assert_debug_snapshot!(parse("binary() & ~ignored()")?, @"
+        Intersection(
+            Filter(Binary),
+            Difference(
+                All,
+                Filter(Ignored),
+            ),
+        )
+        ");
+        insta::assert_debug_snapshot!(
+            parse("size('>1KiB')")?,
+            @"Filter(Size(1025..18446744073709551615))");
+        insta::assert_debug_snapshot!(parse("size(x)").unwrap_err().kind(), @r#"Expression("Invalid file size: must start with a number")"#);
+        insta::assert_debug_snapshot!(
+            parse("owned_by('@org/*')")?,
+            @r#"
+        Filter(
+            OwnedBy(OwnerPattern(Glob(GlobPattern("@org/*")))),
+        )
+        "#);
+        insta::assert_debug_snapshot!(parse("owned_by('[')").unwrap_err().kind(), @r#"Expression("Invalid owner pattern")"#);
+        assert!(parse("all() | symlink()")?.has_filters());
+        assert!(!parse("all() | tracked")?.has_filters());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_size_range() {
+        assert_eq!(parse_size_range("10"), Ok(10..11));
+        assert_eq!(parse_size_range("=10"), Ok(10..11));
+        assert_eq!(parse_size_range("<10"), Ok(0..10));
+        assert_eq!(parse_size_range("<=10"), Ok(0..11));
+        assert_eq!(parse_size_range(">10"), Ok(11..u64::MAX));
+        assert_eq!(parse_size_range(">= 1M"), Ok(1024 * 1024..u64::MAX));
+        assert_eq!(parse_size_range("<2KiB"), Ok(0..2048));
+        assert!(parse_size_range(">").is_err());
+        assert!(parse_size_range("1X").is_err());
+    }
+
+    #[test]
+    fn test_to_candidate_expression() {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+        let expr = FilesetExpression::prefix_path(repo_path_buf("foo"))
+            .intersection(FilesetExpression::filter(FilesetFilterPredicate::Binary))
+            .difference(
+                FilesetExpression::file_path(repo_path_buf("foo/bar"))
+                    .intersection(FilesetExpression::filter(FilesetFilterPredicate::Symlink)),
+            );
+        insta::assert_debug_snapshot!(expr.to_candidate_expression(true), @r#"
+        Difference(
+            Intersection(
+                Pattern(PrefixPath("foo")),
+                All,
+            ),
+            Intersection(
+                Pattern(FilePath("foo/bar")),
+                None,
+            ),
+        )
+        "#);
+    }
+
     #[test]
     fn test_parse_compound_expression() -> TestResult {
         let settings = insta_settings();
@@ -1373,6 +2058,40 @@ mod tests {
         "#);
     }
 
+    #[test]
+    fn test_build_matcher_files_in_and_regex_patterns() -> TestResult {
+        let settings = insta_settings();
+        let _guard = settings.bind_to_scope();
+
+        let expr = FilesetExpression::union_all(vec![
+            FilesetExpression::pattern(FilePattern::root_files_in_path("foo")?),
+            FilesetExpression::pattern(FilePattern::root_files_in_path("foo/bar")?),
+            FilesetExpression::pattern(FilePattern::file_name_at(
+                repo_path_buf("foo"),
+                "baz",
+                false,
+            )?),
+            FilesetExpression::pattern(FilePattern::path_regex_at(repo_path_buf(""), "x", true)?),
+        ]);
+        insta::assert_debug_snapshot!(expr.to_matcher(), @r#"
+        UnionMatcher {
+            input1: FilesInMatcher {
+                tree: Dir {
+                    "foo": FilesIn {
+                        "bar": FilesIn {},
+                    },
+                },
+            },
+            input2: RegexMatcher {
+                tree: Some(RegexSet(["(?i:x)"])) {
+                    "foo": Some(RegexSet(["(?:^|/)baz$"])) {},
+                },
+            },
+        }
+        "#);
+        Ok(())
+    }
+
     #[test]
     fn test_build_matcher_union_patterns_of_same_kind() {
         let settings = insta_settings();
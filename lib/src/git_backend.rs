@@ -1066,6 +1066,15 @@ impl Backend for GitBackend {
         Ok(Box::pin(Cursor::new(data)))
     }
 
+    async fn file_size(&self, _path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let locked_repo = self.lock_git_repo();
+        let git_blob_id = validate_git_object_id(&locked_repo, id)?;
+        let header = locked_repo
+            .find_header(git_blob_id)
+            .map_err(|err| map_not_found_err(err, id))?;
+        Ok(header.size())
+    }
+
     async fn write_file(
         &self,
         _path: &RepoPath,
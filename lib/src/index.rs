@@ -33,6 +33,7 @@ use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
 use crate::store::Store;
+use crate::str_util::StringExpression;
 
 /// Returned by [`IndexStore`] in the event of an error.
 #[derive(Debug, Error)]
@@ -162,6 +163,16 @@ pub trait Index: Send + Sync {
         commit_id: &CommitId,
     ) -> IndexResult<Option<Box<dyn Iterator<Item = RepoPathBuf> + '_>>>;
 
+    /// Returns paths which may contain lines matching the `text` expression in
+    /// the tree of the specified commit. The paths are sorted. Returns `None`
+    /// if the index can't narrow down the paths, or if too many paths may
+    /// contain the lines.
+    async fn paths_maybe_containing_lines(
+        &self,
+        commit_id: &CommitId,
+        text: &StringExpression,
+    ) -> IndexResult<Option<Vec<RepoPathBuf>>>;
+
     /// Resolves the revset `expression` against the index and corresponding
     /// `store`.
     fn evaluate_revset(
@@ -32,6 +32,7 @@ pub mod absorb;
 pub mod annotate;
 pub mod backend;
 pub mod bisect;
+pub mod codeowners;
 pub mod commit;
 pub mod commit_builder;
 pub mod config;
@@ -76,6 +77,7 @@ pub mod local_working_copy;
 pub mod lock;
 pub mod matchers;
 pub mod merge;
+pub mod merge_driver;
 pub mod merged_tree;
 pub mod merged_tree_builder;
 pub mod object_id;
@@ -89,6 +91,7 @@ pub mod ref_name;
 pub mod refs;
 pub mod repo;
 pub mod repo_path;
+pub mod rerere;
 pub mod revset;
 mod revset_parser;
 pub mod rewrite;
@@ -107,6 +110,7 @@ pub mod simple_op_store;
 pub mod ssh_signing;
 pub mod stacked_table;
 pub mod store;
+pub mod structured_merge;
 pub use jj_core::str_util;
 pub mod submodule_store;
 #[cfg(feature = "testing")]
@@ -242,6 +242,114 @@ fn prefix_tree_to_visit_sets(tree: &RepoPathTree<PrefixNodeKind>) -> Visit {
     Visit::sets(dirs, files)
 }
 
+/// Matches files in directories non-recursively.
+#[derive(PartialEq, Eq, Debug)]
+pub struct FilesInMatcher {
+    tree: RepoPathTree<FilesInNodeKind>,
+}
+
+impl FilesInMatcher {
+    pub fn new(dirs: impl IntoIterator<Item = impl AsRef<RepoPath>>) -> Self {
+        let mut tree = RepoPathTree::default();
+        for dir in dirs {
+            tree.add(dir.as_ref()).set_value(FilesInNodeKind::FilesIn);
+        }
+        Self { tree }
+    }
+}
+
+impl Matcher for FilesInMatcher {
+    fn matches(&self, file: &RepoPath) -> bool {
+        file.parent()
+            .and_then(|dir| self.tree.get(dir))
+            .is_some_and(|sub| *sub.value() == FilesInNodeKind::FilesIn)
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        let Some(sub) = self.tree.get(dir) else {
+            return Visit::Nothing;
+        };
+        // all child nodes are either target directories or their ancestors
+        let dirs: HashSet<_> = sub.children().map(|(name, _)| name.to_owned()).collect();
+        match sub.value() {
+            FilesInNodeKind::FilesIn => Visit::Specific {
+                dirs: VisitDirs::Set(dirs),
+                files: VisitFiles::All,
+            },
+            FilesInNodeKind::Dir => Visit::sets(dirs, HashSet::new()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum FilesInNodeKind {
+    /// Represents an intermediate directory.
+    #[default]
+    Dir,
+    /// Represents a directory whose files should match.
+    FilesIn,
+}
+
+/// Matches file paths with unanchored regular expressions.
+///
+/// Each pattern is evaluated against the file path relative to the directory
+/// the pattern was added to.
+#[derive(Clone, Debug)]
+pub struct RegexMatcher {
+    tree: RepoPathTree<Option<regex::RegexSet>>,
+}
+
+impl RegexMatcher {
+    /// Creates matcher from `(dir, regex)` pairs.
+    ///
+    /// Regex flags should be embedded in the pattern string because only the
+    /// source pattern is compiled into the matcher.
+    pub fn new<'a>(
+        dir_patterns: impl IntoIterator<Item = (&'a RepoPath, &'a regex::Regex)>,
+    ) -> Self {
+        let mut dir_patterns = dir_patterns.into_iter().collect_vec();
+        dir_patterns.sort_unstable_by_key(|&(dir, _)| dir);
+
+        let mut tree: RepoPathTree<Option<regex::RegexSet>> = Default::default();
+        for (dir, chunk) in &dir_patterns.into_iter().chunk_by(|&(dir, _)| dir) {
+            let regex = regex::RegexSet::new(chunk.map(|(_, regex)| regex.as_str()))
+                .expect("compiled regex should be valid");
+            let sub = tree.add(dir);
+            assert!(sub.value().is_none());
+            sub.set_value(Some(regex));
+        }
+        Self { tree }
+    }
+}
+
+impl Matcher for RegexMatcher {
+    fn matches(&self, file: &RepoPath) -> bool {
+        // check if any ancestor (dir, patterns) matches 'file'
+        self.tree
+            .walk_to(file)
+            .take_while(|(_, tail_path)| !tail_path.is_root()) // only dirs
+            .any(|(sub, tail_path)| {
+                let tail = tail_path.as_internal_file_string();
+                sub.value().as_ref().is_some_and(|pat| pat.is_match(tail))
+            })
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        for (sub, tail_path) in self.tree.walk_to(dir) {
+            // 'dir' or its ancestor has patterns
+            if sub.value().is_some() {
+                return Visit::SOME;
+            }
+            // 'dir' found, and is an ancestor of pattern paths
+            if tail_path.is_root() {
+                let sub_dirs = sub.children().map(|(name, _)| name.to_owned()).collect();
+                return Visit::sets(sub_dirs, HashSet::new());
+            }
+        }
+        Visit::Nothing
+    }
+}
+
 /// Matches file or prefix paths with glob patterns.
 #[derive(Clone, Debug)]
 pub struct GlobsMatcher {
@@ -939,6 +1047,92 @@ mod tests {
         assert_eq!(m.visit(repo_path("foo/bar/baz")), Visit::AllRecursively);
     }
 
+    #[test]
+    fn test_files_in_matcher_root() {
+        let m = FilesInMatcher::new([RepoPath::root()]);
+        assert!(!m.matches(RepoPath::root()));
+        assert!(m.matches(repo_path("file")));
+        assert!(!m.matches(repo_path("dir/file")));
+        assert_eq!(
+            m.visit(RepoPath::root()),
+            Visit::Specific {
+                dirs: VisitDirs::Set(hashset! {}),
+                files: VisitFiles::All,
+            }
+        );
+        assert_eq!(m.visit(repo_path("dir")), Visit::Nothing);
+    }
+
+    #[test]
+    fn test_files_in_matcher_nested() {
+        let m = FilesInMatcher::new([repo_path("foo"), repo_path("foo/bar/baz")]);
+        assert!(!m.matches(repo_path("file")));
+        assert!(!m.matches(repo_path("foo")));
+        assert!(m.matches(repo_path("foo/file")));
+        assert!(m.matches(repo_path("foo/bar")));
+        assert!(!m.matches(repo_path("foo/bar/file")));
+        assert!(m.matches(repo_path("foo/bar/baz/file")));
+        assert!(!m.matches(repo_path("foo/bar/baz/dir/file")));
+
+        assert_eq!(
+            m.visit(RepoPath::root()),
+            Visit::sets(hashset! {repo_path_component_buf("foo")}, hashset! {})
+        );
+        assert_eq!(
+            m.visit(repo_path("foo")),
+            Visit::Specific {
+                dirs: VisitDirs::Set(hashset! {repo_path_component_buf("bar")}),
+                files: VisitFiles::All,
+            }
+        );
+        assert_eq!(
+            m.visit(repo_path("foo/bar")),
+            Visit::sets(hashset! {repo_path_component_buf("baz")}, hashset! {})
+        );
+        assert_eq!(
+            m.visit(repo_path("foo/bar/baz")),
+            Visit::Specific {
+                dirs: VisitDirs::Set(hashset! {}),
+                files: VisitFiles::All,
+            }
+        );
+        assert_eq!(m.visit(repo_path("foo/bar/baz/dir")), Visit::Nothing);
+        assert_eq!(m.visit(repo_path("qux")), Visit::Nothing);
+    }
+
+    #[test]
+    fn test_regex_matcher() {
+        let re1 = regex::Regex::new("a.c").unwrap();
+        let re2 = regex::Regex::new("(?i)(?:^|/)bar$").unwrap();
+        let m = RegexMatcher::new([(RepoPath::root(), &re1), (repo_path("foo"), &re2)]);
+        assert!(!m.matches(RepoPath::root()));
+        assert!(m.matches(repo_path("abc")));
+        assert!(m.matches(repo_path("dir/xabcx")));
+        assert!(m.matches(repo_path("a/c")));
+        assert!(!m.matches(repo_path("bar")));
+        assert!(m.matches(repo_path("foo/BAR")));
+        assert!(m.matches(repo_path("foo/dir/bar")));
+        assert!(!m.matches(repo_path("foo/dir/xbar")));
+        // Patterns are evaluated against dir-relative paths
+        assert!(!m.matches(repo_path("foo")));
+
+        assert_eq!(m.visit(RepoPath::root()), Visit::SOME);
+        assert_eq!(m.visit(repo_path("foo/dir")), Visit::SOME);
+
+        let m = RegexMatcher::new([(repo_path("foo/bar"), &re1)]);
+        assert_eq!(
+            m.visit(RepoPath::root()),
+            Visit::sets(hashset! {repo_path_component_buf("foo")}, hashset! {})
+        );
+        assert_eq!(
+            m.visit(repo_path("foo")),
+            Visit::sets(hashset! {repo_path_component_buf("bar")}, hashset! {})
+        );
+        assert_eq!(m.visit(repo_path("foo/bar")), Visit::SOME);
+        assert_eq!(m.visit(repo_path("foo/bar/baz")), Visit::SOME);
+        assert_eq!(m.visit(repo_path("qux")), Visit::Nothing);
+    }
+
     #[test]
     fn test_union_matcher_concatenate_roots() {
         let m1 = PrefixMatcher::new([repo_path("foo"), repo_path("bar")]);
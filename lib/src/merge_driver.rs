@@ -0,0 +1,359 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merge drivers which automatically resolve file conflicts left by the
+//! builtin content merge.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::thread;
+
+use bstr::BString;
+use futures::channel::oneshot;
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::config::ConfigGetError;
+use crate::files;
+use crate::files::MergeResult;
+use crate::fileset;
+use crate::fileset::FilesetAliasesMap;
+use crate::fileset::FilesetDiagnostics;
+use crate::fileset::FilesetExpression;
+use crate::fileset::FilesetParseContext;
+use crate::matchers::Matcher;
+use crate::merge::Merge;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathUiConverter;
+use crate::settings::UserSettings;
+use crate::structured_merge;
+use crate::tree_merge::MergeOptions;
+
+/// Builtin merge driver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinMergeDriver {
+    /// Includes the lines of all sides in conflicting hunks.
+    Union,
+    /// Takes the first side (e.g. the destination of rebase) in conflicting
+    /// hunks.
+    Ours,
+    /// Takes the last side (e.g. the commit being rebased) in conflicting
+    /// hunks.
+    Theirs,
+    /// Merges JSON files by keys and array elements.
+    Json,
+    /// Merges TOML files by keys and array elements.
+    Toml,
+    /// Merges YAML files by keys and array elements.
+    Yaml,
+}
+
+/// How a merge driver resolves file conflicts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MergeDriverKind {
+    /// Builtin driver.
+    Builtin(BuiltinMergeDriver),
+    /// External command with `%O`, `%A`, `%B`, and `%P` placeholders. The
+    /// command is expected to write the merged contents to the `%A` file, and
+    /// exit with zero status if the conflict is resolved.
+    External(Vec<String>),
+}
+
+#[derive(Debug)]
+struct MergeDriver {
+    matcher: Box<dyn Matcher>,
+    kind: MergeDriverKind,
+}
+
+/// Merge drivers selected by file path.
+#[derive(Clone, Debug, Default)]
+pub struct MergeDrivers {
+    drivers: Arc<[MergeDriver]>,
+}
+
+/// Entry of the `merge.drivers` config table.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawMergeDriverConfig {
+    patterns: Vec<String>,
+    #[serde(default)]
+    builtin: Option<BuiltinMergeDriver>,
+    #[serde(default)]
+    command: Option<Vec<String>>,
+    #[serde(default = "default_merge_driver_enabled")]
+    enabled: bool,
+}
+
+fn default_merge_driver_enabled() -> bool {
+    true
+}
+
+impl MergeDrivers {
+    /// Loads merge drivers from the `merge.drivers` table of `settings`.
+    ///
+    /// Drivers are sorted by name, and the first driver matching the file path
+    /// is used.
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        // Patterns in config files aren't relative to the cwd.
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::new(),
+            base: PathBuf::new(),
+        };
+        let aliases_map = FilesetAliasesMap::new();
+        let context = FilesetParseContext {
+            aliases_map: &aliases_map,
+            path_converter: &path_converter,
+        };
+        let drivers = settings
+            .table_keys("merge.drivers")
+            .sorted()
+            .map(|name| -> Result<_, ConfigGetError> {
+                let config: RawMergeDriverConfig = settings.get(["merge", "drivers", name])?;
+                let config_error =
+                    |error: Box<dyn std::error::Error + Send + Sync>| ConfigGetError::Type {
+                        name: format!("merge.drivers.{name}"),
+                        error,
+                        source_path: None,
+                    };
+                if !config.enabled {
+                    return Ok(None);
+                }
+                let mut diagnostics = FilesetDiagnostics::new();
+                let expressions: Vec<_> = config
+                    .patterns
+                    .iter()
+                    .map(|text| fileset::parse(&mut diagnostics, text, &context))
+                    .try_collect()
+                    .map_err(|err| config_error(err.into()))?;
+                let expression = FilesetExpression::union_all(expressions);
+                if expression.has_filters() {
+                    return Err(config_error("File predicates are not supported".into()));
+                }
+                let kind = match (config.builtin, config.command) {
+                    (Some(builtin), None) => MergeDriverKind::Builtin(builtin),
+                    (None, Some(command)) if !command.is_empty() => {
+                        MergeDriverKind::External(command)
+                    }
+                    (None, Some(_)) => return Err(config_error("Empty command".into())),
+                    _ => {
+                        let message = "Exactly one of `builtin` or `command` must be set";
+                        return Err(config_error(message.into()));
+                    }
+                };
+                Ok(Some(MergeDriver {
+                    matcher: expression.to_matcher(),
+                    kind,
+                }))
+            })
+            .flatten_ok()
+            .try_collect()?;
+        Ok(Self { drivers })
+    }
+
+    /// Returns true if no drivers are configured.
+    pub fn is_empty(&self) -> bool {
+        self.drivers.is_empty()
+    }
+
+    /// Returns the driver to be used for the file at `path`.
+    pub fn find(&self, path: &RepoPath) -> Option<&MergeDriverKind> {
+        self.drivers
+            .iter()
+            .find(|driver| driver.matcher.matches(path))
+            .map(|driver| &driver.kind)
+    }
+}
+
+/// Error from an external merge driver.
+#[derive(Debug, Error)]
+pub enum MergeDriverError {
+    /// Failed to set up files or run the command.
+    #[error("Failed to run merge driver {program}")]
+    Io {
+        /// Name of the command.
+        program: String,
+        /// Underlying error.
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Merges the file `contents` at `path` by the `driver`.
+///
+/// Returns `Ok(None)` if the driver can't resolve the conflict, which includes
+/// the case where the external command exits with non-zero status. External
+/// drivers only support 3-way merges. External commands are run in a separate
+/// thread so the async executor isn't blocked.
+pub async fn run_merge_driver<T: AsRef<[u8]>>(
+    driver: &MergeDriverKind,
+    path: &RepoPath,
+    contents: &Merge<T>,
+    options: &MergeOptions,
+) -> Result<Option<BString>, MergeDriverError> {
+    match driver {
+        MergeDriverKind::Builtin(builtin) => Ok(merge_with_builtin(*builtin, contents, options)),
+        MergeDriverKind::External(command) => {
+            let [ours, base, theirs] = contents.as_slice() else {
+                return Ok(None);
+            };
+            let command = command.clone();
+            let path = path.to_owned();
+            let contents = [base, ours, theirs].map(|content| content.as_ref().to_vec());
+            let (sender, receiver) = oneshot::channel();
+            thread::spawn(move || {
+                let contents = contents.each_ref().map(Vec::as_slice);
+                sender
+                    .send(run_external_merge_driver(&command, &path, contents))
+                    .ok();
+            });
+            receiver
+                .await
+                .expect("merge driver thread should send result")
+        }
+    }
+}
+
+fn merge_with_builtin<T: AsRef<[u8]>>(
+    builtin: BuiltinMergeDriver,
+    contents: &Merge<T>,
+    options: &MergeOptions,
+) -> Option<BString> {
+    match builtin {
+        BuiltinMergeDriver::Union | BuiltinMergeDriver::Ours | BuiltinMergeDriver::Theirs => {}
+        BuiltinMergeDriver::Json => return structured_merge::merge_json(contents, options),
+        BuiltinMergeDriver::Toml => return structured_merge::merge_toml(contents, options),
+        BuiltinMergeDriver::Yaml => return structured_merge::merge_yaml(contents, options),
+    }
+    let hunks = match files::merge_hunks(contents, options) {
+        MergeResult::Resolved(content) => return Some(content),
+        MergeResult::Conflict(hunks) => hunks,
+    };
+    let mut merged = BString::default();
+    for hunk in &hunks {
+        if let Some(content) = hunk.as_resolved() {
+            merged.extend_from_slice(content);
+            continue;
+        }
+        match builtin {
+            BuiltinMergeDriver::Union => {
+                for content in hunk.adds() {
+                    if !merged.is_empty() && !merged.ends_with(b"\n") && !content.is_empty() {
+                        merged.push(b'\n');
+                    }
+                    merged.extend_from_slice(content);
+                }
+            }
+            BuiltinMergeDriver::Ours => merged.extend_from_slice(hunk.first()),
+            BuiltinMergeDriver::Theirs => {
+                merged.extend_from_slice(hunk.adds().last().unwrap());
+            }
+            BuiltinMergeDriver::Json | BuiltinMergeDriver::Toml | BuiltinMergeDriver::Yaml => {
+                unreachable!()
+            }
+        }
+    }
+    Some(merged)
+}
+
+fn run_external_merge_driver(
+    command: &[String],
+    path: &RepoPath,
+    [base, ours, theirs]: [&[u8]; 3],
+) -> Result<Option<BString>, MergeDriverError> {
+    let program = &command[0];
+    let io_error = |source| MergeDriverError::Io {
+        program: program.clone(),
+        source,
+    };
+    let temp_dir = tempfile::Builder::new()
+        .prefix("jj-merge-driver-")
+        .tempdir()
+        .map_err(io_error)?;
+    let base_path = temp_dir.path().join("base");
+    let ours_path = temp_dir.path().join("ours");
+    let theirs_path = temp_dir.path().join("theirs");
+    fs::write(&base_path, base).map_err(io_error)?;
+    fs::write(&ours_path, ours).map_err(io_error)?;
+    fs::write(&theirs_path, theirs).map_err(io_error)?;
+
+    let args = command[1..].iter().map(|arg| {
+        arg.replace("%O", &base_path.to_string_lossy())
+            .replace("%A", &ours_path.to_string_lossy())
+            .replace("%B", &theirs_path.to_string_lossy())
+            .replace("%P", path.as_internal_file_string())
+    });
+    tracing::info!(?command, ?path, "running merge driver");
+    let status = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .map_err(io_error)?;
+    if !status.success() {
+        tracing::info!(%status, "merge driver left conflicts");
+        return Ok(None);
+    }
+    let merged = fs::read(&ours_path).map_err(io_error)?;
+    Ok(Some(merged.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::DiffAlgorithm;
+    use crate::files::FileMergeHunkLevel;
+    use crate::merge::SameChange;
+
+    fn merge(builtin: BuiltinMergeDriver, inputs: &[&str]) -> BString {
+        let options = MergeOptions {
+            hunk_level: FileMergeHunkLevel::Line,
+            same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
+        };
+        merge_with_builtin(builtin, &Merge::from_vec(inputs.to_vec()), &options).unwrap()
+    }
+
+    #[test]
+    fn test_merge_with_builtin() {
+        let inputs = ["a\nX\nc\nd\n", "a\nb\nc\nd\n", "a\nY\nc\nD\n"];
+        assert_eq!(merge(BuiltinMergeDriver::Union, &inputs), "a\nX\nY\nc\nD\n");
+        assert_eq!(merge(BuiltinMergeDriver::Ours, &inputs), "a\nX\nc\nD\n");
+        assert_eq!(merge(BuiltinMergeDriver::Theirs, &inputs), "a\nY\nc\nD\n");
+
+        // Resolved without the driver
+        let inputs = ["a\nb\nc\nX\n", "a\nb\nc\nd\n", "A\nb\nc\nd\n"];
+        assert_eq!(merge(BuiltinMergeDriver::Union, &inputs), "A\nb\nc\nX\n");
+    }
+
+    #[test]
+    fn test_merge_union_missing_newline() {
+        let inputs = ["a\nX", "a\n", "a\nY"];
+        assert_eq!(merge(BuiltinMergeDriver::Union, &inputs), "a\nX\nY");
+    }
+
+    #[test]
+    fn test_merge_with_builtin_multiple_sides() {
+        let inputs = ["X\n", "a\n", "Y\n", "a\n", "Z\n"];
+        assert_eq!(merge(BuiltinMergeDriver::Union, &inputs), "X\nY\nZ\n");
+        assert_eq!(merge(BuiltinMergeDriver::Ours, &inputs), "X\n");
+        assert_eq!(merge(BuiltinMergeDriver::Theirs, &inputs), "Z\n");
+    }
+}
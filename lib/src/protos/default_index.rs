@@ -12,4 +12,18 @@ pub struct SegmentControl {
     /// Hashes (or file names) of the changed-path index segments.
     #[prost(bytes = "vec", repeated, tag = "3")]
     pub changed_path_segment_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// First commit stored in the commit-metadata index segments. Unset if
+    /// commit-metadata index is disabled.
+    #[prost(uint32, optional, tag = "4")]
+    pub commit_metadata_start_commit_pos: ::core::option::Option<u32>,
+    /// Hashes (or file names) of the commit-metadata index segments.
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub commit_metadata_segment_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// First commit stored in the trigram index segments. Unset if trigram index
+    /// is disabled.
+    #[prost(uint32, optional, tag = "6")]
+    pub trigram_start_commit_pos: ::core::option::Option<u32>,
+    /// Hashes (or file names) of the trigram index segments.
+    #[prost(bytes = "vec", repeated, tag = "7")]
+    pub trigram_segment_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
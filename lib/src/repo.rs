@@ -21,6 +21,7 @@ use std::collections::hash_map::Entry;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fs;
+use std::mem;
 use std::path::Path;
 use std::slice;
 use std::sync::Arc;
@@ -46,6 +47,7 @@ use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::commit_builder::CommitBuilder;
 use crate::commit_builder::DetachedCommitBuilder;
+use crate::config::ConfigGetError;
 use crate::dag_walk;
 use crate::dag_walk_async;
 use crate::default_index::DefaultIndexStore;
@@ -93,6 +95,7 @@ use crate::refs::diff_named_ref_targets;
 use crate::refs::diff_named_remote_refs;
 use crate::refs::merge_ref_targets;
 use crate::refs::merge_remote_refs;
+use crate::rerere::RerereStore;
 use crate::revset;
 use crate::revset::ResolvedRevsetExpression;
 use crate::revset::RevsetEvaluationError;
@@ -113,7 +116,9 @@ use crate::store::Store;
 use crate::submodule_store::SubmoduleStore;
 use crate::transaction::Transaction;
 use crate::transaction::TransactionCommitError;
+use crate::tree_merge::AutoResolution;
 use crate::tree_merge::MergeOptions;
+use crate::tree_merge::auto_resolve_conflicts;
 use crate::view::RenameWorkspaceError;
 use crate::view::View;
 
@@ -224,7 +229,7 @@ impl ReadonlyRepo {
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
         let merge_options =
-            MergeOptions::from_settings(settings).map_err(|err| BackendInitError(err.into()))?;
+            load_merge_options(settings, &repo_path).map_err(|err| BackendInitError(err.into()))?;
         let store = Store::new(backend, signer, merge_options);
 
         let op_store_path = repo_path.join("op_store");
@@ -628,6 +633,17 @@ pub struct RepoLoader {
     submodule_store: Arc<dyn SubmoduleStore>,
 }
 
+fn load_merge_options(
+    settings: &UserSettings,
+    repo_path: &Path,
+) -> Result<MergeOptions, ConfigGetError> {
+    let mut options = MergeOptions::from_settings(settings)?;
+    if settings.get_bool("merge.rerere")? {
+        options.rerere = Some(Arc::new(RerereStore::load(repo_path)));
+    }
+    Ok(options)
+}
+
 impl RepoLoader {
     pub fn new(
         settings: UserSettings,
@@ -656,7 +672,7 @@ impl RepoLoader {
         store_factories: &StoreFactories,
     ) -> Result<Self, StoreLoadError> {
         let merge_options =
-            MergeOptions::from_settings(settings).map_err(|err| BackendLoadError(err.into()))?;
+            load_merge_options(settings, repo_path).map_err(|err| BackendLoadError(err.into()))?;
         let store = Store::new(
             store_factories.load_backend(settings, &repo_path.join("store"))?,
             Signer::from_settings(settings)?,
@@ -957,6 +973,9 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    /// File conflicts resolved by recorded resolutions or merge drivers while
+    /// rewriting commits.
+    auto_resolutions: Vec<AutoResolution>,
 }
 
 impl MutableRepo {
@@ -968,6 +987,7 @@ impl MutableRepo {
             view: view.clone(),
             commit_predecessors: Default::default(),
             parent_mapping: Default::default(),
+            auto_resolutions: vec![],
         }
     }
 
@@ -983,6 +1003,21 @@ impl MutableRepo {
         self.index.downcast_ref::<DefaultMutableIndex>().is_some()
     }
 
+    /// Resolves file conflicts in the rewritten `tree` by recorded resolutions
+    /// and merge drivers. The results can be obtained by
+    /// [`Self::take_auto_resolutions()`].
+    pub async fn auto_resolve_conflicts(&mut self, tree: MergedTree) -> BackendResult<MergedTree> {
+        let (tree, resolutions) = auto_resolve_conflicts(tree).await?;
+        self.auto_resolutions.extend(resolutions);
+        Ok(tree)
+    }
+
+    /// Returns file conflicts which were resolved, or failed to be resolved,
+    /// by [`Self::auto_resolve_conflicts()`] since the last call.
+    pub fn take_auto_resolutions(&mut self) -> Vec<AutoResolution> {
+        mem::take(&mut self.auto_resolutions)
+    }
+
     pub fn has_changes(&self) -> bool {
         !(self.commit_predecessors.is_empty()
             && self.parent_mapping.is_empty()
@@ -1499,6 +1534,7 @@ impl MutableRepo {
         options: &RebaseOptions,
         mut progress: impl FnMut(Commit, RebasedCommit),
     ) -> BackendResult<()> {
+        self.record_resolutions().await?;
         let roots = self.parent_mapping.keys().cloned().collect();
         self.transform_descendants_with_options(
             roots,
@@ -1519,6 +1555,30 @@ impl MutableRepo {
         Ok(())
     }
 
+    /// Records resolutions of conflicts which were resolved by rewriting
+    /// commits in place, if `merge.rerere` is enabled.
+    async fn record_resolutions(&self) -> BackendResult<()> {
+        let store = self.store();
+        let Some(rerere) = &store.merge_options().rerere else {
+            return Ok(());
+        };
+        for (old_id, rewrite) in &self.parent_mapping {
+            let Rewrite::Rewritten(new_id) = rewrite else {
+                continue;
+            };
+            let old_commit = store.get_commit_async(old_id).await?;
+            let new_commit = store.get_commit_async(new_id).await?;
+            // Conflicts introduced by rebasing aren't resolved by the user.
+            if !old_commit.has_conflict() || old_commit.parent_ids() != new_commit.parent_ids() {
+                continue;
+            }
+            rerere
+                .record_tree_resolutions(store, &old_commit.tree(), &new_commit.tree())
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Rebase descendants of the rewritten commits.
     ///
     /// The descendants of the commits registered in `self.parent_mappings` will
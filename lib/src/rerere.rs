@@ -0,0 +1,405 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recorded resolutions of conflicted hunks, similar to Git's `rerere`.
+//!
+//! A conflicted hunk is identified by the contents of its sides and bases
+//! regardless of their order. When a commit resolves a conflict, the
+//! resolution of each hunk is recorded, and is reused when the same hunk
+//! conflicts again.
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use blake2::Blake2b512;
+use bstr::BString;
+use bstr::ByteSlice as _;
+use digest::Digest as _;
+use itertools::Itertools as _;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::conflicts;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::file_util::persist_temp_file;
+use crate::files;
+use crate::files::MergeResult;
+use crate::hex_util;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+/// Error from [`RerereStore`].
+#[derive(Debug, Error)]
+pub enum RerereError {
+    /// Failed to access the store.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// No recorded resolution matches the ID prefix.
+    #[error("No recorded resolution matches ID {0}")]
+    NotFound(String),
+    /// Multiple recorded resolutions match the ID prefix.
+    #[error("ID prefix {0} is ambiguous")]
+    AmbiguousId(String),
+}
+
+/// Resolution recorded in [`RerereStore`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedResolution {
+    /// Hash of the conflicted hunk.
+    pub id: String,
+    /// Path of the file where the resolution was last recorded.
+    pub path: RepoPathBuf,
+}
+
+/// Database of recorded resolutions, stored in the `rerere` directory of the
+/// repo.
+///
+/// Each resolution is stored in a directory named after the ID of the
+/// conflicted hunk.
+#[derive(Debug)]
+pub struct RerereStore {
+    path: PathBuf,
+}
+
+impl RerereStore {
+    /// Loads the store of the repo at `repo_path`. The directory is created
+    /// when the first resolution is recorded.
+    pub fn load(repo_path: &Path) -> Self {
+        Self {
+            path: repo_path.join("rerere"),
+        }
+    }
+
+    /// Resolves all conflicted `hunks` by recorded resolutions, and returns
+    /// the merged content.
+    ///
+    /// Returns `None` if any of the conflicted hunks has no recorded
+    /// resolution.
+    pub fn resolve_hunks(&self, hunks: &[Merge<BString>]) -> Result<Option<BString>, PathError> {
+        let mut merged = BString::default();
+        for hunk in hunks {
+            if let Some(content) = hunk.as_resolved() {
+                merged.extend_from_slice(content);
+                continue;
+            }
+            let resolution_path = self.path.join(hunk_id(hunk)).join("resolution");
+            match fs::read(&resolution_path) {
+                Ok(content) => merged.extend_from_slice(&content),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err).context(&resolution_path),
+            }
+        }
+        Ok(Some(merged))
+    }
+
+    /// Records resolutions of the conflicted `hunks` of the file at `path`,
+    /// which was resolved to `resolved` content.
+    ///
+    /// Resolutions are found by locating the unconflicted hunks in the
+    /// `resolved` content. Nothing is recorded if they can't be located
+    /// unambiguously.
+    /// Returns the number of recorded resolutions.
+    pub fn record_file(
+        &self,
+        path: &RepoPath,
+        hunks: &[Merge<BString>],
+        resolved: &[u8],
+    ) -> Result<usize, PathError> {
+        let Some(resolutions) = split_resolution(hunks, resolved) else {
+            return Ok(0);
+        };
+        let conflicted_hunks = hunks.iter().filter(|hunk| !hunk.is_resolved());
+        for (hunk, resolution) in conflicted_hunks.zip_eq(&resolutions) {
+            self.record_hunk(path, hunk, resolution)?;
+        }
+        Ok(resolutions.len())
+    }
+
+    fn record_hunk(
+        &self,
+        path: &RepoPath,
+        hunk: &Merge<BString>,
+        resolution: &[u8],
+    ) -> Result<(), PathError> {
+        let dir = self.path.join(hunk_id(hunk));
+        fs::create_dir_all(&dir).context(&dir)?;
+        for (name, content) in [
+            ("resolution", resolution),
+            ("path", path.as_internal_file_string().as_bytes()),
+        ] {
+            let file_path = dir.join(name);
+            let mut temp_file = NamedTempFile::new_in(&dir).context(&dir)?;
+            temp_file.write_all(content).context(temp_file.path())?;
+            persist_temp_file(temp_file, &file_path).context(&file_path)?;
+        }
+        Ok(())
+    }
+
+    /// Lists recorded resolutions sorted by ID.
+    pub fn list(&self) -> Result<Vec<RecordedResolution>, PathError> {
+        let entries = match fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err).context(&self.path),
+        };
+        let mut resolutions = vec![];
+        for entry in entries {
+            let entry = entry.context(&self.path)?;
+            let Ok(id) = entry.file_name().into_string() else {
+                continue;
+            };
+            let path_file = entry.path().join("path");
+            let Ok(path) = fs::read_to_string(&path_file) else {
+                continue;
+            };
+            let Ok(path) = RepoPathBuf::from_internal_string(path) else {
+                continue;
+            };
+            resolutions.push(RecordedResolution { id, path });
+        }
+        resolutions.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(resolutions)
+    }
+
+    /// Looks up the recorded resolution whose ID starts with `id_prefix`.
+    pub fn resolve_id_prefix(&self, id_prefix: &str) -> Result<RecordedResolution, RerereError> {
+        let matches = self
+            .list()?
+            .into_iter()
+            .filter(|resolution| resolution.id.starts_with(id_prefix))
+            .collect_vec();
+        let resolution = match <[_; 1]>::try_from(matches) {
+            Ok([resolution]) => resolution,
+            Err(matches) if matches.is_empty() => {
+                return Err(RerereError::NotFound(id_prefix.to_owned()));
+            }
+            Err(_) => return Err(RerereError::AmbiguousId(id_prefix.to_owned())),
+        };
+        Ok(resolution)
+    }
+
+    /// Deletes the recorded `resolution`.
+    pub fn forget(&self, resolution: &RecordedResolution) -> Result<(), PathError> {
+        let dir = self.path.join(&resolution.id);
+        fs::remove_dir_all(&dir).context(&dir)
+    }
+
+    /// Records resolutions of the file conflicts in `base_tree` which are
+    /// resolved in `new_tree`.
+    ///
+    /// Failures to write to the store are logged and ignored.
+    pub async fn record_tree_resolutions(
+        &self,
+        store: &Store,
+        base_tree: &MergedTree,
+        new_tree: &MergedTree,
+    ) -> BackendResult<()> {
+        for (path, value) in base_tree.conflicts() {
+            let Some(file_ids) = value?.to_file_merge() else {
+                continue;
+            };
+            let new_value = new_tree.path_value(&path).await?;
+            let Some(Some(TreeValue::File { id, .. })) = new_value.as_resolved() else {
+                continue;
+            };
+            // Hunks should be split in the same way as the tree merge does.
+            let file_ids = file_ids.simplify();
+            let contents = conflicts::extract_as_single_hunk(&file_ids, store, &path).await?;
+            let MergeResult::Conflict(hunks) = files::merge_hunks(&contents, store.merge_options())
+            else {
+                continue;
+            };
+            let resolved =
+                conflicts::extract_as_single_hunk(&Merge::resolved(Some(id.clone())), store, &path)
+                    .await?;
+            let resolved = resolved.into_resolved().unwrap();
+            match self.record_file(&path, &hunks, &resolved) {
+                Ok(count) => tracing::debug!(?path, count, "recorded resolutions"),
+                Err(err) => tracing::warn!(?err, ?path, "failed to record resolutions"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Computes the ID of the conflicted `hunk`. The order of the sides and the
+/// bases doesn't matter.
+fn hunk_id(hunk: &Merge<BString>) -> String {
+    let mut hasher = Blake2b512::new();
+    let removes = hunk.removes().sorted().map(|term| (b'-', term));
+    let adds = hunk.adds().sorted().map(|term| (b'+', term));
+    for (tag, term) in removes.chain(adds) {
+        hasher.update([tag]);
+        hasher.update((term.len() as u64).to_le_bytes());
+        hasher.update(term);
+    }
+    hex_util::encode_hex(&hasher.finalize()[..16])
+}
+
+/// Splits `resolved` content into resolutions of the conflicted `hunks`.
+fn split_resolution<'a>(hunks: &[Merge<BString>], resolved: &'a [u8]) -> Option<Vec<&'a [u8]>> {
+    let mut resolutions = vec![];
+    let mut pos = 0;
+    let mut in_conflict = false;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let Some(content) = hunk.as_resolved() else {
+            if in_conflict {
+                // Adjacent conflicts can't be separated.
+                return None;
+            }
+            in_conflict = true;
+            continue;
+        };
+        let rest = &resolved[pos..];
+        let offset = if !in_conflict {
+            rest.starts_with(content).then_some(0)?
+        } else if i == hunks.len() - 1 {
+            rest.ends_with(content)
+                .then(|| rest.len() - content.len())?
+        } else {
+            // If the unconflicted content also appears elsewhere, the
+            // resolution can't be split reliably.
+            let offset = rest.find(content)?;
+            (rest.rfind(content) == Some(offset)).then_some(offset)?
+        };
+        if in_conflict {
+            resolutions.push(&rest[..offset]);
+            in_conflict = false;
+        }
+        pos += offset + content.len();
+    }
+    if in_conflict {
+        resolutions.push(&resolved[pos..]);
+    } else if pos != resolved.len() {
+        return None;
+    }
+    Some(resolutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(terms: &[&str]) -> Merge<BString> {
+        Merge::from_vec(terms.iter().map(|&term| BString::from(term)).collect_vec())
+    }
+
+    fn resolved(content: &str) -> Merge<BString> {
+        Merge::resolved(content.into())
+    }
+
+    #[test]
+    fn test_hunk_id() {
+        // The order of sides doesn't matter
+        assert_eq!(
+            hunk_id(&hunk(&["a\n", "base\n", "b\n"])),
+            hunk_id(&hunk(&["b\n", "base\n", "a\n"]))
+        );
+        assert_ne!(
+            hunk_id(&hunk(&["a\n", "base\n", "b\n"])),
+            hunk_id(&hunk(&["base\n", "a\n", "b\n"]))
+        );
+    }
+
+    #[test]
+    fn test_split_resolution() {
+        let hunks = [
+            resolved("1\n"),
+            hunk(&["a\n", "x\n", "b\n"]),
+            resolved("2\n"),
+            hunk(&["c\n", "y\n", "d\n"]),
+        ];
+        assert_eq!(
+            split_resolution(&hunks, b"1\nab\n2\ncd\n"),
+            Some(vec![&b"ab\n"[..], b"cd\n"])
+        );
+        // Conflict resolved to empty content
+        assert_eq!(
+            split_resolution(&hunks, b"1\n2\ncd\n"),
+            Some(vec![&b""[..], b"cd\n"])
+        );
+        // Unconflicted hunk was changed
+        assert_eq!(split_resolution(&hunks, b"1\nab\n3\ncd\n"), None);
+        assert_eq!(split_resolution(&hunks, b"0\nab\n2\ncd\n"), None);
+
+        let hunks = [hunk(&["a\n", "x\n", "b\n"]), resolved("2\n")];
+        assert_eq!(
+            split_resolution(&hunks, b"ab\n2\n"),
+            Some(vec![&b"ab\n"[..]])
+        );
+        assert_eq!(split_resolution(&hunks, b"ab\n2\n3\n"), None);
+
+        // Resolution containing the following unconflicted content
+        let hunks = [
+            resolved("{\n"),
+            hunk(&["a\n", "x\n", "b\n"]),
+            resolved("}\n"),
+            hunk(&["c\n", "y\n", "d\n"]),
+        ];
+        assert_eq!(split_resolution(&hunks, b"{\nab\n}\n}\ncd\n"), None);
+        assert_eq!(
+            split_resolution(&hunks, b"{\nab\n}\ncd\n"),
+            Some(vec![&b"ab\n"[..], b"cd\n"])
+        );
+    }
+
+    #[test]
+    fn test_record_and_resolve() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = RerereStore::load(temp_dir.path());
+        let path = RepoPath::from_internal_string("dir/file").unwrap();
+        let hunks = [
+            resolved("1\n"),
+            hunk(&["a\n", "x\n", "b\n"]),
+            resolved("2\n"),
+        ];
+        assert_eq!(store.resolve_hunks(&hunks).unwrap(), None);
+        assert_eq!(store.list().unwrap(), vec![]);
+
+        assert_eq!(store.record_file(path, &hunks, b"1\nab\n2\n").unwrap(), 1);
+        assert_eq!(
+            store.resolve_hunks(&hunks).unwrap(),
+            Some(BString::from("1\nab\n2\n"))
+        );
+        // Same conflict in different context
+        let other_hunks = [resolved("0\n"), hunk(&["b\n", "x\n", "a\n"])];
+        assert_eq!(
+            store.resolve_hunks(&other_hunks).unwrap(),
+            Some(BString::from("0\nab\n"))
+        );
+
+        let resolutions = store.list().unwrap();
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[0].path.as_ref(), path);
+        assert_matches::assert_matches!(
+            store.resolve_id_prefix("zzz"),
+            Err(RerereError::NotFound(_))
+        );
+        let id_prefix = &resolutions[0].id[..4];
+        let resolution = store.resolve_id_prefix(id_prefix).unwrap();
+        assert_eq!(resolution, resolutions[0]);
+        store.forget(&resolution).unwrap();
+        assert_eq!(store.list().unwrap(), vec![]);
+        assert_eq!(store.resolve_hunks(&hunks).unwrap(), None);
+    }
+}
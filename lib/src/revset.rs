@@ -221,6 +221,9 @@ pub enum RevsetFilterPredicate {
     },
     /// Commits with conflicts
     HasConflict,
+    /// Commits modifying files owned by an owner matching the pattern
+    /// according to the `CODEOWNERS` file in the commit.
+    TouchesOwner(StringExpression),
     /// Commits that are cryptographically signed.
     Signed,
     /// Custom predicates provided by extensions
@@ -1179,6 +1182,13 @@ static BUILTIN_FUNCTION_MAP: LazyLock<HashMap<&str, RevsetFunction>> = LazyLock:
     });
     // TODO: Remove diff_contains() in jj 0.44+
     map.insert("diff_contains", map["diff_lines"]);
+    map.insert("touches_owner", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let expr = expect_string_expression(diagnostics, arg)?;
+        Ok(RevsetExpression::filter(
+            RevsetFilterPredicate::TouchesOwner(expr),
+        ))
+    });
     map.insert("conflicts", |_diagnostics, function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
@@ -1253,6 +1263,12 @@ pub fn expect_fileset_expression(
         diagnostics.extend_with(inner_diagnostics, |diag| {
             RevsetParseError::expression("In fileset expression", node.span).with_source(diag)
         });
+        if expression.has_filters() {
+            return Err(RevsetParseError::expression(
+                "File predicates are not supported in revsets",
+                node.span,
+            ));
+        }
         Ok(expression)
     })
 }
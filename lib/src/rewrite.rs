@@ -394,29 +394,31 @@ impl<'repo> CommitRewriter<'repo> {
             let new_base_tree_fut = merge_commit_trees(self.mut_repo, &new_parents);
             let old_tree = self.old_commit.tree();
             let (old_base_tree, new_base_tree) = try_join!(old_base_tree_fut, new_base_tree_fut)?;
-            (
-                old_base_tree.tree_ids() == self.old_commit.tree_ids(),
-                MergedTree::merge(Merge::from_vec(vec![
-                    (
-                        new_base_tree,
-                        format!(
-                            "{} (rebase destination)",
-                            conflict_label_for_commits(&new_parents)
-                        ),
-                    ),
-                    (
-                        old_base_tree,
-                        format!(
-                            "{} (parents of rebased revision)",
-                            conflict_label_for_commits(&old_parents)
-                        ),
+            let was_empty = old_base_tree.tree_ids() == self.old_commit.tree_ids();
+            let new_tree = MergedTree::merge(Merge::from_vec(vec![
+                (
+                    new_base_tree,
+                    format!(
+                        "{} (rebase destination)",
+                        conflict_label_for_commits(&new_parents)
                     ),
-                    (
-                        old_tree,
-                        format!("{} (rebased revision)", self.old_commit.conflict_label()),
+                ),
+                (
+                    old_base_tree,
+                    format!(
+                        "{} (parents of rebased revision)",
+                        conflict_label_for_commits(&old_parents)
                     ),
-                ]))
-                .await?,
+                ),
+                (
+                    old_tree,
+                    format!("{} (rebased revision)", self.old_commit.conflict_label()),
+                ),
+            ]))
+            .await?;
+            (
+                was_empty,
+                self.mut_repo.auto_resolve_conflicts(new_tree).await?,
             )
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
@@ -1418,6 +1420,7 @@ pub async fn squash_commits<'repo>(
                 [source.diff.clone().invert()],
             ))
             .await?;
+            let new_source_tree = repo.auto_resolve_conflicts(new_source_tree).await?;
             repo.rewrite_commit(&source.commit.commit)
                 .set_tree(new_source_tree)
                 .write()
@@ -1467,6 +1470,7 @@ pub async fn squash_commits<'repo>(
         source_commits.into_iter().map(|source| source.diff),
     ))
     .await?;
+    let destination_tree = repo.auto_resolve_conflicts(destination_tree).await?;
 
     let commit_builder = repo
         .rewrite_commit(&rewritten_destination)
@@ -1478,6 +1482,95 @@ pub async fn squash_commits<'repo>(
     }))
 }
 
+/// Commit to be written by [`apply_rebase_plan()`].
+#[derive(Clone, Debug)]
+pub struct PlannedCommit {
+    /// Commits whose changes make up the new commit. The first commit is
+    /// rewritten, and the changes of the other commits are squashed into it.
+    pub commits: Vec<Commit>,
+    /// Description of the new commit.
+    pub description: String,
+}
+
+/// Rewrites the `planned` commits into a linear chain on top of
+/// `new_parent_ids`, and abandons the `dropped` commits. Returns the new
+/// commits in the same order as `planned`. Commits which wouldn't change are
+/// kept as is.
+///
+/// The caller is responsible for rebasing the descendants of the old commits.
+pub async fn apply_rebase_plan(
+    mut_repo: &mut MutableRepo,
+    new_parent_ids: Vec<CommitId>,
+    planned: Vec<PlannedCommit>,
+    dropped: &[Commit],
+) -> BackendResult<Vec<Commit>> {
+    for commit in dropped {
+        mut_repo.record_abandoned_commit(commit);
+    }
+    let mut parent_ids = new_parent_ids;
+    let mut new_commits = Vec::with_capacity(planned.len());
+    for PlannedCommit {
+        commits,
+        description,
+    } in planned
+    {
+        let (first, squashed) = commits
+            .split_first()
+            .expect("planned commit should have at least one commit");
+        if squashed.is_empty()
+            && *first.parent_ids() == parent_ids
+            && first.description() == description
+        {
+            parent_ids = vec![first.id().clone()];
+            new_commits.push(first.clone());
+            continue;
+        }
+
+        let mut diffs = Vec::with_capacity(squashed.len());
+        for commit in squashed {
+            diffs.push(Diff::new(
+                (
+                    commit.parent_tree(mut_repo).await?,
+                    format!(
+                        "{} (parents of squashed revision)",
+                        commit.parents_conflict_label().await?
+                    ),
+                ),
+                (
+                    commit.tree(),
+                    format!("{} (squashed revision)", commit.conflict_label()),
+                ),
+            ));
+        }
+        let mut builder = CommitRewriter::new(mut_repo, first.clone(), parent_ids)
+            .rebase()
+            .await?
+            .set_description(description)
+            .detach();
+        if !diffs.is_empty() {
+            let new_tree = MergedTree::merge(Merge::from_diffs(
+                (
+                    builder.tree(),
+                    format!("{} (squash destination)", first.conflict_label()),
+                ),
+                diffs,
+            ))
+            .await?;
+            let new_tree = mut_repo.auto_resolve_conflicts(new_tree).await?;
+            builder
+                .set_tree(new_tree)
+                .set_predecessors(commits.iter().ids().cloned().collect());
+        }
+        let new_commit = builder.write(mut_repo).await?;
+        for commit in squashed {
+            mut_repo.set_rewritten_commit(commit.id().clone(), new_commit.id().clone());
+        }
+        parent_ids = vec![new_commit.id().clone()];
+        new_commits.push(new_commit);
+    }
+    Ok(new_commits)
+}
+
 /// Find divergent commits from the target that are already present with
 /// identical contents in the destination. These commits should be able to be
 /// safely abandoned.
@@ -24,11 +24,13 @@ use std::time::SystemTime;
 
 use clru::CLruCache;
 use futures::AsyncRead;
+use futures::AsyncReadExt as _;
 use futures::stream::BoxStream;
 use pollster::FutureExt as _;
 
 use crate::backend;
 use crate::backend::Backend;
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
@@ -236,6 +238,25 @@ impl Store {
         self.backend.read_file(path, id).await
     }
 
+    /// Reads the whole contents of the file into memory.
+    pub async fn read_file_contents(&self, path: &RepoPath, id: &FileId) -> BackendResult<Vec<u8>> {
+        let mut reader = self.read_file(path, id).await?;
+        let mut content = vec![];
+        reader
+            .read_to_end(&mut content)
+            .await
+            .map_err(|err| BackendError::ReadFile {
+                path: path.to_owned(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+        Ok(content)
+    }
+
+    pub async fn file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        self.backend.file_size(path, id).await
+    }
+
     pub async fn write_file(
         &self,
         path: &RepoPath,
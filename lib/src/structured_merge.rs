@@ -0,0 +1,905 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured merge of JSON, TOML, and YAML files.
+//!
+//! Files are parsed into trees of maps, arrays, and scalar values, and merged
+//! key by key. Arrays are merged element by element. The merge fails if sides
+//! made different changes to the same key or element, in which case the caller
+//! should fall back to the line-based merge result.
+//!
+//! The merge also fails if the merged file can't be written without changing
+//! the formatting of the unchanged parts. JSON and YAML files are re-serialized,
+//! so they can only be merged if every side is formatted in the same way as
+//! the serializer would, and has no comments.
+
+use std::fmt::Write as _;
+use std::iter::zip;
+
+use bstr::BString;
+
+use crate::files;
+use crate::files::FileMergeHunkLevel;
+use crate::files::MergeResult;
+use crate::merge::Merge;
+use crate::merge::SameChange;
+use crate::tree_merge::MergeOptions;
+
+/// Tree of values parsed from a structured file.
+#[derive(Clone, Debug, PartialEq)]
+enum Node<K, L> {
+    /// Map of entries in the order they appear in the file.
+    Map(Vec<(K, Self)>),
+    Array(Vec<Self>),
+    Leaf(L),
+}
+
+/// Merges JSON `contents`. The merged file is pretty-printed with the
+/// indentation of the first side. Numbers are kept as written.
+///
+/// Returns `None` if any of the sides isn't a valid JSON document, if any of
+/// the sides isn't pretty-printed in the same way, or if the sides can't be
+/// merged structurally.
+pub fn merge_json<T: AsRef<[u8]>>(contents: &Merge<T>, options: &MergeOptions) -> Option<BString> {
+    merge_with(contents, options, parse_json, emit_json)
+}
+
+/// Merges TOML `contents`. Unchanged tables and values of the first side are
+/// kept as is, including comments and formatting.
+///
+/// Returns `None` if any of the sides isn't a valid TOML document, or if the
+/// sides can't be merged structurally.
+pub fn merge_toml<T: AsRef<[u8]>>(contents: &Merge<T>, options: &MergeOptions) -> Option<BString> {
+    merge_with(contents, options, parse_toml, emit_toml)
+}
+
+/// Merges YAML `contents`. The merged file is re-serialized.
+///
+/// Returns `None` if any of the sides isn't a valid single-document YAML file,
+/// if any of the sides can't be re-serialized as is (e.g. because it contains
+/// comments), or if the sides can't be merged structurally.
+pub fn merge_yaml<T: AsRef<[u8]>>(contents: &Merge<T>, options: &MergeOptions) -> Option<BString> {
+    merge_with(contents, options, parse_yaml, emit_yaml)
+}
+
+fn merge_with<T, K, L>(
+    contents: &Merge<T>,
+    options: &MergeOptions,
+    parse: impl Fn(&str) -> Option<Node<K, L>>,
+    emit: impl Fn(&Node<K, L>, &str) -> Option<String>,
+) -> Option<BString>
+where
+    T: AsRef<[u8]>,
+    K: Clone + PartialEq,
+    L: Clone + PartialEq,
+{
+    let texts = contents.try_map(|content| str::from_utf8(content.as_ref()).map_err(|_| ()));
+    let texts = texts.ok()?;
+    // Empty file is treated as absent so that added files can be merged.
+    let nodes = texts.try_map(|text| {
+        if text.trim().is_empty() {
+            Ok(None)
+        } else {
+            parse(text).map(Some).ok_or(())
+        }
+    });
+    let nodes = nodes.ok()?;
+    // Unchanged parts of the merged file should be written as they were. We
+    // can't tell which parts of the file are unchanged, but if every side is
+    // reproduced byte for byte, so is the merged file.
+    for (text, node) in zip(texts.iter(), nodes.iter()) {
+        if let Some(node) = node
+            && emit(node, text)? != *text
+        {
+            return None;
+        }
+    }
+    let merged = merge_nodes(&nodes.map(Option::as_ref), options)??;
+    let merged = emit(&merged, texts.first())?;
+    Some(merged.into())
+}
+
+/// Merges `nodes`. Returns `Some(None)` if the node is deleted, or `None` if
+/// the nodes can't be merged.
+fn merge_nodes<K, L>(
+    nodes: &Merge<Option<&Node<K, L>>>,
+    options: &MergeOptions,
+) -> Option<Option<Node<K, L>>>
+where
+    K: Clone + PartialEq,
+    L: Clone + PartialEq,
+{
+    if let Some(node) = resolve_trivial_by_eq(nodes, options.same_change) {
+        return Some(node.cloned());
+    }
+    // Absent bases are treated as empty maps or arrays so that nodes added by
+    // multiple sides can be merged.
+    if nodes.adds().any(Option::is_none) {
+        return None;
+    }
+    if nodes
+        .iter()
+        .all(|node| matches!(node, None | Some(Node::Map(_))))
+    {
+        let maps = nodes.map(|node| match node {
+            Some(Node::Map(entries)) => entries.as_slice(),
+            _ => &[],
+        });
+        merge_maps(&maps, options).map(|entries| Some(Node::Map(entries)))
+    } else if nodes
+        .iter()
+        .all(|node| matches!(node, None | Some(Node::Array(_))))
+    {
+        let arrays = nodes.map(|node| match node {
+            Some(Node::Array(elements)) => elements.as_slice(),
+            _ => &[],
+        });
+        merge_arrays(&arrays, options).map(|elements| Some(Node::Array(elements)))
+    } else {
+        None
+    }
+}
+
+fn merge_maps<K, L>(
+    maps: &Merge<&[(K, Node<K, L>)]>,
+    options: &MergeOptions,
+) -> Option<Vec<(K, Node<K, L>)>>
+where
+    K: Clone + PartialEq,
+    L: Clone + PartialEq,
+{
+    // Keys are ordered as in the first side. Keys added by the other sides are
+    // inserted before their following key.
+    let mut keys: Vec<&K> = vec![];
+    for entries in maps.adds().chain(maps.removes()) {
+        for (i, (key, _)) in entries.iter().enumerate() {
+            if keys.contains(&key) {
+                continue;
+            }
+            let next_pos = entries[i + 1..]
+                .iter()
+                .find_map(|(next_key, _)| keys.iter().position(|k| *k == next_key));
+            keys.insert(next_pos.unwrap_or(keys.len()), key);
+        }
+    }
+    let mut merged = vec![];
+    for key in keys {
+        let values = maps.map(|entries| {
+            entries
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value)
+        });
+        if let Some(value) = merge_nodes(&values, options)? {
+            merged.push((key.clone(), value));
+        }
+    }
+    Some(merged)
+}
+
+fn merge_arrays<K, L>(
+    arrays: &Merge<&[Node<K, L>]>,
+    options: &MergeOptions,
+) -> Option<Vec<Node<K, L>>>
+where
+    K: Clone + PartialEq,
+    L: Clone + PartialEq,
+{
+    // Map each distinct element to a line, and merge the arrays by lines.
+    let mut unique: Vec<&Node<K, L>> = vec![];
+    let texts = arrays.map(|elements| {
+        let mut text = String::new();
+        for element in *elements {
+            let index = unique
+                .iter()
+                .position(|e| *e == element)
+                .unwrap_or_else(|| {
+                    unique.push(element);
+                    unique.len() - 1
+                });
+            writeln!(text, "{index}").unwrap();
+        }
+        text
+    });
+    let to_elements = |text: &[u8]| -> Vec<&Node<K, L>> {
+        text.split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let index: usize = str::from_utf8(line).unwrap().parse().unwrap();
+                unique[index]
+            })
+            .collect()
+    };
+    let options = MergeOptions {
+        hunk_level: FileMergeHunkLevel::Line,
+        ..options.clone()
+    };
+    let hunks = match files::merge_hunks(&texts, &options) {
+        MergeResult::Resolved(text) => {
+            return Some(to_elements(&text).into_iter().cloned().collect());
+        }
+        MergeResult::Conflict(hunks) => hunks,
+    };
+    let mut merged = vec![];
+    for hunk in &hunks {
+        let sides = hunk.map(|text| to_elements(text));
+        if let Some(elements) = sides.as_resolved() {
+            merged.extend(elements.iter().copied().cloned());
+            continue;
+        }
+        // Elements changed in place can be merged one by one.
+        let len = sides.first().len();
+        if sides.iter().any(|elements| elements.len() != len) {
+            return None;
+        }
+        for i in 0..len {
+            let values = sides.map(|elements| Some(elements[i]));
+            merged.extend(merge_nodes(&values, &options)?);
+        }
+    }
+    Some(merged)
+}
+
+/// Like [`Merge::resolve_trivial()`], but only requires the values to be
+/// comparable for equality.
+fn resolve_trivial_by_eq<'a, T: PartialEq>(
+    values: &Merge<Option<&'a T>>,
+    same_change: SameChange,
+) -> Option<Option<&'a T>> {
+    let mut unique: Vec<Option<&T>> = vec![];
+    let indices = values.map(|value| {
+        unique.iter().position(|v| v == value).unwrap_or_else(|| {
+            unique.push(*value);
+            unique.len() - 1
+        })
+    });
+    let index = *indices.resolve_trivial(same_change)?;
+    Some(unique[index])
+}
+
+type JsonNode = Node<String, yaml_rust2::Yaml>;
+
+/// Parses JSON `text`. Since JSON is a subset of YAML, the YAML parser is
+/// used, which keeps the text of numbers that don't fit in `i64`. Non-JSON
+/// input would be rejected when the parsed node is emitted back.
+fn parse_json(text: &str) -> Option<JsonNode> {
+    let docs = yaml_rust2::YamlLoader::load_from_str(text).ok()?;
+    let [doc] = docs.as_slice() else {
+        return None;
+    };
+    json_node(doc)
+}
+
+fn json_node(yaml: &yaml_rust2::Yaml) -> Option<JsonNode> {
+    use yaml_rust2::Yaml;
+    match yaml {
+        Yaml::Hash(hash) => {
+            let entries = hash
+                .iter()
+                .map(|(key, value)| match key {
+                    Yaml::String(key) => Some((key.clone(), json_node(value)?)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?;
+            Some(Node::Map(entries))
+        }
+        Yaml::Array(array) => Some(Node::Array(
+            array.iter().map(json_node).collect::<Option<_>>()?,
+        )),
+        Yaml::Alias(_) | Yaml::BadValue => None,
+        _ => Some(Node::Leaf(yaml.clone())),
+    }
+}
+
+fn emit_json(node: &JsonNode, first_text: &str) -> Option<String> {
+    // Use the indentation of the first indented line.
+    let indent = first_text
+        .lines()
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ");
+    let mut out = String::new();
+    write_json(&mut out, node, indent, 0);
+    if first_text.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn write_json(out: &mut String, node: &JsonNode, indent: &str, depth: usize) {
+    match node {
+        Node::Map(entries) if entries.is_empty() => out.push_str("{}"),
+        Node::Map(entries) => {
+            out.push_str("{\n");
+            for (i, (key, value)) in entries.iter().enumerate() {
+                out.push_str(&indent.repeat(depth + 1));
+                write_json_string(out, key);
+                out.push_str(": ");
+                write_json(out, value, indent, depth + 1);
+                out.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&indent.repeat(depth));
+            out.push('}');
+        }
+        Node::Array(elements) if elements.is_empty() => out.push_str("[]"),
+        Node::Array(elements) => {
+            out.push_str("[\n");
+            for (i, element) in elements.iter().enumerate() {
+                out.push_str(&indent.repeat(depth + 1));
+                write_json(out, element, indent, depth + 1);
+                out.push_str(if i + 1 < elements.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&indent.repeat(depth));
+            out.push(']');
+        }
+        Node::Leaf(value) => write_json_leaf(out, value),
+    }
+}
+
+fn write_json_leaf(out: &mut String, value: &yaml_rust2::Yaml) {
+    use yaml_rust2::Yaml;
+    match value {
+        Yaml::Real(text) => out.push_str(text),
+        Yaml::Integer(value) => write!(out, "{value}").unwrap(),
+        Yaml::String(text) => write_json_string(out, text),
+        Yaml::Boolean(value) => write!(out, "{value}").unwrap(),
+        Yaml::Null => out.push_str("null"),
+        Yaml::Array(_) | Yaml::Hash(_) | Yaml::Alias(_) | Yaml::BadValue => {
+            panic!("leaf should be a scalar")
+        }
+    }
+}
+
+fn write_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// TOML scalar value without decoration, compared by its representation.
+#[derive(Clone, Debug)]
+struct TomlLeaf(toml_edit::Value);
+
+impl PartialEq for TomlLeaf {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_string() == other.0.to_string()
+    }
+}
+
+type TomlNode = Node<String, TomlLeaf>;
+
+fn parse_toml(text: &str) -> Option<TomlNode> {
+    let doc: toml_edit::DocumentMut = text.parse().ok()?;
+    Some(toml_table_node(doc.as_table()))
+}
+
+fn toml_table_node(table: &toml_edit::Table) -> TomlNode {
+    let entries = table
+        .iter()
+        .filter_map(|(key, item)| Some((key.to_owned(), toml_item_node(item)?)))
+        .collect();
+    Node::Map(entries)
+}
+
+fn toml_item_node(item: &toml_edit::Item) -> Option<TomlNode> {
+    match item {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Value(value) => Some(toml_value_node(value)),
+        toml_edit::Item::Table(table) => Some(toml_table_node(table)),
+        toml_edit::Item::ArrayOfTables(array) => {
+            Some(Node::Array(array.iter().map(toml_table_node).collect()))
+        }
+    }
+}
+
+fn toml_value_node(value: &toml_edit::Value) -> TomlNode {
+    match value {
+        toml_edit::Value::Array(array) => Node::Array(array.iter().map(toml_value_node).collect()),
+        toml_edit::Value::InlineTable(table) => Node::Map(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_owned(), toml_value_node(value)))
+                .collect(),
+        ),
+        _ => {
+            let mut value = value.clone();
+            value.decor_mut().clear();
+            Node::Leaf(TomlLeaf(value))
+        }
+    }
+}
+
+fn emit_toml(node: &TomlNode, first_text: &str) -> Option<String> {
+    let mut doc: toml_edit::DocumentMut = first_text.parse().ok()?;
+    let toml_edit::Item::Table(table) = toml_item(node, Some(&doc.as_item().clone())) else {
+        return None;
+    };
+    *doc.as_table_mut() = table;
+    Some(doc.to_string())
+}
+
+/// Converts `node` to TOML item, reusing the formatting of the `first` item.
+fn toml_item(node: &TomlNode, first: Option<&toml_edit::Item>) -> toml_edit::Item {
+    if let Some(item) = first
+        && toml_item_node(item).as_ref() == Some(node)
+    {
+        return item.clone();
+    }
+    match (node, first) {
+        (Node::Map(_), Some(toml_edit::Item::Table(_)) | None) => {
+            let mut table = match first {
+                Some(toml_edit::Item::Table(table)) => table.clone(),
+                _ => toml_edit::Table::new(),
+            };
+            toml_table(node, &mut table);
+            toml_edit::Item::Table(table)
+        }
+        (Node::Array(elements), Some(toml_edit::Item::ArrayOfTables(_)) | None)
+            if !elements.is_empty()
+                && elements
+                    .iter()
+                    .all(|element| matches!(element, Node::Map(_))) =>
+        {
+            let first_tables = match first {
+                Some(toml_edit::Item::ArrayOfTables(array)) => array.iter().collect(),
+                _ => vec![],
+            };
+            let mut array = toml_edit::ArrayOfTables::new();
+            for (i, element) in elements.iter().enumerate() {
+                let mut table = first_tables
+                    .get(i)
+                    .map_or_else(toml_edit::Table::new, |&table| table.clone());
+                toml_table(element, &mut table);
+                array.push(table);
+            }
+            toml_edit::Item::ArrayOfTables(array)
+        }
+        _ => toml_edit::Item::Value(toml_value(node, first.and_then(|item| item.as_value()))),
+    }
+}
+
+fn toml_table(node: &TomlNode, table: &mut toml_edit::Table) {
+    let Node::Map(entries) = node else {
+        panic!("node should be a map");
+    };
+    table.retain(|key, _| entries.iter().any(|(k, _)| k == key));
+    for (key, value) in entries {
+        let item = toml_item(value, table.get(key));
+        table.insert(key, item);
+    }
+    if table.is_empty() {
+        table.set_implicit(false);
+    }
+}
+
+/// Converts `node` to TOML value, reusing the formatting of the `first` value.
+fn toml_value(node: &TomlNode, first: Option<&toml_edit::Value>) -> toml_edit::Value {
+    if let Some(value) = first
+        && toml_value_node(value) == *node
+    {
+        return value.clone();
+    }
+    match node {
+        Node::Map(entries) => {
+            let mut table = match first {
+                Some(toml_edit::Value::InlineTable(table)) => table.clone(),
+                _ => toml_edit::InlineTable::new(),
+            };
+            table.retain(|key, _| entries.iter().any(|(k, _)| k == key));
+            for (key, value) in entries {
+                let value = toml_value(value, table.get(key));
+                table.insert(key, value);
+            }
+            toml_edit::Value::InlineTable(table)
+        }
+        Node::Array(elements) => {
+            let first_array = first.and_then(|value| value.as_array());
+            let mut array = toml_edit::Array::new();
+            for (i, element) in elements.iter().enumerate() {
+                let first_element = first_array.and_then(|array| array.get(i));
+                array.push_formatted(toml_value(element, first_element));
+            }
+            toml_edit::Value::Array(array)
+        }
+        Node::Leaf(TomlLeaf(value)) => value.clone(),
+    }
+}
+
+type YamlNode = Node<yaml_rust2::Yaml, yaml_rust2::Yaml>;
+
+fn parse_yaml(text: &str) -> Option<YamlNode> {
+    let docs = yaml_rust2::YamlLoader::load_from_str(text).ok()?;
+    let [doc] = docs.as_slice() else {
+        return None;
+    };
+    yaml_node(doc)
+}
+
+fn yaml_node(yaml: &yaml_rust2::Yaml) -> Option<YamlNode> {
+    use yaml_rust2::Yaml;
+    match yaml {
+        Yaml::Hash(hash) => {
+            let entries = hash
+                .iter()
+                .map(|(key, value)| {
+                    if matches!(key, Yaml::Hash(_) | Yaml::Array(_)) {
+                        return None;
+                    }
+                    Some((key.clone(), yaml_node(value)?))
+                })
+                .collect::<Option<_>>()?;
+            Some(Node::Map(entries))
+        }
+        Yaml::Array(array) => Some(Node::Array(
+            array.iter().map(yaml_node).collect::<Option<_>>()?,
+        )),
+        Yaml::Alias(_) | Yaml::BadValue => None,
+        _ => Some(Node::Leaf(yaml.clone())),
+    }
+}
+
+fn emit_yaml(node: &YamlNode, first_text: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut emitter = yaml_rust2::YamlEmitter::new(&mut out);
+    emitter.multiline_strings(true);
+    emitter.dump(&to_yaml(node)).ok()?;
+    if !first_text.trim_start().starts_with("---")
+        && let Some(rest) = out.strip_prefix("---\n")
+    {
+        out = rest.to_owned();
+    }
+    out.push('\n');
+    Some(out)
+}
+
+fn to_yaml(node: &YamlNode) -> yaml_rust2::Yaml {
+    use yaml_rust2::Yaml;
+    match node {
+        Node::Map(entries) => Yaml::Hash(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), to_yaml(value)))
+                .collect(),
+        ),
+        Node::Array(elements) => Yaml::Array(elements.iter().map(to_yaml).collect()),
+        Node::Leaf(value) => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::diff::DiffAlgorithm;
+    use crate::merge_driver::MergeDrivers;
+
+    fn options() -> MergeOptions {
+        MergeOptions {
+            hunk_level: FileMergeHunkLevel::Line,
+            same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
+        }
+    }
+
+    fn merge3(
+        merge: fn(&Merge<String>, &MergeOptions) -> Option<BString>,
+        base: &str,
+        ours: &str,
+        theirs: &str,
+    ) -> Option<String> {
+        let contents = Merge::from_vec(vec![ours, base, theirs]).map(|text| text.to_string());
+        merge(&contents, &options()).map(|merged| merged.to_string())
+    }
+
+    #[test]
+    fn test_merge_json_keys() {
+        let base = indoc! {r#"
+            {
+                "name": "foo",
+                "deps": {
+                    "a": "1.0"
+                }
+            }
+        "#};
+        let ours = indoc! {r#"
+            {
+                "name": "foo",
+                "deps": {
+                    "a": "1.0",
+                    "b": "2.0"
+                }
+            }
+        "#};
+        let theirs = indoc! {r#"
+            {
+                "name": "bar",
+                "deps": {
+                    "a": "1.0",
+                    "c": "3.0"
+                }
+            }
+        "#};
+        insta::assert_snapshot!(merge3(merge_json, base, ours, theirs).unwrap(), @r#"
+        {
+            "name": "bar",
+            "deps": {
+                "a": "1.0",
+                "b": "2.0",
+                "c": "3.0"
+            }
+        }
+        "#);
+
+        // Same key changed differently
+        let theirs = ours.replace("2.0", "2.1");
+        let ours = ours.replace("2.0", "2.2");
+        assert_eq!(merge3(merge_json, base, &ours, &theirs), None);
+
+        // Invalid JSON
+        assert_eq!(merge3(merge_json, base, "{", theirs.as_str()), None);
+    }
+
+    #[test]
+    fn test_merge_json_arrays() {
+        // Elements inserted at different places
+        let merged = merge3(
+            merge_json,
+            "[\n  1,\n  2,\n  3\n]",
+            "[\n  0,\n  1,\n  2,\n  3\n]",
+            "[\n  1,\n  2,\n  3,\n  4\n]",
+        );
+        insta::assert_snapshot!(merged.unwrap(), @"
+        [
+          0,
+          1,
+          2,
+          3,
+          4
+        ]
+        ");
+
+        // Elements changed in place are merged recursively
+        let base = indoc! {r#"
+            [
+              {
+                "a": 1,
+                "b": 1
+              }
+            ]
+        "#};
+        let ours = base.replace("\"a\": 1", "\"a\": 2");
+        let theirs = base.replace("\"b\": 1", "\"b\": 2");
+        insta::assert_snapshot!(merge3(merge_json, base, &ours, &theirs).unwrap(), @r#"
+        [
+          {
+            "a": 2,
+            "b": 2
+          }
+        ]
+        "#);
+
+        // Elements inserted at the same place
+        let merged = merge3(
+            merge_json,
+            "[\n  1\n]",
+            "[\n  1,\n  2\n]",
+            "[\n  1,\n  3\n]",
+        );
+        assert_eq!(merged, None);
+    }
+
+    #[test]
+    fn test_merge_json_added_file() {
+        let merged = merge3(merge_json, "", "{\n  \"a\": 1\n}", "{\n  \"b\": 2\n}");
+        insta::assert_snapshot!(merged.unwrap(), @r#"
+        {
+          "a": 1,
+          "b": 2
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_merge_json_numbers() {
+        // Numbers are kept as written, even if they don't fit in i64 or f64
+        let base = indoc! {r#"
+            {
+              "a": 1.10
+            }
+        "#};
+        let ours = indoc! {r#"
+            {
+              "a": 1.10,
+              "b": 12345678901234567890123
+            }
+        "#};
+        let theirs = indoc! {r#"
+            {
+              "c": 0.10000000000000000001,
+              "a": 1.10
+            }
+        "#};
+        insta::assert_snapshot!(merge3(merge_json, base, ours, theirs).unwrap(), @r#"
+        {
+          "c": 0.10000000000000000001,
+          "a": 1.10,
+          "b": 12345678901234567890123
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_merge_json_formatting() {
+        // The merged file would be formatted differently
+        let base = "{\"a\": 1}\n";
+        let ours = "{\"a\": 1, \"b\": 2}\n";
+        let theirs = "{\"c\": 3, \"a\": 1}\n";
+        assert_eq!(merge3(merge_json, base, ours, theirs), None);
+
+        // String escapes would be rewritten
+        let base = "{\n  \"a\": \"\\u00e9\"\n}\n";
+        let ours = "{\n  \"a\": \"\\u00e9\",\n  \"b\": 2\n}\n";
+        let theirs = "{\n  \"c\": 3,\n  \"a\": \"\\u00e9\"\n}\n";
+        assert_eq!(merge3(merge_json, base, ours, theirs), None);
+    }
+
+    #[test]
+    fn test_merge_toml() {
+        let base = indoc! {r#"
+            # Package metadata
+            [package]
+            name = "foo"  # the name
+            version = "1.0.0"
+
+            [dependencies]
+            a = "1.0"
+        "#};
+        let ours = indoc! {r#"
+            # Package metadata
+            [package]
+            name = "foo"  # the name
+            version = "1.1.0"
+
+            [dependencies]
+            a = "1.0"
+            b = { version = "2.0", features = ["x"] }
+        "#};
+        let theirs = indoc! {r#"
+            # Package metadata
+            [package]
+            name = "foo"
+            version = "1.0.0"
+
+            [dependencies]
+            a = "1.0"
+            c = "3.0"
+
+            [[bin]]
+            name = "foo"
+        "#};
+        insta::assert_snapshot!(merge3(merge_toml, base, ours, theirs).unwrap(), @r#"
+        # Package metadata
+        [package]
+        name = "foo"  # the name
+        version = "1.1.0"
+
+        [dependencies]
+        a = "1.0"
+        b = { version = "2.0", features = ["x"] }
+        c = "3.0"
+
+        [[bin]]
+        name = "foo"
+        "#);
+
+        // Same key changed differently
+        let theirs = base.replace("1.0.0", "2.0.0");
+        assert_eq!(merge3(merge_toml, base, ours, &theirs), None);
+    }
+
+    #[test]
+    fn test_merge_yaml() {
+        let base = indoc! {"
+            name: foo
+            steps:
+              - checkout
+              - build
+        "};
+        let ours = indoc! {"
+            name: foo
+            image: ubuntu
+            steps:
+              - checkout
+              - build
+        "};
+        let theirs = indoc! {"
+            name: foo
+            steps:
+              - checkout
+              - build
+              - test
+        "};
+        insta::assert_snapshot!(merge3(merge_yaml, base, ours, theirs).unwrap(), @"
+        name: foo
+        image: ubuntu
+        steps:
+          - checkout
+          - build
+          - test
+        ");
+
+        // Same key changed differently
+        let theirs = base.replace("name: foo", "name: bar");
+        let ours = base.replace("name: foo", "name: baz");
+        assert_eq!(merge3(merge_yaml, base, &ours, &theirs), None);
+
+        // Numbers are kept as written
+        let ours = format!("{base}version: 1.10\n");
+        let theirs = format!("size: 12345678901234567890123\n{base}");
+        insta::assert_snapshot!(merge3(merge_yaml, base, &ours, &theirs).unwrap(), @"
+        size: 12345678901234567890123
+        name: foo
+        steps:
+          - checkout
+          - build
+        version: 1.10
+        ");
+    }
+
+    #[test]
+    fn test_merge_yaml_comments() {
+        // Comments would be dropped
+        let base = indoc! {"
+            # The name
+            name: foo
+        "};
+        let ours = format!("{base}image: ubuntu\n");
+        let theirs = format!("steps: []\n{base}");
+        assert_eq!(merge3(merge_yaml, base, &ours, &theirs), None);
+    }
+
+    #[test]
+    fn test_merge_multiple_sides() {
+        let contents = Merge::from_vec(vec![
+            "{\n  \"a\": 1\n}",
+            "{}",
+            "{\n  \"b\": 2\n}",
+            "{}",
+            "{\n  \"c\": 3\n}",
+        ]);
+        let merged = merge_json(&contents, &options()).unwrap();
+        insta::assert_snapshot!(merged, @r#"
+        {
+          "a": 1,
+          "b": 2,
+          "c": 3
+        }
+        "#);
+    }
+}
@@ -21,6 +21,7 @@ use std::iter::zip;
 use std::sync::Arc;
 use std::vec;
 
+use bstr::BString;
 use futures::AsyncReadExt as _;
 use futures::FutureExt as _;
 use futures::StreamExt as _;
@@ -32,20 +33,29 @@ use itertools::Itertools as _;
 use crate::backend;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::CopyId;
 use crate::backend::MergedTreeVal;
 use crate::backend::MergedTreeValue;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::config::ConfigGetError;
+use crate::diff::DiffAlgorithm;
 use crate::files;
 use crate::files::FileMergeHunkLevel;
+use crate::files::MergeResult;
 use crate::merge::Merge;
 use crate::merge::SameChange;
+use crate::merge_driver::MergeDriverError;
+use crate::merge_driver::MergeDrivers;
+use crate::merge_driver::run_merge_driver;
+use crate::merged_tree::MergedTree;
 use crate::merged_tree::all_merged_tree_entries;
+use crate::merged_tree_builder::MergedTreeBuilder;
 use crate::object_id::ObjectId as _;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
+use crate::rerere::RerereStore;
 use crate::settings::UserSettings;
 use crate::store::Store;
 use crate::tree::Tree;
@@ -57,6 +67,12 @@ pub struct MergeOptions {
     pub hunk_level: FileMergeHunkLevel,
     /// Whether to resolve conflict that makes the same change at all sides.
     pub same_change: SameChange,
+    /// Algorithm used to compute line diffs between sides.
+    pub diff_algorithm: DiffAlgorithm,
+    /// Drivers to resolve file conflicts which can't be merged by hunks.
+    pub drivers: MergeDrivers,
+    /// Recorded resolutions to reuse for conflicted hunks, if enabled.
+    pub rerere: Option<Arc<RerereStore>>,
 }
 
 impl MergeOptions {
@@ -67,6 +83,11 @@ impl MergeOptions {
             // needed. It wouldn't be translated to FileMergeHunkLevel.
             hunk_level: settings.get("merge.hunk-level")?,
             same_change: settings.get("merge.same-change")?,
+            diff_algorithm: settings.get("diff.algorithm")?,
+            drivers: MergeDrivers::from_settings(settings)?,
+            // The store location depends on the repo, so it's set up by the
+            // caller.
+            rerere: None,
         })
     }
 }
@@ -402,15 +423,28 @@ async fn try_resolve_file_values<T: Borrow<TreeValue>>(
     }
 }
 
-/// Resolves file-level conflict by merging content hunks.
+/// Contents of file-level conflict.
+enum FileConflict {
+    /// The conflict can be resolved without reading the file contents.
+    Resolved(TreeValue),
+    /// The file contents to be merged.
+    Contents {
+        contents: Merge<Vec<u8>>,
+        executable: bool,
+        copy_id: CopyId,
+    },
+}
+
+/// Reads the file contents of the given `conflict`. Returns `None` if the
+/// conflict can't be merged as a file.
 ///
 /// The input `conflict` is supposed to be simplified. It shouldn't contain
 /// non-file values that cancel each other.
-async fn try_resolve_file_conflict(
+async fn read_file_conflict(
     store: &Store,
     filename: &RepoPath,
     conflict: &MergedTreeVal<'_>,
-) -> BackendResult<Option<TreeValue>> {
+) -> BackendResult<Option<FileConflict>> {
     let options = store.merge_options();
     // If there are any non-file or any missing parts in the conflict, we can't
     // merge it. We check early so we don't waste time reading file contents if
@@ -459,11 +493,11 @@ async fn try_resolve_file_conflict(
     if let Some(&resolved_file_id) = file_id_conflict.resolve_trivial(options.same_change) {
         // Don't bother reading the file contents if the conflict can be trivially
         // resolved.
-        return Ok(Some(TreeValue::File {
+        return Ok(Some(FileConflict::Resolved(TreeValue::File {
             id: resolved_file_id.clone(),
             executable,
             copy_id: copy_id.clone(),
-        }));
+        })));
     }
 
     // While the input conflict should be simplified by caller, it might contain
@@ -489,16 +523,152 @@ async fn try_resolve_file_conflict(
             BackendResult::Ok(content)
         })
         .await?;
-    if let Some(merged_content) = files::try_merge(&contents, options) {
+    Ok(Some(FileConflict::Contents {
+        contents,
+        executable,
+        copy_id: copy_id.clone(),
+    }))
+}
+
+/// Resolves file-level conflict by merging content hunks.
+///
+/// The input `conflict` is supposed to be simplified. It shouldn't contain
+/// non-file values that cancel each other.
+async fn try_resolve_file_conflict(
+    store: &Store,
+    filename: &RepoPath,
+    conflict: &MergedTreeVal<'_>,
+) -> BackendResult<Option<TreeValue>> {
+    let options = store.merge_options();
+    let (contents, executable, copy_id) =
+        match read_file_conflict(store, filename, conflict).await? {
+            None => return Ok(None),
+            Some(FileConflict::Resolved(value)) => return Ok(Some(value)),
+            Some(FileConflict::Contents {
+                contents,
+                executable,
+                copy_id,
+            }) => (contents, executable, copy_id),
+        };
+    match files::merge_hunks(&contents, options) {
+        MergeResult::Resolved(merged_content) => {
+            let id = store
+                .write_file(filename, &mut merged_content.as_slice())
+                .await?;
+            Ok(Some(TreeValue::File {
+                id,
+                executable,
+                copy_id,
+            }))
+        }
+        MergeResult::Conflict(_) => Ok(None),
+    }
+}
+
+/// How a file conflict left by merging trees was resolved.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AutoResolutionKind {
+    /// Resolved by reusing recorded resolutions of the conflict hunks.
+    Recorded,
+    /// Resolved by the merge driver configured for the file.
+    MergeDriver,
+}
+
+/// File conflict which was resolved, or failed to be resolved, by
+/// [`auto_resolve_conflicts()`].
+#[derive(Debug)]
+pub struct AutoResolution {
+    /// Path to the conflicted file.
+    pub path: RepoPathBuf,
+    /// How the conflict was resolved, or the error of the merge driver.
+    pub result: Result<AutoResolutionKind, MergeDriverError>,
+}
+
+/// Resolves file conflicts left in `tree` by recorded resolutions and the
+/// configured merge drivers.
+///
+/// Unlike the content merge done by [`MergedTree::merge()`], these resolutions
+/// depend on state outside of the trees, and merge drivers may run external
+/// commands. This should only be called when rewriting commits, not when
+/// computing trees to be displayed. Conflicts which couldn't be resolved
+/// aren't reported unless the merge driver failed.
+pub async fn auto_resolve_conflicts(
+    tree: MergedTree,
+) -> BackendResult<(MergedTree, Vec<AutoResolution>)> {
+    let store = tree.store().clone();
+    let options = store.merge_options();
+    if tree.tree_ids().is_resolved() || (options.drivers.is_empty() && options.rerere.is_none()) {
+        return Ok((tree, vec![]));
+    }
+    let mut builder = MergedTreeBuilder::new(tree.clone());
+    let mut resolutions = vec![];
+    for (path, value) in tree.conflicts() {
+        let driver = options.drivers.find(&path);
+        if driver.is_none() && options.rerere.is_none() {
+            continue;
+        }
+        let value = value?;
+        let simplified = value.map(|value| value.as_ref()).simplify();
+        let Some(FileConflict::Contents {
+            contents,
+            executable,
+            copy_id,
+        }) = read_file_conflict(&store, &path, &simplified).await?
+        else {
+            continue;
+        };
+        let recorded = match files::merge_hunks(&contents, options) {
+            MergeResult::Resolved(_) => None,
+            MergeResult::Conflict(hunks) => resolve_by_rerere(options, &path, &hunks),
+        };
+        let (merged_content, kind) = if let Some(content) = recorded {
+            (content, AutoResolutionKind::Recorded)
+        } else if let Some(driver) = driver {
+            match run_merge_driver(driver, &path, &contents, options).await {
+                Ok(Some(content)) => (content, AutoResolutionKind::MergeDriver),
+                Ok(None) => continue,
+                Err(err) => {
+                    resolutions.push(AutoResolution {
+                        path,
+                        result: Err(err),
+                    });
+                    continue;
+                }
+            }
+        } else {
+            continue;
+        };
         let id = store
-            .write_file(filename, &mut merged_content.as_slice())
+            .write_file(&path, &mut merged_content.as_slice())
             .await?;
-        Ok(Some(TreeValue::File {
+        let value = TreeValue::File {
             id,
             executable,
-            copy_id: copy_id.clone(),
-        }))
-    } else {
-        Ok(None)
+            copy_id,
+        };
+        builder.set_or_remove(path.clone(), Merge::normal(value));
+        resolutions.push(AutoResolution {
+            path,
+            result: Ok(kind),
+        });
+    }
+    if !resolutions
+        .iter()
+        .any(|resolution| resolution.result.is_ok())
+    {
+        return Ok((tree, resolutions));
     }
+    Ok((builder.write_tree().await?, resolutions))
+}
+
+fn resolve_by_rerere(
+    options: &MergeOptions,
+    filename: &RepoPath,
+    hunks: &[Merge<BString>],
+) -> Option<BString> {
+    let rerere = options.rerere.as_ref()?;
+    rerere.resolve_hunks(hunks).unwrap_or_else(|err| {
+        tracing::warn!(?err, ?filename, "failed to read recorded resolutions");
+        None
+    })
 }
@@ -21,12 +21,15 @@ use jj_lib::conflicts::ConflictMaterializeOptions;
 use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
 use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::find_conflict_line_ranges;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
 use jj_lib::conflicts::parse_conflict;
 use jj_lib::conflicts::update_from_content;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::files::FileMergeHunkLevel;
 use jj_lib::merge::Merge;
 use jj_lib::merge::SameChange;
+use jj_lib::merge_driver::MergeDrivers;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::store::Store;
@@ -583,6 +586,7 @@ fn test_materialize_parse_roundtrip() {
 #[test_case(ConflictMarkerStyle::Diff)]
 #[test_case(ConflictMarkerStyle::Snapshot)]
 #[test_case(ConflictMarkerStyle::Git)]
+#[test_case(ConflictMarkerStyle::Zdiff3)]
 fn test_materialize_update_roundtrip(style: ConflictMarkerStyle) -> TestResult {
     let test_repo = TestRepo::init();
     let store = test_repo.repo.store();
@@ -641,6 +645,151 @@ fn test_materialize_update_roundtrip(style: ConflictMarkerStyle) -> TestResult {
     Ok(())
 }
 
+#[test]
+fn test_materialize_update_zdiff3() -> TestResult {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = repo_path("file");
+    let base_id = testutils::write_file(
+        store,
+        path,
+        indoc! {"
+            line 1
+            base 2
+            line 3
+            base 4
+            line 5
+        "},
+    );
+    let left_id = testutils::write_file(
+        store,
+        path,
+        indoc! {"
+            line 1
+            new 2.1
+            left 2.2
+            new 2.3
+            line 3
+            new 4.1
+            left 4.2
+            line 5
+        "},
+    );
+    let right_id = testutils::write_file(
+        store,
+        path,
+        indoc! {"
+            line 1
+            new 2.1
+            right 2.2
+            new 2.3
+            line 3
+            new 4.1
+            right 4.2
+            line 5
+        "},
+    );
+
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3);
+    insta::assert_snapshot!(materialized, @"
+    line 1
+    new 2.1
+    <<<<<<< side #1
+    left 2.2
+    ||||||| base
+    base 2
+    =======
+    right 2.2
+    >>>>>>> side #2
+    new 2.3
+    line 3
+    new 4.1
+    <<<<<<< side #1
+    left 4.2
+    ||||||| base
+    base 4
+    =======
+    right 4.2
+    >>>>>>> side #2
+    line 5
+    ");
+
+    // The lines moved out of the conflict markers aren't added to the base
+    let parsed = update_from_content(
+        &conflict,
+        store,
+        path,
+        materialized.as_bytes(),
+        MIN_CONFLICT_MARKER_LEN,
+    )
+    .block_on()?;
+    assert_eq!(parsed, conflict);
+
+    // Resolving one conflict keeps the base of the other conflict intact
+    let edited = materialized.replacen(
+        indoc! {"
+            <<<<<<< side #1
+            left 2.2
+            ||||||| base
+            base 2
+            =======
+            right 2.2
+            >>>>>>> side #2
+        "},
+        "resolved 2.2\n",
+        1,
+    );
+    let parsed = update_from_content(
+        &conflict,
+        store,
+        path,
+        edited.as_bytes(),
+        MIN_CONFLICT_MARKER_LEN,
+    )
+    .block_on()?;
+    let contents = extract_as_single_hunk(&parsed, store, path).block_on()?;
+    insta::assert_debug_snapshot!(contents, @r#"
+    Conflicted(
+        [
+            "line 1\nnew 2.1\nresolved 2.2\nnew 2.3\nline 3\nnew 4.1\nleft 4.2\nline 5\n",
+            "line 1\nnew 2.1\nresolved 2.2\nnew 2.3\nline 3\nbase 4\nline 5\n",
+            "line 1\nnew 2.1\nresolved 2.2\nnew 2.3\nline 3\nnew 4.1\nright 4.2\nline 5\n",
+        ],
+    )
+    "#);
+
+    // Editing one conflict keeps the other conflict intact
+    let edited = materialized.replacen("left 2.2\n", "edited 2.2\n", 1);
+    let parsed = update_from_content(
+        &conflict,
+        store,
+        path,
+        edited.as_bytes(),
+        MIN_CONFLICT_MARKER_LEN,
+    )
+    .block_on()?;
+    let contents = extract_as_single_hunk(&parsed, store, path).block_on()?;
+    insta::assert_debug_snapshot!(contents, @r#"
+    Conflicted(
+        [
+            "line 1\nnew 2.1\nedited 2.2\nnew 2.3\nline 3\nnew 4.1\nleft 4.2\nline 5\n",
+            "line 1\nbase 2\nline 3\nbase 4\nline 5\n",
+            "line 1\nnew 2.1\nright 2.2\nnew 2.3\nline 3\nnew 4.1\nright 4.2\nline 5\n",
+        ],
+    )
+    "#);
+    let materialized_edited =
+        materialize_conflict_string(store, path, &parsed, ConflictMarkerStyle::Zdiff3);
+    assert_eq!(materialized_edited, edited);
+    Ok(())
+}
+
 #[test]
 fn test_materialize_conflict_no_newlines_at_eof() {
     let test_repo = TestRepo::init();
@@ -1471,6 +1620,31 @@ fn test_parse_conflict_wrong_arity() {
     );
 }
 
+#[test]
+fn test_find_conflict_line_ranges() {
+    let input = indoc! {b"
+        line 1
+        <<<<<<< conflict 1 of 2
+        +++++++ side 1
+        left
+        ------- base
+        +++++++ side 2
+        right
+        >>>>>>> conflict 1 of 2 ends
+        line 2
+        <<<<
+        <<<<<<< conflict 2 of 2
+        %%%%%%% diff from base to side 1
+        -line 3
+        +++++++ side 2
+        right
+        >>>>>>> conflict 2 of 2 ends
+        "};
+    assert_eq!(find_conflict_line_ranges(input, 7), vec![1..8, 10..16]);
+    // Shorter markers are ignored
+    assert!(find_conflict_line_ranges(input, 8).is_empty());
+}
+
 #[test]
 fn test_parse_conflict_malformed_missing_removes() {
     // Right number of adds but missing removes
@@ -2547,6 +2721,9 @@ fn materialize_conflict_string_with_labels(
         merge: MergeOptions {
             hunk_level: FileMergeHunkLevel::Line,
             same_change: SameChange::Accept,
+            diff_algorithm: DiffAlgorithm::Default,
+            drivers: MergeDrivers::default(),
+            rerere: None,
         },
     };
     String::from_utf8(
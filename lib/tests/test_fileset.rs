@@ -0,0 +1,219 @@
+// Copyright 2026 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::fileset::FilePattern;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::fileset::FilesetFilterPredicate;
+use jj_lib::fileset::OwnerPattern;
+use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+use testutils::TestRepo;
+use testutils::TestResult;
+use testutils::TestThreeWayMergeTreeBuilder;
+use testutils::create_tree_with;
+use testutils::repo_path;
+
+fn resolve_paths(expression: &FilesetExpression, trees: &[&MergedTree]) -> Vec<String> {
+    let resolved = expression
+        .resolve_filters(trees, &GitIgnoreFile::empty())
+        .block_on()
+        .unwrap();
+    assert!(!resolved.has_filters());
+    let matcher = resolved.to_matcher();
+    let mut paths = vec![];
+    for tree in trees {
+        for (path, _) in tree.entries_matching(matcher.as_ref()) {
+            paths.push(path.as_internal_file_string().to_owned());
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+#[test]
+fn test_resolve_filters_metadata() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let tree = create_tree_with(repo, |builder| {
+        builder.file(repo_path("file"), "a\n");
+        builder
+            .file(repo_path("dir/exec"), "#!/bin/sh\n")
+            .executable(true);
+        builder.symlink(repo_path("dir/link"), "file");
+        builder.file(repo_path("dir/bin"), b"\0\x01\x02");
+        builder.file(repo_path("large"), "x".repeat(2048));
+    });
+    let filter = FilesetExpression::filter;
+
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Executable), &[&tree]),
+        ["dir/exec"]
+    );
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Symlink), &[&tree]),
+        ["dir/link"]
+    );
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Binary), &[&tree]),
+        ["dir/bin"]
+    );
+    assert_eq!(
+        resolve_paths(
+            &filter(FilesetFilterPredicate::Size(1024..u64::MAX)),
+            &[&tree]
+        ),
+        ["large"]
+    );
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Conflicted), &[&tree]),
+        [] as [&str; 0]
+    );
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Tracked), &[&tree]),
+        ["dir/bin", "dir/exec", "dir/link", "file", "large"]
+    );
+
+    // Combined with path patterns
+    let expression = FilesetExpression::prefix_path(repo_path("dir").to_owned())
+        .intersection(FilesetExpression::all().difference(filter(FilesetFilterPredicate::Binary)));
+    assert_eq!(
+        resolve_paths(&expression, &[&tree]),
+        ["dir/exec", "dir/link"]
+    );
+}
+
+#[test]
+fn test_resolve_filters_conflicted() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut builder = TestThreeWayMergeTreeBuilder::new(repo.store().clone());
+    builder.base().file(repo_path("conflict"), "base\n");
+    builder.parent1().file(repo_path("conflict"), "left\n");
+    builder.parent2().file(repo_path("conflict"), "\0right\n");
+    builder.base().file(repo_path("resolved"), "same\n");
+    builder.parent1().file(repo_path("resolved"), "same\n");
+    builder.parent2().file(repo_path("resolved"), "same\n");
+    let tree = builder.write_merged_tree();
+    let filter = FilesetExpression::filter;
+
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Conflicted), &[&tree]),
+        ["conflict"]
+    );
+    // Any side of the conflict can match
+    assert_eq!(
+        resolve_paths(&filter(FilesetFilterPredicate::Binary), &[&tree]),
+        ["conflict"]
+    );
+}
+
+#[test]
+fn test_resolve_filters_ignored() -> TestResult {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let tree1 = create_tree_with(repo, |builder| {
+        builder.file(repo_path(".gitignore"), "*.log\nbuild/\n");
+        builder.file(repo_path("a.log"), "");
+        builder.file(repo_path("a.txt"), "");
+        builder.file(repo_path("build/out"), "");
+        builder.file(repo_path("sub/.gitignore"), "*.txt\n");
+        builder.file(repo_path("sub/b.txt"), "");
+        builder.file(repo_path("sub/b.tmp"), "");
+    });
+    let tree2 = create_tree_with(repo, |builder| {
+        builder.file(repo_path("c.log"), "");
+    });
+    let ignored = FilesetExpression::filter(FilesetFilterPredicate::Ignored);
+
+    assert_eq!(
+        resolve_paths(&ignored, &[&tree1]),
+        ["a.log", "build/out", "sub/b.txt"]
+    );
+    // .gitignore rules in tree2 don't apply to tree1
+    assert_eq!(resolve_paths(&ignored, &[&tree2]), [] as [&str; 0]);
+
+    // Base ignores apply to all trees
+    let base_ignores =
+        GitIgnoreFile::empty().chain(repo_path(""), std::path::Path::new(""), b"*.tmp\n*.log\n")?;
+    let resolved = ignored
+        .resolve_filters(&[&tree2], &base_ignores)
+        .block_on()?;
+    let matcher = resolved.to_matcher();
+    assert!(matcher.matches(repo_path("c.log")));
+    assert!(!matcher.matches(repo_path("sub/b.tmp")));
+
+    // Patterns can narrow down the files to be tested
+    let expression =
+        FilesetExpression::pattern(FilePattern::root_files_in_path("sub")?).intersection(ignored);
+    assert_eq!(resolve_paths(&expression, &[&tree1]), ["sub/b.txt"]);
+    Ok(())
+}
+
+#[test]
+fn test_resolve_filters_owned_by() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let tree1 = create_tree_with(repo, |builder| {
+        builder.file(
+            repo_path(".github/CODEOWNERS"),
+            "* @org/core\n/docs/ @org/docs\n*.rs @org/rust @alice\n",
+        );
+        builder.file(repo_path("README"), "");
+        builder.file(repo_path("docs/guide.md"), "");
+        builder.file(repo_path("src/lib.rs"), "");
+    });
+    // CODEOWNERS in the root directory is used if there's no .github one
+    let tree2 = create_tree_with(repo, |builder| {
+        builder.file(repo_path("CODEOWNERS"), "/src/ @bob\n");
+        builder.file(repo_path("src/main.rs"), "");
+    });
+    let owned_by = |owner: &str| {
+        FilesetExpression::filter(FilesetFilterPredicate::OwnedBy(
+            OwnerPattern::glob(owner).unwrap(),
+        ))
+    };
+
+    assert_eq!(
+        resolve_paths(&owned_by("@org/core"), &[&tree1]),
+        [".github/CODEOWNERS", "README"]
+    );
+    assert_eq!(
+        resolve_paths(&owned_by("@org/*"), &[&tree1]),
+        [
+            ".github/CODEOWNERS",
+            "README",
+            "docs/guide.md",
+            "src/lib.rs"
+        ]
+    );
+    assert_eq!(
+        resolve_paths(&owned_by("@alice"), &[&tree1]),
+        ["src/lib.rs"]
+    );
+    assert_eq!(
+        resolve_paths(&owned_by("@bob"), &[&tree1, &tree2]),
+        ["src/main.rs"]
+    );
+    assert_eq!(
+        resolve_paths(&owned_by("@carol"), &[&tree1]),
+        [] as [&str; 0]
+    );
+}
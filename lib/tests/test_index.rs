@@ -43,6 +43,8 @@ use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::revset::GENERATION_RANGE_FULL;
 use jj_lib::revset::PARENTS_RANGE_FULL;
 use jj_lib::revset::ResolvedExpression;
+use jj_lib::str_util::StringExpression;
+use jj_lib::str_util::StringPattern;
 use pollster::FutureExt as _;
 use test_case::test_case;
 use testutils::CommitBuilderExt as _;
@@ -51,6 +53,7 @@ use testutils::TestResult;
 use testutils::assert_tree_eq;
 use testutils::commit_transactions;
 use testutils::create_tree;
+use testutils::create_tree_with;
 use testutils::repo_path;
 use testutils::repo_path_buf;
 use testutils::test_backend::TestBackend;
@@ -930,6 +933,243 @@ fn test_build_changed_path_segments() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn test_build_commit_metadata_segments() -> TestResult {
+    let test_repo = TestRepo::init();
+    let repo = test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id();
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+
+    let mut tx = repo.start_transaction();
+    for i in 1..10 {
+        tx.repo_mut()
+            .new_commit(
+                vec![root_commit_id.clone()],
+                repo.store().empty_merged_tree(),
+            )
+            .set_description(format!("commit {i}"))
+            .write_unwrap();
+    }
+    let repo = tx.commit("test").block_on()?;
+
+    // Index the last 4 commits
+    default_index_store
+        .build_commit_metadata_index_at_operation(repo.op_id(), repo.store(), 4, |_| ())
+        .block_on()?;
+    let repo = repo.reload_at(repo.operation()).block_on()?;
+    let stats = as_readonly_index(&repo).stats();
+    assert_eq!(stats.commit_metadata_commits_range, Some(6..10));
+    assert_eq!(stats.commit_metadata_levels.len(), 1);
+    assert_eq!(stats.commit_metadata_levels[0].num_commits, 4);
+
+    // Index remainders
+    default_index_store
+        .build_commit_metadata_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()?;
+    let repo = repo.reload_at(repo.operation()).block_on()?;
+    let stats = as_readonly_index(&repo).stats();
+    assert_eq!(stats.commit_metadata_commits_range, Some(0..10));
+    assert_eq!(stats.commit_metadata_levels.len(), 2);
+    assert_eq!(stats.commit_metadata_levels[0].num_commits, 6);
+    assert_eq!(stats.commit_metadata_levels[1].num_commits, 4);
+
+    // New commits should be indexed incrementally
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .new_commit(
+            vec![root_commit_id.clone()],
+            repo.store().empty_merged_tree(),
+        )
+        .write_unwrap();
+    let repo = tx.commit("test").block_on()?;
+    let stats = as_readonly_index(&repo).stats();
+    assert_eq!(stats.commit_metadata_commits_range, Some(0..11));
+    // Changed-path index should be unaffected
+    assert_eq!(stats.changed_path_commits_range, None);
+    Ok(())
+}
+
+#[test]
+fn test_build_trigram_segments() -> TestResult {
+    let test_repo = TestRepo::init();
+    let repo = test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id();
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+
+    let mut tx = repo.start_transaction();
+    for i in 1..10 {
+        let tree = create_tree(&repo, &[(repo_path("file"), &format!("line {i}\n"))]);
+        tx.repo_mut()
+            .new_commit(vec![root_commit_id.clone()], tree)
+            .write_unwrap();
+    }
+    let repo = tx.commit("test").block_on()?;
+
+    // Index the last 4 commits
+    default_index_store
+        .build_trigram_index_at_operation(repo.op_id(), repo.store(), 4, |_| ())
+        .block_on()?;
+    let repo = repo.reload_at(repo.operation()).block_on()?;
+    let stats = as_readonly_index(&repo).stats();
+    assert_eq!(stats.trigram_commits_range, Some(6..10));
+    assert_eq!(stats.trigram_levels.len(), 1);
+    assert_eq!(stats.trigram_levels[0].num_commits, 4);
+    // "lin", "ine", "ne ", "e {i}"
+    assert_eq!(stats.trigram_levels[0].num_trigrams, 4 * 4);
+
+    // Index remainders
+    default_index_store
+        .build_trigram_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()?;
+    let repo = repo.reload_at(repo.operation()).block_on()?;
+    let stats = as_readonly_index(&repo).stats();
+    assert_eq!(stats.trigram_commits_range, Some(0..10));
+    assert_eq!(stats.trigram_levels.len(), 2);
+    assert_eq!(stats.trigram_levels[0].num_commits, 6);
+    assert_eq!(stats.trigram_levels[0].num_trigrams, 5 * 4);
+    assert_eq!(stats.trigram_levels[1].num_commits, 4);
+
+    // New commits should be indexed incrementally
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .new_commit(
+            vec![root_commit_id.clone()],
+            repo.store().empty_merged_tree(),
+        )
+        .write_unwrap();
+    let repo = tx.commit("test").block_on()?;
+    let stats = as_readonly_index(&repo).stats();
+    assert_eq!(stats.trigram_commits_range, Some(0..11));
+    // Other auxiliary indexes should be unaffected
+    assert_eq!(stats.changed_path_commits_range, None);
+    assert_eq!(stats.commit_metadata_commits_range, None);
+    Ok(())
+}
+
+#[test]
+fn test_paths_maybe_containing_lines() -> TestResult {
+    let test_repo = TestRepo::init();
+    let repo = test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id();
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+
+    let mut tx = repo.start_transaction();
+    let tree1 = create_tree(
+        &repo,
+        &[(repo_path("file1"), "foo\n"), (repo_path("file2"), "bar\n")],
+    );
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(vec![root_commit_id.clone()], tree1)
+        .write_unwrap();
+    let tree2 = create_tree(
+        &repo,
+        &[
+            (repo_path("file1"), "foo\nQux\n"),
+            (repo_path("file2"), "bar\n"),
+            (repo_path("file3"), "baz\n"),
+        ],
+    );
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![commit1.id().clone()], tree2)
+        .write_unwrap();
+    let repo = tx.commit("test").block_on()?;
+
+    let paths_containing = |repo: &ReadonlyRepo, src: &str, kind: &str| {
+        let pattern = StringPattern::from_str_kind(src, kind).unwrap();
+        repo.index()
+            .paths_maybe_containing_lines(commit2.id(), &StringExpression::pattern(pattern))
+            .block_on()
+            .unwrap()
+    };
+
+    // Not indexed
+    assert_eq!(paths_containing(&repo, "qux", "substring"), None);
+
+    default_index_store
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()?;
+    default_index_store
+        .build_trigram_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()?;
+    let repo = repo.reload_at(repo.operation()).block_on()?;
+
+    // Trigrams are indexed per commit, so all paths changed by the commit are
+    // included
+    assert_eq!(
+        paths_containing(&repo, "Qux", "substring"),
+        Some(vec![repo_path_buf("file1"), repo_path_buf("file3")])
+    );
+    assert_eq!(
+        paths_containing(&repo, "qux", "substring-i"),
+        Some(vec![repo_path_buf("file1"), repo_path_buf("file3")])
+    );
+    assert_eq!(
+        paths_containing(&repo, "bar", "substring"),
+        Some(vec![repo_path_buf("file1"), repo_path_buf("file2")])
+    );
+    assert_eq!(
+        paths_containing(&repo, "baz", "exact"),
+        Some(vec![repo_path_buf("file1"), repo_path_buf("file3")])
+    );
+    assert_eq!(
+        paths_containing(&repo, "nowhere", "substring"),
+        Some(vec![])
+    );
+    // Patterns without literals can't be narrowed down
+    assert_eq!(paths_containing(&repo, "b.r", "regex"), None);
+    Ok(())
+}
+
+#[test]
+fn test_paths_maybe_containing_lines_many_paths() -> TestResult {
+    let test_repo = TestRepo::init();
+    let repo = test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id();
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+
+    let mut tx = repo.start_transaction();
+    let tree1 = create_tree(&repo, &[(repo_path("file"), "foo\n")]);
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(vec![root_commit_id.clone()], tree1)
+        .write_unwrap();
+    let tree2 = create_tree_with(&repo, |builder| {
+        builder.file(repo_path("file"), "foo\n");
+        for i in 0..1001 {
+            builder.file(repo_path(&format!("dir/{i}")), "bar\n");
+        }
+    });
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![commit1.id().clone()], tree2)
+        .write_unwrap();
+    let repo = tx.commit("test").block_on()?;
+
+    default_index_store
+        .build_changed_path_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()?;
+    default_index_store
+        .build_trigram_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()?;
+    let repo = repo.reload_at(repo.operation()).block_on()?;
+
+    let paths_containing = |src: &str| {
+        let pattern = StringPattern::substring(src);
+        repo.index()
+            .paths_maybe_containing_lines(commit2.id(), &StringExpression::pattern(pattern))
+            .block_on()
+            .unwrap()
+    };
+
+    // Commits which don't contain the trigrams don't add paths
+    assert_eq!(paths_containing("foo"), Some(vec![repo_path_buf("file")]));
+    // Too many paths to be worth narrowing down
+    assert_eq!(paths_containing("bar"), None);
+    Ok(())
+}
+
 #[test]
 fn test_build_changed_path_segments_partially_enabled() -> TestResult {
     let test_repo = TestRepo::init();
@@ -36,6 +36,7 @@ use jj_lib::backend::TreeValue;
 use jj_lib::conflict_labels::ConflictLabels;
 use jj_lib::conflicts::ConflictMaterializeOptions;
 use jj_lib::default_backend_factories::default_working_copy_factories;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::file_util;
 use jj_lib::file_util::check_symlink_support;
 use jj_lib::file_util::symlink_dir;
@@ -50,6 +51,7 @@ use jj_lib::local_working_copy::TreeStateSettings;
 use jj_lib::matchers::FilesMatcher;
 use jj_lib::merge::Merge;
 use jj_lib::merge::SameChange;
+use jj_lib::merge_driver::MergeDrivers;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree_builder::MergedTreeBuilder;
 use jj_lib::op_store::OperationId;
@@ -1363,6 +1365,9 @@ fn test_snapshot_modified_materialized_conflict(
                 merge: MergeOptions {
                     hunk_level: FileMergeHunkLevel::Line,
                     same_change: SameChange::Accept,
+                    diff_algorithm: DiffAlgorithm::Default,
+                    drivers: MergeDrivers::default(),
+                    rerere: None,
                 },
             },
         )?;
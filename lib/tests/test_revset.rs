@@ -140,6 +140,24 @@ fn build_changed_path_index(repo: &ReadonlyRepo) -> Arc<ReadonlyRepo> {
     repo.reload_at(repo.operation()).block_on().unwrap()
 }
 
+fn build_commit_metadata_index(repo: &ReadonlyRepo) -> Arc<ReadonlyRepo> {
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+    default_index_store
+        .build_commit_metadata_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()
+        .unwrap();
+    repo.reload_at(repo.operation()).block_on().unwrap()
+}
+
+fn build_trigram_index(repo: &ReadonlyRepo) -> Arc<ReadonlyRepo> {
+    let default_index_store: &DefaultIndexStore = repo.index_store().downcast_ref().unwrap();
+    default_index_store
+        .build_trigram_index_at_operation(repo.op_id(), repo.store(), u32::MAX, |_| ())
+        .block_on()
+        .unwrap();
+    repo.reload_at(repo.operation()).block_on().unwrap()
+}
+
 #[test]
 fn test_resolve_symbol_empty_string() -> TestResult {
     let test_repo = TestRepo::init();
@@ -3648,10 +3666,15 @@ fn test_evaluate_expression_merges() {
     );
 }
 
-#[test]
-fn test_evaluate_expression_description() {
+#[test_case(false; "without commit-metadata index")]
+#[test_case(true; "with commit-metadata index")]
+fn test_evaluate_expression_description(indexed: bool) {
     let test_repo = TestRepo::init();
-    let repo = &test_repo.repo;
+    let repo = &if indexed {
+        build_commit_metadata_index(&test_repo.repo)
+    } else {
+        test_repo.repo.clone()
+    };
 
     let mut tx = repo.start_transaction();
     let mut_repo = tx.repo_mut();
@@ -3736,10 +3759,15 @@ fn test_evaluate_expression_description() {
     );
 }
 
-#[test]
-fn test_evaluate_expression_author() {
+#[test_case(false; "without commit-metadata index")]
+#[test_case(true; "with commit-metadata index")]
+fn test_evaluate_expression_author(indexed: bool) {
     let test_repo = TestRepo::init();
-    let repo = &test_repo.repo;
+    let repo = &if indexed {
+        build_commit_metadata_index(&test_repo.repo)
+    } else {
+        test_repo.repo.clone()
+    };
 
     let mut tx = repo.start_transaction();
     let mut_repo = tx.repo_mut();
@@ -4073,10 +4101,15 @@ fn test_evaluate_expression_signed() {
     assert!(unsigned_commits.contains(commit2.id()));
 }
 
-#[test]
-fn test_evaluate_expression_committer() {
+#[test_case(false; "without commit-metadata index")]
+#[test_case(true; "with commit-metadata index")]
+fn test_evaluate_expression_committer(indexed: bool) {
     let test_repo = TestRepo::init();
-    let repo = &test_repo.repo;
+    let repo = &if indexed {
+        build_commit_metadata_index(&test_repo.repo)
+    } else {
+        test_repo.repo.clone()
+    };
 
     let mut tx = repo.start_transaction();
     let mut_repo = tx.repo_mut();
@@ -4781,6 +4814,98 @@ fn test_evaluate_expression_file(indexed: bool) {
     );
 }
 
+#[test_case(false; "without changed-path index")]
+#[test_case(true; "with changed-path index")]
+fn test_evaluate_expression_touches_owner(indexed: bool) {
+    let test_repo = TestRepo::init();
+    let repo = if indexed {
+        build_changed_path_index(&test_repo.repo)
+    } else {
+        test_repo.repo.clone()
+    };
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let codeowners = repo_path("CODEOWNERS");
+    let ci_file = repo_path("ci/build.yml");
+    let readme_file = repo_path("README.md");
+    let src_file = repo_path("src/main.rs");
+    let codeowners_content = "/ci/ @infra\n*.md @docs @infra-lead\n";
+    let tree1 = create_tree(
+        &repo,
+        &[
+            (codeowners, codeowners_content),
+            (ci_file, "1"),
+            (readme_file, "1"),
+        ],
+    );
+    let tree2 = create_tree(
+        &repo,
+        &[
+            (codeowners, codeowners_content),
+            (ci_file, "1"),
+            (readme_file, "1"),
+            (src_file, "1"),
+        ],
+    );
+    let tree3 = create_tree(
+        &repo,
+        &[
+            (codeowners, codeowners_content),
+            (ci_file, "2"),
+            (readme_file, "1"),
+            (src_file, "1"),
+        ],
+    );
+    let tree4 = create_tree(
+        &repo,
+        &[
+            (codeowners, codeowners_content),
+            (ci_file, "2"),
+            (src_file, "1"),
+        ],
+    );
+    let commit1 = mut_repo
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree1)
+        .write_unwrap();
+    let commit2 = mut_repo
+        .new_commit(vec![commit1.id().clone()], tree2)
+        .write_unwrap();
+    let commit3 = mut_repo
+        .new_commit(vec![commit2.id().clone()], tree3)
+        .write_unwrap();
+    let commit4 = mut_repo
+        .new_commit(vec![commit3.id().clone()], tree4)
+        .write_unwrap();
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "touches_owner('@infra')"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    // Owners of deleted files are looked up by path
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "touches_owner('@docs')"),
+        vec![commit4.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "touches_owner('@infra*')"),
+        vec![
+            commit4.id().clone(),
+            commit3.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "touches_owner('@nobody')"),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("{} & touches_owner('*')", commit2.id())),
+        vec![]
+    );
+}
+
 #[test_case(false; "without changed-path index")]
 #[test_case(true; "with changed-path index")]
 fn test_evaluate_expression_diff_lines(indexed: bool) {
@@ -4970,6 +5095,107 @@ fn test_evaluate_expression_diff_lines(indexed: bool) {
     );
 }
 
+#[test_case(false; "without trigram index")]
+#[test_case(true; "with trigram index")]
+fn test_evaluate_expression_diff_lines_literal(indexed: bool) {
+    let test_repo = TestRepo::init();
+    let repo = if indexed {
+        build_trigram_index(&test_repo.repo)
+    } else {
+        test_repo.repo.clone()
+    };
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let file1 = repo_path("file1");
+    let file2 = repo_path("file2");
+    let tree1 = create_tree(&repo, &[(file1, "foo bar\nhello world\n")]);
+    let tree2 = create_tree(
+        &repo,
+        &[(file1, "foo bar\nHello World!\n"), (file2, "baz qux\n")],
+    );
+    let tree3 = create_tree(
+        &repo,
+        &[(file1, "foo baz\nHello World!\n"), (file2, "baz qux\n")],
+    );
+    let commit1 = mut_repo
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree1)
+        .write_unwrap();
+    let commit2 = mut_repo
+        .new_commit(vec![commit1.id().clone()], tree2)
+        .write_unwrap();
+    let commit3 = mut_repo
+        .new_commit(vec![commit2.id().clone()], tree3)
+        .write_unwrap();
+
+    let query = |revset_str: &str| resolve_commit_ids(mut_repo, revset_str);
+
+    assert_eq!(
+        query("diff_lines(substring:'foo bar')"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines_removed(substring:'foo bar')"),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines(exact:'foo baz')"),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines_added(substring:'qux')"),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines(glob:'*World!')"),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(query("diff_lines(substring:'nowhere')"), vec![]);
+
+    // case-insensitive patterns
+    assert_eq!(
+        query("diff_lines(substring-i:'HELLO WORLD')"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines(exact-i:'HELLO WORLD!')"),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines(glob-i:'*WORLD!')"),
+        vec![commit2.id().clone()]
+    );
+
+    // patterns without literals
+    assert_eq!(
+        query("diff_lines(regex:'W.rld')"),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(
+        query("diff_lines(~substring:'nowhere')"),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone(),
+        ]
+    );
+
+    // compound expressions
+    assert_eq!(
+        query("diff_lines(substring:'foo bar' | substring:'qux')"),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone(),
+        ]
+    );
+    assert_eq!(
+        query("diff_lines(substring:'foo' & substring:'baz')"),
+        vec![commit3.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_diff_lines_non_utf8() {
     let test_workspace = TestWorkspace::init();
@@ -36,9 +36,11 @@ use jj_lib::rewrite::CommitRewriter;
 use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehavior;
 use jj_lib::rewrite::MoveCommitsTarget;
+use jj_lib::rewrite::PlannedCommit;
 use jj_lib::rewrite::RebaseOptions;
 use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::RewriteRefsOptions;
+use jj_lib::rewrite::apply_rebase_plan;
 use jj_lib::rewrite::find_duplicate_divergent_commits;
 use jj_lib::rewrite::find_recursive_merge_commits;
 use jj_lib::rewrite::merge_commit_trees;
@@ -2376,6 +2378,105 @@ fn test_commit_with_selection() {
     assert!(full_selection.is_full_selection());
 }
 
+#[test]
+fn test_apply_rebase_plan() -> TestResult {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Reorder C before A, squash B into A, and drop D. E should be rebased onto
+    // the rewritten C.
+    //
+    // E
+    // D
+    // C
+    // B
+    // A
+    let mut tx = repo.start_transaction();
+    let path_a = repo_path("file_a");
+    let path_b = repo_path("file_b");
+    let path_c = repo_path("file_c");
+    let path_d = repo_path("file_d");
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(
+            vec![repo.store().root_commit_id().clone()],
+            create_tree(repo, &[(path_a, "a")]),
+        )
+        .set_description("A")
+        .write_unwrap();
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(
+            vec![commit_a.id().clone()],
+            create_tree(repo, &[(path_a, "a"), (path_b, "b")]),
+        )
+        .set_description("B")
+        .write_unwrap();
+    let commit_c = tx
+        .repo_mut()
+        .new_commit(
+            vec![commit_b.id().clone()],
+            create_tree(repo, &[(path_a, "a"), (path_b, "b"), (path_c, "c")]),
+        )
+        .set_description("C")
+        .write_unwrap();
+    let commit_d = tx
+        .repo_mut()
+        .new_commit(
+            vec![commit_c.id().clone()],
+            create_tree(
+                repo,
+                &[(path_a, "a"), (path_b, "b"), (path_c, "c"), (path_d, "d")],
+            ),
+        )
+        .write_unwrap();
+    let commit_e = write_random_commit_with_parents(tx.repo_mut(), &[&commit_d]);
+
+    let new_commits = apply_rebase_plan(
+        tx.repo_mut(),
+        vec![repo.store().root_commit_id().clone()],
+        vec![
+            PlannedCommit {
+                commits: vec![commit_c.clone()],
+                description: "C".to_owned(),
+            },
+            PlannedCommit {
+                commits: vec![commit_a.clone(), commit_b.clone()],
+                description: "A and B".to_owned(),
+            },
+        ],
+        slice::from_ref(&commit_d),
+    )
+    .block_on()?;
+    let [new_commit_c, new_commit_ab] = new_commits.as_slice() else {
+        panic!("unexpected commits: {new_commits:?}");
+    };
+    assert_eq!(
+        new_commit_c.parent_ids(),
+        [repo.store().root_commit_id().clone()]
+    );
+    assert_tree_eq!(new_commit_c.tree(), create_tree(repo, &[(path_c, "c")]));
+    assert_eq!(new_commit_c.description(), "C");
+    assert_eq!(new_commit_ab.parent_ids(), [new_commit_c.id().clone()]);
+    assert_tree_eq!(
+        new_commit_ab.tree(),
+        create_tree(repo, &[(path_a, "a"), (path_b, "b"), (path_c, "c")])
+    );
+    assert_eq!(new_commit_ab.description(), "A and B");
+    assert_eq!(
+        new_commit_ab.store_commit().predecessors,
+        [commit_a.id().clone(), commit_b.id().clone()]
+    );
+
+    let rebase_map =
+        rebase_descendants_with_options_return_map(tx.repo_mut(), &RebaseOptions::default());
+    assert_eq!(rebase_map.len(), 1);
+    let new_commit_e =
+        assert_rebased_onto(tx.repo_mut(), &rebase_map, &commit_e, &[new_commit_c.id()]);
+    assert_eq!(new_commit_e.change_id(), commit_e.change_id());
+    Ok(())
+}
+
 #[test]
 fn test_find_duplicate_divergent_commits() -> TestResult {
     let test_repo = TestRepo::init();